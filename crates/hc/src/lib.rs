@@ -124,6 +124,8 @@ pub enum Opt {
     App(hc_bundle::HcAppBundle),
     /// Work with DNA bundles
     Dna(hc_bundle::HcDnaBundle),
+    /// Work with Web hApp bundles
+    WebApp(hc_bundle::HcWebAppBundle),
     /// Work with sandboxed environments for testing and development
     Sandbox(hc_sandbox::HcSandbox),
 }
@@ -134,6 +136,7 @@ impl Opt {
         match self {
             Self::App(cmd) => cmd.run().await?,
             Self::Dna(cmd) => cmd.run().await?,
+            Self::WebApp(cmd) => cmd.run().await?,
             Self::Sandbox(cmd) => cmd.run().await?,
         }
         Ok(())
@@ -1,7 +1,9 @@
 #![forbid(missing_docs)]
 //! Binary `hc-dna` command executable.
 
-use holochain_types::prelude::{AppManifest, DnaManifest};
+use crate::inspect::DnaOverrides;
+use crate::signing::SignOptions;
+use holochain_types::prelude::{AppManifest, DnaManifest, WebAppManifest, YamlProperties};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -11,6 +13,9 @@ pub const DNA_BUNDLE_EXT: &str = "dna";
 /// The file extension to use for hApp bundles
 pub const APP_BUNDLE_EXT: &str = "happ";
 
+/// The file extension to use for Web hApp bundles
+pub const WEB_APP_BUNDLE_EXT: &str = "webhapp";
+
 /// Work with Holochain DNA bundles
 #[derive(Debug, StructOpt)]
 pub enum HcDnaBundle {
@@ -27,6 +32,9 @@ pub enum HcDnaBundle {
     /// $ hc-dna pack ./some/directory/foo/`
     ///
     /// will create file `./some/directory/foo.dna`
+    ///
+    /// Use `--sign` to sign the bundle with a publisher key from lair,
+    /// so conductors which only trust certain publishers will install it.
     Pack {
         /// The path to the unpacked directory containing a `dna.yaml` manifest
         path: std::path::PathBuf,
@@ -37,6 +45,10 @@ pub enum HcDnaBundle {
         /// and given the name "[DIRECTORY].dna"
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// (flattened)
+        #[structopt(flatten)]
+        sign: SignOptions,
     },
 
     /// Unpack the parts of `.dna` file out into a directory.
@@ -58,6 +70,44 @@ pub enum HcDnaBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Print the DnaHash that a `.dna` bundle will have once installed.
+    ///
+    /// Pass the same `--uuid` and `--properties` that the DNA will be
+    /// installed with to get the hash of the overridden DNA.
+    Hash {
+        /// The path to the `.dna` bundle
+        path: std::path::PathBuf,
+
+        /// The UUID to install the DNA with, in place of the one in its manifest
+        #[structopt(long)]
+        uuid: Option<String>,
+
+        /// A YAML file of properties to install the DNA with,
+        /// in place of those in its manifest
+        #[structopt(long)]
+        properties: Option<PathBuf>,
+    },
+
+    /// Summarize a `.dna` bundle: its DnaHash, and the wasm hash and
+    /// entry defs of each of its zomes.
+    Inspect {
+        /// The path to the `.dna` bundle
+        path: std::path::PathBuf,
+
+        /// The UUID to install the DNA with, in place of the one in its manifest
+        #[structopt(long)]
+        uuid: Option<String>,
+
+        /// A YAML file of properties to install the DNA with,
+        /// in place of those in its manifest
+        #[structopt(long)]
+        properties: Option<PathBuf>,
+
+        /// Print the summary as JSON rather than as a table
+        #[structopt(long)]
+        json: bool,
+    },
 }
 
 /// Work with Holochain hApp bundles
@@ -76,6 +126,9 @@ pub enum HcAppBundle {
     /// $ hc-app pack ./some/directory/foo/`
     ///
     /// will create file `./some/directory/foo.happ`
+    ///
+    /// Use `--sign` to sign the bundle with a publisher key from lair,
+    /// so conductors which only trust certain publishers will install it.
     Pack {
         /// The path to the unpacked directory containing a `happ.yaml` manifest
         path: std::path::PathBuf,
@@ -86,6 +139,10 @@ pub enum HcAppBundle {
         /// and given the name "[DIRECTORY].happ"
         #[structopt(short = "o", long)]
         output: Option<PathBuf>,
+
+        /// (flattened)
+        #[structopt(flatten)]
+        sign: SignOptions,
     },
 
     /// Unpack the parts of `.happ` file out into a directory.
@@ -107,6 +164,67 @@ pub enum HcAppBundle {
         #[structopt(short = "f", long)]
         force: bool,
     },
+
+    /// Summarize a `.happ` bundle: each app slot, its provisioning
+    /// strategy, and the DnaHash of the DNA it will install.
+    Inspect {
+        /// The path to the `.happ` bundle
+        path: std::path::PathBuf,
+
+        /// Print the summary as JSON rather than as a table
+        #[structopt(long)]
+        json: bool,
+    },
+}
+
+/// Work with Web hApp bundles, which combine a web UI with a hApp bundle
+#[derive(Debug, StructOpt)]
+pub enum HcWebAppBundle {
+    /// Pack the contents of a directory into a `.webhapp` bundle file.
+    ///
+    /// e.g.:
+    ///
+    /// $ hc web-app pack ./some/directory/foo/`
+    ///
+    /// will create file `./some/directory/foo.webhapp`
+    ///
+    /// Use `--sign` to sign the bundle with a publisher key from lair,
+    /// so conductors which only trust certain publishers will install it.
+    /// The signature covers the UI and the nested hApp bundle.
+    Pack {
+        /// The path to the unpacked directory containing a `web-happ.yaml` manifest
+        path: std::path::PathBuf,
+
+        /// Specify the output path for the packed bundle file.
+        ///
+        /// If not specified, the file will be placed alongside the input directory,
+        /// and given the name "[DIRECTORY].webhapp"
+        #[structopt(short = "o", long)]
+        output: Option<PathBuf>,
+
+        /// (flattened)
+        #[structopt(flatten)]
+        sign: SignOptions,
+    },
+
+    /// Unpack the parts of `.webhapp` file out into a directory.
+    ///
+    /// (`hc web-app unpack my-app.webhapp` creates dir `my-app`)
+    Unpack {
+        /// The path to the bundle to unpack
+        path: std::path::PathBuf,
+
+        /// Specify the directory for the unpacked directory.
+        ///
+        /// If not specified, the directory will be placed alongside the
+        /// bundle file, with the same name as the bundle file name.
+        #[structopt(short = "o", long)]
+        output: Option<PathBuf>,
+
+        /// Overwrite an existing directory, if one exists.
+        #[structopt(short = "f", long)]
+        force: bool,
+    },
 }
 
 impl HcDnaBundle {
@@ -116,9 +234,13 @@ impl HcDnaBundle {
             Self::Init { path } => {
                 crate::init::init_dna(path).await?;
             }
-            Self::Pack { path, output } => {
-                let (bundle_path, _) = crate::packing::pack::<DnaManifest>(&path, output).await?;
+            Self::Pack { path, output, sign } => {
+                let (bundle_path, bundle) =
+                    crate::packing::pack::<DnaManifest>(&path, output).await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
+                if let Some(publisher) = sign.sign(&bundle_path, bundle).await? {
+                    println!("Signed bundle as {}", publisher);
+                }
             }
             Self::Unpack {
                 path,
@@ -130,6 +252,28 @@ impl HcDnaBundle {
                         .await?;
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Hash {
+                path,
+                uuid,
+                properties,
+            } => {
+                let overrides = dna_overrides(uuid, properties).await?;
+                println!("{}", crate::inspect::dna_hash(&path, overrides).await?);
+            }
+            Self::Inspect {
+                path,
+                uuid,
+                properties,
+                json,
+            } => {
+                let overrides = dna_overrides(uuid, properties).await?;
+                let summary = crate::inspect::inspect_dna(&path, overrides).await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&summary)?);
+                } else {
+                    print!("{}", summary);
+                }
+            }
         }
         Ok(())
     }
@@ -142,9 +286,13 @@ impl HcAppBundle {
             Self::Init { path } => {
                 crate::init::init_app(path).await?;
             }
-            Self::Pack { path, output } => {
-                let (bundle_path, _) = crate::packing::pack::<AppManifest>(&path, output).await?;
+            Self::Pack { path, output, sign } => {
+                let (bundle_path, bundle) =
+                    crate::packing::pack::<AppManifest>(&path, output).await?;
                 println!("Wrote bundle {}", bundle_path.to_string_lossy());
+                if let Some(publisher) = sign.sign(&bundle_path, bundle).await? {
+                    println!("Signed bundle as {}", publisher);
+                }
             }
             Self::Unpack {
                 path,
@@ -156,7 +304,59 @@ impl HcAppBundle {
                         .await?;
                 println!("Unpacked to directory {}", dir_path.to_string_lossy());
             }
+            Self::Inspect { path, json } => {
+                let summary = crate::inspect::inspect_app(&path).await?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&summary)?);
+                } else {
+                    print!("{}", summary);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl HcWebAppBundle {
+    /// Run this command
+    pub async fn run(self) -> anyhow::Result<()> {
+        match self {
+            Self::Pack { path, output, sign } => {
+                let (bundle_path, bundle) =
+                    crate::packing::pack::<WebAppManifest>(&path, output).await?;
+                println!("Wrote bundle {}", bundle_path.to_string_lossy());
+                if let Some(publisher) = sign.sign(&bundle_path, bundle).await? {
+                    println!("Signed bundle as {}", publisher);
+                }
+            }
+            Self::Unpack {
+                path,
+                output,
+                force,
+            } => {
+                let dir_path = crate::packing::unpack::<WebAppManifest>(
+                    WEB_APP_BUNDLE_EXT,
+                    &path,
+                    output,
+                    force,
+                )
+                .await?;
+                println!("Unpacked to directory {}", dir_path.to_string_lossy());
+            }
         }
         Ok(())
     }
 }
+
+async fn dna_overrides(
+    uuid: Option<String>,
+    properties: Option<PathBuf>,
+) -> anyhow::Result<DnaOverrides> {
+    let properties = match properties {
+        Some(path) => Some(YamlProperties::new(serde_yaml::from_slice(
+            &ffs::read(&path).await?,
+        )?)),
+        None => None,
+    };
+    Ok(DnaOverrides { uuid, properties })
+}
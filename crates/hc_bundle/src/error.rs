@@ -20,6 +20,26 @@ pub enum HcBundleError {
     #[error(transparent)]
     MrBundleError(#[from] mr_bundle::error::MrBundleError),
 
+    /// KeystoreError
+    #[error("Keystore error: {0}")]
+    KeystoreError(#[from] holochain_keystore::KeystoreError),
+
+    /// BundleSignatureError
+    #[error(transparent)]
+    BundleSignatureError(#[from] holochain_types::prelude::BundleSignatureError),
+
+    /// AppBundleError
+    #[error(transparent)]
+    AppBundleError(#[from] holochain_types::prelude::AppBundleError),
+
+    /// RibosomeError
+    #[error("Ribosome error: {0}")]
+    RibosomeError(#[from] holochain::core::ribosome::error::RibosomeError),
+
+    /// A zome's `entry_defs` callback returned an error
+    #[error("The entry_defs callback of zome '{0}' failed: {1}")]
+    EntryDefsCallbackFailed(holochain_types::prelude::ZomeName, String),
+
     /// SerializedBytesError
     #[error("Internal serialization error: {0}")]
     SerializedBytesError(#[from] SerializedBytesError),
@@ -0,0 +1,281 @@
+#![forbid(missing_docs)]
+
+//! Summarizing DNA and hApp bundles without installing them
+
+use crate::error::{HcBundleError, HcBundleResult};
+use holo_hash::{DnaHashB64, WasmHashB64};
+use holochain::core::ribosome::guest_callback::entry_defs::{
+    EntryDefsHostAccess, EntryDefsInvocation, EntryDefsResult,
+};
+use holochain::core::ribosome::real_ribosome::RealRibosome;
+use holochain::core::ribosome::RibosomeT;
+use holochain_types::prelude::*;
+use mr_bundle::{Bundle, Location};
+use std::path::Path;
+
+/// The properties and UUID to use in place of those in a DNA manifest,
+/// as the conductor does when registering a DNA
+#[derive(Debug, Default)]
+pub struct DnaOverrides {
+    /// Replaces the UUID
+    pub uuid: Option<String>,
+    /// Replaces the properties
+    pub properties: Option<YamlProperties>,
+}
+
+/// What a DNA bundle will produce when installed
+#[derive(Debug, serde::Serialize)]
+pub struct DnaSummary {
+    /// The DNA name
+    pub name: String,
+    /// The DNA UUID, after any override
+    pub uuid: String,
+    /// The hash of the DNA, after any overrides
+    pub dna_hash: DnaHashB64,
+    /// The zomes, in initialization order
+    pub zomes: Vec<ZomeSummary>,
+}
+
+/// A zome within a [`DnaSummary`]
+#[derive(Debug, serde::Serialize)]
+pub struct ZomeSummary {
+    /// The zome name
+    pub name: ZomeName,
+    /// The hash of the zome's wasm. None for inline zomes.
+    pub wasm_hash: Option<WasmHashB64>,
+    /// The entry defs returned by the zome's `entry_defs` callback
+    pub entry_defs: Vec<EntryDef>,
+}
+
+/// What a hApp bundle will provision when installed
+#[derive(Debug, serde::Serialize)]
+pub struct AppSummary {
+    /// The app name
+    pub name: String,
+    /// The app slots, ordered by id
+    pub slots: Vec<SlotSummary>,
+}
+
+/// An app slot within an [`AppSummary`]
+#[derive(Debug, serde::Serialize)]
+pub struct SlotSummary {
+    /// The slot id
+    pub id: SlotId,
+    /// The provisioning strategy, e.g. "create"
+    pub provisioning: String,
+    /// Whether provisioning is deferred
+    pub deferred: bool,
+    /// How many clones may be created at runtime
+    pub clone_limit: u32,
+    /// Where the slot's DNA is found, if the strategy uses one
+    pub location: Option<Location>,
+    /// The hash of the DNA at that location, as the conductor will install it
+    pub dna_hash: Option<DnaHashB64>,
+}
+
+/// Read the DNA bundle at `path` and apply the overrides, in the same order
+/// as the conductor does
+async fn read_dna_file(path: &Path, overrides: DnaOverrides) -> HcBundleResult<DnaFile> {
+    let bundle: DnaBundle = Bundle::read_from_file(path).await?.into();
+    let mut dna_file = bundle.into_dna_file().await?;
+    if let Some(properties) = overrides.properties {
+        dna_file = dna_file
+            .with_properties(SerializedBytes::try_from(properties)?)
+            .await?;
+    }
+    if let Some(uuid) = overrides.uuid {
+        dna_file = dna_file.with_uuid(uuid).await?;
+    }
+    Ok(dna_file)
+}
+
+/// Compute the DnaHash of the DNA bundle at `path`, without running any wasm
+pub async fn dna_hash(path: &Path, overrides: DnaOverrides) -> HcBundleResult<DnaHashB64> {
+    Ok(read_dna_file(path, overrides)
+        .await?
+        .dna_hash()
+        .clone()
+        .into())
+}
+
+/// Summarize the DNA bundle at `path`, calling each zome's `entry_defs`
+/// callback to list its entry defs
+pub async fn inspect_dna(path: &Path, overrides: DnaOverrides) -> HcBundleResult<DnaSummary> {
+    let dna_file = read_dna_file(path, overrides).await?;
+    let mut entry_defs = match RealRibosome::new(dna_file.clone())
+        .run_entry_defs(EntryDefsHostAccess, EntryDefsInvocation)?
+    {
+        EntryDefsResult::Defs(defs) => defs,
+        EntryDefsResult::Err(zome_name, msg) => {
+            return Err(HcBundleError::EntryDefsCallbackFailed(zome_name, msg))
+        }
+    };
+    let dna = dna_file.dna_def();
+    let zomes = dna
+        .zomes
+        .iter()
+        .map(|(name, zome)| ZomeSummary {
+            name: name.clone(),
+            wasm_hash: match zome {
+                ZomeDef::Wasm(zome) => Some(zome.wasm_hash.clone().into()),
+                ZomeDef::Inline(_) => None,
+            },
+            entry_defs: entry_defs
+                .remove(name)
+                .map(|defs| defs.into_iter().collect())
+                .unwrap_or_default(),
+        })
+        .collect();
+    Ok(DnaSummary {
+        name: dna.name.clone(),
+        uuid: dna.uuid.clone(),
+        dna_hash: dna_file.dna_hash().clone().into(),
+        zomes,
+    })
+}
+
+/// Summarize the hApp bundle at `path`, resolving the DNA of each slot
+pub async fn inspect_app(path: &Path) -> HcBundleResult<AppSummary> {
+    let bundle: AppBundle = Bundle::read_from_file(path).await?.into();
+    let manifest = bundle
+        .manifest()
+        .clone()
+        .validate()
+        .map_err(AppBundleError::from)?;
+    let mut slots = Vec::new();
+    for (id, slot) in manifest.slots() {
+        let (provisioning, deferred, clone_limit, location) = match slot {
+            AppSlotManifestValidated::Create {
+                deferred,
+                clone_limit,
+                location,
+                ..
+            } => ("create", *deferred, *clone_limit, Some(location)),
+            AppSlotManifestValidated::CreateClone {
+                deferred,
+                clone_limit,
+                location,
+                ..
+            } => ("create_clone", *deferred, *clone_limit, Some(location)),
+            AppSlotManifestValidated::UseExisting {
+                deferred,
+                clone_limit,
+                ..
+            } => ("use_existing", *deferred, *clone_limit, None),
+            AppSlotManifestValidated::CreateIfNotExists {
+                deferred,
+                clone_limit,
+                location,
+                ..
+            } => (
+                "create_if_not_exists",
+                *deferred,
+                *clone_limit,
+                Some(location),
+            ),
+            AppSlotManifestValidated::Disabled { clone_limit, .. } => {
+                ("disabled", false, *clone_limit, None)
+            }
+        };
+        let dna_hash = match location {
+            Some(location) => {
+                let dna_bundle = DnaBundle::decode(&bundle.resolve(location).await?)?;
+                Some(dna_bundle.into_dna_file().await?.dna_hash().clone().into())
+            }
+            None => None,
+        };
+        slots.push(SlotSummary {
+            id: id.clone(),
+            provisioning: provisioning.to_string(),
+            deferred,
+            clone_limit,
+            location: location.cloned(),
+            dna_hash,
+        });
+    }
+    slots.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(AppSummary {
+        name: manifest.name().to_string(),
+        slots,
+    })
+}
+
+fn format_location(location: &Location) -> String {
+    match location {
+        Location::Bundled(path) => format!("bundled: {}", path.display()),
+        Location::Path(path) => format!("path: {}", path.display()),
+        Location::Url(url) => format!("url: {}", url),
+    }
+}
+
+impl std::fmt::Display for DnaSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "DNA:      {}", self.name)?;
+        writeln!(f, "UUID:     {}", self.uuid)?;
+        writeln!(f, "DNA hash: {}", self.dna_hash)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<20} {:<55} {:<20} {:<10} {:>11} {:<12}",
+            "zome", "wasm hash", "entry def", "visibility", "validations", "package"
+        )?;
+        for zome in &self.zomes {
+            let wasm_hash = zome
+                .wasm_hash
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "(inline)".to_string());
+            if zome.entry_defs.is_empty() {
+                writeln!(f, "{:<20} {:<55}", zome.name.0, wasm_hash)?;
+            }
+            for entry_def in &zome.entry_defs {
+                let id = match &entry_def.id {
+                    EntryDefId::App(id) => id.clone(),
+                    id => format!("{:?}", id),
+                };
+                writeln!(
+                    f,
+                    "{:<20} {:<55} {:<20} {:<10} {:>11} {:<12}",
+                    zome.name.0,
+                    wasm_hash,
+                    id,
+                    format!("{:?}", entry_def.visibility),
+                    u8::from(entry_def.required_validations),
+                    format!("{:?}", entry_def.required_validation_type),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for AppSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "App: {}", self.name)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<20} {:<22} {:>8} {:>11} {:<55} {:<30}",
+            "slot", "provisioning", "deferred", "clone limit", "dna hash", "location"
+        )?;
+        for slot in &self.slots {
+            writeln!(
+                f,
+                "{:<20} {:<22} {:>8} {:>11} {:<55} {:<30}",
+                slot.id,
+                slot.provisioning,
+                slot.deferred,
+                slot.clone_limit,
+                slot.dna_hash
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                slot.location
+                    .as_ref()
+                    .map(format_location)
+                    .unwrap_or_default(),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
 mod cli;
 mod error;
 mod init;
-mod packing;
+mod inspect;
+pub mod packing;
+mod signing;
 
-pub use cli::{HcAppBundle, HcDnaBundle};
+pub use cli::{HcAppBundle, HcDnaBundle, HcWebAppBundle};
+pub use error::{HcBundleError, HcBundleResult};
+pub use inspect::{
+    dna_hash, inspect_app, inspect_dna, AppSummary, DnaOverrides, DnaSummary, SlotSummary,
+    ZomeSummary,
+};
+pub use signing::SignOptions;
@@ -0,0 +1,158 @@
+#![forbid(missing_docs)]
+
+//! Signing packed bundles with a publisher key from lair
+
+use crate::error::HcBundleResult;
+use holochain_keystore::lair_keystore::spawn_lair_keystore;
+use holochain_keystore::KeystoreSender;
+use holochain_keystore::KeystoreSenderExt;
+use holochain_types::prelude::{sign_bundle, AgentPubKey, BundleSignature};
+use mr_bundle::{Bundle, Manifest};
+use std::convert::TryFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+/// Options for signing a bundle as it's packed
+#[derive(Debug, StructOpt)]
+pub struct SignOptions {
+    /// Sign the bundle with this publisher key, which must be in the keystore.
+    /// The key is Base64, e.g. `uhCAk71wNXTv7lstvi4PfUr_JDvxLucF9WzUgWPNIEZIoPGMF4b_o`
+    #[structopt(long, parse(try_from_str = parse_agent_key))]
+    pub sign: Option<AgentPubKey>,
+
+    /// Generate a new publisher key in the keystore and sign the bundle with it.
+    /// The new key is printed so it can be used to sign again and
+    /// given to conductors as a trusted publisher.
+    #[structopt(long, conflicts_with = "sign")]
+    pub sign_with_new_key: bool,
+
+    /// Write the signature to a detached `[BUNDLE].sig` file
+    /// instead of embedding it in the bundle.
+    #[structopt(long)]
+    pub detached: bool,
+
+    /// The lair keystore directory.
+    /// If not specified, lair's default location is used.
+    #[structopt(long)]
+    pub keystore_path: Option<PathBuf>,
+}
+
+impl SignOptions {
+    /// Sign the bundle which was packed to `bundle_path`, if signing was requested
+    pub async fn sign<M: Manifest>(
+        &self,
+        bundle_path: &Path,
+        bundle: Bundle<M>,
+    ) -> HcBundleResult<Option<AgentPubKey>> {
+        if self.sign.is_none() && !self.sign_with_new_key {
+            return Ok(None);
+        }
+        let keystore = spawn_lair_keystore(self.keystore_path.as_deref()).await?;
+        let publisher = match &self.sign {
+            Some(publisher) => publisher.clone(),
+            None => {
+                let publisher = keystore.generate_sign_keypair_from_pure_entropy().await?;
+                println!("Generated publisher key {}", publisher);
+                publisher
+            }
+        };
+        sign_packed_bundle(bundle_path, bundle, &keystore, &publisher, self.detached).await?;
+        Ok(Some(publisher))
+    }
+}
+
+/// Sign a bundle as `publisher`, either embedding the signature and
+/// rewriting the bundle file, or writing a detached signature file
+/// alongside it. Returns the path written to.
+pub async fn sign_packed_bundle<M: Manifest>(
+    bundle_path: &Path,
+    mut bundle: Bundle<M>,
+    keystore: &KeystoreSender,
+    publisher: &AgentPubKey,
+    detached: bool,
+) -> HcBundleResult<PathBuf> {
+    let signature = sign_bundle(&bundle, keystore, publisher).await?;
+    if detached {
+        let signature_path = BundleSignature::detached_path(bundle_path);
+        signature.write_to_file(&signature_path).await?;
+        Ok(signature_path)
+    } else {
+        bundle.add_signature(signature);
+        bundle.write_to_file(bundle_path).await?;
+        Ok(bundle_path.to_owned())
+    }
+}
+
+fn parse_agent_key(arg: &str) -> Result<AgentPubKey, String> {
+    AgentPubKey::try_from(arg).map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_keystore::test_keystore::spawn_test_keystore;
+    use holochain_types::prelude::{verify_bundle_publisher, DnaManifest};
+
+    async fn packed_bundle(dir: &Path) -> (PathBuf, Bundle<DnaManifest>) {
+        let manifest_yaml = r#"
+---
+manifest_version: "1"
+name: test dna
+uuid: blablabla
+zomes:
+  - name: zome1
+    bundled: zome-1.wasm
+        "#;
+        let dna_dir = dir.join("test-dna");
+        std::fs::create_dir(&dna_dir).unwrap();
+        std::fs::write(dna_dir.join("zome-1.wasm"), &[1, 2, 3]).unwrap();
+        std::fs::write(dna_dir.join("dna.yaml"), manifest_yaml.as_bytes()).unwrap();
+        crate::packing::pack::<DnaManifest>(&dna_dir, None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn sign_embedded_and_detached() {
+        let tmpdir = tempdir::TempDir::new("hc-bundle-sign-test").unwrap();
+        let keystore = spawn_test_keystore().await.unwrap();
+        let publisher = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let trusted = [publisher.clone()];
+
+        // Embedded in the rewritten bundle file
+        let (bundle_path, bundle) = packed_bundle(tmpdir.path()).await;
+        let written = sign_packed_bundle(&bundle_path, bundle, &keystore, &publisher, false)
+            .await
+            .unwrap();
+        assert_eq!(written, bundle_path);
+        let signed: Bundle<DnaManifest> = Bundle::read_from_file(&bundle_path).await.unwrap();
+        assert_eq!(
+            verify_bundle_publisher(&signed, None, &trusted)
+                .await
+                .unwrap(),
+            publisher
+        );
+
+        // Detached, leaving the bundle file unsigned
+        std::fs::remove_dir_all(tmpdir.path()).unwrap();
+        std::fs::create_dir(tmpdir.path()).unwrap();
+        let (bundle_path, bundle) = packed_bundle(tmpdir.path()).await;
+        let written = sign_packed_bundle(&bundle_path, bundle, &keystore, &publisher, true)
+            .await
+            .unwrap();
+        assert_eq!(written, tmpdir.path().join("test-dna.dna.sig"));
+        let unsigned: Bundle<DnaManifest> = Bundle::read_from_file(&bundle_path).await.unwrap();
+        assert!(unsigned.signatures().is_empty());
+        let signature = BundleSignature::read_from_file(&written).await.unwrap();
+        assert_eq!(
+            verify_bundle_publisher(&unsigned, Some(&signature), &trusted)
+                .await
+                .unwrap(),
+            publisher
+        );
+    }
+}
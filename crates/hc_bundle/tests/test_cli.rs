@@ -13,6 +13,39 @@ fn read_dna(path: &Path) -> anyhow::Result<DnaBundle> {
     Ok(DnaBundle::decode(&ffs::sync::read(path).unwrap())?)
 }
 
+fn read_web_app(path: &Path) -> anyhow::Result<WebAppBundle> {
+    Ok(WebAppBundle::decode(&ffs::sync::read(path).unwrap())?)
+}
+
+/// Pack the fixture DNAs and hApp into `dir` rather than alongside the
+/// fixtures, so that tests running at the same time don't share files.
+/// Returns the path of the hApp bundle.
+fn pack_fixture_app(dir: &Path) -> PathBuf {
+    let app_dir = dir.join("my-app");
+    std::fs::create_dir_all(app_dir.join("dnas")).unwrap();
+    std::fs::copy("tests/fixtures/my-app/happ.yaml", app_dir.join("happ.yaml")).unwrap();
+    for dna in &["dna1", "dna2"] {
+        let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+        let cmd = cmd.args(&[
+            "pack",
+            &format!("tests/fixtures/my-app/dnas/{}", dna),
+            "-o",
+            app_dir.join(format!("dnas/{}.dna", dna)).to_str().unwrap(),
+        ]);
+        cmd.assert().success();
+    }
+    let app_path = dir.join("my-app.happ");
+    let mut cmd = Command::cargo_bin("hc-app").unwrap();
+    let cmd = cmd.args(&[
+        "pack",
+        app_dir.to_str().unwrap(),
+        "-o",
+        app_path.to_str().unwrap(),
+    ]);
+    cmd.assert().success();
+    app_path
+}
+
 #[tokio::test]
 async fn roundtrip() {
     {
@@ -39,3 +72,87 @@ async fn roundtrip() {
     let _original_dna1 = read_dna(&dna1_path).unwrap();
     let _original_dna2 = read_dna(&dna2_path).unwrap();
 }
+
+#[tokio::test]
+async fn web_app_roundtrip() {
+    let tmp = tempdir::TempDir::new("web-app").unwrap();
+    let web_app_dir = tmp.path().join("my-web-app");
+    std::fs::create_dir(&web_app_dir).unwrap();
+    let app_path = pack_fixture_app(tmp.path());
+    std::fs::rename(&app_path, web_app_dir.join("my-app.happ")).unwrap();
+    std::fs::write(web_app_dir.join("ui.zip"), &[1, 2, 3]).unwrap();
+    std::fs::write(
+        web_app_dir.join("web-happ.yaml"),
+        r#"
+manifest_version: "1"
+name: my-web-app
+ui:
+  bundled: ui.zip
+happ_manifest:
+  bundled: my-app.happ
+"#,
+    )
+    .unwrap();
+    {
+        let mut cmd = Command::cargo_bin("hc-web-app").unwrap();
+        let cmd = cmd.args(&["pack", web_app_dir.to_str().unwrap()]);
+        cmd.assert().success();
+    }
+
+    let web_app_path = tmp.path().join("my-web-app.webhapp");
+    let web_app = read_web_app(&web_app_path).unwrap();
+    assert_eq!(web_app.web_ui_zip_bytes().await.unwrap(), vec![1, 2, 3]);
+    web_app.happ_bundle().await.unwrap();
+
+    std::fs::remove_dir_all(&web_app_dir).unwrap();
+    {
+        let mut cmd = Command::cargo_bin("hc-web-app").unwrap();
+        let cmd = cmd.args(&["unpack", web_app_path.to_str().unwrap()]);
+        cmd.assert().success();
+    }
+    assert!(web_app_dir.join("web-happ.yaml").is_file());
+    assert!(web_app_dir.join("ui.zip").is_file());
+    assert!(web_app_dir.join("my-app.happ").is_file());
+}
+
+#[tokio::test]
+async fn dna_hash_matches_app_inspect() {
+    let tmp = tempdir::TempDir::new("inspect").unwrap();
+    let app_path = pack_fixture_app(tmp.path());
+    let dna_path = |dna: &str| {
+        tmp.path()
+            .join(format!("my-app/dnas/{}.dna", dna))
+            .to_str()
+            .unwrap()
+            .to_string()
+    };
+
+    let dna_hash = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("hc-dna").unwrap();
+        let output = cmd.arg("hash").args(args).output().unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    let dna1_hash = dna_hash(&[&dna_path("dna1")]);
+    let dna2_hash = dna_hash(&[&dna_path("dna2")]);
+    assert_ne!(dna1_hash, dna2_hash);
+    assert_ne!(dna_hash(&[&dna_path("dna1"), "--uuid", "other"]), dna1_hash);
+
+    let mut cmd = Command::cargo_bin("hc-app").unwrap();
+    let output = cmd
+        .args(&["inspect", app_path.to_str().unwrap(), "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["name"], "fixture-app");
+    let slots = summary["slots"].as_array().unwrap();
+    assert_eq!(slots.len(), 2);
+    assert_eq!(slots[0]["id"], "slot-1");
+    assert_eq!(slots[0]["provisioning"], "create");
+    assert_eq!(slots[0]["dna_hash"], dna1_hash.as_str());
+    assert_eq!(slots[1]["id"], "slot-2");
+    assert_eq!(slots[1]["deferred"], true);
+    assert_eq!(slots[1]["clone_limit"], 10);
+    assert_eq!(slots[1]["dna_hash"], dna2_hash.as_str());
+}
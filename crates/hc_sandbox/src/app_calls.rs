@@ -0,0 +1,344 @@
+//! Helpers for talking to a conductor's app interface:
+//! making zome calls and listening for signals.
+//!
+//! Payloads are given as YAML (or JSON, which is also YAML) and
+//! encoded with msgpack, the same as a UI client would.
+//! Responses and signals are decoded from msgpack and printed as JSON,
+//! with holo hashes shown in their base-64 form.
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use anyhow::ensure;
+use futures::StreamExt;
+use holochain_conductor_api::AppAuthenticationToken;
+use holochain_conductor_api::AppRequest;
+use holochain_conductor_api::AppResponse;
+use holochain_conductor_api::ZomeCall;
+use holochain_types::prelude::*;
+use holochain_websocket::websocket_connect;
+use holochain_websocket::WebsocketConfig;
+use holochain_websocket::WebsocketMessage;
+use holochain_websocket::WebsocketReceiver;
+use holochain_websocket::WebsocketSender;
+use structopt::StructOpt;
+use url2::prelude::*;
+
+use crate::expect_match;
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AppRequest::ZomeCall on a running conductor's app interface
+/// and prints the decoded response.
+pub struct ZomeCallCli {
+    /// The port of the app interface to call.
+    pub port: u16,
+    #[structopt(parse(try_from_str = parse_cell_id))]
+    /// The cell to call, as `<dna-hash>:<agent-key>`.
+    /// e.g. `uhC0k...:uhCAk...`
+    pub cell: CellId,
+    /// The zome to call.
+    pub zome: String,
+    /// The function to call.
+    pub function: String,
+    #[structopt(short, long)]
+    /// The payload for the function, as YAML or JSON.
+    /// e.g. `--payload '{ content: hello }'`.
+    /// Defaults to `null`, which is the encoding of `()`.
+    pub payload: Option<String>,
+    #[structopt(long, parse(try_from_str = parse_agent_key))]
+    /// The agent making the call.
+    /// Defaults to the agent of the cell.
+    pub provenance: Option<AgentPubKey>,
+    #[structopt(short, long)]
+    /// A token from `call issue-app-token` to authenticate
+    /// the connection with before calling.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Listens on a running conductor's app interface and prints
+/// each signal as it arrives, until the connection closes.
+pub struct SignalsCli {
+    /// The port of the app interface to listen on.
+    pub port: u16,
+    #[structopt(short, long)]
+    /// A token from `call issue-app-token` to authenticate
+    /// the connection with before listening.
+    pub token: Option<String>,
+}
+
+/// An active connection to a conductor's app interface.
+pub struct AppCmdRunner {
+    client: WebsocketSender,
+    rx: WebsocketReceiver,
+}
+
+impl AppCmdRunner {
+    /// Connect to the app interface on this port.
+    pub async fn try_new(port: u16) -> std::io::Result<Self> {
+        let (client, rx) = websocket_connect(
+            url2!("ws://127.0.0.1:{}", port),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await?;
+        Ok(Self { client, rx })
+    }
+
+    /// Make an App request to this conductor.
+    pub async fn command(&mut self, cmd: AppRequest) -> anyhow::Result<AppResponse> {
+        let response: Result<AppResponse, _> = self.client.request(cmd).await;
+        Ok(response?)
+    }
+
+    /// Calls [`AppRequest::Authenticate`], binding this connection to the token's app.
+    pub async fn authenticate(&mut self, token: String) -> anyhow::Result<InstalledAppId> {
+        let resp = self
+            .command(AppRequest::Authenticate {
+                token: AppAuthenticationToken(token),
+            })
+            .await?;
+        match resp {
+            AppResponse::Authenticated { installed_app_id } => Ok(installed_app_id),
+            _ => Err(anyhow!("Failed to authenticate, got: {:?}", resp)),
+        }
+    }
+
+    /// Wait for the next signal from the conductor.
+    /// Returns `None` once the connection is closed.
+    pub async fn next_signal(&mut self) -> anyhow::Result<Option<Signal>> {
+        while let Some(msg) = self.rx.next().await {
+            match msg {
+                WebsocketMessage::Signal(bytes) => return Ok(Some(Signal::try_from(bytes)?)),
+                WebsocketMessage::Close(_) => break,
+                // The conductor doesn't make requests of app clients.
+                WebsocketMessage::Request(..) => (),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for AppCmdRunner {
+    fn drop(&mut self) {
+        let f = self.client.close(0, "closing connection".to_string());
+        tokio::task::spawn(f);
+    }
+}
+
+#[doc(hidden)]
+pub async fn zome_call_cli(args: ZomeCallCli) -> anyhow::Result<()> {
+    let mut cmd = AppCmdRunner::try_new(args.port).await?;
+    if let Some(token) = args.token.clone() {
+        cmd.authenticate(token).await?;
+    }
+    let response = zome_call(&mut cmd, args).await?;
+    msg!(
+        "Zome call response:\n{}",
+        serde_json::to_string_pretty(&msgpack_to_json(response.as_bytes())?)?
+    );
+    Ok(())
+}
+
+#[doc(hidden)]
+pub async fn signals_cli(args: SignalsCli) -> anyhow::Result<()> {
+    let mut cmd = AppCmdRunner::try_new(args.port).await?;
+    if let Some(token) = args.token {
+        cmd.authenticate(token).await?;
+    }
+    msg!("Listening for signals on app port {}", args.port);
+    while let Some(signal) = cmd.next_signal().await? {
+        match signal {
+            Signal::App(cell_id, signal) => msg!(
+                "App signal from cell {}:{}\n{}",
+                cell_id.dna_hash(),
+                cell_id.agent_pubkey(),
+                serde_json::to_string_pretty(&msgpack_to_json(signal.into_inner().as_bytes())?)?
+            ),
+            Signal::System(signal) => msg!("System signal: {:?}", signal),
+        }
+    }
+    msg!("App interface closed the connection");
+    Ok(())
+}
+
+/// Calls [`AppRequest::ZomeCall`] with the YAML payload encoded as msgpack,
+/// and returns the raw response.
+pub async fn zome_call(cmd: &mut AppCmdRunner, args: ZomeCallCli) -> anyhow::Result<ExternIO> {
+    let ZomeCallCli {
+        cell,
+        zome,
+        function,
+        payload,
+        provenance,
+        ..
+    } = args;
+    let payload: serde_yaml::Value = match payload {
+        Some(payload) => serde_yaml::from_str(&payload)?,
+        None => serde_yaml::Value::Null,
+    };
+    let provenance = provenance.unwrap_or_else(|| cell.agent_pubkey().clone());
+    let call = ZomeCall {
+        cell_id: cell,
+        zome_name: zome.into(),
+        fn_name: function.into(),
+        payload: ExternIO::encode(payload)?,
+        cap: None,
+        provenance,
+    };
+    let resp = cmd.command(AppRequest::ZomeCall(Box::new(call))).await?;
+    Ok(*expect_match!(resp => AppResponse::ZomeCall, "Failed to call zome"))
+}
+
+/// Decode msgpack into JSON for display.
+pub fn msgpack_to_json(bytes: &[u8]) -> anyhow::Result<serde_json::Value> {
+    let mut bytes = bytes;
+    let value = rmpv::decode::read_value(&mut bytes)?;
+    ensure!(bytes.is_empty(), "Trailing bytes after msgpack value");
+    Ok(to_json(value))
+}
+
+fn to_json(value: rmpv::Value) -> serde_json::Value {
+    use rmpv::Value;
+    use serde_json::Value as Json;
+    match value {
+        Value::Nil => Json::Null,
+        Value::Boolean(b) => Json::Bool(b),
+        Value::Integer(i) => match (i.as_u64(), i.as_i64()) {
+            (Some(u), _) => u.into(),
+            (_, Some(i)) => i.into(),
+            _ => Json::Null,
+        },
+        Value::F32(f) => f64::from(f).into(),
+        Value::F64(f) => f.into(),
+        Value::String(s) => match s.into_str() {
+            Some(s) => Json::String(s),
+            None => Json::Null,
+        },
+        Value::Binary(b) if is_holo_hash(&b) => Json::String(holo_hash::holo_hash_encode(&b)),
+        Value::Binary(b) | Value::Ext(_, b) => b.into(),
+        Value::Array(a) => Json::Array(a.into_iter().map(to_json).collect()),
+        Value::Map(m) => Json::Object(
+            m.into_iter()
+                .map(|(k, v)| {
+                    let k = match k {
+                        Value::String(s) => s.into_str().unwrap_or_default(),
+                        k => k.to_string(),
+                    };
+                    (k, to_json(v))
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Whether these bytes are a holo hash: 39 bytes starting with the prefix
+/// of a known hash type, and ending with the location of the hash's core
+fn is_holo_hash(bytes: &[u8]) -> bool {
+    if bytes.len() != holo_hash::HOLO_HASH_FULL_LEN {
+        return false;
+    }
+    let bytes = bytes.to_vec();
+    let known_type = AgentPubKey::from_raw_39(bytes.clone()).is_ok()
+        || AnyDhtHash::from_raw_39(bytes.clone()).is_ok()
+        || DhtOpHash::from_raw_39(bytes.clone()).is_ok()
+        || DnaHash::from_raw_39(bytes.clone()).is_ok()
+        || NetIdHash::from_raw_39(bytes.clone()).is_ok()
+        || WasmHash::from_raw_39(bytes.clone()).is_ok();
+    let (core, location) = bytes[holo_hash::HOLO_HASH_PREFIX_LEN..].split_at(32);
+    known_type && holo_hash::encode::holo_dht_location_bytes(core) == location
+}
+
+fn parse_cell_id(arg: &str) -> anyhow::Result<CellId> {
+    let mut parts = arg.splitn(2, ':');
+    let dna = parts.next().unwrap_or_default();
+    let agent = parts
+        .next()
+        .ok_or_else(|| anyhow!("Cell must be given as <dna-hash>:<agent-key>"))?;
+    let dna = DnaHash::try_from(dna).map_err(|e| anyhow!("{:?}", e))?;
+    Ok(CellId::new(dna, parse_agent_key(agent)?))
+}
+
+fn parse_agent_key(arg: &str) -> anyhow::Result<AgentPubKey> {
+    AgentPubKey::try_from(arg).map_err(|e| anyhow!("{:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msgpack_decodes_to_json() {
+        #[derive(Debug, serde::Serialize)]
+        struct Response {
+            content: String,
+            count: u32,
+            hash: EntryHash,
+        }
+        let hash = EntryHash::from_raw_32(vec![1; 32]);
+        let response = ExternIO::encode(Response {
+            content: "hello".into(),
+            count: 3,
+            hash: hash.clone(),
+        })
+        .unwrap();
+        let json = msgpack_to_json(response.as_bytes()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "content": "hello",
+                "count": 3,
+                "hash": hash.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn only_holo_hashes_decode_as_hashes() {
+        let agent = AgentPubKey::from_raw_32(vec![4; 32]);
+        let encoded = ExternIO::encode(&agent).unwrap();
+        assert_eq!(
+            msgpack_to_json(encoded.as_bytes()).unwrap(),
+            serde_json::json!(agent.to_string())
+        );
+
+        let binary = |bytes: Vec<u8>| {
+            let mut encoded = Vec::new();
+            rmpv::encode::write_value(&mut encoded, &rmpv::Value::Binary(bytes)).unwrap();
+            encoded
+        };
+
+        // Any other 39 bytes are just bytes
+        assert_eq!(
+            msgpack_to_json(&binary(vec![4; 39])).unwrap(),
+            serde_json::json!(vec![4; 39])
+        );
+
+        // Including a known prefix with the wrong location
+        let mut wrong_location = agent.get_raw_39().to_vec();
+        wrong_location[38] ^= 1;
+        assert_eq!(
+            msgpack_to_json(&binary(wrong_location.clone())).unwrap(),
+            serde_json::json!(wrong_location)
+        );
+    }
+
+    #[test]
+    fn yaml_payload_roundtrips() {
+        let payload: serde_yaml::Value =
+            serde_yaml::from_str("{ content: hi, tags: [a, b] }").unwrap();
+        let encoded = ExternIO::encode(payload).unwrap();
+        assert_eq!(
+            msgpack_to_json(encoded.as_bytes()).unwrap(),
+            serde_json::json!({ "content": "hi", "tags": ["a", "b"] })
+        );
+    }
+
+    #[test]
+    fn parses_cell_id() {
+        let dna = DnaHash::from_raw_32(vec![2; 32]);
+        let agent = AgentPubKey::from_raw_32(vec![3; 32]);
+        let cell = parse_cell_id(&format!("{}:{}", dna, agent)).unwrap();
+        assert_eq!(cell, CellId::new(dna.clone(), agent));
+        assert!(parse_cell_id(&dna.to_string()).is_err());
+    }
+}
@@ -14,15 +14,22 @@ use anyhow::ensure;
 use holochain_conductor_api::AdminInterfaceConfig;
 use holochain_conductor_api::AdminRequest;
 use holochain_conductor_api::AdminResponse;
+use holochain_conductor_api::AppAuthenticationToken;
+use holochain_conductor_api::AppInterfaceInfo;
+use holochain_conductor_api::DhtOpStage;
+use holochain_conductor_api::DumpFullStateFilter;
+use holochain_conductor_api::FullStateDump;
 use holochain_conductor_api::InterfaceDriver;
 use holochain_p2p::kitsune_p2p;
 use holochain_p2p::kitsune_p2p::agent_store::AgentInfoSigned;
+use holochain_types::prelude::DhtOpType;
 use holochain_types::prelude::InstallAppDnaPayload;
 use holochain_types::prelude::InstallAppPayload;
 use holochain_types::prelude::InstalledCell;
-use holochain_types::prelude::{AgentPubKey, AppBundleSource};
+use holochain_types::prelude::{AgentPubKey, AppBundleSource, BundleSignature};
 use holochain_types::prelude::{CellId, InstallAppBundlePayload};
 use holochain_types::prelude::{DnaHash, InstalledApp};
+use holochain_types::prelude::{MigrateAgentPayload, RegisterDnaPayload};
 use portpicker::is_free;
 use std::convert::TryFrom;
 
@@ -72,6 +79,13 @@ pub enum AdminRequestCli {
     /// [Unimplemented].
     AddAgents,
     ListAgents(ListAgents),
+    IssueAppToken(IssueAppToken),
+    RevokeAppToken(RevokeAppToken),
+    /// Calls AdminRequest::ListAppInterfaces.
+    ListAppWs,
+    DetachAppWs(DetachAppWs),
+    BanAgent(BanAgent),
+    UnbanAgent(UnbanAgent),
 }
 #[derive(Debug, StructOpt, Clone)]
 /// Calls AdminRequest::AddAdminInterfaces
@@ -152,9 +166,9 @@ pub struct DeactivateApp {
 }
 
 #[derive(Debug, StructOpt, Clone)]
-/// Calls AdminRequest::DumpState
-/// and dumps the current cell's state.
-/// TODO: Add pretty print.
+/// Calls AdminRequest::DumpFullState
+/// and prints the current cell's state
+/// as a table of ops or as JSON.
 /// TODO: Default to dumping all cell state.
 pub struct DumpState {
     #[structopt(parse(try_from_str = parse_dna_hash))]
@@ -163,6 +177,18 @@ pub struct DumpState {
     #[structopt(parse(try_from_str = parse_agent_key))]
     /// The agent half of the cell id to dump.
     pub agent_key: AgentPubKey,
+    #[structopt(long)]
+    /// Print the dump as JSON instead of a table.
+    pub json: bool,
+    #[structopt(long = "op-type", number_of_values = 1)]
+    /// Only include ops of this type (e.g. `StoreEntry`).
+    /// Can be passed multiple times.
+    pub op_types: Vec<DhtOpType>,
+    #[structopt(long = "stage", number_of_values = 1)]
+    /// Only include ops at this stage.
+    /// One of `validation_limbo`, `integration_limbo` or `integrated`.
+    /// Can be passed multiple times.
+    pub stages: Vec<DhtOpStage>,
 }
 #[derive(Debug, StructOpt, Clone)]
 /// Calls AdminRequest::RequestAgentInfo
@@ -177,6 +203,63 @@ pub struct ListAgents {
     pub dna: Option<DnaHash>,
 }
 
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::IssueAppAuthenticationToken
+/// and prints a token that app interface
+/// connections can authenticate with.
+pub struct IssueAppToken {
+    /// The InstalledAppId to bind connections to.
+    pub app_id: String,
+    #[structopt(long)]
+    /// How long the token can be used to authenticate for.
+    /// Defaults to an hour.
+    pub expiry_seconds: Option<u64>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::RevokeAppAuthenticationToken
+/// so the token can no longer be used to authenticate.
+pub struct RevokeAppToken {
+    /// The token to revoke.
+    pub token: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::DetachAppInterface
+/// and closes the app interface and its connections.
+pub struct DetachAppWs {
+    /// The id of the app interface to detach, as listed by `list-app-ws`.
+    pub id: String,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::BanAgent
+/// and stops talking to an agent on a dna's network.
+pub struct BanAgent {
+    #[structopt(parse(try_from_str = parse_dna_hash))]
+    /// The dna network to ban the agent from.
+    pub dna: DnaHash,
+    #[structopt(parse(try_from_str = parse_agent_key))]
+    /// The agent to ban.
+    pub agent_key: AgentPubKey,
+    #[structopt(long)]
+    /// How long to ban the agent for.
+    /// If not set the agent is banned until unbanned.
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, StructOpt, Clone)]
+/// Calls AdminRequest::UnbanAgent
+/// and lifts a ban on an agent on a dna's network.
+pub struct UnbanAgent {
+    #[structopt(parse(try_from_str = parse_dna_hash))]
+    /// The dna network to unban the agent on.
+    pub dna: DnaHash,
+    #[structopt(parse(try_from_str = parse_agent_key))]
+    /// The agent to unban.
+    pub agent_key: AgentPubKey,
+}
+
 #[doc(hidden)]
 pub async fn call(holochain_path: &Path, req: Call) -> anyhow::Result<()> {
     let Call {
@@ -272,8 +355,13 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
             msg!("Deactivated app: {:?}", app_id);
         }
         AdminRequestCli::DumpState(args) => {
+            let json = args.json;
             let state = dump_state(cmd, args).await?;
-            msg!("DUMP STATE \n{}", state);
+            if json {
+                msg!("DUMP STATE \n{}", serde_json::to_string_pretty(&state)?);
+            } else {
+                msg!("DUMP STATE \n{}", state);
+            }
         }
         AdminRequestCli::AddAgents => todo!("Adding agent info via cli is coming soon"),
         AdminRequestCli::ListAgents(args) => {
@@ -323,6 +411,34 @@ async fn call_inner(cmd: &mut CmdRunner, call: AdminRequestCli) -> anyhow::Resul
                 msg!("{}\n", out);
             }
         }
+        AdminRequestCli::IssueAppToken(args) => {
+            let app_id = args.app_id.clone();
+            let token = issue_app_token(cmd, args).await?;
+            msg!("Issued token for app {:?}: {}", app_id, token.0);
+        }
+        AdminRequestCli::RevokeAppToken(args) => {
+            revoke_app_token(cmd, args).await?;
+            msg!("Revoked token");
+        }
+        AdminRequestCli::ListAppWs => {
+            let interfaces = list_app_interfaces(cmd).await?;
+            msg!("App Interfaces: {:?}", interfaces);
+        }
+        AdminRequestCli::DetachAppWs(args) => {
+            let id = args.id.clone();
+            detach_app_interface(cmd, args).await?;
+            msg!("Detached App interface {}", id);
+        }
+        AdminRequestCli::BanAgent(args) => {
+            let agent_key = args.agent_key.clone();
+            ban_agent(cmd, args).await?;
+            msg!("Banned agent {}", agent_key);
+        }
+        AdminRequestCli::UnbanAgent(args) => {
+            let agent_key = args.agent_key.clone();
+            unban_agent(cmd, args).await?;
+            msg!("Unbanned agent {}", agent_key);
+        }
     }
     Ok(())
 }
@@ -414,6 +530,13 @@ pub async fn install_app_bundle(
         path,
     } = args;
 
+    // Send a detached signature along with the bundle, if there is one
+    let signature_path = BundleSignature::detached_path(&path);
+    let signature = if signature_path.is_file() {
+        Some(BundleSignature::read_from_file(&signature_path).await?)
+    } else {
+        None
+    };
     let bundle = AppBundleSource::Path(path).resolve().await?;
 
     let agent_key = match agent_key {
@@ -426,6 +549,7 @@ pub async fn install_app_bundle(
         agent_key,
         source: AppBundleSource::Bundle(bundle),
         membrane_proofs: Default::default(),
+        signature,
     };
 
     let r = AdminRequest::InstallAppBundle(Box::new(payload));
@@ -442,6 +566,28 @@ pub async fn install_app_bundle(
     Ok(installed_app)
 }
 
+/// Calls [`AdminRequest::RegisterDna`] and returns the registered hash.
+pub async fn register_dna(
+    cmd: &mut CmdRunner,
+    payload: RegisterDnaPayload,
+) -> anyhow::Result<DnaHash> {
+    let resp = cmd
+        .command(AdminRequest::RegisterDna(Box::new(payload)))
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::DnaRegistered, "Failed to register dna"))
+}
+
+/// Calls [`AdminRequest::MigrateAgent`] and returns the slot's new cell.
+pub async fn migrate_agent(
+    cmd: &mut CmdRunner,
+    payload: MigrateAgentPayload,
+) -> anyhow::Result<CellId> {
+    let resp = cmd
+        .command(AdminRequest::MigrateAgent(Box::new(payload)))
+        .await?;
+    Ok(expect_match!(resp => AdminResponse::AgentMigrated, "Failed to migrate agent"))
+}
+
 /// Calls [`AdminRequest::ListCellIds`].
 pub async fn list_dnas(cmd: &mut CmdRunner) -> anyhow::Result<Vec<DnaHash>> {
     let resp = cmd.command(AdminRequest::ListDnas).await?;
@@ -516,16 +662,20 @@ pub async fn attach_app_interface(cmd: &mut CmdRunner, args: AddAppWs) -> anyhow
     }
 }
 
-/// Calls [`AdminRequest::DumpState`] and dumps the current cell's state.
-// TODO: Add pretty print.
+/// Calls [`AdminRequest::DumpFullState`] and dumps the current cell's state.
 // TODO: Default to dumping all cell state.
-pub async fn dump_state(cmd: &mut CmdRunner, args: DumpState) -> anyhow::Result<String> {
+pub async fn dump_state(cmd: &mut CmdRunner, args: DumpState) -> anyhow::Result<FullStateDump> {
+    let filter = DumpFullStateFilter {
+        op_types: args.op_types.clone(),
+        stages: args.stages.clone(),
+    };
     let resp = cmd
-        .command(AdminRequest::DumpState {
+        .command(AdminRequest::DumpFullState {
             cell_id: Box::new(args.into()),
+            filter: Some(filter),
         })
         .await?;
-    Ok(expect_match!(resp => AdminResponse::StateDumped, "Failed to dump state"))
+    Ok(expect_match!(resp => AdminResponse::FullStateDumped, "Failed to dump state"))
 }
 
 /// Calls [`AdminRequest::AddAgentInfo`] with and adds the list of agent info.
@@ -554,6 +704,35 @@ pub async fn request_agent_info(
     Ok(expect_match!(resp => AdminResponse::AgentInfoRequested, "Failed to request agent info"))
 }
 
+/// Calls [`AdminRequest::IssueAppAuthenticationToken`] and returns the new token.
+pub async fn issue_app_token(
+    cmd: &mut CmdRunner,
+    args: IssueAppToken,
+) -> anyhow::Result<AppAuthenticationToken> {
+    let resp = cmd
+        .command(AdminRequest::IssueAppAuthenticationToken {
+            installed_app_id: args.app_id,
+            expiry_seconds: args.expiry_seconds,
+        })
+        .await?;
+    Ok(
+        expect_match!(resp => AdminResponse::AppAuthenticationTokenIssued, "Failed to issue app token"),
+    )
+}
+
+/// Calls [`AdminRequest::RevokeAppAuthenticationToken`].
+pub async fn revoke_app_token(cmd: &mut CmdRunner, args: RevokeAppToken) -> anyhow::Result<()> {
+    let resp = cmd
+        .command(AdminRequest::RevokeAppAuthenticationToken {
+            token: AppAuthenticationToken(args.token),
+        })
+        .await?;
+    match resp {
+        AdminResponse::AppAuthenticationTokenRevoked => Ok(()),
+        _ => Err(anyhow!("Failed to revoke app token, got: {:?}", resp)),
+    }
+}
+
 fn parse_agent_key(arg: &str) -> anyhow::Result<AgentPubKey> {
     AgentPubKey::try_from(arg).map_err(|e| anyhow::anyhow!("{:?}", e))
 }
@@ -565,7 +744,13 @@ fn parse_dna_hash(arg: &str) -> anyhow::Result<DnaHash> {
 impl From<CellId> for DumpState {
     fn from(cell_id: CellId) -> Self {
         let (dna, agent_key) = cell_id.into_dna_and_agent();
-        Self { agent_key, dna }
+        Self {
+            agent_key,
+            dna,
+            json: false,
+            op_types: Vec::new(),
+            stages: Vec::new(),
+        }
     }
 }
 
@@ -585,3 +770,59 @@ impl From<ListAgents> for Option<CellId> {
             .map(|(d, a)| CellId::new(d, a))
     }
 }
+
+/// Calls [`AdminRequest::ListAppInterfaces`].
+pub async fn list_app_interfaces(cmd: &mut CmdRunner) -> anyhow::Result<Vec<AppInterfaceInfo>> {
+    let resp = cmd.command(AdminRequest::ListAppInterfaces).await?;
+    Ok(expect_match!(resp => AdminResponse::AppInterfacesListed, "Failed to list app interfaces"))
+}
+
+/// Calls [`AdminRequest::DetachAppInterface`].
+pub async fn detach_app_interface(cmd: &mut CmdRunner, args: DetachAppWs) -> anyhow::Result<()> {
+    let resp = cmd
+        .command(AdminRequest::DetachAppInterface {
+            id: args.id.clone(),
+        })
+        .await?;
+    match resp {
+        AdminResponse::AppInterfaceDetached => Ok(()),
+        _ => Err(anyhow!(
+            "Failed to detach app interface {}, got: {:?}",
+            args.id,
+            resp
+        )),
+    }
+}
+
+/// Calls [`AdminRequest::BanAgent`].
+pub async fn ban_agent(cmd: &mut CmdRunner, args: BanAgent) -> anyhow::Result<()> {
+    let resp = cmd
+        .command(AdminRequest::BanAgent {
+            dna_hash: args.dna,
+            agent_pub_key: args.agent_key,
+            duration_ms: args.duration_ms,
+        })
+        .await?;
+    ensure!(
+        matches!(resp, AdminResponse::AgentBanned),
+        "Failed to ban agent, got: {:?}",
+        resp
+    );
+    Ok(())
+}
+
+/// Calls [`AdminRequest::UnbanAgent`].
+pub async fn unban_agent(cmd: &mut CmdRunner, args: UnbanAgent) -> anyhow::Result<()> {
+    let resp = cmd
+        .command(AdminRequest::UnbanAgent {
+            dna_hash: args.dna,
+            agent_pub_key: args.agent_key,
+        })
+        .await?;
+    ensure!(
+        matches!(resp, AdminResponse::AgentUnbanned),
+        "Failed to unban agent, got: {:?}",
+        resp
+    );
+    Ok(())
+}
@@ -69,6 +69,15 @@ pub enum HcSandboxSubcommand {
     /// Make a call to a conductor's admin interface.
     Call(crate::calls::Call),
 
+    /// Call a zome function through a running conductor's app interface.
+    ZomeCall(crate::app_calls::ZomeCallCli),
+
+    /// Print the signals emitted on a running conductor's app interface.
+    Signals(crate::app_calls::SignalsCli),
+
+    /// Bring up or tear down the conductors described by a scenario file.
+    Scenario(crate::scenario::ScenarioCmd),
+
     /// List sandboxes found in `$(pwd)/.hc`.
     List {
         /// Show more verbose information.
@@ -95,6 +104,10 @@ pub struct Run {
     /// (flattened)
     #[structopt(flatten)]
     existing: Existing,
+
+    /// (flattened)
+    #[structopt(flatten)]
+    watch: crate::watch::WatchOptions,
 }
 
 impl HcSandbox {
@@ -130,7 +143,11 @@ impl HcSandbox {
                     crate::save::release_ports(std::env::current_dir()?).await?;
                 }
             }
-            HcSandboxSubcommand::Run(Run { ports, existing }) => {
+            HcSandboxSubcommand::Run(Run {
+                ports,
+                existing,
+                watch,
+            }) => {
                 let paths = existing.load()?;
                 if paths.is_empty() {
                     return Ok(());
@@ -138,7 +155,21 @@ impl HcSandbox {
                 let holochain_path = self.holochain_path.clone();
                 let force_admin_ports = self.force_admin_ports.clone();
                 tokio::task::spawn(async move {
-                    if let Err(e) = run_n(&holochain_path, paths, ports, force_admin_ports).await {
+                    let result = match watch.watch.clone() {
+                        Some(dir) => {
+                            crate::watch::run_watch(
+                                &holochain_path,
+                                paths,
+                                ports,
+                                force_admin_ports,
+                                dir,
+                                watch,
+                            )
+                            .await
+                        }
+                        None => run_n(&holochain_path, paths, ports, force_admin_ports).await,
+                    };
+                    if let Err(e) = result {
                         tracing::error!(failed_to_run = ?e);
                     }
                 });
@@ -148,6 +179,11 @@ impl HcSandbox {
             HcSandboxSubcommand::Call(call) => {
                 crate::calls::call(&self.holochain_path, call).await?
             }
+            HcSandboxSubcommand::ZomeCall(args) => crate::app_calls::zome_call_cli(args).await?,
+            HcSandboxSubcommand::Signals(args) => crate::app_calls::signals_cli(args).await?,
+            HcSandboxSubcommand::Scenario(cmd) => {
+                crate::scenario::scenario(&self.holochain_path, cmd).await?
+            }
             // HcSandboxSubcommand::Task => todo!("Running custom tasks is coming soon"),
             HcSandboxSubcommand::List { verbose } => {
                 crate::save::list(std::env::current_dir()?, verbose)?
@@ -201,7 +201,7 @@ impl From<Network> for KitsuneP2pConfig {
                 kit.transport_pool = vec![TransportConfig::Proxy {
                     sub_transport: Box::new(transport),
                     proxy_config: holochain_p2p::kitsune_p2p::ProxyConfig::RemoteProxyClient {
-                        proxy_url,
+                        proxy_urls: vec![proxy_url],
                     },
                 }]
             }
@@ -46,6 +46,12 @@
 //! ```
 //! If you have already created a sandbox previously then it will be reused
 //! (usually cleared on reboots).
+//!
+//! While working on a DNA, `--watch` repacks it whenever its manifest or wasms
+//! change and reloads it into the running app, keeping the app ports attached:
+//! ```shell
+//! hc sandbox r -p=9000 --watch ./workdir/my-dna
+//! ```
 //! #### Generate
 //! Generates new conductor sandboxes and installs apps / dnas.
 //! ```shell
@@ -73,6 +79,24 @@
 //! ```shell
 //! hc sandbox call list-cells
 //! ```
+//! #### Zome calls and signals
+//! `zome-call` calls a zome function through a running conductor's app
+//! interface, encoding a YAML or JSON payload with msgpack and printing the
+//! decoded response. `signals` prints each signal emitted on an app interface.
+//!
+//! ```shell
+//! hc sandbox zome-call 9000 <dna-hash>:<agent-key> my_zome create_post --payload '{ content: hello }'
+//! hc sandbox signals 9000
+//! ```
+//! #### Scenarios
+//! A scenario YAML file declares several conductors at once: the apps and
+//! agents each installs, the network transport, which conductors proxy for
+//! others, and whether to exchange peer info between them.
+//! See the [`scenario`] module for the format.
+//! ```shell
+//! hc sandbox scenario up ./my-scenario.yaml
+//! hc sandbox scenario down ./my-scenario.yaml
+//! ```
 //! #### List and Clean
 //! These commands allow you to list the persisted sandboxes
 //! in the current directory (from the`.hcXXX`) file.
@@ -129,6 +153,7 @@ macro_rules! msg {
     })
 }
 
+pub mod app_calls;
 pub mod calls;
 pub mod cli;
 #[doc(hidden)]
@@ -138,6 +163,8 @@ pub mod dna;
 pub mod generate;
 pub mod run;
 pub mod sandbox;
+pub mod scenario;
+pub mod watch;
 pub mod save;
 pub use cli::HcSandbox;
 
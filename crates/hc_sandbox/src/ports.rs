@@ -66,6 +66,10 @@ pub(crate) fn random_admin_port(config: &mut ConductorConfig) {
                 *port = 0;
             }
         }
+        // Unix sockets are bound to a path so don't need a free port
+        Some(AdminInterfaceConfig {
+            driver: InterfaceDriver::UnixSocket { .. },
+        }) => {}
         None => {
             let port = 0;
             config.admin_interfaces = Some(vec![AdminInterfaceConfig {
@@ -0,0 +1,696 @@
+//! Declarative multi-conductor setups.
+//!
+//! A scenario file describes a set of conductors, the apps and agents
+//! each installs, how they are networked, and whether they should learn
+//! about each other's peers up front:
+//!
+//! ```yaml
+//! name: chat-test
+//! # Where sandboxes are created, relative to this file.
+//! # Defaults to the system temp directory.
+//! root: ./sandboxes
+//! network:
+//!   transport: quic # or mem
+//!   bootstrap: https://bootstrap-staging.holo.host
+//! # Add every conductor's agent info to every other conductor.
+//! exchange_peers: true
+//! conductors:
+//!   - name: proxy
+//!     proxy: server
+//!     apps:
+//!       - id: chat
+//!         bundle: ./chat.happ
+//!   - name: alice
+//!     app_ports: [9000]
+//!     # Be hosted by the `proxy` conductor above.
+//!     # Can also be `remote: kitsune-proxy://...`.
+//!     proxy:
+//!       conductor: proxy
+//!     apps:
+//!       - id: chat
+//!         dnas: [./chat.dna]
+//!         # Install the app this many times, each with a new agent,
+//!         # as `chat-0`, `chat-1`, ...
+//!         agents: 2
+//! ```
+//!
+//! `hc sandbox scenario up` creates each conductor's sandbox as
+//! `<root>/<name>/<conductor>`, runs it, installs its apps and attaches its
+//! app ports, then exchanges peers. Sandboxes that already exist are run
+//! as they are, so a scenario can be brought back up after a restart.
+//! `hc sandbox scenario down` removes them again.
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::ensure;
+use holochain_conductor_api::InterfaceDriver;
+use holochain_p2p::kitsune_p2p::agent_store::AgentInfo;
+use holochain_p2p::kitsune_p2p::KitsuneP2pConfig;
+use holochain_p2p::kitsune_p2p::ProxyAcceptConfig;
+use holochain_p2p::kitsune_p2p::ProxyConfig;
+use holochain_p2p::kitsune_p2p::TransportConfig;
+use holochain_types::prelude::InstalledAppId;
+use structopt::StructOpt;
+use tokio::process::Child;
+use url2::Url2;
+
+use crate::calls::*;
+use crate::cmds::*;
+use crate::config::CONDUCTOR_CONFIG;
+use crate::run::run_async;
+use crate::CmdRunner;
+
+/// How long to wait for a proxy server conductor to publish its url.
+const PROXY_URL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Debug, StructOpt)]
+/// Bring a scenario file's conductors up or down.
+pub enum ScenarioCmd {
+    /// Create (if needed) and run every conductor in the scenario,
+    /// until interrupted with ctrl-c.
+    Up {
+        /// The scenario YAML file.
+        file: PathBuf,
+    },
+    /// Remove every conductor sandbox the scenario created.
+    Down {
+        /// The scenario YAML file.
+        file: PathBuf,
+    },
+}
+
+/// A set of conductors to create and run together.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Names the directory the scenario's sandboxes are created in.
+    pub name: String,
+    /// The directory to create the scenario's directory in.
+    /// Defaults to the system temp directory.
+    #[serde(default)]
+    pub root: Option<PathBuf>,
+    /// The network every conductor uses.
+    #[serde(default)]
+    pub network: ScenarioNetwork,
+    /// Add every conductor's agent info to every other conductor,
+    /// so they don't need to find each other through bootstrapping.
+    #[serde(default)]
+    pub exchange_peers: bool,
+    /// The conductors to create.
+    pub conductors: Vec<ScenarioConductor>,
+}
+
+/// The network shared by a scenario's conductors.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioNetwork {
+    /// The transport to use.
+    #[serde(default)]
+    pub transport: ScenarioTransport,
+    /// A bootstrap service for the conductors to find each other through.
+    #[serde(default)]
+    pub bootstrap: Option<Url2>,
+}
+
+/// The transport a scenario's conductors use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioTransport {
+    /// The in-process memory transport.
+    /// Conductors in different processes can't reach each other with this.
+    Mem,
+    /// The QUIC transport.
+    Quic,
+}
+
+impl Default for ScenarioTransport {
+    fn default() -> Self {
+        Self::Quic
+    }
+}
+
+/// A conductor within a [`Scenario`].
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioConductor {
+    /// Names the conductor's sandbox directory.
+    pub name: String,
+    /// App interface ports to attach.
+    #[serde(default)]
+    pub app_ports: Vec<u16>,
+    /// The QUIC interface / port to bind to.
+    #[serde(default)]
+    pub bind_to: Option<Url2>,
+    /// This conductor's part in proxying, if any.
+    #[serde(default)]
+    pub proxy: Option<ProxyRole>,
+    /// The apps to install.
+    #[serde(default)]
+    pub apps: Vec<ScenarioApp>,
+}
+
+/// A conductor's part in proxying.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyRole {
+    /// Be a proxy server, accepting all requests to proxy.
+    Server,
+    /// Be hosted by the proxy server at this url.
+    Remote(Url2),
+    /// Be hosted by the scenario's conductor of this name,
+    /// which must be a `server` and install at least one app.
+    Conductor(String),
+}
+
+/// An app installed by a [`ScenarioConductor`].
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioApp {
+    /// The installed app id.
+    /// Suffixed with `-0`, `-1`, ... when there is more than one agent.
+    pub id: InstalledAppId,
+    /// A `.happ` bundle to install.
+    #[serde(default)]
+    pub bundle: Option<PathBuf>,
+    /// `.dna` files to install, if not installing a bundle.
+    #[serde(default)]
+    pub dnas: Vec<PathBuf>,
+    /// How many agents install the app, each with a new key.
+    #[serde(default = "one")]
+    pub agents: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+impl Scenario {
+    /// Read a scenario file, resolving its paths relative to the file.
+    pub fn load(file: &Path) -> anyhow::Result<Self> {
+        let mut scenario: Self = serde_yaml::from_str(&std::fs::read_to_string(file)?)?;
+        let dir = file.parent().unwrap_or_else(|| Path::new("."));
+        let resolve = |path: &mut PathBuf| {
+            if path.is_relative() {
+                *path = dir.join(&path);
+            }
+        };
+        if let Some(root) = scenario.root.as_mut() {
+            resolve(root);
+        }
+        for app in scenario
+            .conductors
+            .iter_mut()
+            .flat_map(|c| c.apps.iter_mut())
+        {
+            if let Some(bundle) = app.bundle.as_mut() {
+                resolve(bundle);
+            }
+            app.dnas.iter_mut().for_each(&resolve);
+        }
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            is_dir_name(&self.name),
+            "Scenario name {} must be a plain directory name",
+            self.name
+        );
+        let mut names = HashSet::new();
+        for conductor in &self.conductors {
+            ensure!(
+                is_dir_name(&conductor.name),
+                "Conductor name {} must be a plain directory name",
+                conductor.name
+            );
+            ensure!(
+                names.insert(conductor.name.as_str()),
+                "Conductor name {} is used more than once",
+                conductor.name
+            );
+            for app in &conductor.apps {
+                ensure!(
+                    app.bundle.is_some() != !app.dnas.is_empty(),
+                    "App {} on conductor {} must have either a bundle or dnas",
+                    app.id,
+                    conductor.name
+                );
+                ensure!(
+                    app.agents > 0,
+                    "App {} on conductor {} must have at least one agent",
+                    app.id,
+                    conductor.name
+                );
+            }
+        }
+        for conductor in &self.conductors {
+            if let Some(ProxyRole::Conductor(server)) = &conductor.proxy {
+                let server = self
+                    .conductors
+                    .iter()
+                    .find(|c| c.name == *server)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Conductor {} is proxied by unknown conductor {}",
+                            conductor.name,
+                            server
+                        )
+                    })?;
+                ensure!(
+                    server.proxy == Some(ProxyRole::Server) && !server.apps.is_empty(),
+                    "Conductor {} is proxied by {}, which must be a proxy server with an app",
+                    conductor.name,
+                    server.name
+                );
+            }
+        }
+        if self.network.transport == ScenarioTransport::Mem && self.conductors.len() > 1 {
+            msg!("Warning: conductors using the mem transport can't reach each other");
+        }
+        Ok(())
+    }
+
+    /// The directory the scenario's sandboxes are created in.
+    pub fn dir(&self) -> PathBuf {
+        self.root
+            .clone()
+            .unwrap_or_else(std::env::temp_dir)
+            .join(&self.name)
+    }
+
+    /// The conductors in the order they must be started:
+    /// proxy servers before the conductors they host.
+    pub fn start_order(&self) -> Vec<&ScenarioConductor> {
+        let (servers, others): (Vec<_>, Vec<_>) = self
+            .conductors
+            .iter()
+            .partition(|c| c.proxy == Some(ProxyRole::Server));
+        servers.into_iter().chain(others).collect()
+    }
+
+    fn network(
+        &self,
+        conductor: &ScenarioConductor,
+        proxy_urls: &HashMap<String, Url2>,
+    ) -> anyhow::Result<KitsuneP2pConfig> {
+        if self.network.transport == ScenarioTransport::Mem {
+            let mut network = KitsuneP2pConfig::default();
+            network.bootstrap_service = self.network.bootstrap.clone();
+            return Ok(network);
+        }
+        let proxy = match &conductor.proxy {
+            None | Some(ProxyRole::Server) => None,
+            Some(ProxyRole::Remote(url)) => Some(url.clone()),
+            Some(ProxyRole::Conductor(name)) => Some(
+                proxy_urls
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Proxy conductor {} is not running", name))?,
+            ),
+        };
+        let mut network: KitsuneP2pConfig = Network {
+            transport: NetworkType::Quic(Quic {
+                bind_to: conductor.bind_to.clone(),
+                override_host: None,
+                override_port: None,
+                proxy,
+            }),
+            bootstrap: self.network.bootstrap.clone(),
+        }
+        .into();
+        if conductor.proxy == Some(ProxyRole::Server) {
+            network.transport_pool = vec![TransportConfig::Proxy {
+                sub_transport: Box::new(TransportConfig::Quic {
+                    bind_to: conductor.bind_to.clone(),
+                    override_host: None,
+                    override_port: None,
+                }),
+                proxy_config: ProxyConfig::LocalProxyServer {
+                    proxy_accept_config: Some(ProxyAcceptConfig::AcceptAll),
+                },
+            }];
+        }
+        Ok(network)
+    }
+}
+
+#[doc(hidden)]
+pub async fn scenario(holochain_path: &Path, cmd: ScenarioCmd) -> anyhow::Result<()> {
+    match cmd {
+        ScenarioCmd::Up { file } => {
+            let scenario = Scenario::load(&file)?;
+            let _conductors = up(holochain_path, &scenario).await?;
+            msg!("Scenario {} is up. Press ctrl-c to stop it.", scenario.name);
+            tokio::signal::ctrl_c().await?;
+            crate::save::release_ports(std::env::current_dir()?).await?;
+        }
+        ScenarioCmd::Down { file } => {
+            let scenario = Scenario::load(&file)?;
+            down(&scenario)?;
+            msg!("Scenario {} is down", scenario.name);
+        }
+    }
+    Ok(())
+}
+
+/// Whether a name is a single path component, so that joining it to a
+/// directory names an entry of that directory.
+fn is_dir_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+/// Create (if needed) and run every conductor in the scenario.
+/// The conductors are stopped when the returned processes are dropped.
+pub async fn up(holochain_path: &Path, scenario: &Scenario) -> anyhow::Result<Vec<Child>> {
+    let dir = scenario.dir();
+    std::fs::create_dir_all(&dir)?;
+    let hc_dir = std::env::current_dir()?;
+    let mut proxy_urls = HashMap::new();
+    let mut children = Vec::new();
+    let mut cmds = Vec::new();
+    for conductor in scenario.start_order() {
+        let path = dir.join(&conductor.name);
+        let fresh = !path.join(CONDUCTOR_CONFIG).exists();
+        if fresh {
+            let network = scenario.network(conductor, &proxy_urls)?;
+            crate::generate::generate(
+                Some(network),
+                Some(dir.clone()),
+                Some(conductor.name.clone().into()),
+            )?;
+            crate::save::save(hc_dir.clone(), vec![path.clone()])?;
+        }
+        let (port, child) = run_async(holochain_path, path.clone(), None).await?;
+        children.push(child);
+        crate::save::lock_live(hc_dir.clone(), &path, port).await?;
+        msg!(
+            "Conductor {} is running on admin port {}",
+            conductor.name,
+            port
+        );
+        let mut cmd = CmdRunner::try_new(port).await?;
+        // A sandbox from an earlier run keeps its apps and interfaces,
+        // so only add what it's missing.
+        for app in &conductor.apps {
+            install(&mut cmd, app).await?;
+        }
+        let attached: Vec<_> = list_app_interfaces(&mut cmd)
+            .await?
+            .into_iter()
+            .filter_map(|i| match i.driver {
+                InterfaceDriver::Websocket { port } => Some(port),
+                _ => None,
+            })
+            .collect();
+        for app_port in &conductor.app_ports {
+            if attached.contains(app_port) {
+                continue;
+            }
+            attach_app_interface(
+                &mut cmd,
+                AddAppWs {
+                    port: Some(*app_port),
+                },
+            )
+            .await?;
+            msg!("Attached app port {} to {}", app_port, conductor.name);
+        }
+        if conductor.proxy == Some(ProxyRole::Server) {
+            let url = proxy_url(&mut cmd).await?;
+            msg!("Conductor {} is proxying at {}", conductor.name, url);
+            proxy_urls.insert(conductor.name.clone(), url);
+        }
+        cmds.push(cmd);
+    }
+    if scenario.exchange_peers {
+        exchange_peers(&mut cmds).await?;
+    }
+    Ok(children)
+}
+
+/// Remove every conductor sandbox the scenario created.
+pub fn down(scenario: &Scenario) -> anyhow::Result<()> {
+    let dir = scenario.dir();
+    let hc_dir = std::env::current_dir()?;
+    let paths: Vec<_> = scenario
+        .conductors
+        .iter()
+        .map(|c| dir.join(&c.name))
+        .collect();
+    // Never remove anything outside of the scenario's directory,
+    // such as where a symlink in it leads.
+    for path in paths.iter().filter(|p| p.exists()) {
+        let inside = match (dir.canonicalize(), path.canonicalize()) {
+            (Ok(dir), Ok(path)) => path.parent() == Some(dir.as_path()),
+            _ => false,
+        };
+        ensure!(
+            inside,
+            "Refusing to remove {}, which is not inside {}",
+            path.display(),
+            dir.display()
+        );
+    }
+    let indices: Vec<_> = crate::save::load(hc_dir.clone())?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, p)| paths.contains(p))
+        .map(|(i, _)| i)
+        .collect();
+    if !indices.is_empty() {
+        crate::save::clean(hc_dir, indices)?;
+    }
+    // Sandboxes that weren't saved in this directory's `.hc`.
+    for path in paths.iter().filter(|p| p.is_dir()) {
+        std::fs::remove_dir_all(path)?;
+    }
+    if dir.is_dir() && std::fs::read_dir(&dir)?.next().is_none() {
+        std::fs::remove_dir(&dir)?;
+    }
+    Ok(())
+}
+
+/// Install one copy of the app per agent, skipping the copies which are
+/// already installed. Installed copies which were deactivated are activated.
+async fn install(cmd: &mut CmdRunner, app: &ScenarioApp) -> anyhow::Result<()> {
+    let active = list_active_apps(cmd).await?;
+    for i in 0..app.agents {
+        let app_id = if app.agents == 1 {
+            app.id.clone()
+        } else {
+            format!("{}-{}", app.id, i)
+        };
+        if active.contains(&app_id) {
+            continue;
+        }
+        // Only installed apps can be activated.
+        let installed = activate_app(
+            cmd,
+            ActivateApp {
+                app_id: app_id.clone(),
+            },
+        )
+        .await
+        .is_ok();
+        if installed {
+            msg!("Activated app {}", app_id);
+            continue;
+        }
+        match &app.bundle {
+            Some(path) => {
+                install_app_bundle(
+                    cmd,
+                    InstallAppBundle {
+                        app_id: Some(app_id.clone()),
+                        agent_key: None,
+                        path: path.clone(),
+                    },
+                )
+                .await?;
+            }
+            None => {
+                install_app(
+                    cmd,
+                    InstallApp {
+                        app_id: app_id.clone(),
+                        agent_key: None,
+                        dnas: app.dnas.clone(),
+                    },
+                )
+                .await?;
+            }
+        }
+        msg!("Installed app {}", app_id);
+    }
+    Ok(())
+}
+
+/// Wait for a proxy server conductor to publish agent info,
+/// which carries the url it can be reached at.
+async fn proxy_url(cmd: &mut CmdRunner) -> anyhow::Result<Url2> {
+    let started = std::time::Instant::now();
+    loop {
+        let infos = request_agent_info(
+            cmd,
+            ListAgents {
+                agent_key: None,
+                dna: None,
+            },
+        )
+        .await?;
+        for info in &infos {
+            let info = AgentInfo::try_from(info)?;
+            if let Some(url) = info.as_urls_ref().first() {
+                return Ok(url.clone());
+            }
+        }
+        if started.elapsed() > PROXY_URL_TIMEOUT {
+            bail!(
+                "Proxy server didn't publish its url within {:?}",
+                PROXY_URL_TIMEOUT
+            );
+        }
+        tokio::time::delay_for(std::time::Duration::from_millis(500)).await;
+    }
+}
+
+/// Add every conductor's agent info to every other conductor.
+async fn exchange_peers(cmds: &mut [CmdRunner]) -> anyhow::Result<()> {
+    let mut infos = Vec::with_capacity(cmds.len());
+    for cmd in cmds.iter_mut() {
+        infos.push(
+            request_agent_info(
+                cmd,
+                ListAgents {
+                    agent_key: None,
+                    dna: None,
+                },
+            )
+            .await?,
+        );
+    }
+    for (i, cmd) in cmds.iter_mut().enumerate() {
+        let others: Vec<_> = infos
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .flat_map(|(_, infos)| infos.iter().cloned())
+            .collect();
+        if !others.is_empty() {
+            add_agent_info(cmd, others).await?;
+        }
+    }
+    msg!("Exchanged peers between {} conductors", cmds.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(yaml: &str) -> anyhow::Result<Scenario> {
+        let scenario: Scenario = serde_yaml::from_str(yaml)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    #[test]
+    fn proxy_servers_start_first() {
+        let scenario = scenario(
+            r#"
+name: test
+exchange_peers: true
+conductors:
+  - name: alice
+    proxy:
+      conductor: proxy
+    apps:
+      - id: chat
+        dnas: [chat.dna]
+        agents: 2
+  - name: proxy
+    proxy: server
+    apps:
+      - id: chat
+        bundle: chat.happ
+"#,
+        )
+        .unwrap();
+        let order: Vec<_> = scenario
+            .start_order()
+            .into_iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(order, vec!["proxy", "alice"]);
+        assert_eq!(scenario.network.transport, ScenarioTransport::Quic);
+        assert_eq!(scenario.conductors[1].apps[0].agents, 1);
+
+        let alice = &scenario.conductors[0];
+        assert!(scenario.network(alice, &HashMap::new()).is_err());
+        let proxy_url = Url2::parse("kitsune-proxy://abc/kitsune-quic/h/127.0.0.1/p/5778/--");
+        let urls = vec![("proxy".to_string(), proxy_url.clone())]
+            .into_iter()
+            .collect();
+        let network = scenario.network(alice, &urls).unwrap();
+        assert_eq!(
+            network.transport_pool,
+            vec![TransportConfig::Proxy {
+                sub_transport: Box::new(TransportConfig::Quic {
+                    bind_to: None,
+                    override_host: None,
+                    override_port: None,
+                }),
+                proxy_config: ProxyConfig::RemoteProxyClient {
+                    proxy_urls: vec![proxy_url],
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_scenarios_are_rejected() {
+        // Duplicate names
+        assert!(scenario("name: t\nconductors: [{ name: a }, { name: a }]").is_err());
+        // Both a bundle and dnas
+        assert!(scenario(
+            "name: t\nconductors: [{ name: a, apps: [{ id: x, bundle: x.happ, dnas: [x.dna] }] }]"
+        )
+        .is_err());
+        // Proxied by a conductor that isn't a server
+        assert!(scenario(
+            "name: t\nconductors: [{ name: a, proxy: { conductor: b } }, { name: b }]"
+        )
+        .is_err());
+        // Names which aren't a single directory
+        for name in &["..", ".", "a/b", "/tmp", ""] {
+            assert!(scenario(&format!("name: '{}'\nconductors: [{{ name: a }}]", name)).is_err());
+            assert!(scenario(&format!("name: t\nconductors: [{{ name: '{}' }}]", name)).is_err());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn down_stays_inside_the_scenario_dir() {
+        let tmp = tempdir::TempDir::new("scenario").unwrap();
+        let outside = tmp.path().join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        let mut scenario = scenario("name: t\nconductors: [{ name: a }]").unwrap();
+        scenario.root = Some(tmp.path().to_owned());
+        std::fs::create_dir_all(scenario.dir()).unwrap();
+        std::os::unix::fs::symlink(&outside, scenario.dir().join("a")).unwrap();
+
+        assert!(down(&scenario).is_err());
+        assert!(outside.exists());
+    }
+}
@@ -0,0 +1,265 @@
+//! Watch a DNA working directory and reload it into running conductors.
+//!
+//! When the DNA manifest or any of its wasms change, the DNA is repacked and
+//! the watched app is reinstalled in each conductor under a fresh uuid, with a
+//! new app id and agent, and the old app is deactivated. With
+//! `--watch-migrate`, the app's slot is instead migrated to the new DNA in
+//! place with [`AdminRequest::MigrateAgent`], keeping the app id and agent but
+//! closing the agent's chain on the old DNA, and the app is only reinstalled
+//! if the old DNA refuses the migration. Either way the app ports are
+//! reattached if they were lost, so a UI can keep using them.
+//!
+//! [`AdminRequest::MigrateAgent`]: holochain_conductor_api::AdminRequest::MigrateAgent
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use holochain_conductor_api::AdminRequest;
+use holochain_conductor_api::AdminResponse;
+use holochain_conductor_api::InterfaceDriver;
+use holochain_types::prelude::*;
+use mr_bundle::Location;
+use mr_bundle::Manifest;
+use structopt::StructOpt;
+
+use crate::calls::*;
+use crate::expect_match;
+use crate::run::run_async;
+use crate::CmdRunner;
+
+/// How often the working directory is checked for changes.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Options for reloading a DNA into running sandboxes.
+#[derive(Debug, StructOpt, Clone)]
+pub struct WatchOptions {
+    #[structopt(short, long)]
+    /// Watch this DNA working directory (containing a `dna.yaml`),
+    /// and reload the DNA into the sandboxes whenever it or its wasms change.
+    pub watch: Option<PathBuf>,
+
+    #[structopt(long, default_value = "test-app", requires = "watch")]
+    /// The app to reload the DNA into.
+    pub watch_app_id: InstalledAppId,
+
+    #[structopt(long, requires = "watch")]
+    /// The slot to reload the DNA into.
+    /// Defaults to `<app-id>-0`, the first slot of a generated sandbox.
+    pub watch_slot: Option<SlotId>,
+
+    #[structopt(long, requires = "watch")]
+    /// Migrate the app's agent to each new DNA in place, rather than
+    /// reinstalling the app. This closes the agent's chain on the old DNA.
+    pub watch_migrate: bool,
+}
+
+/// Where the watched DNA currently lives in one conductor.
+struct Reloading {
+    cmd: CmdRunner,
+    /// The app id given on the command line
+    base_app_id: InstalledAppId,
+    /// The app currently running the DNA
+    app_id: InstalledAppId,
+    /// The hash of the last DNA loaded, registered without a uuid
+    dna_hash: Option<DnaHash>,
+    app_ports: Vec<u16>,
+    migrate: bool,
+}
+
+/// Run the sandboxes, then reload the watched DNA into each of them
+/// whenever it changes. Runs until the process is interrupted.
+pub async fn run_watch(
+    holochain_path: &Path,
+    paths: Vec<PathBuf>,
+    app_ports: Vec<u16>,
+    force_admin_ports: Vec<u16>,
+    dir: PathBuf,
+    options: WatchOptions,
+) -> anyhow::Result<()> {
+    let WatchOptions {
+        watch_app_id: app_id,
+        watch_slot: slot,
+        watch_migrate: migrate,
+        ..
+    } = options;
+    let slot = slot.unwrap_or_else(|| format!("{}-0", app_id));
+    let mut force_admin_ports = force_admin_ports.into_iter();
+    let mut app_ports = app_ports.into_iter();
+    // The conductors are stopped when these are dropped.
+    let mut children = Vec::with_capacity(paths.len());
+    let mut conductors = Vec::with_capacity(paths.len());
+    for path in paths {
+        let (port, child) =
+            run_async(holochain_path, path.clone(), force_admin_ports.next()).await?;
+        children.push(child);
+        crate::save::lock_live(std::env::current_dir()?, &path, port).await?;
+        msg!("Running conductor on admin port {}", port);
+        let mut conductor = Reloading {
+            cmd: CmdRunner::try_new(port).await?,
+            base_app_id: app_id.clone(),
+            app_id: app_id.clone(),
+            dna_hash: None,
+            app_ports: app_ports.next().into_iter().collect(),
+            migrate,
+        };
+        conductor.attach_app_ports().await?;
+        conductors.push(conductor);
+    }
+
+    msg!("Watching {} for changes", dir.display());
+    let mut last = snapshot(&dir)?;
+    loop {
+        tokio::time::delay_for(POLL_INTERVAL).await;
+        let current = match snapshot(&dir) {
+            Ok(current) => current,
+            Err(e) => {
+                msg!("Failed to read {}: {:?}", dir.display(), e);
+                continue;
+            }
+        };
+        if current == last {
+            continue;
+        }
+        last = current;
+        let dna_path = match holochain_cli_bundle::packing::pack::<DnaManifest>(&dir, None).await {
+            Ok((dna_path, _)) => dna_path,
+            Err(e) => {
+                msg!("Failed to pack {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        msg!("Repacked {}", dna_path.display());
+        for conductor in conductors.iter_mut() {
+            if let Err(e) = conductor.reload(&dna_path, &slot).await {
+                msg!("Failed to reload {}: {:?}", conductor.app_id, e);
+            }
+        }
+    }
+}
+
+impl Reloading {
+    async fn reload(&mut self, dna_path: &Path, slot: &SlotId) -> anyhow::Result<()> {
+        let dna_hash = register_dna(
+            &mut self.cmd,
+            RegisterDnaPayload {
+                uuid: None,
+                properties: None,
+                source: DnaSource::Path(dna_path.to_path_buf()),
+            },
+        )
+        .await?;
+        if self.dna_hash.as_ref() == Some(&dna_hash) {
+            msg!("DNA {} is unchanged", dna_hash);
+            return Ok(());
+        }
+        if self.migrate {
+            let migrated = migrate_agent(
+                &mut self.cmd,
+                MigrateAgentPayload {
+                    installed_app_id: self.app_id.clone(),
+                    slot_id: slot.clone(),
+                    new_dna_hash: dna_hash.clone(),
+                    membrane_proof: None,
+                },
+            )
+            .await;
+            match migrated {
+                Ok(cell_id) => {
+                    msg!("Swapped app {} slot {} to {:?}", self.app_id, slot, cell_id);
+                }
+                Err(e) => {
+                    msg!(
+                        "Couldn't swap app {} in place, reinstalling it under a fresh uuid: {:?}",
+                        self.app_id,
+                        e
+                    );
+                    self.reinstall(dna_path, slot).await?;
+                }
+            }
+        } else {
+            self.reinstall(dna_path, slot).await?;
+        }
+        self.dna_hash = Some(dna_hash);
+        self.attach_app_ports().await
+    }
+
+    async fn reinstall(&mut self, dna_path: &Path, slot: &SlotId) -> anyhow::Result<()> {
+        let uuid = nanoid::nanoid!();
+        let dna_hash = register_dna(
+            &mut self.cmd,
+            RegisterDnaPayload {
+                uuid: Some(uuid.clone()),
+                properties: None,
+                source: DnaSource::Path(dna_path.to_path_buf()),
+            },
+        )
+        .await?;
+        let app_id = format!("{}-{}", self.base_app_id, uuid);
+        let agent_key = generate_agent_pub_key(&mut self.cmd).await?;
+        let payload = InstallAppPayload {
+            installed_app_id: app_id.clone(),
+            agent_key,
+            dnas: vec![InstallAppDnaPayload::hash_only(dna_hash, slot.clone())],
+        };
+        let resp = self
+            .cmd
+            .command(AdminRequest::InstallApp(payload.into()))
+            .await?;
+        expect_match!(resp => AdminResponse::AppInstalled, "Failed to install app");
+        activate_app(
+            &mut self.cmd,
+            ActivateApp {
+                app_id: app_id.clone(),
+            },
+        )
+        .await?;
+        let old_app_id = std::mem::replace(&mut self.app_id, app_id);
+        if let Err(e) = deactivate_app(&mut self.cmd, DeactivateApp { app_id: old_app_id }).await {
+            msg!("Failed to deactivate the old app: {:?}", e);
+        }
+        msg!("Installed app {}", self.app_id);
+        Ok(())
+    }
+
+    /// Attach any of this conductor's app ports that aren't attached.
+    async fn attach_app_ports(&mut self) -> anyhow::Result<()> {
+        let attached: Vec<_> = list_app_interfaces(&mut self.cmd)
+            .await?
+            .into_iter()
+            .filter_map(|i| match i.driver {
+                InterfaceDriver::Websocket { port } => Some(port),
+                _ => None,
+            })
+            .collect();
+        for port in self.app_ports.clone() {
+            if !attached.contains(&port) {
+                attach_app_interface(&mut self.cmd, AddAppWs { port: Some(port) }).await?;
+                msg!("Attached app port {}", port);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The modification times of the DNA manifest and the wasms it refers to.
+fn snapshot(dir: &Path) -> anyhow::Result<BTreeMap<PathBuf, Option<SystemTime>>> {
+    let manifest_path = dir.join(DnaManifest::path());
+    let manifest: DnaManifest = serde_yaml::from_str(&std::fs::read_to_string(&manifest_path)?)?;
+    let modified = |path: PathBuf| {
+        let time = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        (path, time)
+    };
+    Ok(std::iter::once(manifest_path)
+        .chain(
+            manifest
+                .locations()
+                .into_iter()
+                .filter_map(|location| match location {
+                    Location::Bundled(path) | Location::Path(path) => Some(dir.join(path)),
+                    Location::Url(_) => None,
+                }),
+        )
+        .map(modified)
+        .collect())
+}
@@ -0,0 +1,137 @@
+//! `hc-sandbox zome-call` and `hc-sandbox signals` against the app interface
+//! of a conductor running in this process
+
+use assert_cmd::prelude::*;
+use holochain::conductor::ConductorHandle;
+use holochain::test_utils::sweetest::{SweetConductor, SweetDnaFile};
+use holochain_conductor_api::InterfaceDriver;
+use holochain_types::dna::zome::inline_zome::InlineZome;
+use holochain_types::prelude::*;
+use std::process::Stdio;
+use tokio::io::AsyncBufReadExt;
+use tokio::process::Command;
+
+const SIGNAL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Message {
+    content: String,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct Echo {
+    content: String,
+    agent: AgentPubKey,
+}
+
+fn echo_zome() -> InlineZome {
+    InlineZome::new_unique(vec![])
+        .callback("echo", |api, message: Message| {
+            Ok(Echo {
+                content: message.content,
+                agent: api.agent_info(())?.agent_latest_pubkey,
+            })
+        })
+        .callback("signal", |api, message: Message| {
+            api.emit_signal(AppSignal::new(ExternIO::encode(message).unwrap()))
+                .map_err(Into::into)
+        })
+}
+
+/// A conductor running the echo zome for one agent, with an app interface
+/// attached. Returns the conductor, the app port and the cell as
+/// `<dna-hash>:<agent-key>`.
+async fn setup() -> (SweetConductor, u16, String) {
+    let (dna, _) = SweetDnaFile::unique_from_inline_zome("echo", echo_zome())
+        .await
+        .unwrap();
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = conductor.setup_app("app", &[dna]).await;
+    let cell_id = app.cells()[0].cell_id().clone();
+    let port = ConductorHandle::clone(&conductor)
+        .add_app_interface(InterfaceDriver::Websocket { port: 0 })
+        .await
+        .unwrap()
+        .port()
+        .unwrap();
+    let cell = format!("{}:{}", cell_id.dna_hash(), cell_id.agent_pubkey());
+    (conductor, port, cell)
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn zome_call_prints_decoded_response() {
+    let (_conductor, port, cell) = setup().await;
+    let agent = cell.splitn(2, ':').nth(1).unwrap().to_string();
+
+    let output = Command::from(std::process::Command::cargo_bin("hc-sandbox").unwrap())
+        .args(&["zome-call", &port.to_string(), &cell, "echo", "echo"])
+        .args(&["--payload", "{ content: hello }"])
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    // The response is printed as JSON after the message line,
+    // with the agent key shown as a hash
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let json = &stdout[stdout.find('{').unwrap()..];
+    let response: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        response,
+        serde_json::json!({ "content": "hello", "agent": agent })
+    );
+
+    // A function which doesn't exist is an error
+    let output = Command::from(std::process::Command::cargo_bin("hc-sandbox").unwrap())
+        .args(&["zome-call", &port.to_string(), &cell, "echo", "missing"])
+        .output()
+        .await
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn signals_prints_each_signal() {
+    let (_conductor, port, cell) = setup().await;
+
+    let mut signals = Command::from(std::process::Command::cargo_bin("hc-sandbox").unwrap())
+        .args(&["signals", &port.to_string()])
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+    let mut lines = tokio::io::BufReader::new(signals.stdout.take().unwrap()).lines();
+
+    // Only signal once the listener is connected
+    let line = tokio::time::timeout(SIGNAL_TIMEOUT, lines.next_line())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert!(line.contains("Listening for signals"), "{}", line);
+
+    let output = Command::from(std::process::Command::cargo_bin("hc-sandbox").unwrap())
+        .args(&["zome-call", &port.to_string(), &cell, "echo", "signal"])
+        .args(&["--payload", "{ content: hi }"])
+        .output()
+        .await
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let mut printed = String::new();
+    let received = tokio::time::timeout(SIGNAL_TIMEOUT, async {
+        while let Some(line) = lines.next_line().await.unwrap() {
+            printed.push_str(&line);
+            printed.push('\n');
+            if line.starts_with('}') {
+                return;
+            }
+        }
+    })
+    .await;
+    assert!(received.is_ok(), "No signal printed, got: {}", printed);
+    assert!(printed.contains(&format!("App signal from cell {}", cell)));
+    let json = &printed[printed.find('{').unwrap()..];
+    let signal: serde_json::Value = serde_json::from_str(json).unwrap();
+    assert_eq!(signal, serde_json::json!({ "content": "hi" }));
+}
@@ -154,13 +154,14 @@
 //!   - Close runs when an agent is deprecating an old source chain in favour of a new one
 //!   - All zomes in a DNA migrate at the same time
 //!   - Any failure fails the migration
-//! - `function post_commit(headers: Vec<HeaderHash>) -> ExternResult<PostCommitResult>`:
-//!   - Allows the guest a final veto to entry commits or to perform side effects in response
-//!   - Executes after the wasm call that originated the commits so not bound by the original atomic transaction
-//!   - Guest is guaranteed that the commits will not be rolled back if Ok(PostCommitResult::Pass) is returned
+//!   - Triggered by the `migrate_agent` admin call, which commits `CloseChain` to the old chain and `OpenChain` to the new one
+//! - `function post_commit(headers: Vec<HeaderHash>) -> ExternResult<PostCommitCallbackResult>`:
+//!   - Allows the guest to perform side effects in response to entry commits
+//!   - Executes after the commits of the originating zome call have been written to the source chain
+//!   - Not bound by the original atomic transaction and does not block the zome call's response
 //!   - Input is all the header hashes that were committed
 //!   - Only the zome that originated the commits is called
-//!   - Any failure fails (rolls back) all commits
+//!   - Commits are never rolled back; a failure is logged and emitted as a `PostCommitFailed` system signal
 //! - `function validate_create_link(create_link_data: ValidateCreateLinkData) -> ExternResult<ValidateLinkResult>`:
 //!   - Allows the guest to pass/fail/retry link creation validation
 //!   - Only the zome that created the link is called
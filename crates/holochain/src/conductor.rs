@@ -28,6 +28,7 @@ pub mod handle;
 pub mod interactive;
 pub mod interface;
 pub mod manager;
+pub mod metrics;
 pub mod p2p_store;
 pub mod paths;
 pub mod state;
@@ -1,5 +1,6 @@
 use crate::conductor::interface::error::InterfaceResult;
 use holochain_serialized_bytes::prelude::*;
+use holochain_types::signal::Signal;
 
 mod admin_interface;
 mod app_interface;
@@ -18,4 +19,17 @@ pub trait InterfaceApi: 'static + Send + Sync + Clone {
         &self,
         request: Result<Self::ApiRequest, SerializedBytesError>,
     ) -> InterfaceResult<Self::ApiResponse>;
+
+    /// Get a copy of this API to serve a newly established connection.
+    /// Any per-connection state, such as authentication, must not be
+    /// shared with other connections.
+    fn for_connection(&self) -> Self {
+        self.clone()
+    }
+
+    /// Whether a signal should be sent out over the connection this API
+    /// is serving.
+    async fn accepts_signal(&self, _signal: &Signal) -> bool {
+        true
+    }
 }
@@ -115,6 +115,14 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::CloneCellCreated(cell_id))
             }
+            MigrateAgent(payload) => {
+                let cell_id = self
+                    .conductor_handle
+                    .clone()
+                    .migrate_agent(*payload)
+                    .await?;
+                Ok(AdminResponse::AgentMigrated(cell_id))
+            }
             InstallApp(payload) => {
                 trace!(?payload.dnas);
                 let InstallAppPayload {
@@ -186,6 +194,14 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     .await?;
                 Ok(AdminResponse::AppBundleInstalled(app))
             }
+            InstallWebAppBundle(payload) => {
+                let info = self
+                    .conductor_handle
+                    .clone()
+                    .install_web_app_bundle(*payload)
+                    .await?;
+                Ok(AdminResponse::WebAppBundleInstalled(info))
+            }
             ListDnas => {
                 let dna_list = self.conductor_handle.list_dnas().await?;
                 Ok(AdminResponse::DnasListed(dna_list))
@@ -240,17 +256,47 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
             }
             AttachAppInterface { port } => {
                 let port = port.unwrap_or(0);
-                let port = self
+                let driver = self
                     .conductor_handle
                     .clone()
-                    .add_app_interface(port)
+                    .add_app_interface(InterfaceDriver::Websocket { port })
                     .await?;
+                let port = driver.port().expect("Websocket driver always has a port");
                 Ok(AdminResponse::AppInterfaceAttached { port })
             }
+            AttachAppUnixSocketInterface { path } => {
+                let driver = self
+                    .conductor_handle
+                    .clone()
+                    .add_app_interface(InterfaceDriver::UnixSocket { path })
+                    .await?;
+                let path = match driver {
+                    InterfaceDriver::UnixSocket { path } => path,
+                    _ => unreachable!("a unix socket driver stays a unix socket driver"),
+                };
+                Ok(AdminResponse::AppUnixSocketInterfaceAttached { path })
+            }
+            ListAppInterfaces => {
+                let interfaces = self.conductor_handle.list_app_interfaces().await?;
+                Ok(AdminResponse::AppInterfacesListed(interfaces))
+            }
+            DetachAppInterface { id } => {
+                self.conductor_handle
+                    .detach_app_interface(id.into())
+                    .await?;
+                Ok(AdminResponse::AppInterfaceDetached)
+            }
             DumpState { cell_id } => {
                 let state = self.conductor_handle.dump_cell_state(&cell_id).await?;
                 Ok(AdminResponse::StateDumped(state))
             }
+            DumpFullState { cell_id, filter } => {
+                let state = self
+                    .conductor_handle
+                    .dump_full_cell_state(&cell_id, filter.unwrap_or_default())
+                    .await?;
+                Ok(AdminResponse::FullStateDumped(state))
+            }
             AddAgentInfo { agent_infos } => {
                 self.conductor_handle.add_agent_infos(agent_infos).await?;
                 Ok(AdminResponse::AgentInfoAdded)
@@ -259,6 +305,41 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let r = self.conductor_handle.get_agent_infos(cell_id).await?;
                 Ok(AdminResponse::AgentInfoRequested(r))
             }
+            IssueAppAuthenticationToken {
+                installed_app_id,
+                expiry_seconds,
+            } => {
+                let token = self
+                    .conductor_handle
+                    .issue_app_authentication_token(installed_app_id, expiry_seconds)
+                    .await?;
+                Ok(AdminResponse::AppAuthenticationTokenIssued(token))
+            }
+            RevokeAppAuthenticationToken { token } => {
+                self.conductor_handle
+                    .revoke_app_authentication_token(&token)
+                    .await;
+                Ok(AdminResponse::AppAuthenticationTokenRevoked)
+            }
+            BanAgent {
+                dna_hash,
+                agent_pub_key,
+                duration_ms,
+            } => {
+                self.conductor_handle
+                    .ban_agent(dna_hash, agent_pub_key, duration_ms)
+                    .await?;
+                Ok(AdminResponse::AgentBanned)
+            }
+            UnbanAgent {
+                dna_hash,
+                agent_pub_key,
+            } => {
+                self.conductor_handle
+                    .unban_agent(dna_hash, agent_pub_key)
+                    .await?;
+                Ok(AdminResponse::AgentUnbanned)
+            }
         }
     }
 }
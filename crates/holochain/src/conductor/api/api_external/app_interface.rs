@@ -1,4 +1,5 @@
 use super::InterfaceApi;
+use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::error::ConductorApiResult;
 use crate::conductor::api::error::ExternalApiWireError;
 use crate::conductor::api::error::SerializationError;
@@ -10,6 +11,7 @@ use crate::conductor::ConductorHandle;
 use holochain_serialized_bytes::prelude::*;
 
 use holochain_types::prelude::*;
+use std::sync::Arc;
 
 pub use holochain_conductor_api::*;
 
@@ -41,6 +43,9 @@ pub trait AppInterfaceApi: 'static + Send + Sync + Clone {
 pub struct RealAppInterfaceApi {
     conductor_handle: ConductorHandle,
     interface_id: AppInterfaceId,
+    /// The App this connection has authenticated as, if any.
+    /// Shared between clones serving the same connection.
+    authenticated_app: Arc<parking_lot::RwLock<Option<InstalledAppId>>>,
 }
 
 impl RealAppInterfaceApi {
@@ -49,6 +54,71 @@ impl RealAppInterfaceApi {
         Self {
             conductor_handle,
             interface_id,
+            authenticated_app: Arc::new(parking_lot::RwLock::new(None)),
+        }
+    }
+
+    /// Check that this connection may access the given App
+    async fn authorize_app(&self, installed_app_id: &InstalledAppId) -> ConductorApiResult<()> {
+        let authenticated_app = self.authenticated_app.read().clone();
+        match authenticated_app {
+            Some(app_id) if &app_id == installed_app_id => Ok(()),
+            Some(app_id) => Err(ConductorApiError::AppUnauthenticated(format!(
+                "Connection is bound to app {} and cannot access app {}",
+                app_id, installed_app_id
+            ))),
+            None => self.check_unauthenticated_allowed().await,
+        }
+    }
+
+    /// Check that this connection may call into the given cell as the given agent
+    async fn authorize_zome_call(&self, call: &ZomeCall) -> ConductorApiResult<()> {
+        let authenticated_app = self.authenticated_app.read().clone();
+        let installed_app_id = match authenticated_app {
+            Some(app_id) => app_id,
+            None => return self.check_unauthenticated_allowed().await,
+        };
+        if !self.app_has_cell(&installed_app_id, &call.cell_id).await? {
+            return Err(ConductorApiError::AppUnauthenticated(format!(
+                "Cell {:?} does not belong to app {}",
+                call.cell_id, installed_app_id
+            )));
+        }
+        if &call.provenance != call.cell_id.agent_pubkey() {
+            return Err(ConductorApiError::AppUnauthenticated(format!(
+                "Provenance {} is not the agent of app {}",
+                call.provenance, installed_app_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check whether the given cell belongs to the given App
+    async fn app_has_cell(
+        &self,
+        installed_app_id: &InstalledAppId,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<bool> {
+        let app = self
+            .conductor_handle
+            .get_app_info(installed_app_id)
+            .await?
+            .ok_or_else(|| {
+                ConductorApiError::AppUnauthenticated(format!(
+                    "App {} is no longer installed",
+                    installed_app_id
+                ))
+            })?;
+        Ok(app.cell_data.iter().any(|cell| cell.as_id() == cell_id))
+    }
+
+    async fn check_unauthenticated_allowed(&self) -> ConductorApiResult<()> {
+        if self.conductor_handle.require_app_authentication().await {
+            Err(ConductorApiError::AppUnauthenticated(
+                "Connection must authenticate before making requests".to_string(),
+            ))
+        } else {
+            Ok(())
         }
     }
 }
@@ -61,11 +131,36 @@ impl AppInterfaceApi for RealAppInterfaceApi {
         request: AppRequest,
     ) -> ConductorApiResult<AppResponse> {
         match request {
-            AppRequest::AppInfo { installed_app_id } => Ok(AppResponse::AppInfo(
-                self.conductor_handle
-                    .get_app_info(&installed_app_id)
-                    .await?,
-            )),
+            AppRequest::Authenticate { token } => {
+                let installed_app_id = self
+                    .conductor_handle
+                    .app_for_authentication_token(&token)
+                    .await?
+                    .ok_or_else(|| {
+                        ConductorApiError::AppUnauthenticated(
+                            "Invalid authentication token".to_string(),
+                        )
+                    })?;
+                let mut authenticated_app = self.authenticated_app.write();
+                match authenticated_app.as_ref() {
+                    Some(app_id) if app_id != &installed_app_id => {
+                        return Err(ConductorApiError::AppUnauthenticated(format!(
+                            "Connection is already bound to app {}",
+                            app_id
+                        )))
+                    }
+                    _ => *authenticated_app = Some(installed_app_id.clone()),
+                }
+                Ok(AppResponse::Authenticated { installed_app_id })
+            }
+            AppRequest::AppInfo { installed_app_id } => {
+                self.authorize_app(&installed_app_id).await?;
+                Ok(AppResponse::AppInfo(
+                    self.conductor_handle
+                        .get_app_info(&installed_app_id)
+                        .await?,
+                ))
+            }
             AppRequest::ZomeCallInvocation(call) => {
                 tracing::warn!(
                     "AppRequest::ZomeCallInvocation is deprecated, use AppRequest::ZomeCall (TODO: update conductor-api)"
@@ -82,6 +177,7 @@ impl AppInterfaceApi for RealAppInterfaceApi {
                     })
             }
             AppRequest::ZomeCall(call) => {
+                self.authorize_zome_call(&call).await?;
                 match self.conductor_handle.call_zome(*call.clone()).await? {
                     Ok(ZomeCallResponse::Ok(output)) => Ok(AppResponse::ZomeCall(Box::new(output))),
                     Ok(ZomeCallResponse::Unauthorized(_, _, _, _)) => Ok(AppResponse::Error(
@@ -107,6 +203,30 @@ impl AppInterfaceApi for RealAppInterfaceApi {
 impl InterfaceApi for RealAppInterfaceApi {
     type ApiRequest = AppRequest;
     type ApiResponse = AppResponse;
+
+    fn for_connection(&self) -> Self {
+        Self::new(self.conductor_handle.clone(), self.interface_id.clone())
+    }
+
+    /// An authenticated connection only receives the signals of its own
+    /// App's cells, while an unauthenticated one receives all signals,
+    /// if it is allowed to exist at all.
+    async fn accepts_signal(&self, signal: &Signal) -> bool {
+        let authenticated_app = self.authenticated_app.read().clone();
+        let installed_app_id = match authenticated_app {
+            Some(app_id) => app_id,
+            None => return self.check_unauthenticated_allowed().await.is_ok(),
+        };
+        let cell_id = match signal {
+            Signal::App(cell_id, _) => cell_id,
+            Signal::System(SystemSignal::PostCommitFailed { cell_id, .. }) => cell_id,
+            Signal::System(_) => return true,
+        };
+        self.app_has_cell(&installed_app_id, cell_id)
+            .await
+            .unwrap_or(false)
+    }
+
     async fn handle_request(
         &self,
         request: Result<Self::ApiRequest, SerializedBytesError>,
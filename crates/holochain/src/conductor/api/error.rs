@@ -90,6 +90,10 @@ pub enum ConductorApiError {
 
     #[error(transparent)]
     JsonDumpError(#[from] serde_json::Error),
+
+    /// The app interface connection is not permitted to make this request
+    #[error("App interface connection is not authenticated for this request: {0}")]
+    AppUnauthenticated(String),
 }
 
 /// All the serialization errors that can occur
@@ -113,6 +117,7 @@ impl From<ConductorApiError> for ExternalApiWireError {
     fn from(err: ConductorApiError) -> Self {
         match err {
             ConductorApiError::DnaReadError(e) => ExternalApiWireError::DnaReadError(e),
+            ConductorApiError::AppUnauthenticated(e) => ExternalApiWireError::AppUnauthenticated(e),
             e => ExternalApiWireError::internal(e),
         }
     }
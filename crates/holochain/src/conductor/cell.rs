@@ -7,6 +7,7 @@
 use super::api::ZomeCall;
 use super::interface::SignalBroadcaster;
 use super::manager::ManagedTaskAdd;
+use super::metrics;
 use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::CellConductorApi;
 use crate::conductor::api::CellConductorApiT;
@@ -17,6 +18,7 @@ use crate::core::queue_consumer::spawn_queue_consumer_tasks;
 use crate::core::queue_consumer::InitialQueueTriggers;
 use crate::core::queue_consumer::QueueTriggers;
 use crate::core::ribosome::guest_callback::init::InitResult;
+use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentResult;
 use crate::core::ribosome::real_ribosome::RealRibosome;
 use crate::core::ribosome::ZomeCallInvocation;
 use crate::core::workflow::call_zome_workflow;
@@ -24,12 +26,14 @@ use crate::core::workflow::error::WorkflowError;
 use crate::core::workflow::genesis_workflow::genesis_workflow;
 use crate::core::workflow::incoming_dht_ops_workflow::incoming_dht_ops_workflow;
 use crate::core::workflow::initialize_zomes_workflow;
+use crate::core::workflow::migrate_agent_workflow;
 use crate::core::workflow::produce_dht_ops_workflow::dht_op_light::light_to_op;
 use crate::core::workflow::CallZomeWorkflowArgs;
 use crate::core::workflow::CallZomeWorkspace;
 use crate::core::workflow::GenesisWorkflowArgs;
 use crate::core::workflow::GenesisWorkspace;
 use crate::core::workflow::InitializeZomesWorkflowArgs;
+use crate::core::workflow::MigrateAgentWorkflowArgs;
 use crate::core::workflow::ZomeCallResult;
 use call_zome_workflow::call_zome_workspace_lock::CallZomeWorkspaceLock;
 use error::CellError;
@@ -465,7 +469,7 @@ impl Cell {
         if header.author() == self.id.agent_pubkey() {
             validation_package::get_as_author(
                 header,
-                env,
+                self.env.clone(),
                 &ribosome,
                 &self.conductor_api,
                 &self.holochain_p2p_cell,
@@ -687,7 +691,8 @@ impl Cell {
             signal_tx,
             is_root_zome_call,
         };
-        Ok(call_zome_workflow(
+        let start = std::time::Instant::now();
+        let result = call_zome_workflow(
             workspace_lock,
             self.holochain_p2p_cell.clone(),
             keystore,
@@ -696,7 +701,15 @@ impl Cell {
             self.queue_triggers.produce_dht_ops.clone(),
         )
         .await
-        .map_err(Box::new)?)
+        .map_err(Box::new)?;
+        // Nested calls are already timed as part of their root call
+        if is_root_zome_call {
+            metrics::record_zome_call(start.elapsed());
+            if result.is_err() {
+                metrics::record_ribosome_error();
+            }
+        }
+        Ok(result)
     }
 
     /// Check if each Zome's init callback has been run, and if not, run it.
@@ -747,6 +760,49 @@ impl Cell {
         Ok(())
     }
 
+    /// Run the `migrate_agent` callbacks and declare the migration on this
+    /// Cell's chain: `Close` commits a CloseChain header naming the DNA being
+    /// migrated to, after which nothing more can be committed; `Open` commits an
+    /// OpenChain header naming the DNA being migrated from.
+    #[tracing::instrument(skip(self))]
+    pub async fn migrate_agent(
+        &self,
+        migrate_agent: MigrateAgent,
+        other_dna_hash: DnaHash,
+    ) -> CellResult<()> {
+        // An opened chain must have run init first, otherwise the OpenChain
+        // header would be mistaken for the init marker
+        if let MigrateAgent::Open = migrate_agent {
+            self.check_or_run_zome_init().await?;
+        }
+
+        let env = self.env.clone();
+        let workspace = CallZomeWorkspace::new(env.clone().into())
+            .map_err(WorkflowError::from)
+            .map_err(Box::new)?;
+        let dna_file = self
+            .conductor_api
+            .get_dna(self.dna_hash())
+            .await
+            .ok_or_else(|| DnaError::DnaMissing(self.dna_hash().to_owned()))?;
+        let args = MigrateAgentWorkflowArgs {
+            dna_def: dna_file.dna_def().clone(),
+            ribosome: RealRibosome::new(dna_file),
+            migrate_agent,
+            other_dna_hash,
+        };
+        let result = migrate_agent_workflow(workspace, env.into(), args)
+            .await
+            .map_err(Box::new)?;
+        match result {
+            MigrateAgentResult::Pass => {
+                self.queue_triggers.produce_dht_ops.clone().trigger();
+                Ok(())
+            }
+            r => Err(CellError::MigrateAgentFailed(r)),
+        }
+    }
+
     /// Delete all data associated with this Cell by deleting the associated
     /// LMDB environment. Completely reverses Cell creation.
     #[tracing::instrument(skip(self))]
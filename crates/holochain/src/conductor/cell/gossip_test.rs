@@ -85,11 +85,18 @@ async fn signature_smoke_test() {
     network_config.transport_pool = vec![kitsune_p2p::TransportConfig::Mem {}];
     // Hit an actual bootstrap service so it can blow up and return an error if we get our end of
     // things totally wrong.
-    network_config.bootstrap_service = Some(url2::url2!("{}", kitsune_p2p::BOOTSTRAP_SERVICE_DEV));
+    let bootstrap = kitsune_p2p_bootstrap::spawn_bootstrap_service(
+        ([127, 0, 0, 1], 0).into(),
+        kitsune_p2p_bootstrap::Store::new(),
+    )
+    .await
+    .unwrap();
+    network_config.bootstrap_service = Some(bootstrap.url());
     let zomes = vec![TestWasm::Anchor];
     let mut conductor_test =
         ConductorTestData::with_network_config(zomes.clone(), false, network_config.clone()).await;
     conductor_test.shutdown_conductor().await;
+    bootstrap.shutdown().await.unwrap();
 }
 
 #[tokio::test(threaded_scheduler)]
@@ -1,5 +1,6 @@
 use call_zome_workflow::CallZomeWorkspaceLock;
 use holochain_lmdb::env::EnvironmentRead;
+use holochain_lmdb::env::EnvironmentWrite;
 use holochain_lmdb::error::DatabaseResult;
 use holochain_lmdb::prelude::*;
 use holochain_p2p::HolochainP2pCell;
@@ -11,6 +12,8 @@ use crate::core::ribosome::RibosomeT;
 use crate::core::workflow::app_validation_workflow::validation_package::get_as_author_custom;
 use crate::core::workflow::app_validation_workflow::validation_package::get_as_author_full;
 use crate::core::workflow::app_validation_workflow::validation_package::get_as_author_sub_chain;
+use crate::core::workflow::app_validation_workflow::validation_package::oversized_package;
+use crate::core::workflow::app_validation_workflow::validation_package::MAX_VALIDATION_PACKAGE_SIZE;
 use holochain_cascade::Cascade;
 use holochain_cascade::DbPair;
 use holochain_cascade::DbPairMut;
@@ -50,7 +53,7 @@ impl ValidationPackageDb {
 #[instrument(skip(header_hashed, env, ribosome, conductor_api, network))]
 pub(super) async fn get_as_author(
     header_hashed: HeaderHashed,
-    env: EnvironmentRead,
+    env: EnvironmentWrite,
     ribosome: &impl RibosomeT,
     conductor_api: &impl CellConductorApiT,
     network: &HolochainP2pCell,
@@ -58,7 +61,7 @@ pub(super) async fn get_as_author(
     let header = header_hashed.as_content();
 
     // Get the source chain with public data only
-    let source_chain = SourceChain::public_only(env.clone())?;
+    let source_chain = SourceChain::public_only(env.clone().into())?;
 
     // Get the header data
     let (app_entry_type, header_seq) = match header
@@ -85,62 +88,51 @@ pub(super) async fn get_as_author(
         None => return Ok(None.into()),
     };
 
+    // Return the package if it has already been built
+    if let RequiredValidationType::SubChain
+    | RequiredValidationType::Full
+    | RequiredValidationType::Custom = required_validation_type
+    {
+        let element_authored = ElementBuf::authored(env.clone().into(), false)?;
+        let meta_authored = MetadataBuf::authored(env.clone().into())?;
+        let mut element_cache = ElementBuf::cache(env.clone().into())?;
+        let mut meta_cache = MetadataBuf::cache(env.clone().into())?;
+        let cascade = Cascade::empty()
+            .with_cache(DbPairMut::new(&mut element_cache, &mut meta_cache))
+            .with_authored(DbPair::new(&element_authored, &meta_authored));
+
+        if let Some(elements) = cascade.get_validation_package_local(&header_hashed.as_hash())? {
+            return Ok(Some(ValidationPackage::new(elements)).into());
+        }
+    }
+
     // Gather the package
-    match required_validation_type {
+    let validation_package = match required_validation_type {
         RequiredValidationType::Element => {
             // TODO: I'm not sure if we should handle this case, it seems like they should already have the element
-            Ok(None.into())
+            return Ok(None.into());
         }
-        RequiredValidationType::SubChain => Ok(Some(get_as_author_sub_chain(
-            header_seq,
-            app_entry_type,
-            &source_chain,
-        )?)
-        .into()),
-        RequiredValidationType::Full => {
-            Ok(Some(get_as_author_full(header_seq, &source_chain)?).into())
+        RequiredValidationType::SubChain => {
+            get_as_author_sub_chain(header_seq, app_entry_type, &source_chain)?
         }
+        RequiredValidationType::Full => get_as_author_full(header_seq, &source_chain)?,
         RequiredValidationType::Custom => {
-            let element_authored = ElementBuf::authored(env.clone(), false)?;
-            let meta_authored = MetadataBuf::authored(env.clone())?;
-            let mut element_cache = ElementBuf::cache(env.clone())?;
-            let mut meta_cache = MetadataBuf::cache(env.clone())?;
-            let cascade = Cascade::empty()
-                .with_cache(DbPairMut::new(&mut element_cache, &mut meta_cache))
-                .with_authored(DbPair::new(&element_authored, &meta_authored));
-
-            if let Some(elements) =
-                cascade.get_validation_package_local(&header_hashed.as_hash())?
-            {
-                return Ok(Some(ValidationPackage::new(elements)).into());
-            }
-
-            let workspace_lock = CallZomeWorkspaceLock::new(CallZomeWorkspace::new(env)?);
+            let workspace_lock =
+                CallZomeWorkspaceLock::new(CallZomeWorkspace::new(env.clone().into())?);
             let result =
                 match get_as_author_custom(&header_hashed, ribosome, network, workspace_lock)? {
                     Some(result) => result,
                     None => return Ok(None.into()),
                 };
             match result {
-                ValidationPackageResult::Success(validation_package) => {
-                    // Cache the package for future calls
-                    meta_cache.register_validation_package(
-                        header_hashed.as_hash(),
-                        validation_package
-                            .0
-                            .iter()
-                            .map(|el| el.header_address().clone()),
-                    );
-
-                    Ok(Some(validation_package).into())
-                }
+                ValidationPackageResult::Success(validation_package) => validation_package,
                 ValidationPackageResult::Fail(reason) => {
                     warn!(
                         msg = "Getting custom validation package fail",
                         error = %reason,
                         ?header
                     );
-                    Ok(None.into())
+                    return Ok(None.into());
                 }
                 ValidationPackageResult::UnresolvedDependencies(deps) => {
                     info!(
@@ -148,18 +140,42 @@ pub(super) async fn get_as_author(
                         missing_dependencies = ?deps,
                         ?header
                     );
-                    Ok(None.into())
+                    return Ok(None.into());
                 }
                 ValidationPackageResult::NotImplemented => {
                     error!(
                         msg = "Entry definition specifies a custom validation package but the callback isn't defined",
                         ?header
                     );
-                    Ok(None.into())
+                    return Ok(None.into());
                 }
             }
         }
+    };
+
+    // Don't send packages which validators will refuse
+    if let Some(size) = oversized_package(&validation_package)? {
+        warn!(
+            msg = "Validation package is too large to send",
+            size,
+            max = MAX_VALIDATION_PACKAGE_SIZE,
+            ?header
+        );
+        return Ok(None.into());
     }
+
+    // Cache the package for future calls
+    let mut meta_cache = MetadataBuf::cache(env.clone().into())?;
+    meta_cache.register_validation_package(
+        header_hashed.as_hash(),
+        validation_package
+            .0
+            .iter()
+            .map(|el| el.header_address().clone()),
+    );
+    env.with_commit(|writer| meta_cache.flush_to_txn(writer))?;
+
+    Ok(Some(validation_package).into())
 }
 
 pub(super) async fn get_as_authority(
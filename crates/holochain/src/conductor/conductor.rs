@@ -12,6 +12,7 @@ use super::api::RealAdminInterfaceApi;
 use super::api::RealAppInterfaceApi;
 use super::config::AdminInterfaceConfig;
 use super::config::InterfaceDriver;
+use super::config::MetricsConfig;
 use super::dna_store::DnaDefBuf;
 use super::dna_store::RealDnaStore;
 use super::entry_def_store::get_entry_defs;
@@ -20,9 +21,10 @@ use super::error::ConductorError;
 use super::error::CreateAppError;
 use super::handle::ConductorHandleImpl;
 use super::interface::error::InterfaceResult;
+use super::interface::websocket::canonical_driver;
 use super::interface::websocket::spawn_admin_interface_task;
 use super::interface::websocket::spawn_app_interface_task;
-use super::interface::websocket::spawn_websocket_listener;
+use super::interface::websocket::spawn_listener;
 use super::interface::websocket::SIGNAL_BUFFER_SIZE;
 use super::interface::SignalBroadcaster;
 use super::manager::keep_alive_task;
@@ -30,6 +32,8 @@ use super::manager::spawn_task_manager;
 use super::manager::ManagedTaskAdd;
 use super::manager::ManagedTaskHandle;
 use super::manager::TaskManagerRunHandle;
+use super::metrics::spawn_metrics_listener;
+use super::metrics::MetricsSources;
 use super::p2p_store;
 use super::p2p_store::all_agent_infos;
 use super::p2p_store::get_single_agent_info;
@@ -37,6 +41,7 @@ use super::p2p_store::inject_agent_infos;
 use super::paths::EnvironmentRootPath;
 use super::state::AppInterfaceId;
 use super::state::ConductorState;
+use super::state::UnclosedMigration;
 use super::CellError;
 use super::{api::CellConductorApi, state::AppInterfaceConfig};
 use super::{api::CellConductorApiT, interface::AppInterfaceRuntime};
@@ -53,6 +58,10 @@ use futures::future;
 use futures::future::TryFutureExt;
 use futures::stream::StreamExt;
 use holo_hash::DnaHash;
+use holochain_conductor_api::AppAuthenticationToken;
+use holochain_conductor_api::AppInterfaceInfo;
+use holochain_conductor_api::DumpFullStateFilter;
+use holochain_conductor_api::FullStateDump;
 use holochain_conductor_api::JsonDump;
 use holochain_keystore::lair_keystore::spawn_lair_keystore;
 use holochain_keystore::test_keystore::spawn_test_keystore;
@@ -68,11 +77,13 @@ use holochain_lmdb::env::ReadManager;
 use holochain_lmdb::exports::SingleStore;
 use holochain_lmdb::fresh_reader;
 use holochain_lmdb::prelude::*;
+use holochain_p2p::HolochainP2pSender;
 use holochain_state::source_chain::SourceChainBuf;
 use holochain_state::wasm::WasmBuf;
 use holochain_types::prelude::*;
 use kitsune_p2p::agent_store::AgentInfoSigned;
 use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::sync::RwLock;
@@ -99,6 +110,10 @@ where
     _state: CellState,
 }
 
+/// The directory within the root environment directory where the web UIs
+/// of apps installed from Web hApp bundles are extracted
+const WEB_UI_DIRECTORY: &str = "web_ui";
+
 pub type StopBroadcaster = tokio::sync::broadcast::Sender<()>;
 pub type StopReceiver = tokio::sync::broadcast::Receiver<()>;
 
@@ -135,6 +150,20 @@ where
     /// Collection app interface data, keyed by id
     app_interfaces: HashMap<AppInterfaceId, AppInterfaceRuntime>,
 
+    /// Tokens issued for authenticating app interface connections,
+    /// mapped to the App each one binds a connection to.
+    /// These are intentionally not persisted.
+    app_authentication_tokens: HashMap<AppAuthenticationToken, IssuedAppAuthenticationToken>,
+
+    /// Whether app interface connections must authenticate before use
+    require_app_authentication: bool,
+
+    /// If set, app bundles must be signed by one of these publishers to be installed
+    trusted_bundle_publishers: Option<Vec<AgentPubKey>>,
+
+    /// The port the metrics listener is bound to, if metrics are enabled
+    metrics_port: Option<u16>,
+
     /// Channel on which to send info about tasks we want to manage
     managed_task_add_sender: mpsc::Sender<ManagedTaskAdd>,
 
@@ -177,6 +206,11 @@ where
     pub fn get_arbitrary_admin_websocket_port(&self) -> Option<u16> {
         self.admin_websocket_ports.get(0).copied()
     }
+
+    /// Returns the port the metrics listener is bound to, if metrics are enabled.
+    pub fn get_metrics_port(&self) -> Option<u16> {
+        self.metrics_port
+    }
 }
 
 //-----------------------------------------------------------------------------
@@ -252,18 +286,10 @@ where
             let admin_api = admin_api.clone();
             let stop_tx = stop_tx.clone();
             async move {
-                match driver {
-                    InterfaceDriver::Websocket { port } => {
-                        let listener = spawn_websocket_listener(port).await?;
-                        let port = listener.local_addr().port().unwrap_or(port);
-                        let handle: ManagedTaskHandle = spawn_admin_interface_task(
-                            listener,
-                            admin_api.clone(),
-                            stop_tx.subscribe(),
-                        )?;
-                        InterfaceResult::Ok((port, handle))
-                    }
-                }
+                let (driver, listener) = spawn_listener(driver).await?;
+                let handle: ManagedTaskHandle =
+                    spawn_admin_interface_task(listener, admin_api.clone(), stop_tx.subscribe())?;
+                InterfaceResult::Ok((driver.port(), handle))
             }
         };
 
@@ -289,7 +315,7 @@ where
 
             // Now that tasks are spawned, register them with the TaskManager
             for (port, handle) in handles {
-                ports.push(port);
+                ports.extend(port);
                 self.manage_task(ManagedTaskAdd::new(
                     handle,
                     Box::new(|result| {
@@ -310,34 +336,100 @@ where
 
     pub(super) async fn add_app_interface_via_handle(
         &mut self,
-        port: u16,
+        driver: InterfaceDriver,
         handle: ConductorHandle,
-    ) -> ConductorResult<u16> {
-        let interface_id: AppInterfaceId = format!("interface-{}", port).into();
+    ) -> ConductorResult<InterfaceDriver> {
+        // Check for a duplicate before binding, so a rejected request can't
+        // disturb the live interface, e.g. by replacing its socket file.
+        // An OS chosen port can't collide.
+        let driver = canonical_driver(driver).map_err(Box::new)?;
+        if driver.port() != Some(0) {
+            let interface_id = app_interface_id(&driver);
+            if self.app_interfaces.contains_key(&interface_id) {
+                return Err(ConductorError::AppInterfaceIdCollision(interface_id));
+            }
+        }
+        let (driver, listener) = spawn_listener(driver).await.map_err(Box::new)?;
+        let interface_id = app_interface_id(&driver);
         let app_api = RealAppInterfaceApi::new(handle, interface_id.clone());
         // This receiver is thrown away because we can produce infinite new
         // receivers from the Sender
         let (signal_tx, _r) = tokio::sync::broadcast::channel(SIGNAL_BUFFER_SIZE);
+        let (detach_tx, detach_rx) = tokio::sync::broadcast::channel(1);
+        let connections = Arc::new(AtomicUsize::new(0));
         let stop_rx = self.managed_task_stop_broadcaster.subscribe();
-        let (port, task) = spawn_app_interface_task(port, app_api, signal_tx.clone(), stop_rx)
-            .await
-            .map_err(Box::new)?;
+        let task = spawn_app_interface_task(
+            listener,
+            app_api,
+            signal_tx.clone(),
+            connections.clone(),
+            stop_rx,
+            detach_rx,
+        );
         // TODO: RELIABILITY: Handle this task by restarting it if it fails and log the error
         self.manage_task(ManagedTaskAdd::dont_handle(task)).await?;
-        let interface = AppInterfaceRuntime::Websocket { signal_tx };
-
-        if self.app_interfaces.contains_key(&interface_id) {
-            return Err(ConductorError::AppInterfaceIdCollision(interface_id));
-        }
+        let interface = AppInterfaceRuntime::Websocket {
+            signal_tx,
+            detach_tx,
+            connections,
+        };
 
         self.app_interfaces.insert(interface_id.clone(), interface);
-        let config = AppInterfaceConfig::websocket(port);
+        let config = AppInterfaceConfig::from_driver(driver.clone());
         self.update_state(|mut state| {
             state.app_interfaces.insert(interface_id, config);
             Ok(state)
         })
         .await?;
-        Ok(port)
+        Ok(driver)
+    }
+
+    /// List the attached app interfaces along with their live connection counts
+    pub(super) async fn list_app_interfaces(&self) -> ConductorResult<Vec<AppInterfaceInfo>> {
+        Ok(self
+            .get_state()
+            .await?
+            .app_interfaces
+            .into_iter()
+            .map(|(id, config)| AppInterfaceInfo {
+                id: id.to_string(),
+                driver: config.driver,
+                connections: self
+                    .app_interfaces
+                    .get(&id)
+                    .map(|i| i.connections())
+                    .unwrap_or(0),
+            })
+            .collect())
+    }
+
+    /// Stop the app interface with this id, close its connections,
+    /// and remove it from the persisted state
+    pub(super) async fn detach_app_interface(
+        &mut self,
+        interface_id: AppInterfaceId,
+    ) -> ConductorResult<()> {
+        let config = self
+            .get_state()
+            .await?
+            .interface_by_id(&interface_id)
+            .ok_or_else(|| ConductorError::AppInterfaceNotFound(interface_id.clone()))?;
+        if let Some(interface) = self.app_interfaces.remove(&interface_id) {
+            interface.detach();
+        }
+        if let InterfaceDriver::UnixSocket { path } = &config.driver {
+            // Nobody can connect to a socket which is no longer listening,
+            // so don't leave the file behind
+            if let Err(e) = std::fs::remove_file(path) {
+                warn!("Failed to remove the socket file {}: {}", path.display(), e);
+            }
+        }
+        self.update_state(|mut state| {
+            state.app_interfaces.remove(&interface_id);
+            Ok(state)
+        })
+        .await?;
+        Ok(())
     }
 
     pub(super) async fn register_dna_wasm(
@@ -373,25 +465,107 @@ where
 
     /// Start all app interfaces currently in state.
     /// This should only be run at conductor initialization.
-    #[allow(irrefutable_let_patterns)]
     pub(super) async fn startup_app_interfaces_via_handle(
         &mut self,
         handle: ConductorHandle,
     ) -> ConductorResult<()> {
         for i in self.get_state().await?.app_interfaces.values() {
             tracing::debug!("Starting up app interface: {:?}", i);
-            let port = if let InterfaceDriver::Websocket { port } = i.driver {
-                port
-            } else {
-                unreachable!()
-            };
             let _ = self
-                .add_app_interface_via_handle(port, handle.clone())
+                .add_app_interface_via_handle(i.driver.clone(), handle.clone())
                 .await?;
         }
         Ok(())
     }
 
+    /// Issue a new token binding app interface connections to an installed App,
+    /// which can be used to authenticate for `expiry_seconds`
+    pub(super) async fn issue_app_authentication_token(
+        &mut self,
+        installed_app_id: InstalledAppId,
+        expiry_seconds: Option<u64>,
+    ) -> ConductorResult<AppAuthenticationToken> {
+        let state = self.get_state().await?;
+        if state.get_app_info(&installed_app_id).is_none() {
+            return Err(ConductorError::AppNotInstalled(installed_app_id));
+        }
+        // Make room by dropping the tokens which can't be used any more
+        let now = std::time::Instant::now();
+        self.app_authentication_tokens.retain(|_, issued| {
+            issued.expires_at > now && state.get_app_info(&issued.installed_app_id).is_some()
+        });
+        if self.app_authentication_tokens.len() >= MAX_APP_AUTHENTICATION_TOKENS {
+            return Err(ConductorError::TooManyAppAuthenticationTokens);
+        }
+        let expiry = std::time::Duration::from_secs(
+            expiry_seconds.unwrap_or(DEFAULT_APP_AUTHENTICATION_TOKEN_EXPIRY_S),
+        );
+        let token = AppAuthenticationToken(base64::encode_config(
+            rand::random::<[u8; 32]>(),
+            base64::URL_SAFE_NO_PAD,
+        ));
+        self.app_authentication_tokens.insert(
+            token.clone(),
+            IssuedAppAuthenticationToken {
+                installed_app_id,
+                expires_at: now.checked_add(expiry).unwrap_or(now),
+            },
+        );
+        Ok(token)
+    }
+
+    /// Revoke a token, so it can no longer be used to authenticate.
+    /// Connections which have already authenticated with it stay bound to its App.
+    pub(super) fn revoke_app_authentication_token(&mut self, token: &AppAuthenticationToken) {
+        self.app_authentication_tokens.remove(token);
+    }
+
+    /// Find the App which a token was issued for, if the token has neither
+    /// expired nor been revoked and the App is still installed
+    pub(super) async fn app_for_authentication_token(
+        &self,
+        token: &AppAuthenticationToken,
+    ) -> ConductorResult<Option<InstalledAppId>> {
+        let issued = match self.app_authentication_tokens.get(token) {
+            Some(issued) if issued.expires_at > std::time::Instant::now() => issued,
+            _ => return Ok(None),
+        };
+        Ok(self
+            .get_state()
+            .await?
+            .get_app_info(&issued.installed_app_id)
+            .map(|_| issued.installed_app_id.clone()))
+    }
+
+    pub(super) fn require_app_authentication(&self) -> bool {
+        self.require_app_authentication
+    }
+
+    pub(super) fn trusted_bundle_publishers(&self) -> Option<Vec<AgentPubKey>> {
+        self.trusted_bundle_publishers.clone()
+    }
+
+    /// The directory containing the extracted web UIs of apps
+    /// installed from Web hApp bundles
+    pub(super) fn web_ui_root_dir(&self) -> std::path::PathBuf {
+        std::path::PathBuf::from(self.root_env_dir.clone()).join(WEB_UI_DIRECTORY)
+    }
+
+    /// The directory an app's web UI is extracted to. The app id is used
+    /// as the directory name, so it must be a plain file name.
+    pub(super) fn web_ui_dir(
+        &self,
+        installed_app_id: &InstalledAppId,
+    ) -> ConductorResult<std::path::PathBuf> {
+        let mut components = std::path::Path::new(installed_app_id).components();
+        match (components.next(), components.next()) {
+            (Some(std::path::Component::Normal(_)), None) => {
+                Ok(self.web_ui_root_dir().join(installed_app_id))
+            }
+            _ => Err(ConductorError::InvalidWebUiAppId(installed_app_id.clone())),
+        }
+    }
+
     pub(super) fn signal_broadcaster(&self) -> SignalBroadcaster {
         SignalBroadcaster::new(
             self.app_interfaces
@@ -470,8 +644,11 @@ where
         &self,
         conductor_handle: ConductorHandle,
     ) -> ConductorResult<Vec<Result<Vec<(Cell, InitialQueueTriggers)>, CreateAppError>>> {
-        // Only create the active apps
-        let active_apps = self.get_state().await?.active_apps;
+        // Only create the active apps, along with the old cells
+        // of their migrations which are yet to be closed
+        let state = self.get_state().await?;
+        let active_apps = state.active_apps;
+        let unclosed_migrations = state.unclosed_migrations;
 
         // Data required to create apps
         let root_env_dir = self.root_env_dir.clone();
@@ -484,12 +661,19 @@ where
                 let root_env_dir = std::path::PathBuf::from(root_env_dir.clone());
                 let conductor_handle = conductor_handle.clone();
                 let keystore = keystore.clone();
+                let unclosed_cells: Vec<CellId> = unclosed_migrations
+                    .iter()
+                    .filter(|m| m.installed_app_id == installed_app_id)
+                    .map(|m| m.old_cell_id.clone())
+                    .collect();
 
                 // Task that creates the cells
                 async move {
                     // Only create cells not already created
                     let cells_to_create = app
                         .all_cells()
+                        .cloned()
+                        .chain(unclosed_cells)
                         .filter(|cell_id| !self.cells.contains_key(cell_id))
                         .map(|cell_id| {
                             (
@@ -699,6 +883,84 @@ where
         Ok(cell_id)
     }
 
+    /// Point an active App's slot at the cell its base cell was migrated to,
+    /// returning the old cell
+    pub(super) async fn migrate_slot_in_db(
+        &mut self,
+        installed_app_id: &InstalledAppId,
+        slot_id: &SlotId,
+        cell_id: CellId,
+    ) -> ConductorResult<CellId> {
+        let (_, old_cell_id) = self
+            .update_state_prime(|mut state| {
+                if let Some(app) = state.active_apps.get_mut(installed_app_id) {
+                    let old_cell_id = app.migrate_slot(slot_id, cell_id)?;
+                    Ok((state, old_cell_id))
+                } else {
+                    Err(ConductorError::AppNotActive(installed_app_id.clone()))
+                }
+            })
+            .await?;
+        Ok(old_cell_id)
+    }
+
+    /// Undo a failed migration of an App's slot: point the slot back at the
+    /// old cell if it was already swapped, then delete the new cell's chain.
+    /// Only for migrations whose new chain has not been opened, as an
+    /// OpenChain may be published as soon as it is committed.
+    pub(super) async fn abort_slot_migration(
+        &mut self,
+        installed_app_id: &InstalledAppId,
+        slot_id: &SlotId,
+        old_cell_id: CellId,
+        new_cell_id: CellId,
+    ) -> ConductorResult<()> {
+        let slot_cell_id = self
+            .get_state()
+            .await?
+            .active_apps
+            .get(installed_app_id)
+            .and_then(|app| app.slot(slot_id).ok().map(|slot| slot.cell_id().clone()));
+        if slot_cell_id.as_ref() == Some(&new_cell_id) {
+            self.migrate_slot_in_db(installed_app_id, slot_id, old_cell_id)
+                .await?;
+        }
+        self.remove_cells(vec![new_cell_id.clone()]);
+        let env = EnvironmentWrite::new(
+            &std::path::PathBuf::from(self.root_env_dir.clone()),
+            EnvironmentKind::Cell(new_cell_id),
+            self.keystore.clone(),
+        )?;
+        env.remove().await?;
+        Ok(())
+    }
+
+    /// Remember that a migration's old chain is yet to be closed
+    pub(super) async fn add_unclosed_migration(
+        &mut self,
+        migration: UnclosedMigration,
+    ) -> ConductorResult<()> {
+        self.update_state(move |mut state| {
+            state.unclosed_migrations.push(migration);
+            Ok(state)
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Forget a migration whose old chain has been closed
+    pub(super) async fn remove_unclosed_migration(
+        &mut self,
+        migration: &UnclosedMigration,
+    ) -> ConductorResult<()> {
+        self.update_state(|mut state| {
+            state.unclosed_migrations.retain(|m| m != migration);
+            Ok(state)
+        })
+        .await?;
+        Ok(())
+    }
+
     pub(super) async fn load_wasms_into_dna_files(
         &self,
     ) -> ConductorResult<(
@@ -828,7 +1090,9 @@ where
         let arc = cell.env();
         let source_chain = SourceChainBuf::new(arc.clone().into())?;
 
-        let peer_dump = p2p_store::dump_state(self.p2p_env.clone().into(), Some(cell_id.clone()))?;
+        let mut peer_dump =
+            p2p_store::dump_state(self.p2p_env.clone().into(), Some(cell_id.clone()))?;
+        peer_dump.peer_scores = self.peer_scores(cell_id.dna_hash()).await?;
         let source_chain_dump = source_chain.dump_state().await?;
         let integration_dump = integrate_dht_ops_workflow::dump_state(arc.clone().into())?;
 
@@ -843,6 +1107,92 @@ where
         Ok(serde_json::to_string_pretty(&out)?)
     }
 
+    pub(super) async fn dump_full_cell_state(
+        &self,
+        cell_id: &CellId,
+        filter: DumpFullStateFilter,
+    ) -> ConductorApiResult<FullStateDump> {
+        let cell = self.cell_by_id(cell_id)?;
+        let arc = cell.env();
+        let source_chain = SourceChainBuf::new(arc.clone().into())?;
+
+        let mut peer_dump =
+            p2p_store::dump_state(self.p2p_env.clone().into(), Some(cell_id.clone()))?;
+        peer_dump.peer_scores = self.peer_scores(cell_id.dna_hash()).await?;
+        let source_chain_dump = (&source_chain.dump_state().await?).into();
+        let integration_dump =
+            integrate_dht_ops_workflow::dump_full_state(arc.clone().into(), &filter)?;
+
+        Ok(FullStateDump {
+            peer_dump,
+            source_chain_dump,
+            integration_dump,
+        })
+    }
+
+    async fn peer_scores(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorResult<Vec<holochain_p2p::kitsune_p2p::actor::PeerScore>> {
+        Ok(self.holochain_p2p.peer_scores(dna_hash.clone()).await?)
+    }
+
+    pub(super) async fn ban_agent(
+        &self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+        duration_ms: Option<u64>,
+    ) -> ConductorResult<()> {
+        Ok(self
+            .holochain_p2p
+            .ban_agent(dna_hash, agent, duration_ms)
+            .await?)
+    }
+
+    pub(super) async fn unban_agent(
+        &self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+    ) -> ConductorResult<()> {
+        Ok(self.holochain_p2p.unban_agent(dna_hash, agent).await?)
+    }
+
+    pub(super) async fn start_metrics_listener_via_handle(
+        &mut self,
+        config: MetricsConfig,
+        handle: ConductorHandle,
+    ) -> ConductorResult<u16> {
+        let stop_rx = self.managed_task_stop_broadcaster.subscribe();
+        let (port, task) = spawn_metrics_listener(config, handle, stop_rx).await?;
+        self.manage_task(ManagedTaskAdd::dont_handle(task)).await?;
+        self.metrics_port = Some(port);
+        Ok(port)
+    }
+
+    /// Periodically prune expired agent info from the peer store
+    pub(super) async fn start_agent_info_pruning(&mut self) -> ConductorResult<()> {
+        let stop_rx = self.managed_task_stop_broadcaster.subscribe();
+        let task = p2p_store::spawn_prune_agent_infos_task(self.p2p_env.clone(), stop_rx);
+        self.manage_task(ManagedTaskAdd::dont_handle(task)).await
+    }
+
+    /// The environments metrics are gathered from.
+    /// Gathering scans whole tables, so it is done without the conductor lock.
+    pub(super) fn metrics_sources(&self) -> MetricsSources {
+        MetricsSources {
+            cells: self
+                .cells
+                .iter()
+                .map(|(cell_id, item)| (cell_id.clone(), item.cell.env().clone()))
+                .collect(),
+            lmdb_paths: vec![
+                ("conductor", self.env.path().to_path_buf()),
+                ("wasm", self.wasm_env.path().to_path_buf()),
+                ("p2p", self.p2p_env.path().to_path_buf()),
+            ],
+        }
+    }
+
     pub(super) fn p2p_env(&self) -> EnvironmentWrite {
         self.p2p_env.clone()
     }
@@ -907,6 +1257,10 @@ where
             cells: HashMap::new(),
             shutting_down: false,
             app_interfaces: HashMap::new(),
+            app_authentication_tokens: HashMap::new(),
+            require_app_authentication: false,
+            trusted_bundle_publishers: None,
+            metrics_port: None,
             managed_task_add_sender: task_tx,
             managed_task_stop_broadcaster: stop_tx,
             task_manager_run_handle,
@@ -964,6 +1318,29 @@ where
     }
 }
 
+/// How long an app authentication token can be used for, unless asked otherwise
+const DEFAULT_APP_AUTHENTICATION_TOKEN_EXPIRY_S: u64 = 60 * 60;
+
+/// How many unexpired app authentication tokens may be outstanding at once
+const MAX_APP_AUTHENTICATION_TOKENS: usize = 1024;
+
+/// An app authentication token which has been handed out
+struct IssuedAppAuthenticationToken {
+    /// The App it binds a connection to
+    installed_app_id: InstalledAppId,
+    /// It can't be used to authenticate from this time on
+    expires_at: std::time::Instant,
+}
+
+/// The id under which an app interface with this (bound) driver is stored
+fn app_interface_id(driver: &InterfaceDriver) -> AppInterfaceId {
+    match driver {
+        InterfaceDriver::Websocket { port } => format!("interface-{}", port),
+        InterfaceDriver::UnixSocket { path } => format!("interface-{}", path.display()),
+    }
+    .into()
+}
+
 /// The database used to store ConductorState. It has only one key-value pair.
 pub type ConductorStateDb = KvStore<UnitDbKey, ConductorState>;
 
@@ -1104,10 +1481,18 @@ mod builder {
         }
 
         async fn finish(
-            conductor: Conductor<DS>,
+            mut conductor: Conductor<DS>,
             conductor_config: ConductorConfig,
             p2p_evt: holochain_p2p::event::HolochainP2pEventReceiver,
         ) -> ConductorResult<ConductorHandle> {
+            conductor.require_app_authentication = conductor_config.require_app_authentication;
+            conductor.trusted_bundle_publishers = conductor_config
+                .bundle_signatures
+                .as_ref()
+                .map(|config| config.trusted_publishers());
+
+            conductor.start_agent_info_pruning().await?;
+
             // Get data before handle
             let keystore = conductor.keystore.clone();
             let holochain_p2p = conductor.holochain_p2p.clone();
@@ -1141,6 +1526,11 @@ mod builder {
             // Create app interfaces
             handle.clone().startup_app_interfaces().await?;
 
+            // Serve metrics
+            if let Some(config) = conductor_config.metrics {
+                handle.clone().start_metrics_listener(config).await?;
+            }
+
             handle.print_setup().await;
 
             Ok(handle)
@@ -183,6 +183,381 @@ async fn can_set_fake_state() {
     assert_eq!(state, conductor.get_state_from_handle().await.unwrap());
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn serves_metrics() {
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    let envs = test_environments();
+    let conductor = ConductorBuilder::new()
+        .config(ConductorConfig {
+            metrics: Some(MetricsConfig { port: 0 }),
+            ..Default::default()
+        })
+        .test(&envs)
+        .await
+        .unwrap();
+    let port = conductor.get_metrics_port().await.unwrap();
+
+    let request = move |path: &'static str| async move {
+        let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    };
+
+    let response = request("/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("# TYPE holochain_zome_call_duration_seconds histogram\n"));
+    assert!(response.contains("holochain_lmdb_size_bytes{env=\"conductor\"} "));
+
+    let response = request("/nope").await;
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+    // A client which never sends a request is let go
+    let mut silent = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+    let mut buf = Vec::new();
+    let read = tokio::time::timeout(
+        std::time::Duration::from_secs(30),
+        silent.read_to_end(&mut buf),
+    )
+    .await;
+    assert_matches!(read, Ok(Ok(0)));
+
+    conductor.shutdown().await;
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn install_app_bundle_requires_trusted_publisher() {
+    use crate::conductor::config::BundleSignatureConfig;
+    use holochain_keystore::KeystoreSenderExt;
+
+    let envs = test_environments();
+    let keystore = envs.keystore();
+    let publisher = keystore
+        .generate_sign_keypair_from_pure_entropy()
+        .await
+        .unwrap();
+    let stranger = keystore
+        .generate_sign_keypair_from_pure_entropy()
+        .await
+        .unwrap();
+
+    let conductor = ConductorBuilder::new()
+        .config(ConductorConfig {
+            bundle_signatures: Some(BundleSignatureConfig {
+                trusted_publishers: vec![publisher.clone().into()],
+            }),
+            ..Default::default()
+        })
+        .test(&envs)
+        .await
+        .unwrap();
+
+    async fn app_bundle_fixture() -> AppBundle {
+        let dna_bundle = DnaBundle::from_dna_file(fake_valid_dna_file(""))
+            .await
+            .unwrap();
+        let manifest: AppManifest = AppManifestCurrentBuilder::default()
+            .name("app".into())
+            .description(None)
+            .slots(vec![AppSlotManifest::sample("slot".into())])
+            .build()
+            .unwrap()
+            .into();
+        // The DNA path used by AppSlotManifest::sample
+        let dna_path = std::path::PathBuf::from("./path/to/my/dnabundle.dna");
+        AppBundle::new(manifest, vec![(dna_path, dna_bundle)], ".".into())
+            .await
+            .unwrap()
+    }
+    let install = |app_id: &str, source, signature| {
+        let conductor = conductor.clone();
+        let keystore = keystore.clone();
+        let app_id = app_id.to_string();
+        async move {
+            let agent_key = keystore
+                .generate_sign_keypair_from_pure_entropy()
+                .await
+                .unwrap();
+            conductor
+                .install_app_bundle(InstallAppBundlePayload {
+                    source,
+                    agent_key,
+                    installed_app_id: Some(app_id),
+                    membrane_proofs: Default::default(),
+                    signature,
+                })
+                .await
+        }
+    };
+
+    // Unsigned
+    assert_matches!(
+        install(
+            "unsigned",
+            AppBundleSource::Bundle(app_bundle_fixture().await),
+            None
+        )
+        .await,
+        Err(ConductorError::BundleSignatureError(
+            BundleSignatureError::Unsigned
+        ))
+    );
+
+    // Signed by an untrusted publisher
+    let untrusted = sign_bundle(&*app_bundle_fixture().await, &keystore, &stranger)
+        .await
+        .unwrap();
+    assert_matches!(
+        install(
+            "untrusted",
+            AppBundleSource::Bundle(app_bundle_fixture().await),
+            Some(untrusted)
+        )
+        .await,
+        Err(ConductorError::BundleSignatureError(
+            BundleSignatureError::UntrustedPublisher(_)
+        ))
+    );
+
+    // A detached signature from a trusted publisher
+    let signature = sign_bundle(&*app_bundle_fixture().await, &keystore, &publisher)
+        .await
+        .unwrap();
+    install(
+        "trusted",
+        AppBundleSource::Bundle(app_bundle_fixture().await),
+        Some(signature.clone()),
+    )
+    .await
+    .unwrap();
+
+    // A bundle file with its signature file alongside
+    let tmp = tempdir::TempDir::new("signed-bundle").unwrap();
+    let bundle_path = tmp.path().join("app.happ");
+    app_bundle_fixture()
+        .await
+        .write_to_file(&bundle_path)
+        .await
+        .unwrap();
+    signature
+        .write_to_file(&BundleSignature::detached_path(&bundle_path))
+        .await
+        .unwrap();
+    install("trusted-file", AppBundleSource::Path(bundle_path), None)
+        .await
+        .unwrap();
+
+    // A trusted signature doesn't cover a DNA which isn't bundled or pinned
+    let manifest: AppManifest = serde_yaml::from_str(
+        r#"
+manifest_version: "1"
+name: app
+description: ~
+slots:
+  - id: slot
+    provisioning:
+      strategy: create
+      deferred: false
+    dna:
+      path: /tmp/unpinned.dna
+"#,
+    )
+    .unwrap();
+    let unpinned_bundle = || AppBundle::new(manifest.clone(), vec![], ".".into());
+    let signature = sign_bundle(&*unpinned_bundle().await.unwrap(), &keystore, &publisher)
+        .await
+        .unwrap();
+    assert_matches!(
+        install(
+            "unpinned",
+            AppBundleSource::Bundle(unpinned_bundle().await.unwrap()),
+            Some(signature)
+        )
+        .await,
+        Err(ConductorError::BundleSignatureError(
+            BundleSignatureError::UnpinnedLocations(_)
+        ))
+    );
+
+    conductor.shutdown().await;
+}
+
+/// A Web hApp bundle with an `index.html` UI and a one slot hApp named "app"
+async fn web_app_bundle_fixture() -> WebAppBundle {
+    use std::io::Write;
+
+    let dna_bundle = DnaBundle::from_dna_file(fake_valid_dna_file(""))
+        .await
+        .unwrap();
+    let manifest: AppManifest = AppManifestCurrentBuilder::default()
+        .name("app".into())
+        .description(None)
+        .slots(vec![AppSlotManifest::sample("slot".into())])
+        .build()
+        .unwrap()
+        .into();
+    // The DNA path used by AppSlotManifest::sample
+    let dna_path = std::path::PathBuf::from("./path/to/my/dnabundle.dna");
+    let happ = AppBundle::new(manifest, vec![(dna_path, dna_bundle)], ".".into())
+        .await
+        .unwrap()
+        .encode()
+        .unwrap();
+
+    let mut ui = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    ui.start_file("index.html", Default::default()).unwrap();
+    ui.write_all(b"<html/>").unwrap();
+    let ui = ui.finish().unwrap().into_inner();
+
+    let manifest: WebAppManifest = WebAppManifestCurrentBuilder::default()
+        .name("web app".into())
+        .ui(WebUI {
+            location: mr_bundle::Location::Bundled("ui.zip".into()),
+            resource_hash: None,
+        })
+        .happ_manifest(AppManifestLocation {
+            location: mr_bundle::Location::Bundled("app.happ".into()),
+            resource_hash: None,
+        })
+        .build()
+        .unwrap()
+        .into();
+    WebAppBundle::new(
+        manifest,
+        vec![("ui.zip".into(), ui), ("app.happ".into(), happ)],
+        ".".into(),
+    )
+    .unwrap()
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn install_web_app_bundle_extracts_ui() {
+    let envs = test_environments();
+    let keystore = envs.keystore();
+    let conductor = ConductorBuilder::new().test(&envs).await.unwrap();
+    let web_app_bundle = web_app_bundle_fixture().await;
+
+    let agent_key = keystore
+        .generate_sign_keypair_from_pure_entropy()
+        .await
+        .unwrap();
+    let info = conductor
+        .clone()
+        .install_web_app_bundle(InstallWebAppBundlePayload {
+            source: WebAppBundleSource::Bundle(web_app_bundle),
+            agent_key,
+            installed_app_id: None,
+            membrane_proofs: Default::default(),
+            signature: None,
+        })
+        .await
+        .unwrap();
+
+    // The app id comes from the nested hApp manifest
+    assert_eq!(info.installed_app_id, "app");
+    let web_ui_path = info.web_ui_path.unwrap();
+    assert_eq!(
+        std::fs::read(web_ui_path.join("index.html")).unwrap(),
+        b"<html/>"
+    );
+    assert_eq!(
+        conductor
+            .get_app_info(&"app".to_string())
+            .await
+            .unwrap()
+            .unwrap()
+            .web_ui_path,
+        Some(web_ui_path)
+    );
+
+    conductor.shutdown().await;
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn install_web_app_bundle_requires_trusted_publisher() {
+    use crate::conductor::config::BundleSignatureConfig;
+    use holochain_keystore::KeystoreSenderExt;
+
+    let envs = test_environments();
+    let keystore = envs.keystore();
+    let publisher = keystore
+        .generate_sign_keypair_from_pure_entropy()
+        .await
+        .unwrap();
+    let conductor = ConductorBuilder::new()
+        .config(ConductorConfig {
+            bundle_signatures: Some(BundleSignatureConfig {
+                trusted_publishers: vec![publisher.clone().into()],
+            }),
+            ..Default::default()
+        })
+        .test(&envs)
+        .await
+        .unwrap();
+    let install = |app_id: &str, web_app_bundle, signature| {
+        let conductor = conductor.clone();
+        let keystore = keystore.clone();
+        let app_id = app_id.to_string();
+        async move {
+            let agent_key = keystore
+                .generate_sign_keypair_from_pure_entropy()
+                .await
+                .unwrap();
+            conductor
+                .install_web_app_bundle(InstallWebAppBundlePayload {
+                    source: WebAppBundleSource::Bundle(web_app_bundle),
+                    agent_key,
+                    installed_app_id: Some(app_id),
+                    membrane_proofs: Default::default(),
+                    signature,
+                })
+                .await
+        }
+    };
+
+    // Signing only the nested hApp bundle isn't enough, since it
+    // says nothing about the UI
+    let web_app_bundle = web_app_bundle_fixture().await;
+    let happ_signature = sign_bundle(
+        &*web_app_bundle.happ_bundle().await.unwrap(),
+        &keystore,
+        &publisher,
+    )
+    .await
+    .unwrap();
+    assert_matches!(
+        install("happ-signed", web_app_bundle, Some(happ_signature)).await,
+        Err(ConductorError::BundleSignatureError(_))
+    );
+    assert!(conductor
+        .get_app_info(&"happ-signed".to_string())
+        .await
+        .unwrap()
+        .is_none());
+
+    // A signature over the Web hApp bundle covers both
+    let web_app_bundle = web_app_bundle_fixture().await;
+    let signature = sign_bundle(&*web_app_bundle, &keystore, &publisher)
+        .await
+        .unwrap();
+    let info = install("trusted", web_app_bundle, Some(signature))
+        .await
+        .unwrap();
+    assert!(info.web_ui_path.unwrap().join("index.html").is_file());
+
+    conductor.shutdown().await;
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn proxy_tls_with_test_keystore() {
     use ghost_actor::GhostControlSender;
@@ -234,7 +609,7 @@ async fn proxy_tls_inner(
             match evt {
                 TransportEvent::IncomingChannel(_, mut write, read) => {
                     println!("YOOTH");
-                    let data = read.read_to_end().await;
+                    let data = read.read_to_end().await?;
                     let data = String::from_utf8_lossy(&data);
                     let data = format!("echo: {}", data);
                     write.write_and_close(data.into_bytes()).await?;
@@ -254,7 +629,7 @@ async fn proxy_tls_inner(
 
     let (_url, mut write, read) = bind2.create_channel(url1).await?;
     write.write_and_close(b"test".to_vec()).await?;
-    let data = read.read_to_end().await;
+    let data = read.read_to_end().await?;
     let data = String::from_utf8_lossy(&data);
     assert_eq!("echo: test", data);
 
@@ -21,6 +21,17 @@ pub enum ConductorError {
     #[error(transparent)]
     AppBundleError(#[from] AppBundleError),
 
+    #[error(transparent)]
+    BundleSignatureError(#[from] BundleSignatureError),
+
+    #[error(transparent)]
+    WebAppBundleError(#[from] WebAppBundleError),
+
+    #[error(
+        "The installed app id '{0}' can't be used as the name of the directory for its web UI"
+    )]
+    InvalidWebUiAppId(InstalledAppId),
+
     #[error(transparent)]
     DatabaseError(#[from] DatabaseError),
 
@@ -66,6 +77,12 @@ pub enum ConductorError {
     #[error("Attempted to add two app interfaces with the same id: {0}")]
     AppInterfaceIdCollision(AppInterfaceId),
 
+    #[error("No app interface with id {0} is attached")]
+    AppInterfaceNotFound(AppInterfaceId),
+
+    #[error("Too many app authentication tokens are outstanding, revoke some or wait for them to expire")]
+    TooManyAppAuthenticationTokens,
+
     // Box is to avoid cycle in error definition
     #[error(transparent)]
     InterfaceError(#[from] Box<InterfaceError>),
@@ -91,6 +108,16 @@ pub enum ConductorError {
     #[error("Tried to perform an operation on an app that was not active: {0}")]
     AppNotActive(InstalledAppId),
 
+    #[error("Tried to migrate an agent to the DNA it is already using: {0}")]
+    MigrateToSameDna(DnaHash),
+
+    #[error("The agent now uses its new chain {new_cell_id:?}, but closing its old chain {old_cell_id:?} failed, migrate the agent again to retry: {reason}")]
+    MigrationNotClosed {
+        old_cell_id: CellId,
+        new_cell_id: CellId,
+        reason: String,
+    },
+
     #[error(transparent)]
     HolochainP2pError(#[from] holochain_p2p::HolochainP2pError),
 
@@ -44,23 +44,35 @@
 use super::api::error::ConductorApiResult;
 use super::api::ZomeCall;
 use super::config::AdminInterfaceConfig;
+use super::config::InterfaceDriver;
+use super::config::MetricsConfig;
+use super::error::ConductorError;
 use super::error::ConductorResult;
 use super::error::CreateAppError;
+use super::interface::error::InterfaceError;
 use super::interface::SignalBroadcaster;
 use super::manager::TaskManagerRunHandle;
+use super::metrics::ConductorMetrics;
 use super::p2p_store::get_agent_info_signed;
 use super::p2p_store::put_agent_info_signed;
 use super::p2p_store::query_agent_info_signed;
+use super::state::UnclosedMigration;
 use super::Cell;
 use super::Conductor;
 use crate::core::workflow::CallZomeWorkspaceLock;
 use crate::core::workflow::ZomeCallResult;
 use derive_more::From;
 use futures::future::FutureExt;
+use holochain_conductor_api::AppAuthenticationToken;
+use holochain_conductor_api::AppInterfaceInfo;
+use holochain_conductor_api::DumpFullStateFilter;
+use holochain_conductor_api::FullStateDump;
 use holochain_conductor_api::InstalledAppInfo;
 use holochain_p2p::event::HolochainP2pEvent::*;
 use holochain_types::prelude::*;
 use kitsune_p2p::agent_store::AgentInfoSigned;
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::*;
@@ -72,6 +84,27 @@ use crate::core::queue_consumer::QueueTriggers;
 #[cfg(any(test, feature = "test_utils"))]
 use holochain_lmdb::env::EnvironmentWrite;
 
+/// Read the detached signature alongside a bundle file, if there is one
+async fn detached_signature_beside(bundle_path: &Path) -> ConductorResult<Option<BundleSignature>> {
+    let signature_path = BundleSignature::detached_path(bundle_path);
+    if signature_path.is_file() {
+        Ok(Some(
+            BundleSignature::read_from_file(&signature_path)
+                .await
+                .map_err(AppBundleError::from)?,
+        ))
+    } else {
+        Ok(None)
+    }
+}
+
+/// How many times closing the old chain of a migration is tried
+/// before giving up until the migration is asked for again
+const MIGRATION_CLOSE_ATTEMPTS: u32 = 3;
+
+/// How long to wait between attempts at closing the old chain of a migration
+const MIGRATION_CLOSE_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
 /// A handle to the Conductor that can easily be passed around and cheaply cloned
 pub type ConductorHandle = Arc<dyn ConductorHandleT>;
 
@@ -98,8 +131,26 @@ pub trait ConductorHandleT: Send + Sync {
     /// Should only be run once at Conductor initialization.
     async fn startup_app_interfaces(self: Arc<Self>) -> ConductorResult<()>;
 
-    /// Add an app interface
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16>;
+    /// Start serving metrics over a local HTTP listener.
+    /// Returns the port the listener is bound to.
+    async fn start_metrics_listener(self: Arc<Self>, config: MetricsConfig)
+        -> ConductorResult<u16>;
+
+    /// Add an app interface with the given driver.
+    /// Returns the driver that was bound, e.g. with the port chosen by the OS.
+    async fn add_app_interface(
+        self: Arc<Self>,
+        driver: InterfaceDriver,
+    ) -> ConductorResult<InterfaceDriver>;
+
+    /// List the attached app interfaces
+    async fn list_app_interfaces(&self) -> ConductorResult<Vec<AppInterfaceInfo>>;
+
+    /// Detach the app interface with this id, closing its connections
+    async fn detach_app_interface(
+        &self,
+        interface_id: super::state::AppInterfaceId,
+    ) -> ConductorResult<()>;
 
     /// Install a [Dna] in this Conductor
     async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()>;
@@ -142,6 +193,9 @@ pub trait ConductorHandleT: Send + Sync {
     /// Get a Websocket port which will
     async fn get_arbitrary_admin_websocket_port(&self) -> Option<u16>;
 
+    /// Get the port the metrics listener is bound to, if metrics are enabled
+    async fn get_metrics_port(&self) -> Option<u16>;
+
     /// Return the JoinHandle for all managed tasks, which when resolved will
     /// signal that the Conductor has completely shut down.
     ///
@@ -167,6 +221,19 @@ pub trait ConductorHandleT: Send + Sync {
     /// Destroy a cloned Cell
     async fn destroy_clone_cell(self: Arc<Self>, cell_id: CellId) -> ConductorResult<()>;
 
+    /// Migrate the agent in an active App's slot to a new DNA.
+    ///
+    /// A new chain is created in the new DNA and opened with a reference to the
+    /// old one, and only then is the old chain closed. The slot now refers to
+    /// the new Cell, whose id is returned. If a step fails before the new
+    /// chain is opened, the slot is pointed back at the old Cell and the new
+    /// chain is deleted. Once it is opened the migration is kept, and if the
+    /// old chain can't be closed, migrating to the same DNA again retries it.
+    async fn migrate_agent(
+        self: Arc<Self>,
+        payload: MigrateAgentPayload,
+    ) -> ConductorResult<CellId>;
+
     /// Install Cells into ConductorState based on installation info, and run
     /// genesis on all new source chains
     async fn install_app(
@@ -181,6 +248,15 @@ pub trait ConductorHandleT: Send + Sync {
         payload: InstallAppBundlePayload,
     ) -> ConductorResult<InstalledApp>;
 
+    /// Install the hApp bundle nested in a Web hApp bundle, and extract its
+    /// web UI into a directory belonging to the installed app.
+    /// If publishers must be trusted, the Web hApp bundle itself must be
+    /// signed, and it's verified before anything in it is unpacked.
+    async fn install_web_app_bundle(
+        self: Arc<Self>,
+        payload: InstallWebAppBundlePayload,
+    ) -> ConductorResult<InstalledAppInfo>;
+
     /// Setup the cells from the database
     /// Only creates any cells that are not already created
     async fn setup_cells(self: Arc<Self>) -> ConductorResult<Vec<CreateAppError>>;
@@ -200,6 +276,16 @@ pub trait ConductorHandleT: Send + Sync {
     /// Dump the cells state
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String>;
 
+    /// Dump the typed state of a cell, keeping only the ops that pass the filter
+    async fn dump_full_cell_state(
+        &self,
+        cell_id: &CellId,
+        filter: DumpFullStateFilter,
+    ) -> ConductorApiResult<FullStateDump>;
+
+    /// Gather the metrics served by the metrics listener
+    async fn metrics(&self) -> ConductorApiResult<ConductorMetrics>;
+
     /// Access the broadcast Sender which will send a Signal across every
     /// attached app interface
     async fn signal_broadcaster(&self) -> SignalBroadcaster;
@@ -210,7 +296,29 @@ pub trait ConductorHandleT: Send + Sync {
         installed_app_id: &InstalledAppId,
     ) -> ConductorResult<Option<InstalledAppInfo>>;
 
-    /// Add signed agent info to the conductor
+    /// Issue a token which binds app interface connections to an installed App,
+    /// and which can be used to authenticate for `expiry_seconds`
+    async fn issue_app_authentication_token(
+        &self,
+        installed_app_id: InstalledAppId,
+        expiry_seconds: Option<u64>,
+    ) -> ConductorResult<AppAuthenticationToken>;
+
+    /// Revoke an authentication token, so it can no longer be used to authenticate
+    async fn revoke_app_authentication_token(&self, token: &AppAuthenticationToken);
+
+    /// Find the App which an authentication token was issued for,
+    /// if the token is still valid
+    async fn app_for_authentication_token(
+        &self,
+        token: &AppAuthenticationToken,
+    ) -> ConductorResult<Option<InstalledAppId>>;
+
+    /// Whether app interface connections must authenticate before use
+    async fn require_app_authentication(&self) -> bool;
+
+    /// Add signed agent info to the conductor.
+    /// Fails without adding any if one of them doesn't verify.
     async fn add_agent_infos(&self, agent_infos: Vec<AgentInfoSigned>) -> ConductorApiResult<()>;
 
     /// Get signed agent info from the conductor
@@ -219,6 +327,18 @@ pub trait ConductorHandleT: Send + Sync {
         cell_id: Option<CellId>,
     ) -> ConductorApiResult<Vec<AgentInfoSigned>>;
 
+    /// Stop talking to a remote agent on a dna's network,
+    /// for `duration_ms` or, if None, until it is unbanned.
+    async fn ban_agent(
+        &self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+        duration_ms: Option<u64>,
+    ) -> ConductorResult<()>;
+
+    /// Lift a ban on a remote agent on a dna's network.
+    async fn unban_agent(&self, dna_hash: DnaHash, agent: AgentPubKey) -> ConductorResult<()>;
+
     /// Print the current setup in a machine readable way.
     async fn print_setup(&self);
 
@@ -282,9 +402,39 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .await
     }
 
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16> {
+    async fn start_metrics_listener(
+        self: Arc<Self>,
+        config: MetricsConfig,
+    ) -> ConductorResult<u16> {
+        self.conductor
+            .write()
+            .await
+            .start_metrics_listener_via_handle(config, self.clone())
+            .await
+    }
+
+    async fn add_app_interface(
+        self: Arc<Self>,
+        driver: InterfaceDriver,
+    ) -> ConductorResult<InterfaceDriver> {
         let mut lock = self.conductor.write().await;
-        lock.add_app_interface_via_handle(port, self.clone()).await
+        lock.add_app_interface_via_handle(driver, self.clone())
+            .await
+    }
+
+    async fn list_app_interfaces(&self) -> ConductorResult<Vec<AppInterfaceInfo>> {
+        self.conductor.read().await.list_app_interfaces().await
+    }
+
+    async fn detach_app_interface(
+        &self,
+        interface_id: super::state::AppInterfaceId,
+    ) -> ConductorResult<()> {
+        self.conductor
+            .write()
+            .await
+            .detach_app_interface(interface_id)
+            .await
     }
 
     async fn register_dna(&self, dna: DnaFile) -> ConductorResult<()> {
@@ -337,13 +487,18 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         trace!(agent = ?cell_id.agent_pubkey(), dispatch_event = ?event);
         match event {
             PutAgentInfoSigned {
+                dna_hash,
                 agent_info_signed,
                 respond,
                 ..
             } => {
                 let env = { self.conductor.read().await.p2p_env() };
-                let res = put_agent_info_signed(env, agent_info_signed)
-                    .map_err(holochain_p2p::HolochainP2pError::other);
+                let space = holochain_p2p::space_holo_to_kit(dna_hash);
+                let res = match agent_info_signed.verify(Some(&space)).await {
+                    Ok(_) => put_agent_info_signed(env, agent_info_signed)
+                        .map_err(holochain_p2p::HolochainP2pError::other),
+                    Err(e) => Err(holochain_p2p::HolochainP2pError::other(e)),
+                };
                 respond.respond(Ok(async move { res }.boxed().into()));
             }
             GetAgentInfoSigned {
@@ -414,6 +569,10 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .get_arbitrary_admin_websocket_port()
     }
 
+    async fn get_metrics_port(&self) -> Option<u16> {
+        self.conductor.read().await.get_metrics_port()
+    }
+
     async fn shutdown(&self) {
         self.conductor.write().await.shutdown()
     }
@@ -458,6 +617,107 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         todo!()
     }
 
+    async fn migrate_agent(
+        self: Arc<Self>,
+        payload: MigrateAgentPayload,
+    ) -> ConductorResult<CellId> {
+        let MigrateAgentPayload {
+            installed_app_id,
+            slot_id,
+            new_dna_hash,
+            membrane_proof,
+        } = payload;
+        let (old_cell, new_cell_id) = {
+            let conductor = self.conductor.read().await;
+            let state = conductor.get_state().await?;
+            let app = state
+                .active_apps
+                .get(&installed_app_id)
+                .ok_or_else(|| ConductorError::AppNotActive(installed_app_id.clone()))?;
+            let old_cell_id = app.slot(&slot_id)?.cell_id().clone();
+            if *old_cell_id.dna_hash() == new_dna_hash {
+                // The agent may already have been migrated here,
+                // with its old chain still to be closed
+                let unclosed = state.unclosed_migrations.iter().find(|m| {
+                    m.installed_app_id == installed_app_id
+                        && m.slot_id == slot_id
+                        && m.new_dna_hash == new_dna_hash
+                });
+                return match unclosed {
+                    Some(unclosed) => {
+                        let unclosed = unclosed.clone();
+                        drop(conductor);
+                        self.close_migrated_chain(unclosed, old_cell_id.clone())
+                            .await?;
+                        Ok(old_cell_id)
+                    }
+                    None => Err(ConductorError::MigrateToSameDna(new_dna_hash)),
+                };
+            }
+            if conductor.dna_store().get(&new_dna_hash).is_none() {
+                return Err(DnaError::DnaMissing(new_dna_hash).into());
+            }
+            let new_cell_id = CellId::new(new_dna_hash.clone(), old_cell_id.agent_pubkey().clone());
+            (conductor.cell_by_id(&old_cell_id)?, new_cell_id)
+        };
+        let old_cell_id = old_cell.id().clone();
+
+        self.conductor
+            .read()
+            .await
+            .genesis_cells(vec![(new_cell_id.clone(), membrane_proof)], self.clone())
+            .await?;
+
+        // Opening the new chain can't be undone, so it comes last
+        let opened = async {
+            self.conductor
+                .write()
+                .await
+                .migrate_slot_in_db(&installed_app_id, &slot_id, new_cell_id.clone())
+                .await?;
+            if let Some(error) = self.clone().setup_cells().await?.into_iter().next() {
+                return Err(error.into());
+            }
+            let new_cell = self.conductor.read().await.cell_by_id(&new_cell_id)?;
+            new_cell
+                .migrate_agent(MigrateAgent::Open, old_cell_id.dna_hash().clone())
+                .await?;
+            ConductorResult::Ok(())
+        }
+        .await;
+
+        if let Err(e) = opened {
+            if let Err(abort_error) = self
+                .conductor
+                .write()
+                .await
+                .abort_slot_migration(&installed_app_id, &slot_id, old_cell_id, new_cell_id)
+                .await
+            {
+                error!(?abort_error, "Failed to undo a failed agent migration");
+            }
+            return Err(e);
+        }
+        drop(old_cell);
+
+        // The OpenChain may already be published, so from here on the
+        // migration only goes forward
+        let unclosed = UnclosedMigration {
+            installed_app_id,
+            slot_id,
+            old_cell_id,
+            new_dna_hash,
+        };
+        self.conductor
+            .write()
+            .await
+            .add_unclosed_migration(unclosed.clone())
+            .await?;
+        self.close_migrated_chain(unclosed, new_cell_id.clone())
+            .await?;
+        Ok(new_cell_id)
+    }
+
     async fn install_app(
         self: Arc<Self>,
         installed_app_id: InstalledAppId,
@@ -495,39 +755,109 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             agent_key,
             installed_app_id,
             membrane_proofs,
+            signature,
         } = payload;
 
-        let bundle = source.resolve().await?;
-
-        let installed_app_id =
-            installed_app_id.unwrap_or_else(|| bundle.manifest().app_name().to_owned());
-        let ops = bundle
-            .resolve_cells(agent_key.clone(), DnaGamut::placeholder(), membrane_proofs)
-            .await?;
+        let trusted_publishers = self.conductor.read().await.trusted_bundle_publishers();
+        // Look for a detached signature alongside a bundle file
+        let signature = match (signature, &source) {
+            (None, AppBundleSource::Path(path)) if trusted_publishers.is_some() => {
+                detached_signature_beside(path).await?
+            }
+            (signature, _) => signature,
+        };
 
-        let cells_to_create = ops.cells_to_create();
+        let bundle = source.resolve().await?;
 
-        for (dna, _) in ops.dnas_to_register {
-            self.clone().register_dna(dna).await?;
+        if let Some(trusted_publishers) = trusted_publishers {
+            let publisher =
+                verify_bundle_publisher(&*bundle, signature.as_ref(), &trusted_publishers).await?;
+            // The signature only covers what's in the bundle or pinned by it
+            let unpinned = bundle.unpinned_locations().await?;
+            if !unpinned.is_empty() {
+                return Err(BundleSignatureError::UnpinnedLocations(unpinned).into());
+            }
+            info!(?publisher, "Verified app bundle publisher");
         }
 
-        self.conductor
-            .read()
+        self.install_verified_app_bundle(bundle, agent_key, installed_app_id, membrane_proofs)
             .await
-            .genesis_cells(cells_to_create, self.clone())
-            .await?;
+    }
 
-        let slots = ops.slots;
-        let app = InstalledApp::new(installed_app_id, agent_key, slots);
+    async fn install_web_app_bundle(
+        self: Arc<Self>,
+        payload: InstallWebAppBundlePayload,
+    ) -> ConductorResult<InstalledAppInfo> {
+        let InstallWebAppBundlePayload {
+            source,
+            agent_key,
+            installed_app_id,
+            membrane_proofs,
+            signature,
+        } = payload;
 
-        // Update the db
-        self.conductor
-            .write()
-            .await
-            .add_inactive_app_to_db(app.clone())
+        let trusted_publishers = self.conductor.read().await.trusted_bundle_publishers();
+        let signature = match (signature, &source) {
+            (None, WebAppBundleSource::Path(path)) if trusted_publishers.is_some() => {
+                detached_signature_beside(path).await?
+            }
+            (signature, _) => signature,
+        };
+
+        let web_app_bundle = source.resolve().await?;
+
+        // Nothing in the bundle is unpacked before its publisher is verified
+        if let Some(trusted_publishers) = trusted_publishers {
+            let publisher =
+                verify_bundle_publisher(&*web_app_bundle, signature.as_ref(), &trusted_publishers)
+                    .await?;
+            let unpinned = web_app_bundle.unpinned_locations().await?;
+            if !unpinned.is_empty() {
+                return Err(BundleSignatureError::UnpinnedLocations(unpinned).into());
+            }
+            info!(?publisher, "Verified web app bundle publisher");
+        }
+
+        let app_bundle = web_app_bundle.happ_bundle().await?;
+        let installed_app_id =
+            installed_app_id.unwrap_or_else(|| app_bundle.manifest().app_name().to_owned());
+        let (web_ui_root_dir, web_ui_dir) = {
+            let conductor = self.conductor.read().await;
+            (
+                conductor.web_ui_root_dir(),
+                conductor.web_ui_dir(&installed_app_id)?,
+            )
+        };
+
+        // Extract the UI before installing, so a broken zip file doesn't leave
+        // an app installed without its UI, but only move it into the app's
+        // directory once installation has succeeded.
+        tokio::fs::create_dir_all(&web_ui_root_dir).await?;
+        let extracting = tokio::task::spawn_blocking(move || {
+            tempdir::TempDir::new_in(&web_ui_root_dir, "extracting")
+        })
+        .await
+        .expect("Creating the web UI directory panicked")?;
+        web_app_bundle.extract_web_ui(extracting.path()).await?;
+
+        // The web app bundle's signature covers the nested hApp bundle
+        let app = self
+            .install_verified_app_bundle(
+                app_bundle,
+                agent_key,
+                Some(installed_app_id),
+                membrane_proofs,
+            )
             .await?;
 
-        Ok(app)
+        if tokio::fs::metadata(&web_ui_dir).await.is_ok() {
+            tokio::fs::remove_dir_all(&web_ui_dir).await?;
+        }
+        tokio::fs::rename(extracting.into_path(), &web_ui_dir).await?;
+
+        let mut info = InstalledAppInfo::from_installed_app(&app, false);
+        info.web_ui_path = Some(web_ui_dir);
+        Ok(info)
     }
 
     async fn setup_cells(self: Arc<Self>) -> ConductorResult<Vec<CreateAppError>> {
@@ -593,6 +923,25 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.dump_cell_state(cell_id).await
     }
 
+    async fn dump_full_cell_state(
+        &self,
+        cell_id: &CellId,
+        filter: DumpFullStateFilter,
+    ) -> ConductorApiResult<FullStateDump> {
+        self.conductor
+            .read()
+            .await
+            .dump_full_cell_state(cell_id, filter)
+            .await
+    }
+
+    async fn metrics(&self) -> ConductorApiResult<ConductorMetrics> {
+        let sources = self.conductor.read().await.metrics_sources();
+        tokio::task::spawn_blocking(move || sources.gather())
+            .await
+            .map_err(InterfaceError::from)?
+    }
+
     async fn signal_broadcaster(&self) -> SignalBroadcaster {
         self.conductor.read().await.signal_broadcaster()
     }
@@ -601,16 +950,58 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         &self,
         installed_app_id: &InstalledAppId,
     ) -> ConductorResult<Option<InstalledAppInfo>> {
-        Ok(self
-            .conductor
+        let conductor = self.conductor.read().await;
+        let mut info = conductor.get_state().await?.get_app_info(installed_app_id);
+        if let Some(info) = info.as_mut() {
+            info.web_ui_path = conductor
+                .web_ui_dir(installed_app_id)
+                .ok()
+                .filter(|dir| dir.is_dir());
+        }
+        Ok(info)
+    }
+
+    async fn issue_app_authentication_token(
+        &self,
+        installed_app_id: InstalledAppId,
+        expiry_seconds: Option<u64>,
+    ) -> ConductorResult<AppAuthenticationToken> {
+        self.conductor
+            .write()
+            .await
+            .issue_app_authentication_token(installed_app_id, expiry_seconds)
+            .await
+    }
+
+    async fn revoke_app_authentication_token(&self, token: &AppAuthenticationToken) {
+        self.conductor
+            .write()
+            .await
+            .revoke_app_authentication_token(token)
+    }
+
+    async fn app_for_authentication_token(
+        &self,
+        token: &AppAuthenticationToken,
+    ) -> ConductorResult<Option<InstalledAppId>> {
+        self.conductor
             .read()
             .await
-            .get_state()
-            .await?
-            .get_app_info(installed_app_id))
+            .app_for_authentication_token(token)
+            .await
+    }
+
+    async fn require_app_authentication(&self) -> bool {
+        self.conductor.read().await.require_app_authentication()
     }
 
     async fn add_agent_infos(&self, agent_infos: Vec<AgentInfoSigned>) -> ConductorApiResult<()> {
+        for agent_info_signed in &agent_infos {
+            agent_info_signed
+                .verify(None)
+                .await
+                .map_err(ConductorError::from)?;
+        }
         self.conductor.read().await.add_agent_infos(agent_infos)
     }
 
@@ -621,6 +1012,27 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.get_agent_infos(cell_id)
     }
 
+    async fn ban_agent(
+        &self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+        duration_ms: Option<u64>,
+    ) -> ConductorResult<()> {
+        self.conductor
+            .read()
+            .await
+            .ban_agent(dna_hash, agent, duration_ms)
+            .await
+    }
+
+    async fn unban_agent(&self, dna_hash: DnaHash, agent: AgentPubKey) -> ConductorResult<()> {
+        self.conductor
+            .read()
+            .await
+            .unban_agent(dna_hash, agent)
+            .await
+    }
+
     async fn print_setup(&self) {
         self.conductor.read().await.print_setup()
     }
@@ -664,4 +1076,77 @@ impl<DS: DnaStore + 'static> ConductorHandleImpl<DS> {
         let lock = self.conductor.read().await;
         Ok(lock.cell_by_id(cell_id)?)
     }
+
+    /// Install an app bundle whose publisher has been verified, if need be
+    async fn install_verified_app_bundle(
+        self: Arc<Self>,
+        bundle: AppBundle,
+        agent_key: AgentPubKey,
+        installed_app_id: Option<InstalledAppId>,
+        membrane_proofs: HashMap<CellNick, MembraneProof>,
+    ) -> ConductorResult<InstalledApp> {
+        let installed_app_id =
+            installed_app_id.unwrap_or_else(|| bundle.manifest().app_name().to_owned());
+        let ops = bundle
+            .resolve_cells(agent_key.clone(), DnaGamut::placeholder(), membrane_proofs)
+            .await?;
+
+        let cells_to_create = ops.cells_to_create();
+
+        for (dna, _) in ops.dnas_to_register {
+            self.clone().register_dna(dna).await?;
+        }
+
+        self.conductor
+            .read()
+            .await
+            .genesis_cells(cells_to_create, self.clone())
+            .await?;
+
+        let slots = ops.slots;
+        let app = InstalledApp::new(installed_app_id, agent_key, slots);
+
+        // Update the db
+        self.conductor
+            .write()
+            .await
+            .add_inactive_app_to_db(app.clone())
+            .await?;
+
+        Ok(app)
+    }
+
+    /// Close the old chain of a migration whose new chain has been opened,
+    /// retrying a few times, then stop its cell
+    async fn close_migrated_chain(
+        &self,
+        unclosed: UnclosedMigration,
+        new_cell_id: CellId,
+    ) -> ConductorResult<()> {
+        let old_cell = self
+            .conductor
+            .read()
+            .await
+            .cell_by_id(&unclosed.old_cell_id)?;
+        let mut attempt = 1;
+        while let Err(e) = old_cell
+            .migrate_agent(MigrateAgent::Close, unclosed.new_dna_hash.clone())
+            .await
+        {
+            if attempt == MIGRATION_CLOSE_ATTEMPTS {
+                return Err(ConductorError::MigrationNotClosed {
+                    old_cell_id: unclosed.old_cell_id,
+                    new_cell_id,
+                    reason: e.to_string(),
+                });
+            }
+            warn!(?e, attempt, "Failed to close a migrated chain, retrying");
+            attempt += 1;
+            tokio::time::delay_for(MIGRATION_CLOSE_RETRY_DELAY).await;
+        }
+        let mut conductor = self.conductor.write().await;
+        conductor.remove_unclosed_migration(&unclosed).await?;
+        conductor.remove_cells(vec![unclosed.old_cell_id]);
+        Ok(())
+    }
 }
@@ -2,14 +2,17 @@
 //! and dispatch them to the appropriate handlers within Holochain.
 //! They also allow emitting responses and one-way Signals.
 //!
-//! Currently the only InterfaceDriver is a Websocket-based one, whose
-//! implementation can be found in the `websocket` module here.
+//! Both InterfaceDrivers are Websocket-based, either over tcp or over a unix
+//! domain socket. The implementation can be found in the `websocket` module here.
 
 use crate::conductor::api::*;
 use error::InterfaceError;
 use error::InterfaceResult;
 use holochain_types::signal::Signal;
 use std::convert::TryInto;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use tokio::sync::broadcast;
 
 #[allow(missing_docs)]
@@ -20,10 +23,15 @@ pub mod websocket;
 /// Other stateful information like websocket ports can be found in
 /// `ConductorState::app_interfaces`
 pub enum AppInterfaceRuntime {
-    /// A websocket app interface
+    /// A websocket app interface, over tcp or a unix domain socket
     Websocket {
         /// The channel for this interface to send Signals across
         signal_tx: broadcast::Sender<Signal>,
+        /// Sending on this channel stops the interface task
+        /// and closes its connections
+        detach_tx: broadcast::Sender<()>,
+        /// The number of currently open connections
+        connections: Arc<AtomicUsize>,
     },
 
     #[cfg(any(test, feature = "test_utils"))]
@@ -43,6 +51,27 @@ impl AppInterfaceRuntime {
             Self::Test { signal_tx, .. } => signal_tx,
         }
     }
+
+    /// Get the number of clients currently connected to the interface
+    pub fn connections(&self) -> usize {
+        match self {
+            Self::Websocket { connections, .. } => connections.load(Ordering::Relaxed),
+            #[cfg(any(test, feature = "test_utils"))]
+            Self::Test { .. } => 0,
+        }
+    }
+
+    /// Stop the interface, closing all of its connections
+    pub fn detach(self) {
+        match self {
+            Self::Websocket { detach_tx, .. } => {
+                // Error only means the task has already stopped
+                let _ = detach_tx.send(());
+            }
+            #[cfg(any(test, feature = "test_utils"))]
+            Self::Test { .. } => {}
+        }
+    }
 }
 
 /// A collection of Senders to be used for emitting Signals from a Cell.
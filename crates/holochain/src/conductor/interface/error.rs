@@ -29,6 +29,8 @@ pub enum InterfaceError {
     IoTodo(#[from] std::io::Error),
     #[error("Failed to find free port")]
     PortError,
+    #[error("Unix domain socket interfaces are not supported on this platform")]
+    UnixSocketUnsupported,
 }
 
 impl From<String> for InterfaceError {
@@ -1,5 +1,7 @@
 //! Module for establishing Websocket-based Interfaces,
 //! i.e. those configured with `InterfaceDriver::Websocket`
+//! or `InterfaceDriver::UnixSocket`, which speaks the same framing
+//! over a unix domain socket rather than tcp.
 
 use super::error::InterfaceError;
 use super::error::InterfaceResult;
@@ -10,12 +12,15 @@ use crate::conductor::manager::ManagedTaskResult;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_types::signal::Signal;
 use holochain_websocket::websocket_bind;
+#[cfg(unix)]
+use holochain_websocket::websocket_bind_unix;
 use holochain_websocket::WebsocketConfig;
 use holochain_websocket::WebsocketListener;
 use holochain_websocket::WebsocketMessage;
 use holochain_websocket::WebsocketReceiver;
 use holochain_websocket::WebsocketSender;
 use std::convert::TryFrom;
+use std::path::Path;
 
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
@@ -44,6 +49,65 @@ pub async fn spawn_websocket_listener(port: u16) -> InterfaceResult<WebsocketLis
     Ok(listener)
 }
 
+/// Create a WebsocketListener bound to a unix domain socket to be used in interfaces.
+/// Only the user running the conductor may connect to it.
+#[cfg(unix)]
+pub async fn spawn_unix_socket_listener(path: &Path) -> InterfaceResult<WebsocketListener> {
+    trace!("Initializing unix socket interface");
+    let listener = websocket_bind_unix(path, Arc::new(WebsocketConfig::default())).await?;
+    trace!("LISTENING AT: {}", listener.local_addr());
+    Ok(listener)
+}
+
+/// Unix domain sockets are not available on this platform
+#[cfg(not(unix))]
+pub async fn spawn_unix_socket_listener(_path: &Path) -> InterfaceResult<WebsocketListener> {
+    Err(InterfaceError::UnixSocketUnsupported)
+}
+
+/// Spell the driver's unix socket path canonically, so one socket
+/// can't be attached twice under different ids. The socket file itself
+/// may not exist yet, so only the directory it goes in is resolved.
+pub fn canonical_driver(driver: InterfaceDriver) -> InterfaceResult<InterfaceDriver> {
+    match driver {
+        InterfaceDriver::UnixSocket { path } => {
+            let file_name = path.file_name().ok_or_else(|| {
+                InterfaceError::Other(format!("'{}' is not a socket file path", path.display()))
+            })?;
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            let path = dir.canonicalize()?.join(file_name);
+            Ok(InterfaceDriver::UnixSocket { path })
+        }
+        driver => Ok(driver),
+    }
+}
+
+/// Create a WebsocketListener for the given driver.
+/// Returns the listener along with the driver that was actually bound,
+/// i.e. with the port chosen by the OS if port 0 was requested,
+/// or the canonical path of a unix socket.
+pub async fn spawn_listener(
+    driver: InterfaceDriver,
+) -> InterfaceResult<(InterfaceDriver, WebsocketListener)> {
+    match canonical_driver(driver)? {
+        InterfaceDriver::Websocket { port } => {
+            let listener = spawn_websocket_listener(port).await?;
+            let port = listener
+                .local_addr()
+                .port()
+                .ok_or(InterfaceError::PortError)?;
+            Ok((InterfaceDriver::Websocket { port }, listener))
+        }
+        InterfaceDriver::UnixSocket { path } => {
+            let listener = spawn_unix_socket_listener(&path).await?;
+            Ok((InterfaceDriver::UnixSocket { path }, listener))
+        }
+    }
+}
+
 /// Create an Admin Interface, which only receives AdminRequest messages
 /// from the external client
 pub fn spawn_admin_interface_task<A: InterfaceApi>(
@@ -72,7 +136,7 @@ pub fn spawn_admin_interface_task<A: InterfaceApi>(
                             };
                             send_sockets.push(tx_to_iface.clone());
                             listener_handles.push(tokio::task::spawn(recv_incoming_admin_msgs(
-                                api.clone(),
+                                api.for_connection(),
                                 rx_from_iface,
                                 tx_to_iface,
                                 num_connections.clone(),
@@ -112,36 +176,43 @@ pub fn spawn_admin_interface_task<A: InterfaceApi>(
 }
 
 /// Create an App Interface, which includes the ability to receive signals
-/// from Cells via a broadcast channel
-pub async fn spawn_app_interface_task<A: InterfaceApi>(
-    port: u16,
+/// from Cells via a broadcast channel.
+///
+/// The interface runs until the conductor shuts down (`stop_rx`) or the
+/// interface is detached (`detach_rx`). Either way, live connections are
+/// sent a close frame before the task ends.
+/// `num_connections` tracks the number of currently open connections.
+pub fn spawn_app_interface_task<A: InterfaceApi>(
+    mut listener: WebsocketListener,
     api: A,
     signal_broadcaster: broadcast::Sender<Signal>,
+    num_connections: Arc<AtomicUsize>,
     mut stop_rx: StopReceiver,
-) -> InterfaceResult<(u16, ManagedTaskHandle)> {
+    mut detach_rx: StopReceiver,
+) -> ManagedTaskHandle {
     trace!("Initializing App interface");
-    let mut listener = websocket_bind(
-        url2!("ws://127.0.0.1:{}", port),
-        Arc::new(WebsocketConfig::default()),
-    )
-    .await?;
-    trace!("LISTENING AT: {}", listener.local_addr());
-    let port = listener
-        .local_addr()
-        .port()
-        .ok_or(InterfaceError::PortError)?;
-    let task = tokio::task::spawn(async move {
+    tokio::task::spawn(async move {
         let mut listener_handles = Vec::new();
+        let mut send_sockets = Vec::new();
 
         let mut handle_connection =
             |tx_to_iface: WebsocketSender, rx_from_iface: WebsocketReceiver| {
                 let rx_from_cell = signal_broadcaster.subscribe();
-                listener_handles.push(tokio::task::spawn(recv_incoming_msgs_and_outgoing_signals(
-                    api.clone(),
-                    rx_from_iface,
-                    rx_from_cell,
-                    tx_to_iface,
-                )));
+                let num_connections = num_connections.clone();
+                num_connections.fetch_add(1, Ordering::Relaxed);
+                send_sockets.push(tx_to_iface.clone());
+                let api = api.for_connection();
+                listener_handles.push(tokio::task::spawn(async move {
+                    let r = recv_incoming_msgs_and_outgoing_signals(
+                        api,
+                        rx_from_iface,
+                        rx_from_cell,
+                        tx_to_iface,
+                    )
+                    .await;
+                    num_connections.fetch_sub(1, Ordering::Relaxed);
+                    r
+                }));
             };
 
         loop {
@@ -149,6 +220,9 @@ pub async fn spawn_app_interface_task<A: InterfaceApi>(
                 // break if we receive on the stop channel
                 _ = stop_rx.recv() => { break; },
 
+                // break if this interface is being detached
+                _ = detach_rx.recv() => { break; },
+
                 // establish a new connection to a client
                 maybe_con = listener.next() => if let Some(connection) = maybe_con {
                     match connection {
@@ -156,7 +230,7 @@ pub async fn spawn_app_interface_task<A: InterfaceApi>(
                             handle_connection(tx_to_iface, rx_from_iface);
                         }
                         Err(err) => {
-                            warn!("Admin socket connection failed: {}", err);
+                            warn!("App socket connection failed: {}", err);
                         }
                     }
                 } else {
@@ -164,11 +238,21 @@ pub async fn spawn_app_interface_task<A: InterfaceApi>(
                 }
             }
         }
+        // Stop accepting new connections
+        drop(listener);
+
+        for mut tx_to_iface in send_sockets {
+            // The connection may well have closed already
+            if let Err(e) =
+                WebsocketSender::close(&mut tx_to_iface, 1000, "Shutting down".into()).await
+            {
+                debug!("App socket failed to close: {}", e);
+            }
+        }
 
         handle_shutdown(listener_handles).await;
         ManagedTaskResult::Ok(())
-    });
-    Ok((port, task))
+    })
 }
 
 async fn handle_shutdown(listener_handles: Vec<JoinHandle<InterfaceResult<()>>>) {
@@ -239,10 +323,12 @@ async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
             // tx and rx together in a new spawned task
             signal = rx_from_cell.next() => {
                 if let Some(signal) = signal {
+                    let signal = signal.map_err(InterfaceError::SignalReceive)?;
+                    if !api.accepts_signal(&signal).await {
+                        continue;
+                    }
                     trace!(msg = "Sending signal!", ?signal);
-                    let bytes = SerializedBytes::try_from(
-                        signal.map_err(InterfaceError::SignalReceive)?,
-                    )?;
+                    let bytes = SerializedBytes::try_from(signal)?;
                     tx_to_iface.signal(bytes).await?;
                 } else {
                     debug!("Closing interface: signal stream empty");
@@ -338,8 +424,10 @@ pub mod test {
     use crate::conductor::api::error::ExternalApiWireError;
     use crate::conductor::api::AdminRequest;
     use crate::conductor::api::AdminResponse;
+    use crate::conductor::api::AppInterfaceApi;
     use crate::conductor::api::RealAdminInterfaceApi;
     use crate::conductor::conductor::ConductorBuilder;
+    use crate::conductor::error::ConductorError;
     use crate::conductor::p2p_store::AgentKv;
     use crate::conductor::p2p_store::AgentKvKey;
     use crate::conductor::state::ConductorState;
@@ -350,6 +438,8 @@ pub mod test {
     use ::fixt::prelude::*;
     use fallible_iterator::FallibleIterator;
     use futures::future::FutureExt;
+    use holochain_conductor_api::DhtOpStage;
+    use holochain_conductor_api::DumpFullStateFilter;
     use holochain_lmdb::buffer::KvStoreT;
     use holochain_lmdb::fresh_reader_test;
     use holochain_lmdb::test_utils::test_environments;
@@ -365,7 +455,9 @@ pub mod test {
     use holochain_zome_types::test_utils::fake_agent_pubkey_2;
     use holochain_zome_types::ExternIO;
     use kitsune_p2p::agent_store::AgentInfoSigned;
+    use kitsune_p2p::fixt::signed_agent_info;
     use kitsune_p2p::fixt::AgentInfoSignedFixturator;
+    use kitsune_p2p::fixt::KitsuneSpaceFixturator;
     use matches::assert_matches;
     use mockall::predicate;
     use observability;
@@ -544,6 +636,155 @@ pub mod test {
         shutdown.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn app_interface_authentication() {
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let dna_hash = dna.dna_hash().clone();
+        let cell_id = CellId::from((dna_hash.clone(), fake_agent_pubkey_1()));
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store
+            .expect_get()
+            .with(predicate::eq(dna_hash))
+            .returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, app_api, handle) = setup_app(vec![(installed_cell, None)], dna_store).await;
+        let app_api = app_api.for_connection();
+
+        // A bad token is rejected
+        let response = app_api
+            .handle_app_request(AppRequest::Authenticate {
+                token: AppAuthenticationToken("bad".into()),
+            })
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppUnauthenticated(_))
+        );
+
+        // As is an expired or revoked one
+        let token = handle
+            .issue_app_authentication_token("test app".into(), Some(0))
+            .await
+            .unwrap();
+        let response = app_api
+            .handle_app_request(AppRequest::Authenticate { token })
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppUnauthenticated(_))
+        );
+        let token = handle
+            .issue_app_authentication_token("test app".into(), None)
+            .await
+            .unwrap();
+        handle.revoke_app_authentication_token(&token).await;
+        let response = app_api
+            .handle_app_request(AppRequest::Authenticate { token })
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppUnauthenticated(_))
+        );
+
+        // Tokens can't be issued for apps which aren't installed
+        assert_matches!(
+            handle
+                .issue_app_authentication_token("other app".into(), None)
+                .await,
+            Err(ConductorError::AppNotInstalled(_))
+        );
+
+        let token = handle
+            .issue_app_authentication_token("test app".into(), None)
+            .await
+            .unwrap();
+        let response = app_api
+            .handle_app_request(AppRequest::Authenticate { token })
+            .await;
+        assert_matches!(response, AppResponse::Authenticated { installed_app_id } if installed_app_id == "test app");
+
+        // Other apps are off limits
+        let response = app_api
+            .handle_app_request(AppRequest::AppInfo {
+                installed_app_id: "other app".into(),
+            })
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppUnauthenticated(_))
+        );
+
+        let call = |cell_id: CellId, provenance: AgentPubKey| ZomeCall {
+            cell_id,
+            zome_name: TestWasm::Foo.into(),
+            fn_name: "foo".into(),
+            payload: ExternIO::encode(()).unwrap(),
+            cap: None,
+            provenance,
+        };
+
+        // Claiming another agent's provenance is rejected
+        let response = app_api
+            .handle_app_request(AppRequest::ZomeCall(Box::new(call(
+                cell_id.clone(),
+                fake_agent_pubkey_2(),
+            ))))
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppUnauthenticated(_))
+        );
+
+        // Calling a cell outside the app is rejected
+        let other_dna = fake_dna_file(&Uuid::new_v4().to_string());
+        let other_cell_id = CellId::new(other_dna.dna_hash().clone(), fake_agent_pubkey_1());
+        let response = app_api
+            .handle_app_request(AppRequest::ZomeCall(Box::new(call(
+                other_cell_id.clone(),
+                fake_agent_pubkey_1(),
+            ))))
+            .await;
+        assert_matches!(
+            response,
+            AppResponse::Error(ExternalApiWireError::AppUnauthenticated(_))
+        );
+
+        // Only the signals of the app's own cells are sent out
+        let signal =
+            |cell_id: CellId| Signal::App(cell_id, AppSignal::new(ExternIO::encode(()).unwrap()));
+        assert!(app_api.accepts_signal(&signal(cell_id.clone())).await);
+        assert!(!app_api.accepts_signal(&signal(other_cell_id)).await);
+        assert!(app_api.accepts_signal(&test_signal("system")).await);
+
+        // The binding is per connection
+        let response = app_api
+            .for_connection()
+            .handle_app_request(AppRequest::AppInfo {
+                installed_app_id: "other app".into(),
+            })
+            .await;
+        assert_matches!(response, AppResponse::AppInfo(None));
+
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        handle.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn activate_app() {
         observability::test_run().ok();
@@ -697,6 +938,64 @@ pub mod test {
         shutdown.await.unwrap();
     }
 
+    #[cfg(unix)]
+    #[tokio::test(threaded_scheduler)]
+    async fn attach_app_unix_socket_interface() {
+        observability::test_run().ok();
+        let (tmpdir, conductor_handle) = setup_admin().await;
+        let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
+        let path = tmpdir.path().join("app.sock");
+        let msg = AdminRequest::AttachAppUnixSocketInterface { path: path.clone() };
+        let msg = msg.try_into().unwrap();
+        let respond = move |bytes: SerializedBytes| {
+            let response: AdminResponse = bytes.try_into().unwrap();
+            assert_matches!(
+                response,
+                AdminResponse::AppUnixSocketInterfaceAttached { .. }
+            );
+            async { Ok(()) }.boxed()
+        };
+        let respond = Box::new(respond);
+        let msg = WebsocketMessage::Request(msg, respond);
+        handle_incoming_message(msg, admin_api).await.unwrap();
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(0o600, mode & 0o777);
+        }
+        // Another spelling of the same path is the same interface
+        let path = path.canonicalize().unwrap();
+        let other_spelling = tmpdir.path().join(".").join("app.sock");
+        assert_matches!(
+            conductor_handle
+                .clone()
+                .add_app_interface(InterfaceDriver::UnixSocket {
+                    path: other_spelling
+                })
+                .await,
+            Err(ConductorError::AppInterfaceIdCollision(_))
+        );
+        // Detaching removes the socket file
+        conductor_handle
+            .detach_app_interface(format!("interface-{}", path.display()).into())
+            .await
+            .unwrap();
+        assert!(!path.exists());
+
+        // So does shutting down
+        let path = tmpdir.path().join("app2.sock");
+        conductor_handle
+            .clone()
+            .add_app_interface(InterfaceDriver::UnixSocket { path: path.clone() })
+            .await
+            .unwrap();
+        assert!(path.exists());
+        conductor_handle.shutdown().await;
+        shutdown.await.unwrap();
+        assert!(!path.exists());
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn dump_state() {
         observability::test_run().ok();
@@ -745,6 +1044,79 @@ pub mod test {
         shutdown.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn dump_full_state() {
+        observability::test_run().ok();
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![("zomey".into(), TestWasm::Foo.into())],
+        );
+        let cell_id = CellId::from((dna.dna_hash().clone(), fake_agent_pubkey_1()));
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store.expect_get().returning(move |_| Some(dna.clone()));
+        dna_store
+            .expect_add_dnas::<Vec<_>>()
+            .times(1)
+            .return_const(());
+        dna_store
+            .expect_add_entry_defs::<Vec<_>>()
+            .times(1)
+            .return_const(());
+
+        let (_tmpdir, conductor_handle) =
+            setup_admin_fake_cells(vec![(cell_id.clone(), None)], dna_store).await;
+        let conductor_handle = activate(conductor_handle).await;
+        let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
+        // Allow agents time to join
+        tokio::time::delay_for(std::time::Duration::from_secs(2)).await;
+
+        // Without a filter every op is included
+        let all = conductor_handle
+            .dump_full_cell_state(&cell_id, Default::default())
+            .await
+            .unwrap();
+        let counts = &all.integration_dump.counts;
+        assert_eq!(
+            all.integration_dump.ops.len(),
+            counts.validation_limbo + counts.integration_limbo + counts.integrated
+        );
+        assert!(all.source_chain_dump.chain_len > 0);
+        assert!(all.source_chain_dump.chain_head.is_some());
+
+        let filter = DumpFullStateFilter {
+            op_types: vec![DhtOpType::RegisterAgentActivity],
+            stages: vec![DhtOpStage::Integrated],
+        };
+        let expected: Vec<_> = all
+            .integration_dump
+            .ops
+            .into_iter()
+            .filter(|op| filter.matches(op))
+            .collect();
+
+        let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
+        let msg = AdminRequest::DumpFullState {
+            cell_id: Box::new(cell_id),
+            filter: Some(filter),
+        };
+        let msg = msg.try_into().unwrap();
+        let respond = move |bytes: SerializedBytes| {
+            let response: AdminResponse = bytes.try_into().unwrap();
+            assert_matches!(
+                response,
+                AdminResponse::FullStateDumped(s) if s.integration_dump.ops == expected
+            );
+            async { Ok(()) }.boxed()
+        };
+        let respond = Box::new(respond);
+        let msg = WebsocketMessage::Request(msg, respond);
+        handle_incoming_message(msg, admin_api).await.unwrap();
+        conductor_handle.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
     async fn make_dna(uuid: &str, zomes: Vec<TestWasm>) -> DnaFile {
         DnaFile::new(
             DnaDef {
@@ -796,9 +1168,14 @@ pub mod test {
         assert_eq!(count, 4);
 
         // - Get agents and space
-        let agent_infos = AgentInfoSignedFixturator::new(Unpredictable)
-            .take(5)
-            .collect::<Vec<_>>();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let mut agent_infos = Vec::new();
+        for _ in 0..5 {
+            agent_infos.push(signed_agent_info(fixt!(KitsuneSpace), now, 1000 * 60 * 20).await);
+        }
 
         let mut expect = to_key(agent_infos.clone());
         let k00: AgentKvKey = (dnas[0].clone(), agents[0].clone()).into();
@@ -818,6 +1195,13 @@ pub mod test {
         let r = make_req(admin_api.clone(), req).await.await.unwrap();
         assert_matches!(r, AdminResponse::AgentInfoAdded);
 
+        // - Agent info that doesn't verify is rejected
+        let req = AdminRequest::AddAgentInfo {
+            agent_infos: vec![fixt!(AgentInfoSigned)],
+        };
+        let r = make_req(admin_api.clone(), req).await.await.unwrap();
+        assert_matches!(r, AdminResponse::Error(_));
+
         // - Request all the infos
         let req = AdminRequest::RequestAgentInfo { cell_id: None };
         let r = make_req(admin_api.clone(), req).await.await.unwrap();
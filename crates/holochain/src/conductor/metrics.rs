@@ -0,0 +1,489 @@
+//! Conductor metrics in the Prometheus text exposition format.
+//!
+//! Zome call latencies and ribosome errors are counted here as they happen.
+//! Everything else (queue depths, op counts, LMDB sizes and the kitsune
+//! counters) is gathered when the endpoint is scraped, on a blocking thread
+//! and without holding the conductor lock.
+//!
+//! The endpoint is served by a deliberately tiny HTTP/1.1 listener which
+//! only binds to localhost and answers `GET /metrics`.
+
+use super::config::MetricsConfig;
+use super::manager::ManagedTaskHandle;
+use super::manager::ManagedTaskResult;
+use super::ConductorHandle;
+use crate::conductor::api::error::ConductorApiResult;
+use crate::conductor::conductor::StopReceiver;
+use crate::core::queue_consumer;
+use crate::core::queue_consumer::QueueDepths;
+use crate::core::workflow::integrate_dht_ops_workflow;
+use holochain_conductor_api::IntegrationStateDump;
+use holochain_lmdb::env::EnvironmentWrite;
+use holochain_zome_types::cell::CellId;
+use std::fmt::Display;
+use std::fmt::Write;
+use std::net::Ipv4Addr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tracing::*;
+
+/// Upper bounds in seconds of the zome call latency histogram buckets
+const ZOME_CALL_BUCKETS: [f64; 10] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 10.0];
+
+/// The largest request we will read before giving up on a client
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// How long a client has to send its request before we give up on it
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicU64 = AtomicU64::new(0);
+static ZOME_CALL_BUCKET_COUNTS: [AtomicU64; ZOME_CALL_BUCKETS.len()] =
+    [ZERO; ZOME_CALL_BUCKETS.len()];
+static ZOME_CALL_COUNT: AtomicU64 = AtomicU64::new(0);
+static ZOME_CALL_SUM_MICROS: AtomicU64 = AtomicU64::new(0);
+static RIBOSOME_ERRORS: AtomicU64 = AtomicU64::new(0);
+
+/// Record how long a zome call took
+pub fn record_zome_call(elapsed: Duration) {
+    let secs = elapsed.as_secs_f64();
+    for (bound, count) in ZOME_CALL_BUCKETS.iter().zip(ZOME_CALL_BUCKET_COUNTS.iter()) {
+        if secs <= *bound {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    ZOME_CALL_COUNT.fetch_add(1, Ordering::Relaxed);
+    ZOME_CALL_SUM_MICROS.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// Record that a zome call failed inside the ribosome
+pub fn record_ribosome_error() {
+    RIBOSOME_ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The metrics gathered from a single cell at scrape time
+#[derive(Debug, Clone)]
+pub struct CellMetrics {
+    /// The cell these metrics are for
+    pub cell_id: CellId,
+    /// Ops waiting on each workflow queue
+    pub queue_depths: QueueDepths,
+    /// Ops at each stage of integration
+    pub op_counts: IntegrationStateDump,
+    /// Size of the cell's LMDB environment on disk
+    pub lmdb_size_bytes: u64,
+}
+
+/// Everything the conductor reports when scraped
+#[derive(Debug, Clone, Default)]
+pub struct ConductorMetrics {
+    /// Per cell metrics
+    pub cells: Vec<CellMetrics>,
+    /// Sizes of the conductor's own LMDB environments on disk,
+    /// keyed by environment name
+    pub lmdb_size_bytes: Vec<(&'static str, u64)>,
+}
+
+/// The environments metrics are gathered from, taken from the conductor
+pub struct MetricsSources {
+    /// Each cell's environment
+    pub cells: Vec<(CellId, EnvironmentWrite)>,
+    /// The paths of the conductor's own LMDB environments,
+    /// keyed by environment name
+    pub lmdb_paths: Vec<(&'static str, PathBuf)>,
+}
+
+impl MetricsSources {
+    /// Count the queued and held ops of every cell and measure the environments.
+    /// This scans whole tables, so don't call it on the async executor.
+    pub fn gather(self) -> ConductorApiResult<ConductorMetrics> {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for (cell_id, env) in self.cells {
+            cells.push(CellMetrics {
+                cell_id,
+                queue_depths: queue_consumer::queue_depths(env.clone().into())?,
+                op_counts: integrate_dht_ops_workflow::dump_state(env.clone().into())?,
+                lmdb_size_bytes: lmdb_size_bytes(env.path()),
+            });
+        }
+        let lmdb_size_bytes = self
+            .lmdb_paths
+            .iter()
+            .map(|(name, path)| (*name, lmdb_size_bytes(path)))
+            .collect();
+        Ok(ConductorMetrics {
+            cells,
+            lmdb_size_bytes,
+        })
+    }
+}
+
+/// The size on disk of the LMDB environment at this path
+pub fn lmdb_size_bytes(path: &Path) -> u64 {
+    std::fs::metadata(path.join("data.mdb"))
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+/// Render the metrics in the Prometheus text exposition format
+pub fn render(metrics: &ConductorMetrics) -> String {
+    let mut out = PromWriter::default();
+
+    out.family(
+        "holochain_zome_call_duration_seconds",
+        "Time taken by zome calls.",
+        "histogram",
+    );
+    for (bound, count) in ZOME_CALL_BUCKETS.iter().zip(ZOME_CALL_BUCKET_COUNTS.iter()) {
+        out.sample(
+            "holochain_zome_call_duration_seconds_bucket",
+            &[("le", &bound.to_string())],
+            count.load(Ordering::Relaxed),
+        );
+    }
+    let count = ZOME_CALL_COUNT.load(Ordering::Relaxed);
+    out.sample(
+        "holochain_zome_call_duration_seconds_bucket",
+        &[("le", "+Inf")],
+        count,
+    );
+    out.sample(
+        "holochain_zome_call_duration_seconds_sum",
+        &[],
+        ZOME_CALL_SUM_MICROS.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+    );
+    out.sample("holochain_zome_call_duration_seconds_count", &[], count);
+
+    out.family(
+        "holochain_ribosome_errors_total",
+        "Zome calls which failed inside the ribosome.",
+        "counter",
+    );
+    out.sample(
+        "holochain_ribosome_errors_total",
+        &[],
+        RIBOSOME_ERRORS.load(Ordering::Relaxed),
+    );
+
+    out.family(
+        "holochain_workflow_queue_depth",
+        "Ops waiting to be processed by each workflow.",
+        "gauge",
+    );
+    for cell in &metrics.cells {
+        let dna = cell.cell_id.dna_hash().to_string();
+        let agent = cell.cell_id.agent_pubkey().to_string();
+        let depths = &cell.queue_depths;
+        for (workflow, depth) in [
+            ("sys_validation", depths.sys_validation),
+            ("app_validation", depths.app_validation),
+            ("integrate_dht_ops", depths.integrate_dht_ops),
+        ]
+        .iter()
+        {
+            out.sample(
+                "holochain_workflow_queue_depth",
+                &[("dna", &dna), ("agent", &agent), ("workflow", *workflow)],
+                depth,
+            );
+        }
+    }
+
+    out.family(
+        "holochain_dht_ops",
+        "Incoming ops held by each cell, by stage.",
+        "gauge",
+    );
+    for cell in &metrics.cells {
+        let dna = cell.cell_id.dna_hash().to_string();
+        let agent = cell.cell_id.agent_pubkey().to_string();
+        let counts = &cell.op_counts;
+        for (stage, count) in [
+            ("validation_limbo", counts.validation_limbo),
+            ("integration_limbo", counts.integration_limbo),
+            ("integrated", counts.integrated),
+        ]
+        .iter()
+        {
+            out.sample(
+                "holochain_dht_ops",
+                &[("dna", &dna), ("agent", &agent), ("stage", *stage)],
+                count,
+            );
+        }
+    }
+
+    out.family(
+        "holochain_lmdb_size_bytes",
+        "Size on disk of each LMDB environment.",
+        "gauge",
+    );
+    for (env, size) in &metrics.lmdb_size_bytes {
+        out.sample("holochain_lmdb_size_bytes", &[("env", *env)], size);
+    }
+    for cell in &metrics.cells {
+        let dna = cell.cell_id.dna_hash().to_string();
+        let agent = cell.cell_id.agent_pubkey().to_string();
+        out.sample(
+            "holochain_lmdb_size_bytes",
+            &[("env", "cell"), ("dna", &dna), ("agent", &agent)],
+            cell.lmdb_size_bytes,
+        );
+    }
+
+    render_kitsune(&mut out);
+
+    out.0
+}
+
+/// The kitsune counters are only populated when observability metrics are enabled
+fn render_kitsune(out: &mut PromWriter) {
+    use kitsune_p2p::dependencies::kitsune_p2p_types::metrics::get_sys_info;
+    use kitsune_p2p::dependencies::kitsune_p2p_types::transport::KitsuneTransportMetrics;
+    use kitsune_p2p::metrics::KitsuneMetrics;
+
+    out.family(
+        "kitsune_message_bytes_total",
+        "Bytes sent or received by kitsune, by message type. Gossip is reported as Gossip and GossipResp.",
+        "counter",
+    );
+    for (metric, count) in KitsuneMetrics::iter() {
+        out.sample(
+            "kitsune_message_bytes_total",
+            &[("message", &format!("{:?}", metric))],
+            count,
+        );
+    }
+
+    out.family(
+        "kitsune_transport_bytes_total",
+        "Bytes written to and read from the kitsune transport.",
+        "counter",
+    );
+    out.sample(
+        "kitsune_transport_bytes_total",
+        &[("direction", "write")],
+        KitsuneTransportMetrics::get(KitsuneTransportMetrics::Write),
+    );
+    out.sample(
+        "kitsune_transport_bytes_total",
+        &[("direction", "read")],
+        KitsuneTransportMetrics::get(KitsuneTransportMetrics::Read),
+    );
+
+    let sys_info = get_sys_info();
+    out.family(
+        "kitsune_sys_used_memory_kilobytes",
+        "Memory used by this process.",
+        "gauge",
+    );
+    out.sample(
+        "kitsune_sys_used_memory_kilobytes",
+        &[],
+        sys_info.used_mem_kb,
+    );
+    out.family(
+        "kitsune_sys_cpu_usage_percent",
+        "CPU used by this process.",
+        "gauge",
+    );
+    out.sample(
+        "kitsune_sys_cpu_usage_percent",
+        &[],
+        sys_info.proc_cpu_usage_pct_1000 as f64 / 1000.0,
+    );
+    out.family(
+        "kitsune_sys_network_bytes_per_second",
+        "Network throughput of this machine averaged over five seconds.",
+        "gauge",
+    );
+    out.sample(
+        "kitsune_sys_network_bytes_per_second",
+        &[("direction", "transmit")],
+        sys_info.tx_bytes_per_sec,
+    );
+    out.sample(
+        "kitsune_sys_network_bytes_per_second",
+        &[("direction", "receive")],
+        sys_info.rx_bytes_per_sec,
+    );
+}
+
+/// Spawn the metrics listener on localhost.
+/// Returns the port it is listening on and the task handle.
+pub async fn spawn_metrics_listener(
+    config: MetricsConfig,
+    conductor_handle: ConductorHandle,
+    mut stop_rx: StopReceiver,
+) -> std::io::Result<(u16, ManagedTaskHandle)> {
+    let mut listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port)).await?;
+    let port = listener.local_addr()?.port();
+    kitsune_p2p::dependencies::kitsune_p2p_types::metrics::init_sys_info_poll();
+    trace!(port, "Metrics listener started");
+
+    let handle = tokio::task::spawn(async move {
+        loop {
+            tokio::select! {
+                // break if we receive on the stop channel
+                _ = stop_rx.recv() => { break; },
+
+                con = listener.accept() => match con {
+                    Ok((stream, _)) => {
+                        tokio::task::spawn(handle_scrape(stream, conductor_handle.clone()));
+                    }
+                    Err(err) => {
+                        warn!("Metrics connection failed: {}", err);
+                    }
+                }
+            }
+        }
+        ManagedTaskResult::Ok(())
+    });
+    Ok((port, handle))
+}
+
+/// Answer a single HTTP request and close the connection
+async fn handle_scrape(mut stream: TcpStream, conductor_handle: ConductorHandle) {
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    // Only the request line matters, but read the whole head so
+    // the client isn't reset before it has finished writing.
+    let read_head = async {
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => return false,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+            if request.len() > MAX_REQUEST_BYTES {
+                return false;
+            }
+        }
+        true
+    };
+    match tokio::time::timeout(REQUEST_READ_TIMEOUT, read_head).await {
+        Ok(true) => (),
+        Ok(false) => return,
+        Err(_) => {
+            debug!("Metrics client didn't send a request in time");
+            return;
+        }
+    }
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => match conductor_handle.metrics().await {
+            Ok(metrics) => http_response("200 OK", &render(&metrics)),
+            Err(e) => {
+                error!(?e, "Failed to gather metrics");
+                http_response("500 Internal Server Error", "")
+            }
+        },
+        (Some("GET"), Some(_)) => http_response("404 Not Found", ""),
+        _ => http_response("405 Method Not Allowed", ""),
+    };
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        warn!("Failed to write metrics response: {}", e);
+    }
+}
+
+fn http_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// Writes metric families and samples in the text exposition format
+#[derive(Default)]
+struct PromWriter(String);
+
+impl PromWriter {
+    fn family(&mut self, name: &str, help: &str, kind: &str) {
+        // Writing to a String can't fail
+        writeln!(self.0, "# HELP {} {}", name, help).ok();
+        writeln!(self.0, "# TYPE {} {}", name, kind).ok();
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        self.0.push_str(name);
+        if !labels.is_empty() {
+            self.0.push('{');
+            for (i, (k, v)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.0.push(',');
+                }
+                write!(self.0, "{}=\"{}\"", k, escape_label(v)).ok();
+            }
+            self.0.push('}');
+        }
+        writeln!(self.0, " {}", value).ok();
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_text_exposition_format() {
+        let cell_id = CellId::new(
+            holo_hash::DnaHash::from_raw_32(vec![0; 32]),
+            holo_hash::AgentPubKey::from_raw_32(vec![1; 32]),
+        );
+        let metrics = ConductorMetrics {
+            cells: vec![CellMetrics {
+                cell_id: cell_id.clone(),
+                queue_depths: QueueDepths {
+                    sys_validation: 1,
+                    app_validation: 2,
+                    integrate_dht_ops: 3,
+                },
+                op_counts: IntegrationStateDump {
+                    validation_limbo: 3,
+                    integration_limbo: 3,
+                    integrated: 7,
+                },
+                lmdb_size_bytes: 4096,
+            }],
+            lmdb_size_bytes: vec![("conductor", 1024)],
+        };
+        record_zome_call(Duration::from_millis(3));
+        let out = render(&metrics);
+
+        let dna = cell_id.dna_hash().to_string();
+        let agent = cell_id.agent_pubkey().to_string();
+        assert!(out.contains("# TYPE holochain_zome_call_duration_seconds histogram\n"));
+        assert!(out.contains("holochain_zome_call_duration_seconds_bucket{le=\"+Inf\"} "));
+        assert!(out.contains(&format!(
+            "holochain_workflow_queue_depth{{dna=\"{}\",agent=\"{}\",workflow=\"app_validation\"}} 2\n",
+            dna, agent
+        )));
+        assert!(out.contains(&format!(
+            "holochain_dht_ops{{dna=\"{}\",agent=\"{}\",stage=\"integrated\"}} 7\n",
+            dna, agent
+        )));
+        assert!(out.contains("holochain_lmdb_size_bytes{env=\"conductor\"} 1024\n"));
+        assert!(out.contains("# TYPE kitsune_message_bytes_total counter\n"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
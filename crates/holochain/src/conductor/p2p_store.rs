@@ -24,10 +24,16 @@ use std::convert::TryInto;
 use std::sync::Arc;
 
 use super::error::ConductorResult;
+use super::manager::ManagedTaskHandle;
+use super::manager::ManagedTaskResult;
+use crate::conductor::conductor::StopReceiver;
 
 const AGENT_KEY_LEN: usize = 64;
 const AGENT_KEY_COMPONENT_LEN: usize = 32;
 
+/// How often expired agent info is pruned from the peer store.
+const PRUNE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 5);
+
 #[derive(Clone)]
 /// Required new type for KvBuf key.
 pub struct AgentKvKey([u8; AGENT_KEY_LEN]);
@@ -243,7 +249,7 @@ pub fn get_agent_info_signed(
             .unwrap()
             .as_millis() as u64;
 
-        if info.signed_at_ms() + info.expires_after_ms() <= now {
+        if info.is_expired(now) {
             p2p_kv
                 .as_store_ref()
                 .delete(writer, &(&*kitsune_space, &*kitsune_agent).into())?;
@@ -278,14 +284,10 @@ pub fn query_agent_info_signed(
                 match iter.next() {
                     Ok(Some((k, v))) => {
                         let info = kitsune_p2p::agent_store::AgentInfo::try_from(&v)?;
-                        let expires = info.signed_at_ms().checked_add(info.expires_after_ms());
-                        match expires {
-                            Some(expires) if expires > now => {
-                                if info.as_space_ref() == kitsune_space.as_ref() {
-                                    out.push(v);
-                                }
-                            }
-                            _ => expired.push(AgentKvKey::from(k)),
+                        if info.is_expired(now) {
+                            expired.push(AgentKvKey::from(k));
+                        } else if info.as_space_ref() == kitsune_space.as_ref() {
+                            out.push(v);
                         }
                     }
                     Ok(None) => break,
@@ -322,6 +324,55 @@ pub fn put_agent_info_signed(
     })?)
 }
 
+/// Delete all agent info that had expired by `now_ms`, along with any
+/// that can't be decoded. Returns how many entries were deleted.
+pub fn prune_agent_infos(environ: EnvironmentWrite, now_ms: u64) -> ConductorResult<usize> {
+    let p2p_kv = AgentKv::new(environ.clone().into())?;
+    let env = environ.guard();
+    env.with_commit(|writer| {
+        let expired: Vec<AgentKvKey> = p2p_kv
+            .iter(writer)?
+            .filter_map(|(k, v)| {
+                Ok(match kitsune_p2p::agent_store::AgentInfo::try_from(&v) {
+                    Ok(info) if !info.is_expired(now_ms) => None,
+                    _ => Some(k),
+                })
+            })
+            .collect()?;
+        for k in &expired {
+            p2p_kv.as_store_ref().delete(writer, k)?;
+        }
+        ConductorResult::Ok(expired.len())
+    })
+}
+
+/// Spawn a task which prunes expired agent info from the peer store
+/// every [`PRUNE_INTERVAL`], until the conductor stops.
+pub(crate) fn spawn_prune_agent_infos_task(
+    environ: EnvironmentWrite,
+    mut stop_rx: StopReceiver,
+) -> ManagedTaskHandle {
+    tokio::task::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = stop_rx.recv() => break,
+                _ = tokio::time::delay_for(PRUNE_INTERVAL) => {
+                    let now = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_millis() as u64;
+                    match prune_agent_infos(environ.clone(), now) {
+                        Ok(0) => (),
+                        Ok(pruned) => tracing::debug!(pruned, "Pruned expired agent info"),
+                        Err(e) => tracing::error!(failed_to_prune_agent_info = ?e),
+                    }
+                }
+            }
+        }
+        ManagedTaskResult::Ok(())
+    })
+}
+
 /// Dump the agents currently in the peer store
 pub fn dump_state(env: EnvironmentRead, cell_id: Option<CellId>) -> DatabaseResult<P2pStateDump> {
     use std::fmt::Write;
@@ -391,6 +442,7 @@ pub fn dump_state(env: EnvironmentRead, cell_id: Option<CellId>) -> DatabaseResu
         this_dna: cell_id.clone().map(|(s, _)| s),
         this_agent: cell_id.clone().map(|(_, a)| a),
         peers,
+        peer_scores: Vec::new(),
     })
 }
 
@@ -403,8 +455,10 @@ mod tests {
     use holochain_lmdb::env::WriteManager;
     use holochain_lmdb::fresh_reader_test;
     use holochain_lmdb::test_utils::test_p2p_env;
+    use kitsune_p2p::fixt::signed_agent_info;
     use kitsune_p2p::fixt::AgentInfoFixturator;
     use kitsune_p2p::fixt::AgentInfoSignedFixturator;
+    use kitsune_p2p::fixt::KitsuneSpaceFixturator;
     use kitsune_p2p::KitsuneBinType;
     use std::convert::TryInto;
 
@@ -488,4 +542,20 @@ mod tests {
 
         assert_eq!(expect, agents);
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn prune_removes_expired_agent_infos() {
+        observability::test_run().ok();
+        let t_env = test_p2p_env();
+        let env = t_env.env();
+        let now = 1_000_000_000_000;
+
+        let fresh = signed_agent_info(fixt!(KitsuneSpace), now - 100, 1000).await;
+        let expired = signed_agent_info(fixt!(KitsuneSpace), now - 2000, 1000).await;
+        inject_agent_infos(env.clone(), vec![fresh.clone(), expired]).unwrap();
+
+        assert_eq!(prune_agent_infos(env.clone(), now).unwrap(), 1);
+        assert_eq!(all_agent_infos(env.clone().into()).unwrap(), vec![fresh]);
+        assert_eq!(prune_agent_infos(env.clone(), now).unwrap(), 0);
+    }
 }
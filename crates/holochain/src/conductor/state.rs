@@ -7,6 +7,7 @@ use holochain_types::prelude::*;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Mutable conductor state, stored in a DB and writeable only via Admin interface.
 ///
@@ -25,6 +26,24 @@ pub struct ConductorState {
     /// List of interfaces any UI can use to access zome functions.
     #[serde(default)]
     pub app_interfaces: HashMap<AppInterfaceId, AppInterfaceConfig>,
+    /// Agent migrations whose new chain was opened, but whose old chain
+    /// is yet to be closed. The old cells keep running until it is.
+    #[serde(default)]
+    pub unclosed_migrations: Vec<UnclosedMigration>,
+}
+
+/// An agent migration whose new chain has been opened, and so can no longer
+/// be undone, but whose old chain could not be closed yet
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct UnclosedMigration {
+    /// The App whose slot was migrated
+    pub installed_app_id: InstalledAppId,
+    /// The slot which was migrated
+    pub slot_id: SlotId,
+    /// The cell whose chain is to be closed
+    pub old_cell_id: CellId,
+    /// The DNA the agent was migrated to
+    pub new_dna_hash: DnaHash,
 }
 
 /// A unique identifier used to refer to an App Interface internally.
@@ -72,9 +91,7 @@ impl ConductorState {
 /// GUIs, browser based web UIs, local native UIs, other local applications and scripts.
 /// We currently have:
 /// * websockets
-///
-/// We will also soon develop
-/// * Unix domain sockets
+/// * websockets over Unix domain sockets
 ///
 /// The cells (referenced by ID) that are to be made available via that interface should be listed.
 #[derive(Clone, Deserialize, Serialize, Debug)]
@@ -95,6 +112,19 @@ impl AppInterfaceConfig {
             driver: InterfaceDriver::Websocket { port },
         }
     }
+
+    /// Create config for a websocket interface over a unix domain socket
+    pub fn unix_socket(path: PathBuf) -> Self {
+        Self::from_driver(InterfaceDriver::UnixSocket { path })
+    }
+
+    /// Create config for an interface with the given driver
+    pub fn from_driver(driver: InterfaceDriver) -> Self {
+        Self {
+            signal_subscriptions: HashMap::new(),
+            driver,
+        }
+    }
 }
 
 // TODO: Tons of consistency check tests were ripped out in the great legacy code cleanup
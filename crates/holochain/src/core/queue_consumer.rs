@@ -28,10 +28,15 @@
 use derive_more::Constructor;
 use derive_more::Display;
 use derive_more::From;
+use fallible_iterator::FallibleIterator;
 use futures::future::Either;
+use holochain_lmdb::env::EnvironmentRead;
 use holochain_lmdb::env::EnvironmentWrite;
 use holochain_lmdb::env::WriteManager;
+use holochain_lmdb::error::DatabaseResult;
+use holochain_lmdb::fresh_reader;
 use holochain_lmdb::prelude::Writer;
+use holochain_state::validation_db::ValidationLimboStatus;
 use tokio::sync;
 use tokio::sync::mpsc;
 
@@ -47,6 +52,7 @@ use produce_dht_ops_consumer::*;
 mod publish_dht_ops_consumer;
 use crate::conductor::api::CellConductorApiT;
 use crate::conductor::manager::ManagedTaskAdd;
+use crate::core::workflow::incoming_dht_ops_workflow::IncomingDhtOpsWorkspace;
 use holochain_p2p::HolochainP2pCell;
 use holochain_state::workspace::WorkspaceError;
 use publish_dht_ops_consumer::*;
@@ -265,6 +271,39 @@ pub enum WorkComplete {
     Incomplete,
 }
 
+/// The number of ops waiting on each of a cell's incoming queues
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueueDepths {
+    /// Ops awaiting sys validation, including those waiting on dependencies
+    pub sys_validation: usize,
+    /// Ops awaiting app validation, including those waiting on dependencies
+    pub app_validation: usize,
+    /// Validated ops waiting to be integrated
+    pub integrate_dht_ops: usize,
+}
+
+/// Count the ops waiting on each of the incoming queues in a cell's environment
+pub fn queue_depths(env: EnvironmentRead) -> Result<QueueDepths, WorkspaceError> {
+    let workspace = IncomingDhtOpsWorkspace::new(env.clone())?;
+    let depths = fresh_reader!(env, |r| {
+        let mut depths = QueueDepths::default();
+        let mut iter = workspace.validation_limbo.iter(&r)?;
+        while let Some((_, v)) = iter.next()? {
+            match v.status {
+                ValidationLimboStatus::Pending | ValidationLimboStatus::AwaitingSysDeps(_) => {
+                    depths.sys_validation += 1
+                }
+                ValidationLimboStatus::SysValidated | ValidationLimboStatus::AwaitingAppDeps(_) => {
+                    depths.app_validation += 1
+                }
+            }
+        }
+        depths.integrate_dht_ops = workspace.integration_limbo.iter(&r)?.count()?;
+        DatabaseResult::Ok(depths)
+    })?;
+    Ok(depths)
+}
+
 /// The only error possible when attempting to trigger: the channel is closed
 #[derive(Debug, Display, thiserror::Error)]
 pub struct QueueTriggerClosedError;
@@ -72,7 +72,7 @@ impl TryFrom<MigrateAgentInvocation> for ExternIO {
 }
 
 /// the aggregate result of all zome callbacks for migrating an agent between dnas
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum MigrateAgentResult {
     /// all implemented migrate agent callbacks in all zomes passed
     Pass,
@@ -34,9 +34,13 @@ impl From<PostCommitHostAccess> for HostAccess {
     }
 }
 
+/// post_commit runs after the call's workspace has been flushed, so it can
+/// read the chain but not write to it: nothing would ever flush its commits.
 impl From<&PostCommitHostAccess> for HostFnAccess {
     fn from(_: &PostCommitHostAccess) -> Self {
-        Self::all()
+        let mut access = Self::all();
+        access.write_workspace = Permission::Deny;
+        access
     }
 }
 
@@ -97,6 +101,7 @@ mod test {
     use crate::fixt::PostCommitInvocationFixturator;
     use ::fixt::prelude::*;
     use holochain_types::dna::zome::HostFnAccess;
+    use holochain_types::dna::zome::Permission;
     use holochain_zome_types::post_commit::PostCommitCallbackResult;
     use holochain_zome_types::ExternIO;
 
@@ -148,10 +153,9 @@ mod test {
         let post_commit_host_access = PostCommitHostAccessFixturator::new(::fixt::Unpredictable)
             .next()
             .unwrap();
-        assert_eq!(
-            HostFnAccess::from(&post_commit_host_access),
-            HostFnAccess::all()
-        );
+        let mut access = HostFnAccess::all();
+        access.write_workspace = Permission::Deny;
+        assert_eq!(HostFnAccess::from(&post_commit_host_access), access);
     }
 
     #[test]
@@ -173,6 +173,17 @@ pub fn check_prev_seq(header: &Header, prev_header: &Header) -> SysValidationRes
     }
 }
 
+/// Check the previous header is not a [CloseChain], as nothing may be
+/// committed to a chain after it has been closed
+pub fn check_prev_not_closed(prev_header: &Header) -> SysValidationResult<()> {
+    match prev_header {
+        Header::CloseChain(_) => {
+            Err(PrevHeaderError::ChainClosed).map_err(|e| ValidationOutcome::from(e).into())
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Check the entry variant matches the variant in the headers entry type
 pub fn check_entry_type(entry_type: &EntryType, entry: &Entry) -> SysValidationResult<()> {
     match (entry_type, entry) {
@@ -147,4 +147,6 @@ pub enum PrevHeaderError {
     MissingPrev,
     #[error("The previous header's timestamp is not before the current header's timestamp")]
     Timestamp,
+    #[error("The previous header is a CloseChain so nothing can follow it")]
+    ChainClosed,
 }
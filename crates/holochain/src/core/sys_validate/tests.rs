@@ -1,6 +1,8 @@
 use super::*;
 use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::MockCellConductorApi;
+use crate::fixt::CloseChainFixturator;
+use crate::fixt::OpenChainFixturator;
 use crate::meta_mock;
 use ::fixt::prelude::*;
 use error::SysValidationError;
@@ -153,6 +155,20 @@ async fn check_previous_seq() {
     );
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn check_prev_not_closed_test() {
+    let prev_header: Header = fixt!(CloseChain).into();
+    assert_matches!(
+        check_prev_not_closed(&prev_header),
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::PrevHeaderError(PrevHeaderError::ChainClosed)
+        ))
+    );
+
+    let prev_header: Header = fixt!(OpenChain).into();
+    assert_matches!(check_prev_not_closed(&prev_header), Ok(()));
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn check_entry_type_test() {
     let entry_fixt = EntryFixturator::new(Predictable);
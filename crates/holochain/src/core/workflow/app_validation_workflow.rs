@@ -7,6 +7,9 @@ use std::sync::Arc;
 use self::validation_package::get_as_author_custom;
 use self::validation_package::get_as_author_full;
 use self::validation_package::get_as_author_sub_chain;
+use self::validation_package::is_complete_chain;
+use self::validation_package::oversized_package;
+use self::validation_package::MAX_VALIDATION_PACKAGE_SIZE;
 
 use super::error::WorkflowError;
 use super::error::WorkflowResult;
@@ -636,99 +639,40 @@ async fn get_validation_package_remote(
             let agent_id = element.header().author().clone();
             {
                 let mut cascade = workspace.full_cascade(network.clone());
-                // Get from author
-                let header_hashed = element.header_hashed();
-                if let Some(validation_package) = cascade
-                    .get_validation_package(agent_id.clone(), header_hashed)
-                    .await?
+                // Get from author, falling back to the gossiper if the author is unavailable
+                if let Some(validation_package) = fetch_validation_package(
+                    &mut cascade,
+                    agent_id.clone(),
+                    from_agent,
+                    element.header_hashed(),
+                )
+                .await
                 {
                     return Ok(Some(validation_package));
                 }
-
-                // Fallback to gossiper if author is unavailable
-                if let Some(from_agent) = from_agent {
-                    if let Some(validation_package) = cascade
-                        .get_validation_package(from_agent, header_hashed)
-                        .await?
-                    {
-                        return Ok(Some(validation_package));
-                    }
-                }
             }
 
             // Fallback to RegisterAgentActivity if gossiper is unavailable
-
-            // When getting agent activity we need to get all the elements from element authorities
-            // in parallel but if the network is small this could overwhelm the authorities and we
-            // might need to retry some of the gets.
-            // One consequence of this is the max timeout becomes the network timeout * NUM_RETRY_GETS
-            // if the data really isn't available.
-            // TODO: Another solution is to up the timeout for parallel gets.
-            const NUM_RETRY_GETS: u8 = 3;
-            let range = 0..element.header().header_seq().saturating_sub(1);
-
-            let mut query = holochain_zome_types::query::ChainQueryFilter::new()
-                .sequence_range(range)
-                .include_entries(true);
-            if let (RequiredValidationType::SubChain, Some(et)) = (
+            get_validation_package_from_activity(
+                element,
                 entry_def.required_validation_type,
-                element.header().entry_type(),
-            ) {
-                query = query.entry_type(et.clone());
-            }
-
-            // Get the activity from the agent authority
-            let options = GetActivityOptions {
-                include_full_headers: true,
-                include_valid_activity: true,
-                retry_gets: NUM_RETRY_GETS,
-                ..Default::default()
-            };
-            let activity = {
-                let mut cascade = workspace.full_cascade(network.clone());
-                cascade.get_agent_activity(agent_id, query, options).await?
-            };
-            match activity {
-                AgentActivityResponse {
-                    status: ChainStatus::Valid(_),
-                    valid_activity: ChainItems::Full(elements),
-                    ..
-                } => {
-                    // Cache this as a validation package
-                    workspace.meta_cache.register_validation_package(
-                        element.header_address(),
-                        elements.iter().map(|el| el.header_address().clone()),
-                    );
-                    Ok(Some(ValidationPackage::new(elements)))
-                }
-                // TODO: If the chain is invalid should we still return
-                // it as the validation package?
-                _ => Ok(None),
-            }
+                workspace,
+                network,
+            )
+            .await
         }
         RequiredValidationType::Custom => {
             let validation_package = {
                 let mut cascade = workspace.full_cascade(network.clone());
                 let agent_id = element.header().author().clone();
-                let header_hashed = element.header_hashed();
-                // Call the author
-                let validation_package = cascade
-                    .get_validation_package(agent_id, header_hashed)
-                    .await?;
-
-                // Fallback to gossiper
-                match &validation_package {
-                    Some(_) => validation_package,
-                    None => {
-                        if let Some(from_agent) = from_agent {
-                            cascade
-                                .get_validation_package(from_agent, header_hashed)
-                                .await?
-                        } else {
-                            None
-                        }
-                    }
-                }
+                // Call the author, falling back to the gossiper
+                fetch_validation_package(
+                    &mut cascade,
+                    agent_id,
+                    from_agent,
+                    element.header_hashed(),
+                )
+                .await
             };
 
             // Fallback to callback
@@ -772,6 +716,132 @@ async fn get_validation_package_remote(
     }
 }
 
+/// Build a [SubChain] or [Full] validation package from the author's
+/// agent activity, for when neither the author nor the gossiper can supply it.
+///
+/// [SubChain]: RequiredValidationType::SubChain
+/// [Full]: RequiredValidationType::Full
+async fn get_validation_package_from_activity(
+    element: &Element,
+    required_validation_type: RequiredValidationType,
+    workspace: &mut AppValidationWorkspace,
+    network: &HolochainP2pCell,
+) -> AppValidationOutcome<Option<ValidationPackage>> {
+    let agent_id = element.header().author().clone();
+    let header_seq = element.header().header_seq();
+
+    // When getting agent activity we need to get all the elements from element authorities
+    // in parallel but if the network is small this could overwhelm the authorities and we
+    // might need to retry some of the gets.
+    // One consequence of this is the max timeout becomes the network timeout * NUM_RETRY_GETS
+    // if the data really isn't available.
+    // TODO: Another solution is to up the timeout for parallel gets.
+    const NUM_RETRY_GETS: u8 = 3;
+
+    // The whole chain is fetched, even for a sub chain, so we can tell
+    // if any of it is missing
+    let query = holochain_zome_types::query::ChainQueryFilter::new()
+        .sequence_range(0..header_seq)
+        .include_entries(true);
+
+    // Get the activity from the agent authority
+    let options = GetActivityOptions {
+        include_full_headers: true,
+        include_valid_activity: true,
+        retry_gets: NUM_RETRY_GETS,
+        ..Default::default()
+    };
+    let activity = {
+        let mut cascade = workspace.full_cascade(network.clone());
+        cascade.get_agent_activity(agent_id, query, options).await?
+    };
+    match activity {
+        AgentActivityResponse {
+            status: ChainStatus::Valid(_),
+            valid_activity: ChainItems::Full(elements),
+            ..
+        } if is_complete_chain(&elements, header_seq) => {
+            let mut elements: Vec<_> = match required_validation_type {
+                RequiredValidationType::SubChain => {
+                    let entry_type = element.header().entry_type();
+                    elements
+                        .into_iter()
+                        .filter(|el| el.header().entry_type() == entry_type)
+                        .collect()
+                }
+                _ => elements,
+            };
+            // Newest first, the same as packages built by the author
+            elements.sort_unstable_by_key(|el| std::cmp::Reverse(el.header().header_seq()));
+            let validation_package = ValidationPackage::new(elements);
+
+            // The element may well be valid, we just can't get its package
+            if let Some(size) = oversized_package(&validation_package)? {
+                warn!(
+                    msg = "Ignoring validation package built from agent activity which is too large",
+                    size,
+                    max = MAX_VALIDATION_PACKAGE_SIZE,
+                    header = ?element.header_address()
+                );
+                return Ok(None);
+            }
+
+            // Cache this as a validation package
+            workspace.meta_cache.register_validation_package(
+                element.header_address(),
+                validation_package
+                    .0
+                    .iter()
+                    .map(|el| el.header_address().clone()),
+            );
+            Ok(Some(validation_package))
+        }
+        // TODO: If the chain is invalid should we still return
+        // it as the validation package?
+        AgentActivityResponse {
+            status: ChainStatus::Valid(_),
+            ..
+        }
+        | AgentActivityResponse {
+            status: ChainStatus::Empty,
+            ..
+        } => {
+            // Some of the chain hasn't reached its authorities yet
+            // so wait for it and try again later
+            match element.header().prev_header() {
+                Some(prev_header) => Outcome::exit_with_awaiting(vec![prev_header.clone()]),
+                None => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Ask the author and then the gossiper for a validation package.
+/// Agents that can't be reached or that send a package which is too
+/// large are treated as unavailable. Responses that are too large are
+/// refused by the network before they are decoded.
+async fn fetch_validation_package<Network: HolochainP2pCellT + Clone>(
+    cascade: &mut Cascade<'_, Network>,
+    author: AgentPubKey,
+    from_agent: Option<AgentPubKey>,
+    header_hashed: &HeaderHashed,
+) -> Option<ValidationPackage> {
+    for agent in std::iter::once(author).chain(from_agent) {
+        match cascade
+            .get_validation_package(agent.clone(), header_hashed)
+            .await
+        {
+            Ok(Some(validation_package)) => return Some(validation_package),
+            Ok(None) => (),
+            Err(e) => {
+                warn!(msg = "Failed to get validation package", error = ?e, ?agent);
+            }
+        }
+    }
+    None
+}
+
 pub async fn run_validation_callback_direct(
     zome: Zome,
     element: Element,
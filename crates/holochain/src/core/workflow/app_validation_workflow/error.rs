@@ -26,6 +26,8 @@ pub enum AppValidationError {
     #[error(transparent)]
     RibosomeError(#[from] RibosomeError),
     #[error(transparent)]
+    SerializedBytesError(#[from] SerializedBytesError),
+    #[error(transparent)]
     SourceChainError(#[from] SourceChainError),
     #[error("The app entry type {0:?} zome id was out of range")]
     ZomeId(ZomeId),
@@ -46,4 +48,5 @@ impl<T> From<AppValidationError> for OutcomeOrError<T, AppValidationError> {
 from_sub_error!(AppValidationError, RibosomeError);
 from_sub_error!(AppValidationError, CascadeError);
 from_sub_error!(AppValidationError, EntryDefStoreError);
+from_sub_error!(AppValidationError, SerializedBytesError);
 from_sub_error!(AppValidationError, SourceChainError);
@@ -5,6 +5,7 @@ use hdk::prelude::ValidationPackage;
 use holo_hash::HeaderHash;
 use holochain_lmdb::env::EnvironmentRead;
 use holochain_lmdb::fresh_reader_test;
+use holochain_lmdb::test_utils::test_environments;
 use holochain_p2p::actor::GetActivityOptions;
 use holochain_p2p::HolochainP2pCellT;
 use holochain_test_wasm_common::AgentActivitySearch;
@@ -12,12 +13,18 @@ use holochain_types::prelude::*;
 use holochain_wasm_test_utils::TestWasm;
 use matches::assert_matches;
 
+use super::fetch_validation_package;
+use super::get_validation_package_from_activity;
+use super::AppValidationWorkspace;
+use crate::conductor::p2p_store::exchange_peer_info;
 use crate::conductor::ConductorHandle;
 use crate::test_utils::conductor_setup::CellHostFnCaller;
 use crate::test_utils::conductor_setup::ConductorTestData;
 use crate::test_utils::host_fn_caller::Post;
 use crate::test_utils::new_zome_call;
 use crate::test_utils::wait_for_integration;
+use ::fixt::prelude::*;
+use holo_hash::fixt::AgentPubKeyFixturator;
 use holochain_cascade::Cascade;
 use holochain_cascade::DbPair;
 use holochain_cascade::DbPairMut;
@@ -26,6 +33,7 @@ use holochain_state::metadata::ChainItemKey;
 use holochain_state::metadata::MetadataBuf;
 use holochain_state::metadata::MetadataBufT;
 use holochain_state::source_chain::SourceChain;
+use kitsune_p2p::KitsuneP2pConfig;
 
 const NUM_COMMITS: usize = 5;
 const GET_AGENT_ACTIVITY_TIMEOUT_MS: u64 = 1000;
@@ -656,6 +664,182 @@ async fn get_agent_activity_host_fn_test() {
     conductor_test.shutdown_conductor().await;
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn validation_package_fallback_test() {
+    observability::test_run().ok();
+
+    // Alice and bob are on separate conductors so every request for a
+    // validation package goes over the network
+    let mut network = KitsuneP2pConfig::default();
+    network.transport_pool = vec![kitsune_p2p::TransportConfig::Quic {
+        bind_to: None,
+        override_host: None,
+        override_port: None,
+    }];
+    let zomes = vec![TestWasm::Create];
+    let mut alice_conductor =
+        ConductorTestData::with_network_config(zomes, false, network.clone()).await;
+    let handle = alice_conductor.handle();
+    let dna_file = alice_conductor
+        .alice_call_data()
+        .ribosome
+        .dna_file()
+        .clone();
+    let (mut bob_conductor, _) = ConductorTestData::new(
+        test_environments(),
+        vec![dna_file],
+        vec![fake_agent_pubkey_2()],
+        network,
+    )
+    .await;
+    exchange_peer_info(vec![
+        handle.get_p2p_env().await,
+        bob_conductor.handle().get_p2p_env().await,
+    ]);
+
+    let alice_call_data = alice_conductor.alice_call_data_mut();
+    let alice_agent_id = alice_call_data.cell_id.agent_pubkey().clone();
+
+    let header_hash = commit_some_data("create_entry", &alice_call_data, &handle).await;
+    let sub_chain_header_hash = commit_some_data("create_msg", &alice_call_data, &handle).await;
+
+    // Wait for alice to integrate her chain as an authority
+    // 3 ops per commit, 10 commits plus 7 for genesis + 2 for init + 2 for cap
+    alice_call_data.triggers.produce_dht_ops.trigger();
+    let expected_count = NUM_COMMITS * 2 * 3 + 9 + 2;
+    wait_for_integration(
+        &alice_call_data.env,
+        expected_count,
+        NUM_ATTEMPTS,
+        DELAY_PER_ATTEMPT.clone(),
+    )
+    .await;
+
+    let alice_source_chain = SourceChain::public_only(alice_call_data.env.clone().into()).unwrap();
+    let element = alice_source_chain
+        .get_element(&header_hash)
+        .unwrap()
+        .unwrap();
+    let sub_chain_element = alice_source_chain
+        .get_element(&sub_chain_header_hash)
+        .unwrap()
+        .unwrap();
+
+    // The only cell on bob's conductor
+    let bob_call_data = bob_conductor.alice_call_data();
+
+    // The packages alice builds and caches as the author
+    let expected_full = bob_call_data
+        .network
+        .get_validation_package(alice_agent_id.clone(), header_hash.clone())
+        .await
+        .unwrap()
+        .0
+        .expect("Alice should build a full chain package");
+    let expected_sub_chain = bob_call_data
+        .network
+        .get_validation_package(alice_agent_id.clone(), sub_chain_header_hash.clone())
+        .await
+        .unwrap()
+        .0
+        .expect("Alice should build a sub chain package");
+
+    // An author that can't be reached falls back to the gossiper
+    let mut workspace = AppValidationWorkspace::new(bob_call_data.env.clone().into()).unwrap();
+    {
+        let mut cascade = workspace.full_cascade(bob_call_data.network.clone());
+        let validation_package = fetch_validation_package(
+            &mut cascade,
+            fixt!(AgentPubKey),
+            Some(alice_agent_id.clone()),
+            element.header_hashed(),
+        )
+        .await;
+        assert_eq!(validation_package, Some(expected_full.clone()));
+    }
+
+    // Wait for bob to hold alice's ops as an authority, plus his own 7 for genesis,
+    // then take alice offline
+    wait_for_integration(
+        &bob_call_data.env,
+        expected_count + 7,
+        NUM_ATTEMPTS,
+        DELAY_PER_ATTEMPT.clone(),
+    )
+    .await;
+    alice_conductor.shutdown_conductor().await;
+
+    // Neither the author nor the gossiper can be reached now
+    let mut workspace = AppValidationWorkspace::new(bob_call_data.env.clone().into()).unwrap();
+    {
+        let mut cascade = workspace.full_cascade(bob_call_data.network.clone());
+        let validation_package = fetch_validation_package(
+            &mut cascade,
+            alice_agent_id.clone(),
+            None,
+            element.header_hashed(),
+        )
+        .await;
+        assert_eq!(validation_package, None);
+    }
+
+    // Without the author or gossiper the package is rebuilt from the
+    // agent activity authorities.
+    // Private entries are hidden differently by the author and the authorities
+    // so only the headers are compared
+    let header_hashes = |validation_package: Option<ValidationPackage>| {
+        validation_package
+            .expect("Expected a validation package")
+            .0
+            .into_iter()
+            .map(|el| el.header_address().clone())
+            .collect::<Vec<_>>()
+    };
+    let validation_package =
+        wait_for_package_from_activity(&element, RequiredValidationType::Full, bob_call_data).await;
+    assert_eq!(
+        header_hashes(validation_package),
+        header_hashes(Some(expected_full))
+    );
+
+    let validation_package = wait_for_package_from_activity(
+        &sub_chain_element,
+        RequiredValidationType::SubChain,
+        bob_call_data,
+    )
+    .await;
+    assert_eq!(
+        header_hashes(validation_package),
+        header_hashes(Some(expected_sub_chain))
+    );
+
+    bob_conductor.shutdown_conductor().await;
+}
+
+/// Rebuild a validation package from the agent activity authorities,
+/// retrying while the author's ops are still on their way to them
+async fn wait_for_package_from_activity(
+    element: &Element,
+    required_validation_type: RequiredValidationType,
+    call_data: &CellHostFnCaller,
+) -> Option<ValidationPackage> {
+    for _ in 0..NUM_ATTEMPTS {
+        let mut workspace = AppValidationWorkspace::new(call_data.env.clone().into()).unwrap();
+        if let Ok(Some(validation_package)) = get_validation_package_from_activity(
+            element,
+            required_validation_type,
+            &mut workspace,
+            &call_data.network,
+        )
+        .await
+        {
+            return Some(validation_package);
+        }
+        tokio::time::delay_for(DELAY_PER_ATTEMPT).await;
+    }
+    None
+}
+
 async fn commit_some_data(
     call: &str,
     alice_call_data: &CellHostFnCaller,
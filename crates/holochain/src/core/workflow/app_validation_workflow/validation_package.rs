@@ -12,6 +12,30 @@ use crate::core::SourceChainResult;
 use holochain_state::source_chain::SourceChain;
 use tracing::*;
 
+pub use holochain_p2p::MAX_VALIDATION_PACKAGE_SIZE;
+
+/// The serialized size of the package if it's bigger than
+/// [MAX_VALIDATION_PACKAGE_SIZE]
+pub fn oversized_package(
+    validation_package: &ValidationPackage,
+) -> Result<Option<usize>, SerializedBytesError> {
+    let size = holochain_serialized_bytes::encode(validation_package)?.len();
+    if size > MAX_VALIDATION_PACKAGE_SIZE {
+        Ok(Some(size))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Check that elements gathered from the network cover every header
+/// before `header_seq`, so none were missing from the authorities
+pub fn is_complete_chain(elements: &[Element], header_seq: u32) -> bool {
+    let mut seqs: Vec<_> = elements.iter().map(|el| el.header().header_seq()).collect();
+    seqs.sort_unstable();
+    seqs.dedup();
+    seqs.len() == header_seq as usize && seqs.iter().enumerate().all(|(i, s)| i as u32 == *s)
+}
+
 pub fn get_as_author_sub_chain(
     header_seq: u32,
     app_entry_type: AppEntryType,
@@ -71,3 +95,81 @@ pub fn get_as_author_custom(
 
     Ok(Some(ribosome.run_validation_package(access, invocation)?))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::CreateFixturator;
+    use crate::fixt::SignatureFixturator;
+    use ::fixt::prelude::*;
+    use matches::assert_matches;
+    use std::convert::TryFrom;
+
+    fn element_at(header_seq: u32) -> Element {
+        element_with_entry(header_seq, None)
+    }
+
+    fn element_with_entry(header_seq: u32, entry: Option<Entry>) -> Element {
+        let mut create = fixt!(Create);
+        create.header_seq = header_seq;
+        Element::new(
+            SignedHeaderHashed::with_presigned(
+                HeaderHashed::from_content_sync(create.into()),
+                fixt!(Signature),
+            ),
+            entry,
+        )
+    }
+
+    #[test]
+    fn complete_chain_needs_every_header() {
+        let elements: Vec<_> = (0..5).rev().map(element_at).collect();
+        assert!(is_complete_chain(&elements, 5));
+        // Too short for a later header
+        assert!(!is_complete_chain(&elements, 6));
+
+        // Missing a header in the middle
+        let gap: Vec<_> = elements
+            .iter()
+            .filter(|el| el.header().header_seq() != 2)
+            .cloned()
+            .collect();
+        assert!(!is_complete_chain(&gap, 5));
+
+        // Duplicates don't cover the gap
+        let mut dupes = gap.clone();
+        dupes.push(element_at(3));
+        assert!(!is_complete_chain(&dupes, 5));
+
+        assert!(is_complete_chain(&[], 0));
+        assert!(!is_complete_chain(&[], 1));
+    }
+
+    #[test]
+    fn small_package_is_not_oversized() {
+        let validation_package = ValidationPackage::new((0..5).map(element_at).collect());
+        assert_eq!(oversized_package(&validation_package).unwrap(), None);
+    }
+
+    #[test]
+    fn large_package_is_oversized() {
+        // Each entry is within the entry size limit but together they aren't
+        let entry = Entry::App(
+            AppEntryBytes::try_from(SerializedBytes::from(UnsafeBytes::from(vec![
+                0u8;
+                ENTRY_SIZE_LIMIT
+                    / 2
+            ])))
+            .unwrap(),
+        );
+        let validation_package = ValidationPackage::new(
+            (0..3)
+                .map(|i| element_with_entry(i, Some(entry.clone())))
+                .collect(),
+        );
+        assert_matches!(
+            oversized_package(&validation_package),
+            Ok(Some(size)) if size > MAX_VALIDATION_PACKAGE_SIZE
+        );
+    }
+}
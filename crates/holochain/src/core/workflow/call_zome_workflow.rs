@@ -7,6 +7,9 @@ use crate::core::queue_consumer::OneshotWriter;
 use crate::core::queue_consumer::TriggerSender;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::guest_callback::post_commit::PostCommitHostAccess;
+use crate::core::ribosome::guest_callback::post_commit::PostCommitInvocation;
+use crate::core::ribosome::guest_callback::post_commit::PostCommitResult;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomeCallHostAccess;
 use crate::core::ribosome::ZomeCallInvocation;
@@ -69,22 +72,97 @@ pub async fn call_zome_workflow<
     mut trigger_produce_dht_ops: TriggerSender,
 ) -> WorkflowResult<ZomeCallResult> {
     let should_write = args.is_root_zome_call;
-    let result = call_zome_workflow_inner(workspace_lock.clone(), network, keystore, args).await?;
+    let zome = args.invocation.zome.clone();
+    let cell_id = args.invocation.cell_id.clone();
+    let signal_tx = args.signal_tx.clone();
+    let post_commit_access =
+        PostCommitHostAccess::new(workspace_lock.clone(), keystore.clone(), network.clone());
+    let chain_head_start_len = workspace_lock.read().await.source_chain.len();
+
+    let (ribosome, result) =
+        call_zome_workflow_inner(workspace_lock.clone(), network, keystore, args).await?;
 
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
     // commit the workspace
-    if should_write {
+    let committed = if should_write {
         let mut guard = workspace_lock.write().await;
         let workspace = &mut guard;
+        let committed = new_header_hashes(workspace, chain_head_start_len)?;
         writer.with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))?;
-    }
+        committed
+    } else {
+        Vec::new()
+    };
 
     trigger_produce_dht_ops.trigger();
 
+    // Nested calls share the root call's workspace,
+    // so the root call runs post_commit for all of their commits.
+    if result.is_ok() && !committed.is_empty() {
+        tokio::task::spawn(run_post_commit(
+            ribosome,
+            post_commit_access,
+            zome,
+            cell_id,
+            committed,
+            signal_tx,
+        ));
+    }
+
     Ok(result)
 }
 
+/// The hashes of the headers added to the source chain since it was this long
+fn new_header_hashes(
+    workspace: &CallZomeWorkspace,
+    chain_head_start_len: usize,
+) -> WorkflowResult<Vec<HeaderHash>> {
+    let mut headers = Vec::new();
+    let mut i = chain_head_start_len;
+    while let Some(element) = workspace.source_chain.get_at_index(i as u32)? {
+        headers.push(element.header_address().clone());
+        i += 1;
+    }
+    Ok(headers)
+}
+
+/// Run the post_commit callback once the commits have been written.
+///
+/// The commits can't be rolled back at this point,
+/// so failures are only logged and sent out as a signal.
+/// The callback only gets read access to the workspace, which has already
+/// been flushed.
+async fn run_post_commit<Ribosome: RibosomeT + Send + 'static>(
+    ribosome: Ribosome,
+    access: PostCommitHostAccess,
+    zome: Zome,
+    cell_id: CellId,
+    headers: Vec<HeaderHash>,
+    mut signal_tx: SignalBroadcaster,
+) {
+    let zome_name = zome.zome_name().clone();
+    let invocation = PostCommitInvocation::new(zome, headers.clone().into());
+    let result =
+        tokio::task::spawn_blocking(move || ribosome.run_post_commit(access, invocation)).await;
+    let reason = match result {
+        Ok(Ok(PostCommitResult::Success)) => return,
+        Ok(Ok(PostCommitResult::Fail(_, reason))) => reason,
+        Ok(Err(e)) => e.to_string(),
+        Err(e) => e.to_string(),
+    };
+    tracing::error!(?cell_id, %zome_name, %reason, "post_commit callback failed");
+    let signal = SystemSignal::PostCommitFailed {
+        cell_id,
+        zome_name,
+        headers,
+        reason,
+    };
+    if let Err(e) = signal_tx.send(signal.into()) {
+        tracing::warn!(?e, "Failed to send post_commit failure signal");
+    }
+}
+
 async fn call_zome_workflow_inner<
     'env,
     Ribosome: RibosomeT + Send + 'static,
@@ -94,7 +172,7 @@ async fn call_zome_workflow_inner<
     network: HolochainP2pCell,
     keystore: KeystoreSender,
     args: CallZomeWorkflowArgs<Ribosome, C>,
-) -> WorkflowResult<ZomeCallResult> {
+) -> WorkflowResult<(Ribosome, ZomeCallResult)> {
     let CallZomeWorkflowArgs {
         ribosome,
         invocation,
@@ -257,7 +335,7 @@ async fn call_zome_workflow_inner<
         }
     }
 
-    Ok(result)
+    Ok((ribosome, result))
 }
 
 pub struct CallZomeWorkspace {
@@ -379,7 +457,9 @@ pub mod tests {
             conductor_api,
             is_root_zome_call: true,
         };
-        call_zome_workflow_inner(workspace.into(), network, keystore, args).await
+        call_zome_workflow_inner(workspace.into(), network, keystore, args)
+            .await
+            .map(|(_, result)| result)
     }
 
     // 1.  Check if there is a Capability token secret in the parameters.
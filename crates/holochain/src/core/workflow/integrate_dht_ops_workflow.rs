@@ -16,6 +16,10 @@ use holochain_cascade::error::CascadeResult;
 use holochain_cascade::Cascade;
 use holochain_cascade::DbPair;
 use holochain_cascade::{error::CascadeError, integrate_single_metadata};
+use holochain_conductor_api::DhtOpDump;
+use holochain_conductor_api::DhtOpStage;
+use holochain_conductor_api::DumpFullStateFilter;
+use holochain_conductor_api::FullIntegrationStateDump;
 use holochain_conductor_api::IntegrationStateDump;
 use holochain_lmdb::buffer::BufferedStore;
 use holochain_lmdb::buffer::KvBufFresh;
@@ -592,3 +596,80 @@ pub fn dump_state(env: EnvironmentRead) -> WorkspaceResult<IntegrationStateDump>
         integrated,
     })
 }
+
+/// Dump the details of every incoming op that passes the filter.
+pub fn dump_full_state(
+    env: EnvironmentRead,
+    filter: &DumpFullStateFilter,
+) -> WorkspaceResult<FullIntegrationStateDump> {
+    let workspace = IncomingDhtOpsWorkspace::new(env.clone())?;
+    let now = timestamp::now();
+    let (counts, ops) = fresh_reader!(env, |r| {
+        let mut counts = IntegrationStateDump {
+            validation_limbo: 0,
+            integration_limbo: 0,
+            integrated: 0,
+        };
+        let mut ops = Vec::new();
+        let mut keep = |op: DhtOpDump| {
+            if filter.matches(&op) {
+                ops.push(op);
+            }
+        };
+
+        let mut iter = workspace.validation_limbo.iter(&r)?;
+        while let Some((k, v)) = iter.next()? {
+            counts.validation_limbo += 1;
+            let time_in_limbo = (now - v.time_added).ok().and_then(|d| d.to_std().ok());
+            keep(DhtOpDump {
+                op_hash: DhtOpHash::from_raw_39_panicky(k.to_vec()),
+                op_type: v.op.get_type(),
+                basis: v.basis,
+                header_hash: v.op.header_hash().clone(),
+                stage: DhtOpStage::ValidationLimbo,
+                limbo_status: Some(v.status),
+                num_tries: Some(v.num_tries),
+                time_in_limbo,
+                validation_status: None,
+                when_integrated: None,
+            });
+        }
+
+        let mut iter = workspace.integration_limbo.iter(&r)?;
+        while let Some((k, v)) = iter.next()? {
+            counts.integration_limbo += 1;
+            keep(DhtOpDump {
+                op_hash: DhtOpHash::from_raw_39_panicky(k.to_vec()),
+                op_type: v.op.get_type(),
+                basis: v.op.dht_basis().clone(),
+                header_hash: v.op.header_hash().clone(),
+                stage: DhtOpStage::IntegrationLimbo,
+                limbo_status: None,
+                num_tries: None,
+                time_in_limbo: None,
+                validation_status: Some(v.validation_status),
+                when_integrated: None,
+            });
+        }
+
+        let mut iter = workspace.integrated_dht_ops.iter(&r)?;
+        while let Some((k, v)) = iter.next()? {
+            counts.integrated += 1;
+            keep(DhtOpDump {
+                op_hash: DhtOpHash::from_raw_39_panicky(k.to_vec()),
+                op_type: v.op.get_type(),
+                basis: v.op.dht_basis().clone(),
+                header_hash: v.op.header_hash().clone(),
+                stage: DhtOpStage::Integrated,
+                limbo_status: None,
+                num_tries: None,
+                time_in_limbo: None,
+                validation_status: Some(v.validation_status),
+                when_integrated: Some(v.when_integrated),
+            });
+        }
+        DatabaseResult::Ok((counts, ops))
+    })?;
+
+    Ok(FullIntegrationStateDump { counts, ops })
+}
@@ -0,0 +1,198 @@
+//! Migrate Agent Workflow: Run the `migrate_agent` callbacks and, if they all
+//! pass, commit the header which declares the migration:
+//! - CloseChain, on the old chain, naming the DNA being migrated to
+//! - OpenChain, on the new chain, naming the DNA being migrated from
+
+use super::error::WorkflowResult;
+use super::CallZomeWorkspace;
+use super::CallZomeWorkspaceLock;
+use crate::core::queue_consumer::OneshotWriter;
+use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentHostAccess;
+use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentInvocation;
+use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentResult;
+use crate::core::ribosome::RibosomeT;
+use derive_more::Constructor;
+use holochain_state::workspace::Workspace;
+use holochain_types::prelude::*;
+use holochain_zome_types::header::builder;
+use tracing::*;
+
+#[derive(Constructor, Debug)]
+pub struct MigrateAgentWorkflowArgs<Ribosome: RibosomeT> {
+    pub dna_def: DnaDef,
+    pub ribosome: Ribosome,
+    pub migrate_agent: MigrateAgent,
+    /// The DNA being migrated to when closing, or from when opening
+    pub other_dna_hash: DnaHash,
+}
+
+pub type MigrateAgentWorkspace = CallZomeWorkspace;
+
+#[instrument(skip(workspace, writer))]
+pub async fn migrate_agent_workflow<'env, Ribosome: RibosomeT>(
+    workspace: MigrateAgentWorkspace,
+    writer: OneshotWriter,
+    args: MigrateAgentWorkflowArgs<Ribosome>,
+) -> WorkflowResult<MigrateAgentResult> {
+    let workspace_lock = CallZomeWorkspaceLock::new(workspace);
+    let result = migrate_agent_workflow_inner(workspace_lock.clone(), args).await?;
+
+    // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
+    if let MigrateAgentResult::Pass = result {
+        let mut guard = workspace_lock.write().await;
+        let workspace: &mut CallZomeWorkspace = &mut guard;
+        // commit the workspace
+        writer.with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))?;
+    }
+    Ok(result)
+}
+
+async fn migrate_agent_workflow_inner<Ribosome: RibosomeT>(
+    workspace: CallZomeWorkspaceLock,
+    args: MigrateAgentWorkflowArgs<Ribosome>,
+) -> WorkflowResult<MigrateAgentResult> {
+    let MigrateAgentWorkflowArgs {
+        dna_def,
+        ribosome,
+        migrate_agent,
+        other_dna_hash,
+    } = args;
+
+    // Call the migrate_agent callbacks
+    let result = {
+        let host_access = MigrateAgentHostAccess::new(workspace.clone());
+        let invocation = MigrateAgentInvocation::new(dna_def, migrate_agent.clone());
+        ribosome.run_migrate_agent(host_access, invocation)?
+    };
+    if let MigrateAgentResult::Fail(_, _) = result {
+        return Ok(result);
+    }
+
+    // Declare the migration on the chain
+    let mut workspace = workspace.write().await;
+    match migrate_agent {
+        MigrateAgent::Close => {
+            workspace
+                .source_chain
+                .put(
+                    builder::CloseChain {
+                        new_dna_hash: other_dna_hash,
+                    },
+                    None,
+                )
+                .await?
+        }
+        MigrateAgent::Open => {
+            workspace
+                .source_chain
+                .put(
+                    builder::OpenChain {
+                        prev_dna_hash: other_dna_hash,
+                    },
+                    None,
+                )
+                .await?
+        }
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::core::ribosome::MockRibosomeT;
+    use crate::core::workflow::fake_genesis;
+    use crate::core::SourceChainError;
+    use crate::fixt::DnaDefFixturator;
+    use crate::fixt::ZomeNameFixturator;
+    use ::fixt::prelude::*;
+    use holochain_lmdb::test_utils::test_cell_env;
+    use holochain_lmdb::test_utils::TestEnvironment;
+    use holochain_zome_types::Header;
+    use matches::assert_matches;
+
+    async fn setup(
+        result: MigrateAgentResult,
+        migrate_agent: MigrateAgent,
+    ) -> (
+        TestEnvironment,
+        CallZomeWorkspaceLock,
+        WorkflowResult<MigrateAgentResult>,
+    ) {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+        fake_genesis(&mut workspace.source_chain).await.unwrap();
+
+        let mut ribosome = MockRibosomeT::new();
+        ribosome
+            .expect_run_migrate_agent()
+            .returning(move |_, _| Ok(result.clone()));
+
+        let args = MigrateAgentWorkflowArgs {
+            dna_def: fixt!(DnaDef),
+            ribosome,
+            migrate_agent,
+            other_dna_hash: fixt!(DnaHash),
+        };
+        let workspace_lock = CallZomeWorkspaceLock::new(workspace);
+        let result = migrate_agent_workflow_inner(workspace_lock.clone(), args).await;
+        (test_env, workspace_lock, result)
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn close_adds_close_chain_header() {
+        let (_test_env, workspace_lock, result) =
+            setup(MigrateAgentResult::Pass, MigrateAgent::Close).await;
+        assert_matches!(result, Ok(MigrateAgentResult::Pass));
+
+        let mut workspace = workspace_lock.write().await;
+        assert_matches!(
+            workspace
+                .source_chain
+                .get_at_index(3)
+                .unwrap()
+                .unwrap()
+                .header(),
+            Header::CloseChain(_)
+        );
+
+        // Nothing more can be committed to the closed chain
+        assert_matches!(
+            workspace
+                .source_chain
+                .put(builder::InitZomesComplete {}, None)
+                .await,
+            Err(SourceChainError::ChainClosed)
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn open_adds_open_chain_header() {
+        let (_test_env, workspace_lock, result) =
+            setup(MigrateAgentResult::Pass, MigrateAgent::Open).await;
+        assert_matches!(result, Ok(MigrateAgentResult::Pass));
+
+        assert_matches!(
+            workspace_lock
+                .read()
+                .await
+                .source_chain
+                .get_at_index(3)
+                .unwrap()
+                .unwrap()
+                .header(),
+            Header::OpenChain(_)
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn failed_callback_commits_nothing() {
+        let fail = MigrateAgentResult::Fail(fixt!(ZomeName), "no".into());
+        let (_test_env, workspace_lock, result) = setup(fail, MigrateAgent::Close).await;
+        assert_matches!(result, Ok(MigrateAgentResult::Fail(_, _)));
+
+        assert_eq!(workspace_lock.read().await.source_chain.len(), 3);
+    }
+}
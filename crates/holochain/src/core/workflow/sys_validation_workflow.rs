@@ -175,6 +175,14 @@ async fn sys_validation_workflow_inner(
                 workspace.put_val_limbo(op_hash, vlv)?;
             }
             Outcome::Rejected => {
+                // Let the network know which peer sent us an invalid op.
+                if let Some(from_agent) = vlv.from_agent.clone() {
+                    if from_agent != network.from_agent() {
+                        if let Err(e) = network.clone().report_invalid_op(from_agent).await {
+                            error!(failed_to_report_invalid_op = ?e);
+                        }
+                    }
+                }
                 let iv = IntegrationLimboValue {
                     op: vlv.op,
                     validation_status: ValidationStatus::Rejected,
@@ -461,7 +469,7 @@ async fn register_agent_activity(
             workspace,
             network,
             incoming_dht_ops_sender,
-            |_| Ok(()),
+            |prev| check_prev_not_closed(prev.header()),
         )
         .await?;
     }
@@ -487,6 +495,7 @@ async fn store_element(
             .ok_or_else(|| ValidationOutcome::DepMissingFromDht(prev_header_hash.clone().into()))?;
         check_prev_timestamp(&header, prev_header.header())?;
         check_prev_seq(&header, prev_header.header())?;
+        check_prev_not_closed(prev_header.header())?;
     }
     Ok(())
 }
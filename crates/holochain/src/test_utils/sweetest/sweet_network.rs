@@ -15,7 +15,7 @@ impl SweetNetwork {
                 override_host: None,
             };
             let proxy_config = holochain_p2p::kitsune_p2p::ProxyConfig::RemoteProxyClient {
-                proxy_url: url2::url2!("{}", proxy_addr.into_string().unwrap()),
+                proxy_urls: vec![url2::url2!("{}", proxy_addr.into_string().unwrap())],
             };
             network.transport_pool = vec![kitsune_p2p::TransportConfig::Proxy {
                 sub_transport: transport.into(),
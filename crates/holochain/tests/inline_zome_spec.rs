@@ -6,7 +6,7 @@ use holochain::{
     test_utils::sweetest::{SweetAgents, SweetConductor, SweetDnaFile},
 };
 use holochain::{
-    conductor::{api::error::ConductorApiError, CellError},
+    conductor::{api::error::ConductorApiError, error::ConductorError, CellError, ConductorHandle},
     core::workflow::error::WorkflowError,
     test_utils::WaitOps,
 };
@@ -14,8 +14,17 @@ use holochain::{
     core::ribosome::guest_callback::validate::ValidateResult, test_utils::wait_for_integration_10s,
 };
 use holochain::{core::SourceChainError, test_utils::display_agent_infos};
-use holochain_types::{dna::zome::inline_zome::InlineZome, signal::Signal};
+use holochain_state::source_chain::SourceChain;
+use holochain_types::{
+    app::MigrateAgentPayload,
+    dna::zome::inline_zome::InlineZome,
+    signal::{Signal, SystemSignal},
+};
 use holochain_zome_types::element::ElementEntry;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use tokio::stream::StreamExt;
 
 #[derive(
@@ -306,6 +315,255 @@ async fn signal_subscription() {
     assert_eq!(signals.len(), N);
 }
 
+/// A failing `post_commit` callback is reported as a system signal, and the
+/// commit itself is kept.
+#[tokio::test(threaded_scheduler)]
+#[cfg(feature = "test_utils")]
+async fn post_commit_failure_is_signalled() {
+    observability::test_run().ok();
+
+    let unit_entry_def = EntryDef::default_with_id("unit");
+    let zome = InlineZome::new_unique(vec![unit_entry_def.clone()])
+        .callback("create_unit", move |api, ()| {
+            let entry_def_id: EntryDefId = unit_entry_def.id.clone();
+            let entry = Entry::app(().try_into().unwrap()).unwrap();
+            let hash = api.create(EntryWithDefId::new(entry_def_id, entry))?;
+            Ok(hash)
+        })
+        .callback("read", |api, hash: HeaderHash| {
+            api.get(GetInput::new(hash.into(), GetOptions::default()))
+                .map_err(Into::into)
+        })
+        .callback("post_commit", |_api, headers: HeaderHashes| {
+            Ok(PostCommitCallbackResult::Fail(headers, "nope".into()))
+        });
+    let (dna_file, _) = SweetDnaFile::unique_from_inline_zome("zome1", zome)
+        .await
+        .unwrap();
+    let mut conductor = SweetConductor::from_config(Default::default()).await;
+    let app = conductor.setup_app("app", &[dna_file]).await;
+    let cell = &app.cells()[0];
+    let zome = cell.zome("zome1");
+
+    let mut signals = conductor.signals().await;
+
+    let hash: HeaderHash = conductor.call(&zome, "create_unit", ()).await;
+
+    let signal = tokio::time::timeout(std::time::Duration::from_secs(10), signals.next())
+        .await
+        .expect("timed out waiting for signal")
+        .expect("signal stream ended");
+    match signal {
+        Signal::System(SystemSignal::PostCommitFailed {
+            cell_id,
+            zome_name,
+            headers,
+            reason,
+        }) => {
+            assert_eq!(&cell_id, cell.cell_id());
+            assert_eq!(zome_name, "zome1".into());
+            assert_eq!(headers, vec![hash.clone()]);
+            assert_eq!(reason, "nope");
+        }
+        other => panic!("unexpected signal: {:?}", other),
+    }
+
+    // The commit was not rolled back
+    let element: Option<Element> = conductor.call(&zome, "read", hash).await;
+    assert!(element.is_some());
+}
+
+/// An agent can be migrated to a new DNA: their old chain is closed and a new
+/// chain is opened which references it
+#[tokio::test(threaded_scheduler)]
+#[cfg(feature = "test_utils")]
+async fn migrate_agent_to_new_dna() {
+    observability::test_run().ok();
+
+    let (old_dna, _) = SweetDnaFile::unique_from_inline_zome("zome1", simple_crud_zome())
+        .await
+        .unwrap();
+    let new_zome = simple_crud_zome().callback("migrate_agent_open", |_api, _: MigrateAgent| {
+        Ok(MigrateAgentCallbackResult::Pass)
+    });
+    let (new_dna, _) = SweetDnaFile::unique_from_inline_zome("zome1", new_zome)
+        .await
+        .unwrap();
+
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = conductor.setup_app("app", &[old_dna.clone()]).await;
+    let old_cell = app.cells()[0].clone();
+    let _: HeaderHash = conductor
+        .call(&old_cell.zome("zome1"), "create_unit", ())
+        .await;
+
+    conductor.register_dna(new_dna.clone()).await.unwrap();
+    let handle = ConductorHandle::clone(&conductor);
+    let new_cell_id = handle
+        .clone()
+        .migrate_agent(MigrateAgentPayload {
+            installed_app_id: "app".into(),
+            slot_id: old_dna.dna_hash().to_string(),
+            new_dna_hash: new_dna.dna_hash().clone(),
+            membrane_proof: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        new_cell_id,
+        CellId::new(new_dna.dna_hash().clone(), old_cell.agent_pubkey().clone())
+    );
+
+    // The old chain ends with a CloseChain naming the new DNA
+    let old_chain = SourceChain::new(old_cell.env().clone().into()).unwrap();
+    let head = old_chain
+        .get_header(old_chain.chain_head().unwrap())
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        head.header(),
+        Header::CloseChain(h) if &h.new_dna_hash == new_dna.dna_hash()
+    ));
+
+    // The new chain ends with an OpenChain naming the old DNA
+    let new_env = handle.get_cell_env(&new_cell_id).await.unwrap();
+    let new_chain = SourceChain::new(new_env.into()).unwrap();
+    let head = new_chain
+        .get_header(new_chain.chain_head().unwrap())
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        head.header(),
+        Header::OpenChain(h) if &h.prev_dna_hash == old_dna.dna_hash()
+    ));
+
+    // Only the new cell is still running
+    let cell_ids = handle.list_cell_ids().await.unwrap();
+    assert!(cell_ids.contains(&new_cell_id));
+    assert!(!cell_ids.contains(old_cell.cell_id()));
+}
+
+/// A migration which the new DNA rejects leaves the agent on their old,
+/// still open chain
+#[tokio::test(threaded_scheduler)]
+#[cfg(feature = "test_utils")]
+async fn rejected_migration_keeps_old_chain() {
+    observability::test_run().ok();
+
+    let (old_dna, _) = SweetDnaFile::unique_from_inline_zome("zome1", simple_crud_zome())
+        .await
+        .unwrap();
+    let new_zome = simple_crud_zome().callback("migrate_agent_open", |_api, _: MigrateAgent| {
+        Ok(MigrateAgentCallbackResult::Fail("not welcome".into()))
+    });
+    let (new_dna, _) = SweetDnaFile::unique_from_inline_zome("zome1", new_zome)
+        .await
+        .unwrap();
+
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = conductor.setup_app("app", &[old_dna.clone()]).await;
+    let old_cell = app.cells()[0].clone();
+
+    conductor.register_dna(new_dna.clone()).await.unwrap();
+    let handle = ConductorHandle::clone(&conductor);
+    let result = handle
+        .clone()
+        .migrate_agent(MigrateAgentPayload {
+            installed_app_id: "app".into(),
+            slot_id: old_dna.dna_hash().to_string(),
+            new_dna_hash: new_dna.dna_hash().clone(),
+            membrane_proof: None,
+        })
+        .await;
+    assert!(result.is_err());
+
+    // The old chain can still be committed to
+    let old_chain = SourceChain::new(old_cell.env().clone().into()).unwrap();
+    assert!(!old_chain.is_closed().unwrap());
+    let _: HeaderHash = conductor
+        .call(&old_cell.zome("zome1"), "create_unit", ())
+        .await;
+
+    // The app still refers to the old cell, and the new one is gone
+    let new_cell_id = CellId::new(new_dna.dna_hash().clone(), old_cell.agent_pubkey().clone());
+    let info = handle
+        .get_app_info(&"app".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.cell_data[0].as_id(), old_cell.cell_id());
+    let cell_ids = handle.list_cell_ids().await.unwrap();
+    assert!(cell_ids.contains(old_cell.cell_id()));
+    assert!(!cell_ids.contains(&new_cell_id));
+}
+
+/// Once the new chain is opened a migration is kept even if the old chain
+/// can't be closed, and migrating again closes it
+#[tokio::test(threaded_scheduler)]
+#[cfg(feature = "test_utils")]
+async fn unclosed_migration_can_be_resumed() {
+    observability::test_run().ok();
+
+    let refuse_close = Arc::new(AtomicBool::new(true));
+    let old_zome = {
+        let refuse_close = refuse_close.clone();
+        simple_crud_zome().callback("migrate_agent_close", move |_api, _: MigrateAgent| {
+            if refuse_close.load(Ordering::SeqCst) {
+                Ok(MigrateAgentCallbackResult::Fail("not yet".into()))
+            } else {
+                Ok(MigrateAgentCallbackResult::Pass)
+            }
+        })
+    };
+    let (old_dna, _) = SweetDnaFile::unique_from_inline_zome("zome1", old_zome)
+        .await
+        .unwrap();
+    let (new_dna, _) = SweetDnaFile::unique_from_inline_zome("zome1", simple_crud_zome())
+        .await
+        .unwrap();
+
+    let mut conductor = SweetConductor::from_standard_config().await;
+    let app = conductor.setup_app("app", &[old_dna.clone()]).await;
+    let old_cell = app.cells()[0].clone();
+    let new_cell_id = CellId::new(new_dna.dna_hash().clone(), old_cell.agent_pubkey().clone());
+    let payload = MigrateAgentPayload {
+        installed_app_id: "app".into(),
+        slot_id: old_dna.dna_hash().to_string(),
+        new_dna_hash: new_dna.dna_hash().clone(),
+        membrane_proof: None,
+    };
+
+    conductor.register_dna(new_dna.clone()).await.unwrap();
+    let handle = ConductorHandle::clone(&conductor);
+    let result = handle.clone().migrate_agent(payload.clone()).await;
+    assert!(matches!(
+        result,
+        Err(ConductorError::MigrationNotClosed { new_cell_id: ref id, .. }) if *id == new_cell_id
+    ));
+
+    // The app keeps the new, opened chain, and the old one is still open
+    let info = handle
+        .get_app_info(&"app".to_string())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.cell_data[0].as_id(), &new_cell_id);
+    let old_chain = SourceChain::new(old_cell.env().clone().into()).unwrap();
+    assert!(!old_chain.is_closed().unwrap());
+    let cell_ids = handle.list_cell_ids().await.unwrap();
+    assert!(cell_ids.contains(old_cell.cell_id()));
+    assert!(cell_ids.contains(&new_cell_id));
+
+    // Migrating again closes the old chain
+    refuse_close.store(false, Ordering::SeqCst);
+    let resumed = handle.clone().migrate_agent(payload).await.unwrap();
+    assert_eq!(resumed, new_cell_id);
+    let old_chain = SourceChain::new(old_cell.env().clone().into()).unwrap();
+    assert!(old_chain.is_closed().unwrap());
+    let cell_ids = handle.list_cell_ids().await.unwrap();
+    assert!(!cell_ids.contains(old_cell.cell_id()));
+}
+
 /// Simple zome which contains a validation rule which can fail
 fn simple_validation_zome() -> InlineZome {
     let entry_def = EntryDef::default_with_id("string");
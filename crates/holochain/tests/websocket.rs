@@ -10,7 +10,7 @@ use holochain::test_utils::sweetest::SweetDnaFile;
 use holochain::{
     conductor::api::ZomeCall,
     conductor::{
-        api::{AdminRequest, AdminResponse, AppRequest, AppResponse},
+        api::{AdminRequest, AdminResponse, AppInterfaceInfo, AppRequest, AppResponse},
         config::*,
         error::ConductorError,
         Conductor,
@@ -85,6 +85,9 @@ fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
             passphrase: "password".into(),
         }),
         keystore_path: None,
+        require_app_authentication: false,
+        metrics: None,
+        bundle_signatures: None,
         use_dangerous_test_keystore: true,
     }
 }
@@ -644,6 +647,71 @@ async fn conductor_admin_interface_ends_with_shutdown_inner() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn list_and_detach_app_interface() -> Result<()> {
+    observability::test_run().ok();
+    let tmp_dir = TempDir::new("conductor_cfg").unwrap();
+    let environment_path = tmp_dir.path().to_path_buf();
+    let config = create_config(0, environment_path);
+    let conductor_handle = Conductor::builder().config(config).build().await?;
+    let (mut client, _) = websocket_client(&conductor_handle).await?;
+
+    let response = client
+        .request(AdminRequest::AttachAppInterface { port: None })
+        .await?;
+    let app_port = match response {
+        AdminResponse::AppInterfaceAttached { port } => port,
+        _ => panic!("Attach app interface failed: {:?}", response),
+    };
+    let (_app_tx, app_rx) = websocket_client_by_port(app_port).await?;
+
+    // The connection is registered once the handshake completes
+    let mut listed = Vec::new();
+    for _ in 0..10 {
+        let response = client.request(AdminRequest::ListAppInterfaces).await?;
+        listed = match response {
+            AdminResponse::AppInterfacesListed(listed) => listed,
+            _ => panic!("List app interfaces failed: {:?}", response),
+        };
+        if listed.iter().any(|i| i.connections == 1) {
+            break;
+        }
+        tokio::time::delay_for(Duration::from_millis(100)).await;
+    }
+    let app_id = format!("interface-{}", app_port);
+    assert_eq!(
+        listed,
+        vec![AppInterfaceInfo {
+            id: app_id.clone(),
+            driver: InterfaceDriver::Websocket { port: app_port },
+            connections: 1,
+        }]
+    );
+
+    let response = client
+        .request(AdminRequest::DetachAppInterface { id: app_id.clone() })
+        .await?;
+    assert_matches!(response, AdminResponse::AppInterfaceDetached);
+
+    // The live connection is closed
+    let incoming: Vec<_> = tokio::time::timeout(Duration::from_secs(1), app_rx.collect())
+        .await
+        .expect("App connection was not closed");
+    assert_matches!(incoming.last(), Some(WebsocketMessage::Close(_)));
+
+    let response = client.request(AdminRequest::ListAppInterfaces).await?;
+    assert_matches!(response, AdminResponse::AppInterfacesListed(listed) if listed.is_empty());
+
+    // Detaching again is an error
+    let response = client
+        .request(AdminRequest::DetachAppInterface { id: app_id })
+        .await?;
+    assert_matches!(response, AdminResponse::Error(_));
+
+    conductor_handle.shutdown().await;
+    Ok(())
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn too_many_open() {
     observability::test_run().ok();
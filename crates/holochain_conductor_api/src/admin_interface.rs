@@ -51,6 +51,24 @@ pub enum AdminRequest {
     /// [`AdminResponse::DnaCloned`]: enum.AdminResponse.html#variant.DnaCloned
     CreateCloneCell(Box<CreateCloneCellPayload>),
 
+    /// Migrate the agent in one of an active App's slots to a new, already
+    /// registered DNA.
+    ///
+    /// The `migrate_agent` callbacks of the old DNA are run with `Close`, and
+    /// if they pass, a `CloseChain` header is committed to the old chain, after
+    /// which nothing more can be committed to it. A new Cell is then created
+    /// for the same agent in the new DNA, its `migrate_agent` callbacks are run
+    /// with `Open`, and an `OpenChain` header referencing the old DNA is
+    /// committed. The slot then refers to the new Cell.
+    ///
+    /// Will be responded to with an [`AdminResponse::AgentMigrated`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`MigrateAgentPayload`]: ../../../holochain_types/app/struct.MigrateAgentPayload.html
+    /// [`AdminResponse::AgentMigrated`]: enum.AdminResponse.html#variant.AgentMigrated
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    MigrateAgent(Box<MigrateAgentPayload>),
+
     /// Install an app from a list of `Dna` paths.
     /// Triggers genesis to be run on all `Cell`s and to be stored.
     /// An `App` is intended for use by
@@ -81,6 +99,10 @@ pub enum AdminRequest {
     /// Note that the new `App` will not be "activated" automatically after installation
     /// and can be activated by calling [`AdminRequest::ActivateApp`].
     ///
+    /// If the conductor is configured with `bundle_signatures`, the bundle
+    /// must carry a valid signature from one of the trusted publishers,
+    /// otherwise installation fails.
+    ///
     /// Will be responded to with an [`AdminResponse::AppInstalled`]
     /// or an [`AdminResponse::Error`]
     ///
@@ -90,6 +112,26 @@ pub enum AdminRequest {
     /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
     InstallAppBundle(Box<InstallAppBundlePayload>),
 
+    /// Install an app from a Web hApp bundle, which contains a zip file of
+    /// UI assets alongside a hApp bundle.
+    ///
+    /// The nested hApp bundle is installed just as with
+    /// [`AdminRequest::InstallAppBundle`], including the checks for trusted
+    /// publishers. The UI is then extracted into a directory for the app
+    /// within the conductor's environment path, which is reported as the
+    /// `web_ui_path` of the app's [`InstalledAppInfo`].
+    /// See [`InstallWebAppBundlePayload`] for full details on the configuration.
+    ///
+    /// Will be responded to with an [`AdminResponse::WebAppBundleInstalled`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`InstallWebAppBundlePayload`]: ../../../holochain_types/web_app/struct.InstallWebAppBundlePayload.html
+    /// [`InstalledAppInfo`]: crate::InstalledAppInfo
+    /// [`AdminRequest::InstallAppBundle`]: enum.AdminRequest.html#variant.InstallAppBundle
+    /// [`AdminResponse::WebAppBundleInstalled`]: enum.AdminResponse.html#variant.WebAppBundleInstalled
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    InstallWebAppBundle(Box<InstallWebAppBundlePayload>),
+
     /// List the hashes of all installed `Dna`s.
     /// Takes no arguments.
     ///
@@ -173,6 +215,45 @@ pub enum AdminRequest {
         /// OS choose a free port
         port: Option<u16>,
     },
+    /// Open up a new websocket interface over a unix domain socket bound at
+    /// the filesystem path specified by argument `path`,
+    /// over which you can then use the [`AppRequest`] API.
+    /// Any active `App` will be callable via this interface.
+    /// Access to the interface is controlled by the permissions on the socket file.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppUnixSocketInterfaceAttached`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppUnixSocketInterfaceAttached`]: enum.AdminResponse.html#variant.AppUnixSocketInterfaceAttached
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    AttachAppUnixSocketInterface {
+        /// Path at which to bind the socket
+        path: std::path::PathBuf,
+    },
+    /// List all the app interfaces currently attached to the conductor,
+    /// along with the number of clients connected to each.
+    /// Takes no arguments.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppInterfacesListed`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppInterfacesListed`]: enum.AdminResponse.html#variant.AppInterfacesListed
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    ListAppInterfaces,
+    /// Detach the app interface with the id specified by argument `id`,
+    /// as given by [`AdminRequest::ListAppInterfaces`]. Live connections
+    /// are sent a close frame, a unix socket file is removed, and the
+    /// interface will not be restarted with the conductor.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppInterfaceDetached`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AppInterfaceDetached`]: enum.AdminResponse.html#variant.AppInterfaceDetached
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    DetachAppInterface {
+        /// The id of the interface to detach
+        id: String,
+    },
     /// Dump the full state of the `Cell` specified by argument `cell_id`,
     /// including its chain, as a string containing JSON.
     ///
@@ -185,6 +266,28 @@ pub enum AdminRequest {
         /// The `CellId` for which to dump state
         cell_id: Box<CellId>,
     },
+    /// Dump the state of the `Cell` specified by argument `cell_id`
+    /// as a typed [`FullStateDump`].
+    ///
+    /// Unlike [`AdminRequest::DumpState`] this includes the details of every
+    /// incoming op, such as its type, basis, validation status and how long
+    /// it has been in the validation limbo, and summarizes the source chain
+    /// instead of including it.
+    ///
+    /// Will be responded to with an [`AdminResponse::FullStateDumped`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`FullStateDump`]: crate::FullStateDump
+    /// [`AdminRequest::DumpState`]: enum.AdminRequest.html#variant.DumpState
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    /// [`AdminResponse::FullStateDumped`]: enum.AdminResponse.html#variant.FullStateDumped
+    DumpFullState {
+        /// The `CellId` for which to dump state
+        cell_id: Box<CellId>,
+        /// Restricts which ops are included.
+        /// All ops are included if this is `None`.
+        filter: Option<crate::DumpFullStateFilter>,
+    },
     /// Add a list [AgentInfoSigned] to this conductor's peer store.
     /// This is another way of finding peers on a dht.
     ///
@@ -210,6 +313,77 @@ pub enum AdminRequest {
         /// Optionally choose a specific agent info
         cell_id: Option<CellId>,
     },
+    /// Issue a token which an App interface connection can present in
+    /// [`AppRequest::Authenticate`] to bind itself to the `App` specified
+    /// by argument `installed_app_id`.
+    /// The token can be used to authenticate for `expiry_seconds`, an hour
+    /// by default, until it is revoked, or for as long as the `App` is installed,
+    /// whichever ends first.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppAuthenticationTokenIssued`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AppRequest::Authenticate`]: enum.AppRequest.html#variant.Authenticate
+    /// [`AdminResponse::AppAuthenticationTokenIssued`]: enum.AdminResponse.html#variant.AppAuthenticationTokenIssued
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    IssueAppAuthenticationToken {
+        /// The App to bind connections to
+        installed_app_id: InstalledAppId,
+        /// How long the token can be used to authenticate for
+        #[serde(default)]
+        expiry_seconds: Option<u64>,
+    },
+    /// Revoke a token issued by [`AdminRequest::IssueAppAuthenticationToken`],
+    /// so it can no longer be used to authenticate.
+    /// Connections which have already authenticated with it stay bound to its `App`.
+    ///
+    /// Will be responded to with an [`AdminResponse::AppAuthenticationTokenRevoked`]
+    ///
+    /// [`AdminRequest::IssueAppAuthenticationToken`]: enum.AdminRequest.html#variant.IssueAppAuthenticationToken
+    /// [`AdminResponse::AppAuthenticationTokenRevoked`]: enum.AdminResponse.html#variant.AppAuthenticationTokenRevoked
+    RevokeAppAuthenticationToken {
+        /// The token to revoke
+        token: crate::AppAuthenticationToken,
+    },
+    /// Stop this conductor talking to the agent specified by argument
+    /// `agent_pub_key` on the network of the dna specified by `dna_hash`.
+    /// Requests from the agent are refused, and it is not gossiped or
+    /// routed to.
+    ///
+    /// The ban lasts `duration_ms` or, if `None`, until an
+    /// [`AdminRequest::UnbanAgent`]. Peers are also banned temporarily
+    /// when their score drops too low, see the `peer_scores` of a
+    /// state dump.
+    ///
+    /// Will be responded to with an [`AdminResponse::AgentBanned`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminRequest::UnbanAgent`]: enum.AdminRequest.html#variant.UnbanAgent
+    /// [`AdminResponse::AgentBanned`]: enum.AdminResponse.html#variant.AgentBanned
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    BanAgent {
+        /// The dna network to ban the agent from
+        dna_hash: DnaHash,
+        /// The agent to ban
+        agent_pub_key: AgentPubKey,
+        /// How long to ban the agent for, `None` = until unbanned
+        duration_ms: Option<u64>,
+    },
+    /// Lift any ban on the agent specified by argument `agent_pub_key`
+    /// on the network of the dna specified by `dna_hash`,
+    /// and forget its score.
+    ///
+    /// Will be responded to with an [`AdminResponse::AgentUnbanned`]
+    /// or an [`AdminResponse::Error`]
+    ///
+    /// [`AdminResponse::AgentUnbanned`]: enum.AdminResponse.html#variant.AgentUnbanned
+    /// [`AdminResponse::Error`]: enum.AppResponse.html#variant.Error
+    UnbanAgent {
+        /// The dna network to unban the agent on
+        dna_hash: DnaHash,
+        /// The agent to unban
+        agent_pub_key: AgentPubKey,
+    },
 }
 
 /// Represents the possible responses to an [`AdminRequest`]
@@ -263,6 +437,15 @@ pub enum AdminResponse {
     /// [`CellId`]: ../../../holochain_types/cell/struct.CellId.html
     AppBundleInstalled(InstalledApp),
 
+    /// The successful response to an [`AdminRequest::InstallWebAppBundle`].
+    ///
+    /// The resulting [`InstalledAppInfo`] includes the directory
+    /// the app's web UI was extracted to.
+    ///
+    /// [`AdminRequest::InstallWebAppBundle`]: enum.AdminRequest.html#variant.InstallWebAppBundle
+    /// [`InstalledAppInfo`]: crate::InstalledAppInfo
+    WebAppBundleInstalled(crate::InstalledAppInfo),
+
     /// The successful response to an [`AdminRequest::CreateCloneCell`].
     ///
     /// The response contains the [`CellId`] of the newly created clone.
@@ -271,6 +454,14 @@ pub enum AdminResponse {
     /// [`CellId`]: ../../../holochain_types/cell/struct.CellId.html
     CloneCellCreated(CellId),
 
+    /// The successful response to an [`AdminRequest::MigrateAgent`].
+    ///
+    /// The response contains the [`CellId`] of the new Cell in the new DNA.
+    ///
+    /// [`AdminRequest::MigrateAgent`]: enum.AdminRequest.html#variant.MigrateAgent
+    /// [`CellId`]: ../../../holochain_types/cell/struct.CellId.html
+    AgentMigrated(CellId),
+
     /// The succesful response to an [`AdminRequest::AddAdminInterfaces`].
     ///
     /// It means the `AdminInterface`s have successfully been added
@@ -318,6 +509,31 @@ pub enum AdminResponse {
         port: u16,
     },
 
+    /// The succesful response to an [`AdminRequest::AttachAppUnixSocketInterface`].
+    ///
+    /// `AppInterfaceApi` successfully attached.
+    /// Contains the path of the bound unix domain socket.
+    ///
+    /// [`AdminRequest::AttachAppUnixSocketInterface`]: enum.AdminRequest.html#variant.AttachAppUnixSocketInterface
+    AppUnixSocketInterfaceAttached {
+        /// Filesystem path of the new `AppInterfaceApi`
+        path: std::path::PathBuf,
+    },
+
+    /// The succesful response to an [`AdminRequest::ListAppInterfaces`].
+    ///
+    /// Contains a description of every attached app interface
+    ///
+    /// [`AdminRequest::ListAppInterfaces`]: enum.AdminRequest.html#variant.ListAppInterfaces
+    AppInterfacesListed(Vec<AppInterfaceInfo>),
+
+    /// The succesful response to an [`AdminRequest::DetachAppInterface`].
+    ///
+    /// It means the interface was detached and its connections closed
+    ///
+    /// [`AdminRequest::DetachAppInterface`]: enum.AdminRequest.html#variant.DetachAppInterface
+    AppInterfaceDetached,
+
     /// The succesful response to an [`AdminRequest::ActivateApp`].
     ///
     /// It means the `App` was activated successfully
@@ -340,6 +556,11 @@ pub enum AdminResponse {
     /// [`AdminRequest::DumpState`]: enum.AdminRequest.html#variant.DumpState
     StateDumped(String),
 
+    /// The succesful response to an [`AdminRequest::DumpFullState`].
+    ///
+    /// [`AdminRequest::DumpFullState`]: enum.AdminRequest.html#variant.DumpFullState
+    FullStateDumped(crate::FullStateDump),
+
     /// The succesful response to an [`AdminRequest::AddAgentInfo`].
     ///
     /// This means the agent info was successfully added to the peer store.
@@ -353,6 +574,44 @@ pub enum AdminResponse {
     ///
     /// [`AdminRequest::RequestAgentInfo`]: enum.AdminRequest.html#variant.RequestAgentInfo
     AgentInfoRequested(Vec<AgentInfoSigned>),
+
+    /// The succesful response to an [`AdminRequest::IssueAppAuthenticationToken`].
+    ///
+    /// Contains the token to hand to the App interface client.
+    ///
+    /// [`AdminRequest::IssueAppAuthenticationToken`]: enum.AdminRequest.html#variant.IssueAppAuthenticationToken
+    AppAuthenticationTokenIssued(crate::AppAuthenticationToken),
+
+    /// The succesful response to an [`AdminRequest::RevokeAppAuthenticationToken`].
+    ///
+    /// [`AdminRequest::RevokeAppAuthenticationToken`]: enum.AdminRequest.html#variant.RevokeAppAuthenticationToken
+    AppAuthenticationTokenRevoked,
+
+    /// The succesful response to an [`AdminRequest::BanAgent`].
+    ///
+    /// [`AdminRequest::BanAgent`]: enum.AdminRequest.html#variant.BanAgent
+    AgentBanned,
+
+    /// The succesful response to an [`AdminRequest::UnbanAgent`].
+    ///
+    /// [`AdminRequest::UnbanAgent`]: enum.AdminRequest.html#variant.UnbanAgent
+    AgentUnbanned,
+}
+
+/// Info about an attached app interface, returned as part of
+/// [`AdminResponse::AppInterfacesListed`]
+///
+/// [`AdminResponse::AppInterfacesListed`]: enum.AdminResponse.html#variant.AppInterfacesListed
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AppInterfaceInfo {
+    /// The id which identifies the interface to [`AdminRequest::DetachAppInterface`]
+    ///
+    /// [`AdminRequest::DetachAppInterface`]: enum.AdminRequest.html#variant.DetachAppInterface
+    pub id: String,
+    /// How the interface is exposed, including the bound port or path
+    pub driver: crate::config::InterfaceDriver,
+    /// The number of clients currently connected
+    pub connections: usize,
 }
 
 /// Error type that goes over the websocket wire.
@@ -376,6 +635,10 @@ pub enum ExternalApiWireError {
     ActivateApp(String),
     /// The zome call is unauthorized
     ZomeCallUnauthorized(String),
+    /// The request is not permitted for this App interface connection,
+    /// either because it is not authenticated or because it targets
+    /// another App or agent than the one it is bound to
+    AppUnauthenticated(String),
 }
 
 impl ExternalApiWireError {
@@ -44,6 +44,25 @@ pub enum AppRequest {
     /// Is currently unimplemented and will return
     /// an [`AppResponse::Unimplemented`](enum.AppResponse.html#variant.Unimplemented)
     SignalSubscription(SignalSubscription),
+
+    /// Authenticate this connection with a token issued by the conductor via
+    /// [`AdminRequest::IssueAppAuthenticationToken`].
+    ///
+    /// Once authenticated, the connection is bound to the token's `App`:
+    /// any request which targets the `Cell`s of another `App`,
+    /// or zome call which claims a provenance other than the `App`'s agent,
+    /// will be rejected.
+    ///
+    /// Will be responded to with an [`AppResponse::Authenticated`]
+    /// or an [`AppResponse::Error`]
+    ///
+    /// [`AdminRequest::IssueAppAuthenticationToken`]: enum.AdminRequest.html#variant.IssueAppAuthenticationToken
+    /// [`AppResponse::Authenticated`]: enum.AppResponse.html#variant.Authenticated
+    /// [`AppResponse::Error`]: enum.AppResponse.html#variant.Error
+    Authenticate {
+        /// The token issued for an App
+        token: AppAuthenticationToken,
+    },
 }
 
 /// Responses to requests received on an App interface
@@ -83,6 +102,35 @@ pub enum AppResponse {
 
     /// DEPRECATED. See `ZomeCall`.
     ZomeCallInvocation(Box<ExternIO>),
+
+    /// The successful response to an [`AppRequest::Authenticate`].
+    ///
+    /// Contains the id of the `App` this connection is now bound to.
+    ///
+    /// [`AppRequest::Authenticate`]: enum.AppRequest.html#variant.Authenticate
+    Authenticated {
+        /// The App this connection is bound to
+        installed_app_id: InstalledAppId,
+    },
+}
+
+/// An opaque token which binds an App interface connection to an `App`.
+/// Issued via [`AdminRequest::IssueAppAuthenticationToken`] and presented
+/// in [`AppRequest::Authenticate`].
+/// Tokens are held in memory and do not survive a conductor restart.
+/// They expire, and can be revoked via [`AdminRequest::RevokeAppAuthenticationToken`].
+///
+/// [`AdminRequest::IssueAppAuthenticationToken`]: enum.AdminRequest.html#variant.IssueAppAuthenticationToken
+/// [`AppRequest::Authenticate`]: enum.AppRequest.html#variant.Authenticate
+/// [`AdminRequest::RevokeAppAuthenticationToken`]: enum.AdminRequest.html#variant.RevokeAppAuthenticationToken
+#[derive(Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct AppAuthenticationToken(pub String);
+
+impl std::fmt::Debug for AppAuthenticationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Don't leak tokens into logs
+        f.write_str("AppAuthenticationToken(..)")
+    }
 }
 
 /// The data provided across an App interface in order to make a zome call
@@ -103,9 +151,12 @@ pub struct ZomeCall {
     pub cap: Option<CapSecret>,
     /// The provenance (source) of the call.
     ///
-    /// NB: **This will go away** as soon as Holochain has a way of determining who
-    /// is making this ZomeCall over this interface. Until we do, the caller simply
-    /// provides this data and Holochain trusts them.
+    /// On a connection authenticated with [`AppRequest::Authenticate`] this must be
+    /// the agent of the bound `App`, otherwise the call is rejected.
+    /// On an unauthenticated connection the caller simply provides this data
+    /// and Holochain trusts them.
+    ///
+    /// [`AppRequest::Authenticate`]: enum.AppRequest.html#variant.Authenticate
     pub provenance: AgentPubKey,
 }
 
@@ -127,6 +178,10 @@ pub struct InstalledAppInfo {
     pub cell_data: Vec<InstalledCell>,
     /// Is this app currently active?
     pub active: bool,
+    /// Where the web UI of this app was extracted to,
+    /// if it was installed from a Web hApp bundle
+    #[serde(default)]
+    pub web_ui_path: Option<std::path::PathBuf>,
 }
 
 impl InstalledAppInfo {
@@ -140,6 +195,7 @@ impl InstalledAppInfo {
             installed_app_id,
             cell_data,
             active,
+            web_ui_path: None,
         }
     }
 }
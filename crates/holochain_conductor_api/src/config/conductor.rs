@@ -6,9 +6,11 @@ use serde::Deserialize;
 use serde::Serialize;
 
 mod admin_interface_config;
+mod bundle_signature_config;
 mod dpki_config;
 #[allow(missing_docs)]
 mod error;
+mod metrics_config;
 mod passphrase_service_config;
 pub mod paths;
 //mod logger_config;
@@ -16,9 +18,11 @@ pub mod paths;
 pub use paths::EnvironmentRootPath;
 
 pub use super::*;
+pub use bundle_signature_config::BundleSignatureConfig;
 pub use dpki_config::DpkiConfig;
 //pub use logger_config::LoggerConfig;
 pub use error::*;
+pub use metrics_config::MetricsConfig;
 pub use passphrase_service_config::PassphraseServiceConfig;
 //pub use signal_config::SignalConfig;
 use std::path::Path;
@@ -73,8 +77,22 @@ pub struct ConductorConfig {
     /// Setup admin interfaces to control this conductor through a websocket connection
     pub admin_interfaces: Option<Vec<AdminInterfaceConfig>>,
 
+    /// Require every App interface connection to authenticate with a token
+    /// issued by the admin interface before making any other request.
+    /// Authenticated connections may only call into their own App as its agent.
+    #[serde(default)]
+    pub require_app_authentication: bool,
+
     /// Config options for the network module. Optional.
     pub network: Option<holochain_p2p::kitsune_p2p::KitsuneP2pConfig>,
+
+    /// Serve metrics over a local HTTP listener. Optional.
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
+
+    /// Only install hApp bundles signed by trusted publishers. Optional.
+    #[serde(default)]
+    pub bundle_signatures: Option<BundleSignatureConfig>,
     //
     //
     // /// Which signals to emit
@@ -107,6 +125,7 @@ impl ConductorConfig {
 pub mod tests {
     use super::*;
     use matches::assert_matches;
+    use std::convert::TryFrom;
     use std::path::Path;
     use std::path::PathBuf;
 
@@ -149,6 +168,9 @@ pub mod tests {
                 passphrase_service: Some(PassphraseServiceConfig::Cmd),
                 keystore_path: None,
                 admin_interfaces: None,
+                require_app_authentication: false,
+                metrics: None,
+                bundle_signatures: None,
                 use_dangerous_test_keystore: false,
             }
         );
@@ -195,6 +217,15 @@ pub mod tests {
         default_rpc_multi_remote_agent_count: 42
         default_rpc_multi_timeout_ms: 42
         agent_info_expires_after_ms: 42
+        quic_max_connections: 42
+        quic_connection_idle_timeout_ms: 42
+        quic_max_concurrent_streams: 42
+        peer_ban_duration_ms: 42
+        event_queue_len_per_space: 42
+        event_interactive_burst: 42
+      lan_discovery:
+        multicast_addr: 239.255.42.98:44498
+        interface: 127.0.0.1
 
     "#;
         let result: ConductorConfigResult<ConductorConfig> = config_from_yaml(yaml);
@@ -220,7 +251,17 @@ pub mod tests {
             tuning_params.default_rpc_multi_remote_agent_count = 42;
             tuning_params.default_rpc_multi_timeout_ms = 42;
             tuning_params.agent_info_expires_after_ms = 42;
+            tuning_params.quic_max_connections = 42;
+            tuning_params.quic_connection_idle_timeout_ms = 42;
+            tuning_params.quic_max_concurrent_streams = 42;
+            tuning_params.peer_ban_duration_ms = 42;
+            tuning_params.event_queue_len_per_space = 42;
+            tuning_params.event_interactive_burst = 42;
         }
+        network_config.lan_discovery = Some(LanDiscoveryConfig {
+            interface: std::net::Ipv4Addr::LOCALHOST,
+            ..Default::default()
+        });
         assert_eq!(
             result.unwrap(),
             ConductorConfig {
@@ -238,11 +279,115 @@ pub mod tests {
                 admin_interfaces: Some(vec![AdminInterfaceConfig {
                     driver: InterfaceDriver::Websocket { port: 1234 }
                 }]),
+                require_app_authentication: false,
+                metrics: None,
+                bundle_signatures: None,
                 network: Some(network_config),
             }
         );
     }
 
+    #[test]
+    fn test_config_unix_socket_admin_interface() {
+        let yaml = r#"---
+    environment_path: /path/to/env
+
+    admin_interfaces:
+      - driver:
+          type: unix_socket
+          path: /path/to/admin.sock
+    "#;
+        let result: ConductorConfig = config_from_yaml(yaml).unwrap();
+        assert_eq!(
+            result.admin_interfaces,
+            Some(vec![AdminInterfaceConfig {
+                driver: InterfaceDriver::UnixSocket {
+                    path: PathBuf::from("/path/to/admin.sock")
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn test_config_metrics() {
+        let yaml = r#"---
+    environment_path: /path/to/env
+
+    metrics:
+      port: 9100
+    "#;
+        let result: ConductorConfig = config_from_yaml(yaml).unwrap();
+        assert_eq!(result.metrics, Some(MetricsConfig { port: 9100 }));
+    }
+
+    #[test]
+    fn test_config_proxy_urls() {
+        let yaml = r#"---
+    environment_path: /path/to/env
+
+    network:
+      transport_pool:
+        - type: proxy
+          sub_transport:
+            type: quic
+          proxy_config:
+            type: remote_proxy_client
+            proxy_url: kitsune-proxy://a/kitsune-quic/h/127.0.0.1/p/5778/--
+        - type: proxy
+          sub_transport:
+            type: quic
+          proxy_config:
+            type: remote_proxy_client
+            proxy_urls:
+              - kitsune-proxy://a/kitsune-quic/h/127.0.0.1/p/5778/--
+              - kitsune-proxy://b/kitsune-quic/h/127.0.0.2/p/5778/--
+    "#;
+        let result: ConductorConfig = config_from_yaml(yaml).unwrap();
+        let proxy_urls = |urls: &[&str]| ProxyConfig::RemoteProxyClient {
+            proxy_urls: urls.iter().map(|url| url2::url2!("{}", url)).collect(),
+        };
+        let proxy_configs = result
+            .network
+            .unwrap()
+            .transport_pool
+            .into_iter()
+            .map(|transport| match transport {
+                TransportConfig::Proxy { proxy_config, .. } => proxy_config,
+                t => panic!("unexpected transport {:?}", t),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            proxy_configs,
+            vec![
+                proxy_urls(&["kitsune-proxy://a/kitsune-quic/h/127.0.0.1/p/5778/--"]),
+                proxy_urls(&[
+                    "kitsune-proxy://a/kitsune-quic/h/127.0.0.1/p/5778/--",
+                    "kitsune-proxy://b/kitsune-quic/h/127.0.0.2/p/5778/--",
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_bundle_signatures() {
+        let yaml = r#"---
+    environment_path: /path/to/env
+
+    bundle_signatures:
+      trusted_publishers:
+        - uhCAkmrkoAHPVf_eufG7eC5fm6QKrW5pPMoktvG5LOC0SnJ4vV1Uv
+    "#;
+        let result: ConductorConfig = config_from_yaml(yaml).unwrap();
+        let expected = holo_hash::AgentPubKey::try_from(
+            "uhCAkmrkoAHPVf_eufG7eC5fm6QKrW5pPMoktvG5LOC0SnJ4vV1Uv",
+        )
+        .unwrap();
+        assert_eq!(
+            result.bundle_signatures.unwrap().trusted_publishers(),
+            vec![expected]
+        );
+    }
+
     #[test]
     fn test_config_keystore() {
         let yaml = r#"---
@@ -269,6 +414,9 @@ pub mod tests {
                 }),
                 keystore_path: Some(PathBuf::from("/path/to/keystore").into()),
                 admin_interfaces: None,
+                require_app_authentication: false,
+                metrics: None,
+                bundle_signatures: None,
                 use_dangerous_test_keystore: true,
             }
         );
@@ -0,0 +1,26 @@
+use holo_hash::AgentPubKey;
+use holo_hash::AgentPubKeyB64;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Requires hApp bundles installed through `InstallAppBundle`, including those
+/// nested in Web hApp bundles installed through `InstallWebAppBundle`, to be signed
+/// by a trusted publisher, either with a signature embedded in the bundle
+/// or a detached signature supplied with the request.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct BundleSignatureConfig {
+    /// The publisher keys whose signatures are accepted.
+    /// An empty list rejects every bundle.
+    pub trusted_publishers: Vec<AgentPubKeyB64>,
+}
+
+impl BundleSignatureConfig {
+    /// The trusted publisher keys
+    pub fn trusted_publishers(&self) -> Vec<AgentPubKey> {
+        self.trusted_publishers
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+}
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Configures the HTTP listener which serves conductor metrics
+/// in the Prometheus text exposition format at `/metrics`.
+///
+/// The listener only ever binds to localhost, so metrics can only
+/// be scraped from the machine the conductor is running on.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct MetricsConfig {
+    /// The port to listen on.
+    /// Use 0 to have the OS assign one.
+    pub port: u16,
+}
@@ -1,11 +1,13 @@
 use serde::Deserialize;
 use serde::Serialize;
+use std::path::PathBuf;
 
 /// Information neeeded to spawn an Admin interface
 #[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
 pub struct AdminInterfaceConfig {
     /// By what means will the interface be exposed?
-    /// Current only option is a local websocket running on a configurable port.
+    /// Either a local websocket running on a configurable port,
+    /// or a unix domain socket at a configurable path.
     pub driver: InterfaceDriver,
     // /// How long will this interface be accessible between authentications?
     // /// TODO: implement once we have authentication
@@ -25,4 +27,21 @@ pub enum InterfaceDriver {
         /// The port on which to establish the WebsocketListener
         port: u16,
     },
+    /// An interface implemented via Websockets over a unix domain socket.
+    /// Access is controlled by the permissions on the socket file,
+    /// which is only accessible to the user running the conductor.
+    UnixSocket {
+        /// The filesystem path at which to bind the socket
+        path: PathBuf,
+    },
+}
+
+impl InterfaceDriver {
+    /// Get the port of a websocket driver, if this is one
+    pub fn port(&self) -> Option<u16> {
+        match self {
+            InterfaceDriver::Websocket { port } => Some(*port),
+            InterfaceDriver::UnixSocket { .. } => None,
+        }
+    }
 }
@@ -1,6 +1,13 @@
 use holo_hash::AgentPubKey;
+use holo_hash::AnyDhtHash;
+use holo_hash::DhtOpHash;
 use holo_hash::DnaHash;
+use holo_hash::HeaderHash;
 use holochain_state::source_chain::SourceChainJsonDump;
+use holochain_state::validation_db::ValidationLimboStatus;
+use holochain_types::dht_op::DhtOpType;
+use holochain_zome_types::Timestamp;
+use holochain_zome_types::ValidationStatus;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -11,6 +18,103 @@ pub struct JsonDump {
     pub integration_dump: IntegrationStateDump,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A typed dump of a cell's state.
+/// Returned by [`AdminRequest::DumpFullState`].
+///
+/// Use display to see a table of the ops.
+///
+/// [`AdminRequest::DumpFullState`]: crate::AdminRequest::DumpFullState
+pub struct FullStateDump {
+    /// Peer info from the p2p store.
+    pub peer_dump: P2pStateDump,
+    /// A summary of this cell's source chain.
+    pub source_chain_dump: SourceChainSummary,
+    /// Every incoming op and where it is currently.
+    pub integration_dump: FullIntegrationStateDump,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// A summary of the source chain without
+/// the elements themselves.
+pub struct SourceChainSummary {
+    /// The number of elements on the chain.
+    pub chain_len: usize,
+    /// The hash of the latest header on the chain.
+    pub chain_head: Option<HeaderHash>,
+    /// The number of ops produced by the chain that
+    /// are published to the network.
+    pub published_ops_count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The counts from [`IntegrationStateDump`] along
+/// with the details of each op.
+pub struct FullIntegrationStateDump {
+    /// The number of ops at each stage.
+    /// These are counted before any filter is applied.
+    pub counts: IntegrationStateDump,
+    /// The ops that matched the filter.
+    pub ops: Vec<DhtOpDump>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+/// The details of a single incoming op.
+pub struct DhtOpDump {
+    /// The hash of the op.
+    pub op_hash: DhtOpHash,
+    /// The type of the op.
+    pub op_type: DhtOpType,
+    /// The basis hash the op was sent to.
+    pub basis: AnyDhtHash,
+    /// The header this op was produced from.
+    pub header_hash: HeaderHash,
+    /// Where the op is currently.
+    pub stage: DhtOpStage,
+    /// The status of the op in the validation limbo.
+    /// Only set for ops in [`DhtOpStage::ValidationLimbo`].
+    pub limbo_status: Option<ValidationLimboStatus>,
+    /// How many times validation has been attempted.
+    /// Only set for ops in [`DhtOpStage::ValidationLimbo`].
+    pub num_tries: Option<u32>,
+    /// How long the op has been in the validation limbo.
+    /// Only set for ops in [`DhtOpStage::ValidationLimbo`].
+    pub time_in_limbo: Option<std::time::Duration>,
+    /// The outcome of validation.
+    /// Not set for ops in [`DhtOpStage::ValidationLimbo`].
+    pub validation_status: Option<ValidationStatus>,
+    /// When the op was integrated.
+    /// Only set for ops in [`DhtOpStage::Integrated`].
+    pub when_integrated: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, derive_more::Display)]
+#[serde(rename_all = "snake_case")]
+/// The stages an incoming op moves through.
+pub enum DhtOpStage {
+    /// Awaiting sys or app validation.
+    #[display(fmt = "validation_limbo")]
+    ValidationLimbo,
+    /// Validated and waiting to be integrated.
+    #[display(fmt = "integration_limbo")]
+    IntegrationLimbo,
+    /// Integrated. This includes rejected ops.
+    #[display(fmt = "integrated")]
+    Integrated,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+/// Restricts which ops are included in a [`FullStateDump`].
+/// An empty list matches everything.
+pub struct DumpFullStateFilter {
+    /// Only include ops of these types.
+    #[serde(default)]
+    pub op_types: Vec<DhtOpType>,
+    /// Only include ops at these stages.
+    #[serde(default)]
+    pub stages: Vec<DhtOpStage>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 /// A collection of many cells dumps for easy viewing.
 /// Use display to see a nice printout.
@@ -43,6 +147,10 @@ pub struct P2pStateDump {
     pub this_agent: Option<(AgentPubKey, kitsune_p2p::KitsuneAgent)>,
     /// All other agent info.
     pub peers: Vec<AgentInfoDump>,
+    /// How the remote agents we have dealt with on this dna's network
+    /// have behaved, and which of them are banned.
+    #[serde(default)]
+    pub peer_scores: Vec<kitsune_p2p::actor::PeerScore>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -80,6 +188,101 @@ impl std::fmt::Display for JsonDump {
     }
 }
 
+impl std::str::FromStr for DhtOpStage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "validation_limbo" => Self::ValidationLimbo,
+            "integration_limbo" => Self::IntegrationLimbo,
+            "integrated" => Self::Integrated,
+            _ => return Err(format!("Unknown DhtOpStage: {}", s)),
+        })
+    }
+}
+
+impl DumpFullStateFilter {
+    /// Does this op pass the filter.
+    pub fn matches(&self, op: &DhtOpDump) -> bool {
+        (self.op_types.is_empty() || self.op_types.contains(&op.op_type))
+            && (self.stages.is_empty() || self.stages.contains(&op.stage))
+    }
+}
+
+impl From<&SourceChainJsonDump> for SourceChainSummary {
+    fn from(dump: &SourceChainJsonDump) -> Self {
+        // Elements are dumped from the head backwards
+        let chain_head = dump
+            .elements
+            .first()
+            .and_then(|e| e.as_ref())
+            .map(|e| e.header_address.clone());
+        Self {
+            chain_len: dump.elements.len(),
+            chain_head,
+            published_ops_count: dump.published_ops_count,
+        }
+    }
+}
+
+impl std::fmt::Display for FullStateDump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let int = &self.integration_dump.counts;
+        let s = &self.source_chain_dump;
+        writeln!(f, "--- Cell State Dump Summary ---")?;
+        writeln!(
+            f,
+            "Number of other peers in p2p store: {},",
+            self.peer_dump.peers.len()
+        )?;
+        writeln!(
+            f,
+            "Ops: Limbo (validation: {} integration: {}) Integrated: {}",
+            int.validation_limbo, int.integration_limbo, int.integrated
+        )?;
+        writeln!(
+            f,
+            "Elements authored: {}, Ops published: {}",
+            s.chain_len, s.published_ops_count
+        )?;
+        writeln!(f)?;
+        write!(f, "{}", self.integration_dump)
+    }
+}
+
+impl std::fmt::Display for FullIntegrationStateDump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<55} {:<27} {:<18} {:<20} {:>6} {:>10}",
+            "op hash", "type", "stage", "status", "tries", "in limbo"
+        )?;
+        for op in &self.ops {
+            let status = match (&op.limbo_status, &op.validation_status) {
+                (Some(limbo), _) => format!("{:?}", limbo),
+                (None, Some(status)) => format!("{:?}", status),
+                (None, None) => String::new(),
+            };
+            let tries = op.num_tries.map(|n| n.to_string()).unwrap_or_default();
+            let in_limbo = op
+                .time_in_limbo
+                .map(|d| format!("{}s", d.as_secs()))
+                .unwrap_or_default();
+            writeln!(
+                f,
+                "{:<55} {:<27} {:<18} {:<20} {:>6} {:>10}",
+                op.op_hash.to_string(),
+                op.op_type.to_string(),
+                op.stage.to_string(),
+                status,
+                tries,
+                in_limbo
+            )?;
+        }
+        Ok(())
+    }
+}
+
 impl std::fmt::Display for IntegrationStateDumps {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[")?;
@@ -121,6 +324,22 @@ impl std::fmt::Display for P2pStateDump {
         for peer in &self.peers {
             writeln!(f, "{}", peer)?;
         }
+        for score in &self.peer_scores {
+            write!(
+                f,
+                "agent: {:?} score: {} (ok: {} timeouts: {} failures: {} invalid ops: {})",
+                score.agent,
+                score.score,
+                score.successes,
+                score.timeouts,
+                score.failures,
+                score.invalid_ops
+            )?;
+            match &score.ban {
+                Some(ban) => writeln!(f, " banned until: {:?}", ban.until_ms)?,
+                None => writeln!(f)?,
+            }
+        }
         Ok(())
     }
 }
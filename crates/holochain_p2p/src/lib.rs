@@ -20,6 +20,10 @@ pub use test::HolochainP2pCellFixturator;
 
 pub use kitsune_p2p;
 
+/// 16mb limit on validation packages, the same as entries, due to websocket limits.
+/// Authors won't send larger packages and validators won't read them.
+pub const MAX_VALIDATION_PACKAGE_SIZE: usize = 16_000_000;
+
 #[mockall::automock]
 #[async_trait::async_trait]
 /// A wrapper around HolochainP2pSender that partially applies the dna_hash / agent_pub_key.
@@ -99,6 +103,9 @@ pub trait HolochainP2pCellT {
         to_agent: AgentPubKey,
         receipt: SerializedBytes,
     ) -> actor::HolochainP2pResult<()>;
+
+    /// Report that a remote agent sent us ops that failed validation.
+    async fn report_invalid_op(&mut self, agent: AgentPubKey) -> actor::HolochainP2pResult<()>;
 }
 
 /// A wrapper around HolochainP2pSender that partially applies the dna_hash / agent_pub_key.
@@ -276,6 +283,13 @@ impl HolochainP2pCellT for HolochainP2pCell {
             )
             .await
     }
+
+    /// Report that a remote agent sent us ops that failed validation.
+    async fn report_invalid_op(&mut self, agent: AgentPubKey) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .report_invalid_op((*self.dna_hash).clone(), agent)
+            .await
+    }
 }
 
 pub use kitsune_p2p::dht_arc;
@@ -624,6 +624,14 @@ impl HolochainP2pHandler for HolochainP2pActor {
             let response = kitsune_p2p
                 .rpc_single(space, to_agent, from_agent, req, None)
                 .await?;
+            // Don't decode a package we would refuse anyway
+            if response.len() > crate::MAX_VALIDATION_PACKAGE_SIZE {
+                return Err(HolochainP2pError::invalid_p2p_message(format!(
+                    "Validation package response of {} bytes is larger than the maximum of {} bytes",
+                    response.len(),
+                    crate::MAX_VALIDATION_PACKAGE_SIZE
+                )));
+            }
             let response = SerializedBytes::from(UnsafeBytes::from(response)).try_into()?;
             Ok(response)
         }
@@ -835,4 +843,69 @@ impl HolochainP2pHandler for HolochainP2pActor {
         .boxed()
         .into())
     }
+
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_report_invalid_op(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let agent = agent.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(
+            async move { Ok(kitsune_p2p.report_invalid_op(space, agent).await?) }
+                .boxed()
+                .into(),
+        )
+    }
+
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_ban_agent(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+        duration_ms: Option<u64>,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let agent = agent.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(
+            async move { Ok(kitsune_p2p.ban_agent(space, agent, duration_ms).await?) }
+                .boxed()
+                .into(),
+        )
+    }
+
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_unban_agent(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let agent = agent.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(
+            async move { Ok(kitsune_p2p.unban_agent(space, agent).await?) }
+                .boxed()
+                .into(),
+        )
+    }
+
+    #[tracing::instrument(skip(self), level = "trace")]
+    fn handle_peer_scores(
+        &mut self,
+        dna_hash: DnaHash,
+    ) -> HolochainP2pHandlerResult<Vec<kitsune_p2p::actor::PeerScore>> {
+        let space = dna_hash.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move { Ok(kitsune_p2p.peer_scores(space).await?) }
+            .boxed()
+            .into())
+    }
 }
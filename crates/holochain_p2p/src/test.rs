@@ -103,6 +103,34 @@ impl HolochainP2pHandler for StubNetwork {
     ) -> HolochainP2pHandlerResult<()> {
         Err("stub".into())
     }
+    fn handle_report_invalid_op(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
+    fn handle_ban_agent(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+        duration_ms: Option<u64>,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
+    fn handle_unban_agent(
+        &mut self,
+        dna_hash: DnaHash,
+        agent: AgentPubKey,
+    ) -> HolochainP2pHandlerResult<()> {
+        Err("stub".into())
+    }
+    fn handle_peer_scores(
+        &mut self,
+        dna_hash: DnaHash,
+    ) -> HolochainP2pHandlerResult<Vec<kitsune_p2p::actor::PeerScore>> {
+        Err("stub".into())
+    }
 }
 
 /// Spawn a stub network that doesn't respond to any messages.
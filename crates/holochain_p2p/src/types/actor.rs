@@ -256,6 +256,19 @@ ghost_actor::ghost_chan! {
 
         /// Send a validation receipt to a remote node.
         fn send_validation_receipt(dna_hash: DnaHash, to_agent: AgentPubKey, from_agent: AgentPubKey, receipt: SerializedBytes) -> ();
+
+        /// Report that a remote agent sent us ops that failed validation.
+        fn report_invalid_op(dna_hash: DnaHash, agent: AgentPubKey) -> ();
+
+        /// Stop talking to a remote agent on a dna's network,
+        /// for `duration_ms` or, if None, until it is unbanned.
+        fn ban_agent(dna_hash: DnaHash, agent: AgentPubKey, duration_ms: Option<u64>) -> ();
+
+        /// Lift a ban on a remote agent, and forget its score.
+        fn unban_agent(dna_hash: DnaHash, agent: AgentPubKey) -> ();
+
+        /// The scores of the remote agents we have dealt with on a dna's network.
+        fn peer_scores(dna_hash: DnaHash) -> Vec<kitsune_p2p::actor::PeerScore>;
     }
 }
 
@@ -49,12 +49,25 @@ impl SourceChain {
         self.0
     }
 
+    /// Whether the chain head is a [CloseChain] header, meaning this chain
+    /// has been migrated to another DNA and can't be committed to.
+    pub fn is_closed(&self) -> SourceChainResult<bool> {
+        let head = self.chain_head()?;
+        let header = self
+            .get_header(head)?
+            .ok_or(SourceChainError::MissingHead)?;
+        Ok(matches!(header.header(), Header::CloseChain(_)))
+    }
+
     /// Add a Element to the source chain, using a HeaderBuilder
     pub async fn put<H: HeaderInner, B: HeaderBuilder<H>>(
         &mut self,
         header_builder: B,
         maybe_entry: Option<Entry>,
     ) -> SourceChainResult<HeaderHash> {
+        if self.is_closed()? {
+            return Err(SourceChainError::ChainClosed);
+        }
         let common = HeaderBuilderCommon {
             author: self.agent_pubkey()?,
             timestamp: timestamp::now(),
@@ -484,6 +497,32 @@ pub mod tests {
         Ok(())
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn closed_chain_rejects_commits() -> SourceChainResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let alice = fixt!(AgentPubKey);
+
+        let mut chain = SourceChain(SourceChainBuf::new(env.clone().into())?);
+        chain.genesis(fake_dna_hash(1), alice, None).await?;
+        assert!(!chain.is_closed()?);
+
+        chain
+            .put(
+                builder::CloseChain {
+                    new_dna_hash: fake_dna_hash(2),
+                },
+                None,
+            )
+            .await?;
+        assert!(chain.is_closed()?);
+
+        let result = chain.put(builder::InitZomesComplete {}, None).await;
+        assert!(matches!(result, Err(SourceChainError::ChainClosed)));
+
+        Ok(())
+    }
+
     // @todo bring all this back when we want to administer cap claims better
     // #[tokio::test(threaded_scheduler)]
     // async fn test_get_cap_claim() -> SourceChainResult<()> {
@@ -24,6 +24,9 @@ pub enum SourceChainError {
     #[error("The source chain's head is pointing to an address which has no content.")]
     MissingHead,
 
+    #[error("The source chain has been closed by a CloseChain header and can't be committed to.")]
+    ChainClosed,
+
     #[error("The content at address {0} is malformed and can't be deserialized.")]
     MalformedEntry(EntryHash),
 
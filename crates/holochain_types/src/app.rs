@@ -87,6 +87,19 @@ impl CreateCloneCellPayload {
     }
 }
 
+/// The instructions for migrating an agent's chain in one of an app's slots to a new DNA
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct MigrateAgentPayload {
+    /// The App whose slot is being migrated
+    pub installed_app_id: InstalledAppId,
+    /// The slot whose base cell will be closed and replaced
+    pub slot_id: SlotId,
+    /// The already-registered DNA to migrate to
+    pub new_dna_hash: DnaHash,
+    /// Proof-of-membership, if required by the new DNA
+    pub membrane_proof: Option<MembraneProof>,
+}
+
 /// A collection of [DnaHash]es paired with an [AgentPubKey] and an app id
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct InstallAppPayload {
@@ -116,6 +129,14 @@ pub struct InstallAppBundlePayload {
     /// Include proof-of-membrane-membership data for cells that require it,
     /// keyed by the CellNick specified in the app bundle manifest.
     pub membrane_proofs: HashMap<CellNick, MembraneProof>,
+
+    /// A detached publisher signature of the bundle.
+    /// If the conductor only accepts bundles from trusted publishers,
+    /// this is checked along with any signatures embedded in the bundle.
+    /// For a bundle given by path, a signature file alongside it
+    /// (e.g. `my-app.happ.sig`) is used if this isn't set.
+    #[serde(default)]
+    pub signature: Option<mr_bundle::BundleSignature>,
 }
 
 /// The possible locations of an AppBundle
@@ -391,6 +412,17 @@ impl InstalledApp {
         Ok(())
     }
 
+    /// Point a slot at the cell which its base cell was migrated to.
+    /// The new cell must use the same agent key as the slot.
+    pub fn migrate_slot(&mut self, slot_id: &SlotId, cell_id: CellId) -> AppResult<CellId> {
+        let slot = self.slot_mut(slot_id)?;
+        if cell_id.agent_pubkey() != slot.agent_key() {
+            return Err(AppError::MigratedAgentMismatch(slot_id.clone(), cell_id));
+        }
+        slot.is_provisioned = true;
+        Ok(std::mem::replace(&mut slot.base_cell_id, cell_id))
+    }
+
     /// Remove a cloned cell
     pub fn remove_clone(&mut self, slot_id: &SlotId, cell_id: &CellId) -> AppResult<bool> {
         let slot = self.slot_mut(slot_id)?;
@@ -515,4 +547,28 @@ mod tests {
             app.all_cells().collect::<HashSet<_>>()
         );
     }
+
+    #[test]
+    fn slot_migration() {
+        let base_cell_id = fixt!(CellId);
+        let agent = base_cell_id.agent_pubkey().clone();
+        let slot = AppSlot::new(base_cell_id.clone(), true, 0);
+        let slot_id: SlotId = "slot_id".into();
+        let mut app = InstalledApp::new("app", agent.clone(), vec![(slot_id.clone(), slot)]);
+
+        let new_cell_id = CellId::new(fixt!(DnaHash), agent);
+        let old_cell_id = app.migrate_slot(&slot_id, new_cell_id.clone()).unwrap();
+
+        assert_eq!(old_cell_id, base_cell_id);
+        assert_eq!(app.slot(&slot_id).unwrap().cell_id(), &new_cell_id);
+        assert_eq!(app.all_cells().collect::<Vec<_>>(), vec![&new_cell_id]);
+        matches::assert_matches!(
+            app.migrate_slot(&"missing".into(), fixt!(CellId)),
+            Err(AppError::SlotIdMissing(_))
+        );
+        matches::assert_matches!(
+            app.migrate_slot(&slot_id, CellId::new(fixt!(DnaHash), fixt!(AgentPubKey))),
+            Err(AppError::MigratedAgentMismatch(_, _))
+        );
+    }
 }
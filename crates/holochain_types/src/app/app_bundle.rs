@@ -37,6 +37,22 @@ impl AppBundle {
             .map_err(Into::into)
     }
 
+    /// The locations this bundle and its DNA bundles depend on whose content
+    /// is neither bundled nor pinned, so isn't covered by a signature over
+    /// this bundle. DNA bundles at unpinned locations aren't looked into.
+    pub async fn unpinned_locations(&self) -> AppBundleResult<Vec<mr_bundle::Location>> {
+        let mut unpinned = self.0.unpinned_locations();
+        for location in self.manifest().locations() {
+            if unpinned.contains(&location) {
+                continue;
+            }
+            let bytes = self.resolve(&location).await?;
+            let dna_bundle: mr_bundle::Bundle<DnaManifest> = mr_bundle::Bundle::decode(&bytes)?;
+            unpinned.extend(dna_bundle.unpinned_locations());
+        }
+        Ok(unpinned)
+    }
+
     /// Given a DnaGamut, decide which of the available DNAs or Cells should be
     /// used for each cell in this app.
     pub async fn resolve_cells(
@@ -2,8 +2,8 @@
 
 //! Defines the hApp Manifest YAML format, including validation.
 
-use mr_bundle::{Location, Manifest};
-use std::path::PathBuf;
+use mr_bundle::{Location, Manifest, ResourceHash};
+use std::{collections::HashMap, path::PathBuf};
 
 pub(crate) mod app_manifest_v1;
 pub mod app_manifest_validated;
@@ -37,6 +37,18 @@ impl Manifest for AppManifest {
         }
     }
 
+    fn pinned_hashes(&self) -> HashMap<Location, ResourceHash> {
+        match self {
+            AppManifest::V1(m) => m
+                .slots
+                .iter()
+                .filter_map(|slot| {
+                    Some((slot.dna.location.clone()?, slot.dna.resource_hash.clone()?))
+                })
+                .collect(),
+        }
+    }
+
     fn path() -> PathBuf {
         "happ.yaml".into()
     }
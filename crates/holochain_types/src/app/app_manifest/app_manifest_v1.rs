@@ -70,6 +70,11 @@ pub struct AppSlotDnaManifest {
     #[serde(flatten)]
     pub(super) location: Option<mr_bundle::Location>,
 
+    /// Pins the content of a "path" or "url" location: the DNA bundle found
+    /// there must have this Blake2b-256 hex digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(super) resource_hash: Option<mr_bundle::ResourceHash>,
+
     /// Optional default properties. May be overridden during installation.
     pub(super) properties: Option<YamlProperties>,
 
@@ -95,6 +100,7 @@ impl AppSlotDnaManifest {
             location: Some(mr_bundle::Location::Bundled(
                 "./path/to/my/dnabundle.dna".into(),
             )),
+            resource_hash: None,
             properties: None,
             uuid: None,
             version: None,
@@ -199,6 +205,7 @@ impl AppManifestV1 {
                  }| {
                     let AppSlotDnaManifest {
                         location,
+                        resource_hash: _,
                         properties,
                         version,
                         uuid,
@@ -293,6 +300,7 @@ pub mod tests {
             id: "nick".into(),
             dna: AppSlotDnaManifest {
                 location,
+                resource_hash: None,
                 properties: Some(YamlProperties::new(serde_yaml::to_value(props).unwrap())),
                 uuid: Some("uuid".into()),
                 version: Some(version),
@@ -38,6 +38,16 @@ impl AppManifestValidated {
         }
         Ok(AppManifestValidated { name, slots })
     }
+
+    /// Accessor
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Accessor
+    pub fn slots(&self) -> &HashMap<CellNick, AppSlotManifestValidated> {
+        &self.slots
+    }
 }
 
 /// Rules to determine if and how a Cell will be created for this Dna
@@ -13,5 +13,8 @@ pub enum AppError {
 
     #[error("Tried to install app '{0}' which contains duplicate slot ids. The following slot ids have duplicates: {1:?}")]
     DuplicateSlotIds(InstalledAppId, Vec<SlotId>),
+
+    #[error("Tried to migrate slot '{0}' to cell {1:?}, which does not use the slot's agent key")]
+    MigratedAgentMismatch(SlotId, CellId),
 }
 pub type AppResult<T> = Result<T, AppError>;
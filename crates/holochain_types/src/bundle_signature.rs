@@ -0,0 +1,187 @@
+//! Signing and verifying DNA and hApp bundles with publisher keys.
+//!
+//! A publisher signs the [`Bundle::signing_bytes`] with an ed25519 key held
+//! in the keystore. The signature can be embedded in the bundle or written
+//! to a detached signature file alongside it.
+
+use crate::prelude::*;
+use holochain_keystore::KeystoreError;
+use mr_bundle::error::MrBundleError;
+use mr_bundle::Bundle;
+pub use mr_bundle::BundleSignature;
+use mr_bundle::Manifest;
+
+/// Errors signing or verifying a bundle
+#[derive(thiserror::Error, Debug)]
+pub enum BundleSignatureError {
+    /// None of the valid signatures were made by a trusted publisher
+    #[error("The bundle isn't signed by any trusted publisher. It was signed by: {0:?}")]
+    UntrustedPublisher(Vec<AgentPubKey>),
+
+    /// The bundle has no valid signatures
+    #[error("The bundle has no valid signatures")]
+    Unsigned,
+
+    /// The bundle depends on resources outside of it which aren't pinned to
+    /// a hash, so a signature can't vouch for them
+    #[error(
+        "The bundle refers to resources which are neither bundled nor pinned to a hash: {0:?}"
+    )]
+    UnpinnedLocations(Vec<mr_bundle::Location>),
+
+    /// Keystore error
+    #[error(transparent)]
+    KeystoreError(#[from] KeystoreError),
+
+    /// Bundle error
+    #[error(transparent)]
+    MrBundleError(#[from] MrBundleError),
+}
+
+/// Result type for bundle signatures
+pub type BundleSignatureResult<T> = Result<T, BundleSignatureError>;
+
+/// Sign a bundle as `publisher`, whose private key must be in the keystore.
+/// The signature isn't added to the bundle; it can be embedded with
+/// [`Bundle::add_signature`] or written to a detached signature file.
+pub async fn sign_bundle<M: Manifest>(
+    bundle: &Bundle<M>,
+    keystore: &KeystoreSender,
+    publisher: &AgentPubKey,
+) -> BundleSignatureResult<BundleSignature> {
+    let signature = publisher
+        .sign_raw(keystore, &bundle.signing_bytes()?)
+        .await?;
+    Ok(BundleSignature {
+        publisher: publisher.get_raw_39().to_vec(),
+        signature: signature.0,
+    })
+}
+
+/// Check a signature is a valid signature of this bundle, returning the
+/// publisher which made it. Returns None if the signature is invalid.
+pub async fn verify_bundle_signature<M: Manifest>(
+    bundle: &Bundle<M>,
+    signature: &BundleSignature,
+) -> BundleSignatureResult<Option<AgentPubKey>> {
+    let publisher = match AgentPubKey::from_raw_39(signature.publisher.clone()) {
+        Ok(publisher) => publisher,
+        Err(_) => return Ok(None),
+    };
+    let valid = publisher
+        .verify_signature_raw(
+            &Signature(signature.signature.clone()),
+            &bundle.signing_bytes()?,
+        )
+        .await?;
+    Ok(if valid { Some(publisher) } else { None })
+}
+
+/// Find a valid signature by one of the `trusted` publishers among the
+/// signatures embedded in the bundle and the `detached` signature, if any.
+/// Returns the publisher whose signature was accepted.
+pub async fn verify_bundle_publisher<M: Manifest>(
+    bundle: &Bundle<M>,
+    detached: Option<&BundleSignature>,
+    trusted: &[AgentPubKey],
+) -> BundleSignatureResult<AgentPubKey> {
+    let mut signed_by = Vec::new();
+    for signature in bundle.signatures().iter().chain(detached) {
+        if let Some(publisher) = verify_bundle_signature(bundle, signature).await? {
+            if trusted.contains(&publisher) {
+                return Ok(publisher);
+            }
+            signed_by.push(publisher);
+        }
+    }
+    if signed_by.is_empty() {
+        Err(BundleSignatureError::Unsigned)
+    } else {
+        Err(BundleSignatureError::UntrustedPublisher(signed_by))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_keystore::test_keystore::spawn_test_keystore;
+    use holochain_keystore::KeystoreSenderExt;
+    use matches::assert_matches;
+
+    fn test_bundle() -> Bundle<DnaManifest> {
+        let manifest = DnaManifest::current(
+            "test".into(),
+            None,
+            None,
+            vec![ZomeManifest {
+                name: "zome".into(),
+                hash: None,
+                location: mr_bundle::Location::Bundled("zome.wasm".into()),
+            }],
+        );
+        Bundle::new_unchecked(manifest, vec![("zome.wasm".into(), vec![1, 2, 3])]).unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn trusted_publisher_signatures() {
+        let keystore = spawn_test_keystore().await.unwrap();
+        let publisher = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let stranger = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let trusted = [publisher.clone()];
+
+        let mut bundle = test_bundle();
+        assert_matches!(
+            verify_bundle_publisher(&bundle, None, &trusted).await,
+            Err(BundleSignatureError::Unsigned)
+        );
+
+        // A detached signature
+        let signature = sign_bundle(&bundle, &keystore, &publisher).await.unwrap();
+        assert_eq!(
+            verify_bundle_publisher(&bundle, Some(&signature), &trusted)
+                .await
+                .unwrap(),
+            publisher
+        );
+
+        // The same signature embedded in the bundle, which survives encoding
+        bundle.add_signature(signature);
+        let bundle: Bundle<DnaManifest> = Bundle::decode(&bundle.encode().unwrap()).unwrap();
+        assert_eq!(
+            verify_bundle_publisher(&bundle, None, &trusted)
+                .await
+                .unwrap(),
+            publisher
+        );
+
+        // Signed, but not by anyone we trust
+        let mut bundle = test_bundle();
+        let signature = sign_bundle(&bundle, &keystore, &stranger).await.unwrap();
+        bundle.add_signature(signature);
+        assert_matches!(
+            verify_bundle_publisher(&bundle, None, &trusted).await,
+            Err(BundleSignatureError::UntrustedPublisher(keys)) if keys == vec![stranger.clone()]
+        );
+
+        // A trusted signature over different contents is invalid
+        let other = Bundle::new_unchecked(
+            bundle.manifest().clone(),
+            vec![("zome.wasm".into(), vec![4, 5, 6])],
+        )
+        .unwrap();
+        let forged = BundleSignature {
+            publisher: publisher.get_raw_39().to_vec(),
+            ..sign_bundle(&other, &keystore, &publisher).await.unwrap()
+        };
+        assert_matches!(
+            verify_bundle_publisher(&bundle, Some(&forged), &trusted).await,
+            Err(BundleSignatureError::UntrustedPublisher(_))
+        );
+    }
+}
@@ -128,6 +128,41 @@ pub enum DhtOpLight {
     RegisterRemoveLink(HeaderHash, DhtBasis),
 }
 
+/// The type of a [DhtOp] or [DhtOpLight] without any of its data.
+/// Useful for filtering and reporting on ops.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Hash, derive_more::Display)]
+pub enum DhtOpType {
+    StoreElement,
+    StoreEntry,
+    RegisterAgentActivity,
+    RegisterUpdatedContent,
+    RegisterUpdatedElement,
+    RegisterDeletedBy,
+    RegisterDeletedEntryHeader,
+    RegisterAddLink,
+    RegisterRemoveLink,
+}
+
+impl std::str::FromStr for DhtOpType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "StoreElement" => Self::StoreElement,
+            "StoreEntry" => Self::StoreEntry,
+            "RegisterAgentActivity" => Self::RegisterAgentActivity,
+            "RegisterUpdatedContent" => Self::RegisterUpdatedContent,
+            "RegisterUpdatedElement" => Self::RegisterUpdatedElement,
+            "RegisterDeletedBy" => Self::RegisterDeletedBy,
+            "RegisterDeletedEntryHeader" => Self::RegisterDeletedEntryHeader,
+            "RegisterAddLink" => Self::RegisterAddLink,
+            "RegisterRemoveLink" => Self::RegisterRemoveLink,
+            _ => return Err(format!("Unknown DhtOpType: {}", s)),
+        })
+    }
+}
+
 impl DhtOp {
     fn as_unique_form(&self) -> UniqueForm<'_> {
         match self {
@@ -282,6 +317,21 @@ impl DhtOpLight {
             | DhtOpLight::RegisterRemoveLink(h, _) => h,
         }
     }
+
+    /// Get the type of this op
+    pub fn get_type(&self) -> DhtOpType {
+        match self {
+            DhtOpLight::StoreElement(_, _, _) => DhtOpType::StoreElement,
+            DhtOpLight::StoreEntry(_, _, _) => DhtOpType::StoreEntry,
+            DhtOpLight::RegisterAgentActivity(_, _) => DhtOpType::RegisterAgentActivity,
+            DhtOpLight::RegisterUpdatedContent(_, _, _) => DhtOpType::RegisterUpdatedContent,
+            DhtOpLight::RegisterUpdatedElement(_, _, _) => DhtOpType::RegisterUpdatedElement,
+            DhtOpLight::RegisterDeletedBy(_, _) => DhtOpType::RegisterDeletedBy,
+            DhtOpLight::RegisterDeletedEntryHeader(_, _) => DhtOpType::RegisterDeletedEntryHeader,
+            DhtOpLight::RegisterAddLink(_, _) => DhtOpType::RegisterAddLink,
+            DhtOpLight::RegisterRemoveLink(_, _) => DhtOpType::RegisterRemoveLink,
+        }
+    }
 }
 
 // FIXME: need to use this in HashableContent
@@ -221,4 +221,72 @@ mod tests {
         assert_eq!(dna_file.dna_def().zomes.len(), 2);
         assert_eq!(dna_file.code().len(), 2);
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn url_zome_is_pinned_by_its_hash() {
+        use mr_bundle::error::{BundleError, MrBundleError};
+
+        let dir = tempdir::TempDir::new("dna_bundle").unwrap();
+        let cache = mr_bundle::ResourceCache::new(dir.path().join("cache"));
+        let wasm = vec![1, 2, 3];
+        let hash = WasmHash::with_data(&DnaWasm::from(wasm.clone())).await;
+        let wrong_hash = WasmHash::with_data(&DnaWasm::from(vec![4, 5, 6])).await;
+        let url = serve_wasm(wasm.clone()).await;
+
+        let bundle_at = |url: String, hash: WasmHash| {
+            let manifest = DnaManifestCurrent {
+                name: "name".into(),
+                uuid: None,
+                properties: None,
+                zomes: vec![ZomeManifest {
+                    name: "zome1".into(),
+                    hash: Some(hash.into()),
+                    location: mr_bundle::Location::Url(url),
+                }],
+            };
+            let mut bundle = mr_bundle::Bundle::new_unchecked(manifest.into(), vec![]).unwrap();
+            bundle.set_resource_cache(cache.clone());
+            DnaBundle::from(bundle)
+        };
+
+        // Wasm which doesn't match the zome's hash is refused, and not cached
+        matches::assert_matches!(
+            bundle_at(url.clone(), wrong_hash).into_dna_file().await,
+            Err(DnaError::MrBundleError(MrBundleError::BundleError(
+                BundleError::ResourceHashMismatch { .. }
+            )))
+        );
+        assert!(!cache.dir().exists());
+
+        // Matching wasm is cached, so it resolves again without the network
+        let dna_file = bundle_at(url, hash.clone()).into_dna_file().await.unwrap();
+        assert_eq!(dna_file.code().get(&hash).unwrap().code().to_vec(), wasm);
+        let dna_file = bundle_at("http://127.0.0.1:1/gone".into(), hash.clone())
+            .into_dna_file()
+            .await
+            .unwrap();
+        assert_eq!(dna_file.code().get(&hash).unwrap().code().to_vec(), wasm);
+    }
+
+    /// Serve `body` over HTTP on a local port, as a remote zome host
+    async fn serve_wasm(body: Vec<u8>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/zome.wasm", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                stream.write_all(header.as_bytes()).await.unwrap();
+                stream.write_all(&body).await.unwrap();
+            }
+        });
+        url
+    }
 }
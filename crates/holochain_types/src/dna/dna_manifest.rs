@@ -1,4 +1,11 @@
-use std::path::PathBuf;
+use holo_hash::WasmHash;
+use holochain_serialized_bytes::prelude::*;
+use mr_bundle::{Location, ResourceHash};
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    path::PathBuf,
+};
 mod dna_manifest_v1;
 
 /// Re-export the current version. When creating a new version, just re-export
@@ -7,7 +14,7 @@ pub use dna_manifest_v1::{
     DnaManifestV1 as DnaManifestCurrent, DnaManifestV1Builder as DnaManifestCurrentBuilder, *,
 };
 
-use super::YamlProperties;
+use super::{wasm::DnaWasm, YamlProperties};
 
 /// The enum which encompasses all versions of the DNA manifest, past and present.
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, derive_more::From)]
@@ -25,6 +32,29 @@ impl mr_bundle::Manifest for DnaManifest {
         }
     }
 
+    /// A zome's wasm is pinned by its `hash`
+    fn pinned_hashes(&self) -> HashMap<Location, ResourceHash> {
+        match self {
+            Self::V1(m) => m
+                .zomes
+                .iter()
+                .filter_map(|zome| {
+                    let hash = WasmHash::from(zome.hash.clone()?);
+                    Some((zome.location.clone(), raw_resource_hash(hash.get_raw_32())))
+                })
+                .collect(),
+        }
+    }
+
+    /// The digest a zome's [WasmHash] is made of, which is over the
+    /// serialized [DnaWasm] rather than the bare wasm
+    fn resource_hash(bytes: &[u8]) -> ResourceHash {
+        let wasm = DnaWasm::from(bytes.to_vec());
+        let serialized = SerializedBytes::try_from(&wasm).expect("wasm can always be serialized");
+        let bytes: Vec<u8> = UnsafeBytes::from(serialized).into();
+        raw_resource_hash(&holo_hash::encode::blake2b_256(&bytes))
+    }
+
     fn path() -> PathBuf {
         "dna.yaml".into()
     }
@@ -34,6 +64,12 @@ impl mr_bundle::Manifest for DnaManifest {
     }
 }
 
+/// A 32 byte digest as a pinned resource hash
+fn raw_resource_hash(raw: &[u8]) -> ResourceHash {
+    let raw: [u8; 32] = raw.try_into().expect("a hash's core is 32 bytes");
+    raw.into()
+}
+
 impl DnaManifest {
     /// Create a DnaManifest based on the current version.
     /// Be sure to update this function when creating a new version.
@@ -15,6 +15,7 @@
 pub mod activity;
 pub mod app;
 pub mod autonomic;
+pub mod bundle_signature;
 pub mod chain;
 pub mod db;
 pub mod dht_op;
@@ -30,6 +31,7 @@ pub mod prelude;
 pub mod signal;
 pub mod timestamp;
 pub mod validate;
+pub mod web_app;
 
 // #[cfg(test)]
 pub mod test_utils;
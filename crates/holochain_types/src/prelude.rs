@@ -12,6 +12,7 @@ pub use crate::activity::*;
 pub use crate::app::error::*;
 pub use crate::app::*;
 pub use crate::autonomic::*;
+pub use crate::bundle_signature::*;
 pub use crate::chain::*;
 pub use crate::db::*;
 pub use crate::dht_op::error::*;
@@ -33,6 +34,7 @@ pub use crate::signal::*;
 pub use crate::timestamp; // for timestmap::now()
 pub use crate::timestamp::*;
 pub use crate::validate::*;
+pub use crate::web_app::*;
 
 pub use crate::fixt::TimestampFixturator;
 #[cfg(feature = "fixturators")]
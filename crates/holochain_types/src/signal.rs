@@ -27,6 +27,18 @@ pub enum SystemSignal {
     /// Since we have no real system signals, we use a test signal for testing
     /// TODO: replace instances of this with something real
     Test(String),
+    /// The `post_commit` callback failed after a zome call's commits were
+    /// written. The commits are not rolled back.
+    PostCommitFailed {
+        /// The cell whose chain was committed to
+        cell_id: CellId,
+        /// The zome whose `post_commit` callback failed
+        zome_name: ZomeName,
+        /// The headers that were committed
+        headers: Vec<HeaderHash>,
+        /// Why the callback failed
+        reason: String,
+    },
 }
 
 /// Create a test signal
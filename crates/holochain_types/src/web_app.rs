@@ -0,0 +1,63 @@
+//! Web hApps: a hApp bundle distributed together with the web UI which
+//! talks to it.
+//!
+//! A Web hApp bundle contains a zip file of UI assets alongside a nested
+//! [`AppBundle`]. When it's installed, the hApp is installed as usual and the
+//! UI is extracted into a directory belonging to the installed app.
+
+mod web_app_bundle;
+mod web_app_manifest;
+
+pub use web_app_bundle::*;
+pub use web_app_manifest::*;
+
+use crate::prelude::*;
+use std::{collections::HashMap, path::PathBuf};
+
+/// A [`WebAppBundle`] along with an [`AgentPubKey`] and optional [`InstalledAppId`]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct InstallWebAppBundlePayload {
+    /// Where to find the Web hApp bundle
+    pub source: WebAppBundleSource,
+
+    /// The agent to use when creating Cells for this App.
+    pub agent_key: AgentPubKey,
+
+    /// The unique identifier for an installed app in this conductor.
+    /// If not specified, it will be derived from the app name in the
+    /// manifest of the nested hApp bundle.
+    pub installed_app_id: Option<InstalledAppId>,
+
+    /// Include proof-of-membrane-membership data for cells that require it,
+    /// keyed by the CellNick specified in the app bundle manifest.
+    pub membrane_proofs: HashMap<CellNick, MembraneProof>,
+
+    /// A detached publisher signature of the Web hApp bundle.
+    /// If the conductor only accepts bundles from trusted publishers,
+    /// this is checked along with any signatures embedded in the bundle.
+    /// For a bundle given by path, a signature file alongside it
+    /// (e.g. `my-app.webhapp.sig`) is used if this isn't set.
+    #[serde(default)]
+    pub signature: Option<mr_bundle::BundleSignature>,
+}
+
+/// The possible locations of a WebAppBundle
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[serde(untagged)]
+pub enum WebAppBundleSource {
+    /// The actual serialized bytes of a bundle
+    Bundle(WebAppBundle),
+    /// A local file path
+    Path(PathBuf),
+}
+
+impl WebAppBundleSource {
+    /// Get the bundle from the source. Consumes the source.
+    pub async fn resolve(self) -> WebAppBundleResult<WebAppBundle> {
+        Ok(match self {
+            Self::Bundle(bundle) => bundle,
+            Self::Path(path) => WebAppBundle::decode(&ffs::read(&path).await?)?,
+        })
+    }
+}
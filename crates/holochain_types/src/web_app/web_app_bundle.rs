@@ -0,0 +1,209 @@
+use std::{
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use super::WebAppManifest;
+use crate::prelude::*;
+use mr_bundle::ResourceBytes;
+
+#[allow(missing_docs)]
+mod error;
+pub use error::*;
+
+/// The most a web UI zip file may hold once extracted, in bytes
+pub const MAX_WEB_UI_SIZE: u64 = 256 * 1024 * 1024;
+
+/// A bundle of a WebAppManifest, a zip file of UI assets and a hApp bundle
+#[derive(Debug, Serialize, Deserialize, derive_more::From, shrinkwraprs::Shrinkwrap)]
+pub struct WebAppBundle(mr_bundle::Bundle<WebAppManifest>);
+
+impl WebAppBundle {
+    /// Create a WebAppBundle from a manifest, the UI zip file and the hApp bundle
+    pub fn new(
+        manifest: WebAppManifest,
+        resources: Vec<(PathBuf, ResourceBytes)>,
+        root_dir: PathBuf,
+    ) -> WebAppBundleResult<Self> {
+        Ok(mr_bundle::Bundle::new(manifest, resources, root_dir)?.into())
+    }
+
+    /// Construct from raw bytes
+    pub fn decode(bytes: &[u8]) -> WebAppBundleResult<Self> {
+        mr_bundle::Bundle::decode(bytes)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+
+    /// Get the nested hApp bundle
+    pub async fn happ_bundle(&self) -> WebAppBundleResult<AppBundle> {
+        let bytes = self
+            .resolve(&self.manifest().happ_bundle_location())
+            .await?;
+        Ok(AppBundle::decode(&bytes)?)
+    }
+
+    /// The locations, here or in the nested hApp bundle and its DNA bundles,
+    /// whose content isn't covered by a signature over this bundle
+    pub async fn unpinned_locations(&self) -> WebAppBundleResult<Vec<mr_bundle::Location>> {
+        let mut unpinned = self.0.unpinned_locations();
+        if !unpinned.contains(&self.manifest().happ_bundle_location()) {
+            unpinned.extend(self.happ_bundle().await?.unpinned_locations().await?);
+        }
+        Ok(unpinned)
+    }
+
+    /// Get the bytes of the zip file of UI assets
+    pub async fn web_ui_zip_bytes(&self) -> WebAppBundleResult<ResourceBytes> {
+        Ok(self
+            .resolve(&self.manifest().web_ui_location())
+            .await?
+            .into_owned())
+    }
+
+    /// Extract the UI assets into a directory, which is created if needed.
+    /// Fails without writing anything outside of `dir` if the zip file
+    /// contains absolute paths or paths leading out of its root, and fails
+    /// once more than [`MAX_WEB_UI_SIZE`] bytes have been extracted.
+    pub async fn extract_web_ui(&self, dir: &Path) -> WebAppBundleResult<()> {
+        let zip = self.web_ui_zip_bytes().await?;
+        let dir = dir.to_owned();
+        tokio::task::spawn_blocking(move || extract_zip(&zip, &dir, MAX_WEB_UI_SIZE))
+            .await
+            .expect("Web UI extraction panicked")
+    }
+}
+
+fn extract_zip(zip: &[u8], dir: &Path, max_size: u64) -> WebAppBundleResult<()> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(zip))?;
+    // Refuse early going by the sizes the zip file claims...
+    let mut declared = 0u64;
+    for i in 0..archive.len() {
+        declared = declared.saturating_add(archive.by_index(i)?.size());
+    }
+    if declared > max_size {
+        return Err(WebAppBundleError::UiTooLarge(max_size));
+    }
+    // ...and don't trust them while extracting.
+    let mut remaining = max_size;
+    std::fs::create_dir_all(dir)?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let path = match file.enclosed_name() {
+            Some(path) => dir.join(path),
+            None => return Err(WebAppBundleError::UnsafeUiPath(file.name().to_owned())),
+        };
+        if file.is_dir() {
+            std::fs::create_dir_all(&path)?;
+        } else {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let written = std::io::copy(
+                &mut (&mut file).take(remaining + 1),
+                &mut std::fs::File::create(&path)?,
+            )?;
+            if written > remaining {
+                return Err(WebAppBundleError::UiTooLarge(max_size));
+            }
+            remaining -= written;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn ui_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    async fn web_app_bundle(ui: Vec<u8>) -> WebAppBundle {
+        let app_manifest: AppManifest = AppManifestCurrentBuilder::default()
+            .name("test app".into())
+            .description(None)
+            .slots(vec![])
+            .build()
+            .unwrap()
+            .into();
+        let happ = AppBundle::new(app_manifest, vec![], PathBuf::from("/"))
+            .await
+            .unwrap()
+            .encode()
+            .unwrap();
+        let manifest: WebAppManifest = serde_yaml::from_str(
+            r#"
+manifest_version: "1"
+name: test web app
+ui:
+  bundled: ui.zip
+happ_manifest:
+  bundled: test.happ
+"#,
+        )
+        .unwrap();
+        WebAppBundle::new(
+            manifest,
+            vec![("ui.zip".into(), ui), ("test.happ".into(), happ)],
+            PathBuf::from("/"),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn web_app_bundle_roundtrip_and_extract() {
+        let ui = ui_zip(&[("index.html", b"<html/>"), ("js/app.js", b"app()")]);
+        let bundle = web_app_bundle(ui.clone()).await;
+        let bundle = WebAppBundle::decode(&bundle.encode().unwrap()).unwrap();
+
+        assert_eq!(bundle.manifest().app_name(), "test web app");
+        assert_eq!(bundle.web_ui_zip_bytes().await.unwrap(), ui);
+        assert_eq!(
+            bundle.happ_bundle().await.unwrap().manifest().app_name(),
+            "test app"
+        );
+
+        let dir = tempdir::TempDir::new("web-ui").unwrap();
+        bundle.extract_web_ui(dir.path()).await.unwrap();
+        assert_eq!(
+            std::fs::read(dir.path().join("index.html")).unwrap(),
+            b"<html/>"
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join("js/app.js")).unwrap(),
+            b"app()"
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn web_ui_cannot_escape_its_directory() {
+        let bundle = web_app_bundle(ui_zip(&[("../escaped.html", b"<html/>")])).await;
+        let dir = tempdir::TempDir::new("web-ui").unwrap();
+        let ui_dir = dir.path().join("ui");
+        matches::assert_matches!(
+            bundle.extract_web_ui(&ui_dir).await,
+            Err(WebAppBundleError::UnsafeUiPath(_))
+        );
+        assert!(!dir.path().join("escaped.html").exists());
+    }
+
+    #[test]
+    fn web_ui_size_is_capped() {
+        let zip = ui_zip(&[("index.html", &[0; 600]), ("app.js", &[0; 600])]);
+        let dir = tempdir::TempDir::new("web-ui").unwrap();
+        matches::assert_matches!(
+            extract_zip(&zip, dir.path(), 1000),
+            Err(WebAppBundleError::UiTooLarge(1000))
+        );
+        extract_zip(&zip, dir.path(), 1200).unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+use mr_bundle::error::MrBundleError;
+
+use crate::prelude::AppBundleError;
+
+/// Errors occurring while installing a WebAppBundle
+#[derive(thiserror::Error, Debug)]
+pub enum WebAppBundleError {
+    #[error(transparent)]
+    AppBundleError(#[from] AppBundleError),
+
+    #[error(transparent)]
+    MrBundleError(#[from] MrBundleError),
+
+    #[error("The web UI zip file is invalid: {0}")]
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error("The web UI zip file contains a path outside of its root: {0}")]
+    UnsafeUiPath(String),
+
+    #[error("The web UI zip file holds more than {0} bytes once extracted")]
+    UiTooLarge(u64),
+
+    #[error(transparent)]
+    StdIoError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    FfsIoError(#[from] ffs::IoError),
+}
+
+pub type WebAppBundleResult<T> = Result<T, WebAppBundleError>;
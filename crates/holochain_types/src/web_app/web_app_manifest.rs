@@ -0,0 +1,75 @@
+//! Defines the Web hApp Manifest YAML format
+
+use mr_bundle::{Location, Manifest, ResourceHash};
+use std::{collections::HashMap, path::PathBuf};
+
+pub(crate) mod web_app_manifest_v1;
+
+pub use web_app_manifest_v1::{
+    WebAppManifestV1 as WebAppManifestCurrent,
+    WebAppManifestV1Builder as WebAppManifestCurrentBuilder, *,
+};
+
+/// Container struct which uses the `manifest_version` field to determine
+/// which manifest version to deserialize to.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, derive_more::From)]
+#[serde(tag = "manifest_version")]
+#[allow(missing_docs)]
+pub enum WebAppManifest {
+    #[serde(rename = "1")]
+    V1(WebAppManifestV1),
+}
+
+impl Manifest for WebAppManifest {
+    fn locations(&self) -> Vec<Location> {
+        match self {
+            WebAppManifest::V1(m) => vec![m.ui.location.clone(), m.happ_manifest.location.clone()],
+        }
+    }
+
+    fn pinned_hashes(&self) -> HashMap<Location, ResourceHash> {
+        match self {
+            WebAppManifest::V1(m) => vec![
+                (m.ui.location.clone(), m.ui.resource_hash.clone()),
+                (
+                    m.happ_manifest.location.clone(),
+                    m.happ_manifest.resource_hash.clone(),
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(location, hash)| Some((location, hash?)))
+            .collect(),
+        }
+    }
+
+    fn path() -> PathBuf {
+        "web-happ.yaml".into()
+    }
+
+    fn bundle_extension() -> &'static str {
+        "webhapp"
+    }
+}
+
+impl WebAppManifest {
+    /// Get the supplied name of the web app
+    pub fn app_name(&self) -> &str {
+        match self {
+            Self::V1(WebAppManifestV1 { name, .. }) => name,
+        }
+    }
+
+    /// Where to find the zip file of UI assets
+    pub fn web_ui_location(&self) -> Location {
+        match self {
+            Self::V1(m) => m.ui.location.clone(),
+        }
+    }
+
+    /// Where to find the nested hApp bundle
+    pub fn happ_bundle_location(&self) -> Location {
+        match self {
+            Self::V1(m) => m.happ_manifest.location.clone(),
+        }
+    }
+}
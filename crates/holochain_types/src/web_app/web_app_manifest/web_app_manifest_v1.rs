@@ -0,0 +1,64 @@
+//! Web hApp Manifest format, version 1.
+//!
+//! A Web hApp manifest looks like:
+//!
+//! ```yaml
+//! manifest_version: "1"
+//! name: my-web-app
+//! ui:
+//!   bundled: ./ui.zip
+//! happ_manifest:
+//!   bundled: ./my-app.happ
+//! ```
+
+use mr_bundle::{Location, ResourceHash};
+
+/// Version 1 of the Web hApp manifest schema
+#[derive(
+    Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, derive_builder::Builder,
+)]
+#[serde(rename_all = "snake_case")]
+pub struct WebAppManifestV1 {
+    /// Name of the Web hApp.
+    pub name: String,
+
+    /// The zip file of UI assets
+    pub ui: WebUI,
+
+    /// The hApp bundle which the UI talks to
+    pub happ_manifest: AppManifestLocation,
+}
+
+/// Where to find the web UI of a Web hApp
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct WebUI {
+    /// Where to find the zip file of UI assets.
+    ///
+    /// Note that since this is flattened,
+    /// there is no actual "location" key in the manifest.
+    #[serde(flatten)]
+    pub location: Location,
+
+    /// Pins the content of a "path" or "url" location: the zip file found
+    /// there must have this Blake2b-256 hex digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_hash: Option<ResourceHash>,
+}
+
+/// Where to find the hApp bundle of a Web hApp
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct AppManifestLocation {
+    /// Where to find the `.happ` bundle.
+    ///
+    /// Note that since this is flattened,
+    /// there is no actual "location" key in the manifest.
+    #[serde(flatten)]
+    pub location: Location,
+
+    /// Pins the content of a "path" or "url" location: the hApp bundle found
+    /// there must have this Blake2b-256 hex digest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_hash: Option<ResourceHash>,
+}
@@ -7,6 +7,10 @@
 //! [WebsocketReceiver](struct.WebsocketReceiver.html)
 //! ).
 //!
+//! The same framing can be run over a unix domain socket with
+//! [websocket_connect_unix](fn.websocket_connect_unix.html) and
+//! [websocket_bind_unix](fn.websocket_bind_unix.html).
+//!
 //! To open a listening socket, use [websocket_bind](fn.websocket_bind.html)
 //! which will give you a [WebsocketListener](struct.WebsocketListener.html)
 //! which is an async Stream whose items resolve to that same tuple (
@@ -172,4 +176,69 @@ mod tests {
 
         assert_eq!("echo: test", &rsp.0,);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_sanity_test() {
+        observability::test_run().ok();
+        let path = std::env::temp_dir().join(format!("hc-ws-{}.sock", nanoid::nanoid!()));
+        let mut server = websocket_bind_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap();
+
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(0o600, mode & 0o777);
+        }
+
+        tokio::task::spawn(async move {
+            while let Some(maybe_con) = server.next().await {
+                let (_send, mut recv) = maybe_con.unwrap();
+
+                tokio::task::spawn(async move {
+                    if let Some(WebsocketMessage::Request(data, respond)) = recv.next().await {
+                        let msg: TestMessage = data.try_into().unwrap();
+                        let msg = TestMessage(format!("echo: {}", msg.0));
+                        respond(msg.try_into().unwrap()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        let (mut send, _recv) = websocket_connect_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap();
+
+        let msg = TestMessage("test".to_string());
+        let rsp: TestMessage = send.request(msg).await.unwrap();
+
+        assert_eq!("echo: test", &rsp.0,);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn unix_socket_only_replaces_stale_sockets() {
+        observability::test_run().ok();
+        let path = std::env::temp_dir().join(format!("hc-ws-{}.sock", nanoid::nanoid!()));
+
+        // a socket file left behind by a listener that is gone
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+        let server = websocket_bind_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .unwrap();
+
+        // but not one that is still listening
+        let err = websocket_bind_unix(&path, Arc::new(WebsocketConfig::default()))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(ErrorKind::AddrInUse, err.kind());
+
+        // the socket file goes away with the listener
+        drop(server);
+        assert!(!path.exists());
+    }
 }
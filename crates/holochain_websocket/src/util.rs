@@ -60,8 +60,21 @@ pub(crate) fn init_tracing() {
     observability::test_run().unwrap();
 }
 
-/// internal socket type
-pub(crate) type RawSocket = tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>;
+/// internal socket type, generic over the underlying byte stream
+/// so we can run the same framing over tcp or unix domain sockets
+pub(crate) type RawSocket<S> = tokio_tungstenite::WebSocketStream<S>;
+
+/// internal helper to convert a unix domain socket path to a url
+#[cfg(unix)]
+pub(crate) fn unix_path_to_url(path: &std::path::Path, scheme: &str) -> Result<Url2> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    Url2::try_parse(format!("{}+unix://{}", scheme, path.display()))
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))
+}
 
 /// internal helper to convert addrs to urls
 pub(crate) fn addr_to_url(a: SocketAddr, scheme: &str) -> Url2 {
@@ -14,6 +14,30 @@ pub struct WebsocketListener {
     config: Arc<WebsocketConfig>,
     local_addr: Url2,
     socket: BoxStream<'static, Result<(WebsocketSender, WebsocketReceiver)>>,
+    #[cfg(unix)]
+    _socket_file: Option<UnixSocketFile>,
+}
+
+/// The socket file a unix domain socket listener is bound to.
+/// It is removed when the listener is dropped, unless it has
+/// been replaced by another socket in the meantime.
+#[cfg(unix)]
+struct UnixSocketFile {
+    path: std::path::PathBuf,
+    dev: u64,
+    ino: u64,
+}
+
+#[cfg(unix)]
+impl Drop for UnixSocketFile {
+    fn drop(&mut self) {
+        use std::os::unix::fs::MetadataExt;
+        if let Ok(meta) = std::fs::symlink_metadata(&self.path) {
+            if meta.dev() == self.dev && meta.ino() == self.ino {
+                let _ = std::fs::remove_file(&self.path);
+            }
+        }
+    }
 }
 
 impl WebsocketListener {
@@ -72,6 +96,99 @@ pub async fn websocket_bind(addr: Url2, config: Arc<WebsocketConfig>) -> Result<
         config,
         local_addr,
         socket,
+        #[cfg(unix)]
+        _socket_file: None,
+    })
+}
+
+/// Bind a new websocket listening socket on the unix domain socket at `path`,
+/// and begin awaiting incoming connections.
+/// Access control is left to the filesystem: the socket file is created with
+/// the mode set in [WebsocketConfig](struct.WebsocketConfig.html).
+/// A stale socket file left behind at `path` by a previous process is replaced,
+/// but binding fails if something is still listening there.
+/// The socket file is removed when the listener is dropped.
+/// Returns a [WebsocketListener](struct.WebsocketListener.html) instance.
+#[cfg(unix)]
+pub async fn websocket_bind_unix<P: AsRef<std::path::Path>>(
+    path: P,
+    config: Arc<WebsocketConfig>,
+) -> Result<WebsocketListener> {
+    use std::os::unix::fs::DirBuilderExt;
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = path.as_ref();
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("'{}' exists and is not a unix socket", path.display()),
+            ));
+        }
+        // Only a socket nobody listens on any more may be replaced
+        match std::os::unix::net::UnixStream::connect(path) {
+            Err(e) if e.kind() == ErrorKind::ConnectionRefused => (),
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::AddrInUse,
+                    format!("'{}' is in use by another listener", path.display()),
+                ));
+            }
+        }
+    }
+
+    // Bind inside a directory only we can enter, so nobody can connect
+    // before the socket has its mode, then move it into place.
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => std::path::Path::new("."),
+    };
+    let private_dir = parent.join(format!(".{}", nanoid::nanoid!(8)));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let private_path = private_dir.join("s");
+    let bind = || {
+        let socket = tokio::net::UnixListener::bind(&private_path)?;
+        std::fs::set_permissions(
+            &private_path,
+            std::fs::Permissions::from_mode(config.unix_socket_mode),
+        )?;
+        std::fs::rename(&private_path, path)?;
+        Result::Ok(socket)
+    };
+    let socket = bind();
+    let _ = std::fs::remove_dir_all(&private_dir);
+    let socket = socket?;
+    let meta = std::fs::symlink_metadata(path)?;
+    let socket_file = UnixSocketFile {
+        path: path.to_path_buf(),
+        dev: meta.dev(),
+        ino: meta.ino(),
+    };
+
+    let local_addr = unix_path_to_url(path, config.scheme)?;
+    let socket = socket
+        .map({
+            let config = config.clone();
+            let local_addr = local_addr.clone();
+            move |socket_result| connect_unix(config.clone(), local_addr.clone(), socket_result)
+        })
+        .buffer_unordered(config.max_pending_connections)
+        .boxed();
+
+    tracing::info!(
+        message = "bind",
+        local_addr = %local_addr,
+    );
+    Ok(WebsocketListener {
+        config,
+        local_addr,
+        socket,
+        _socket_file: Some(socket_file),
     })
 }
 
@@ -85,22 +202,49 @@ async fn connect(
             socket.set_keepalive(Some(std::time::Duration::from_secs(
                 config.tcp_keepalive_s as u64,
             )))?;
+            let remote_addr = addr_to_url(socket.peer_addr()?, config.scheme);
             tracing::debug!(
                 message = "accepted incoming raw socket",
-                remote_addr = %socket.peer_addr()?,
+                remote_addr = %remote_addr,
             );
-            let socket = tokio_tungstenite::accept_async_with_config(
-                socket,
-                Some(tungstenite::protocol::WebSocketConfig {
-                    max_send_queue: Some(config.max_send_queue),
-                    max_message_size: Some(config.max_message_size),
-                    max_frame_size: Some(config.max_frame_size),
-                }),
-            )
-            .await
-            .map_err(|e| Error::new(ErrorKind::Other, e))?;
-            build_websocket_pair(config, socket)
+            accept(config, socket, remote_addr).await
         }
         Err(e) => Err(Error::new(ErrorKind::Other, e)),
     }
 }
+
+/// Connects the new unix domain socket listener.
+/// Unix socket peers are unnamed, so they are identified by the
+/// listener url (the pair builder appends a unique fragment).
+#[cfg(unix)]
+async fn connect_unix(
+    config: Arc<WebsocketConfig>,
+    local_addr: Url2,
+    socket_result: std::io::Result<tokio::net::UnixStream>,
+) -> Result<(WebsocketSender, WebsocketReceiver)> {
+    match socket_result {
+        Ok(socket) => {
+            tracing::debug!(
+                message = "accepted incoming raw unix socket",
+                local_addr = %local_addr,
+            );
+            accept(config, socket, local_addr).await
+        }
+        Err(e) => Err(Error::new(ErrorKind::Other, e)),
+    }
+}
+
+/// Perform the websocket handshake on an accepted raw socket
+async fn accept<S>(
+    config: Arc<WebsocketConfig>,
+    socket: S,
+    remote_addr: Url2,
+) -> Result<(WebsocketSender, WebsocketReceiver)>
+where
+    S: 'static + tokio::io::AsyncRead + tokio::io::AsyncWrite + std::marker::Unpin + Send,
+{
+    let socket = tokio_tungstenite::accept_async_with_config(socket, Some(config.to_tungstenite()))
+        .await
+        .map_err(|e| Error::new(ErrorKind::Other, e))?;
+    build_websocket_pair(config, socket, remote_addr)
+}
@@ -117,20 +117,46 @@ pub async fn websocket_connect(
     )
     .await
     .map_err(|e| Error::new(ErrorKind::Other, e))?;
-    build_websocket_pair(config, socket)
+    let remote_addr = addr_to_url(socket.get_ref().peer_addr()?, config.scheme);
+    build_websocket_pair(config, socket, remote_addr)
+}
+
+/// Establish a new outgoing websocket connection over the unix domain socket
+/// at `path`. Returns a split websocket connection pair: (
+/// [WebsocketSender](struct.WebsocketSender.html),
+/// [WebsocketReceiver](struct.WebsocketReceiver.html)
+/// ).
+#[cfg(unix)]
+pub async fn websocket_connect_unix<P: AsRef<std::path::Path>>(
+    path: P,
+    config: Arc<WebsocketConfig>,
+) -> Result<(WebsocketSender, WebsocketReceiver)> {
+    let path = path.as_ref();
+    let remote_addr = unix_path_to_url(path, config.scheme)?;
+    let socket = tokio::net::UnixStream::connect(path).await?;
+    // the websocket handshake still needs an http request uri,
+    // the host is meaningless here
+    let (socket, _) = tokio_tungstenite::client_async_with_config(
+        format!("{}://localhost/", config.scheme),
+        socket,
+        Some(config.to_tungstenite()),
+    )
+    .await
+    .map_err(|e| Error::new(ErrorKind::Other, e))?;
+    build_websocket_pair(config, socket, remote_addr)
 }
 
 /// internal set up the tokio tasks that keep a websocket running
 /// and produce the public (WebsocketSender, WebsocketReceiver) pair.
-pub(crate) fn build_websocket_pair(
+pub(crate) fn build_websocket_pair<S>(
     config: Arc<WebsocketConfig>,
-    socket: RawSocket,
-) -> Result<(WebsocketSender, WebsocketReceiver)> {
-    let remote_addr = url2!(
-        "{}#{}",
-        addr_to_url(socket.get_ref().peer_addr()?, config.scheme),
-        nanoid::nanoid!(),
-    );
+    socket: RawSocket<S>,
+    remote_addr: Url2,
+) -> Result<(WebsocketSender, WebsocketReceiver)>
+where
+    S: 'static + tokio::io::AsyncRead + tokio::io::AsyncWrite + std::marker::Unpin + Send,
+{
+    let remote_addr = url2!("{}#{}", remote_addr, nanoid::nanoid!());
 
     // split the sink and stream so we can handle them simultaneously
     use futures::stream::StreamExt;
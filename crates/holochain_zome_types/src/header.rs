@@ -304,7 +304,7 @@ pub struct DeleteLink {
 }
 
 /// When migrating to a new version of a DNA, this header is committed to the
-/// new chain to declare the migration path taken. **Currently unused**
+/// new chain to declare the migration path taken.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, SerializedBytes)]
 pub struct OpenChain {
     pub author: AgentPubKey,
@@ -316,7 +316,8 @@ pub struct OpenChain {
 }
 
 /// When migrating to a new version of a DNA, this header is committed to the
-/// old chain to declare the migration path taken. **Currently unused**
+/// old chain to declare the migration path taken. Nothing can be committed
+/// to a chain after its CloseChain.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, SerializedBytes)]
 pub struct CloseChain {
     pub author: AgentPubKey,
@@ -0,0 +1,41 @@
+use kitsune_p2p::KitsuneP2pResult;
+use kitsune_p2p_bootstrap::*;
+use kitsune_p2p_types::dependencies::ghost_actor;
+use structopt::StructOpt;
+
+mod opt;
+use opt::*;
+
+#[tokio::main]
+async fn main() {
+    let _ = ghost_actor::dependencies::tracing::subscriber::set_global_default(
+        tracing_subscriber::FmtSubscriber::builder()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .finish(),
+    );
+
+    if let Err(e) = inner().await {
+        eprintln!("{:?}", e);
+    }
+}
+
+async fn inner() -> KitsuneP2pResult<()> {
+    let opt = Opt::from_args();
+
+    let limits = StoreLimits {
+        agents_per_space: opt.max_agents_per_space,
+        agents: opt.max_agents,
+    };
+    let store = match opt.persist {
+        Some(path) => Store::open(path, limits).await?,
+        None => Store::new(limits),
+    };
+
+    let service = spawn_bootstrap_service(opt.bind_to, store).await?;
+
+    println!("{}", service.url());
+
+    // wait for ctrl-c, then flush the store on the way out
+    tokio::signal::ctrl_c().await?;
+    service.shutdown().await
+}
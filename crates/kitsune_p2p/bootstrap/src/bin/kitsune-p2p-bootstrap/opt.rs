@@ -0,0 +1,21 @@
+/// Option Parsing
+#[derive(structopt::StructOpt, Debug)]
+#[structopt(name = "kitsune-p2p-bootstrap")]
+pub struct Opt {
+    /// To which network interface / port should we bind?
+    #[structopt(short = "b", long, default_value = "0.0.0.0:0")]
+    pub bind_to: std::net::SocketAddr,
+
+    /// Persist agent infos to this file, loading any already in it.
+    /// Default: None = only keep agent infos in memory.
+    #[structopt(short = "p", long)]
+    pub persist: Option<std::path::PathBuf>,
+
+    /// The most agents to hold for any one space.
+    #[structopt(long, default_value = "1024")]
+    pub max_agents_per_space: usize,
+
+    /// The most agents to hold across all spaces.
+    #[structopt(long, default_value = "65536")]
+    pub max_agents: usize,
+}
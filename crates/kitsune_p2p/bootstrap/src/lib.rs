@@ -0,0 +1,29 @@
+#![deny(missing_docs)]
+//! Bootstrap service for kitsune-p2p.
+//!
+//! Implements the same small HTTP api that kitsune-p2p speaks to
+//! `https://bootstrap.holo.host`, so private networks and tests can run
+//! their own bootstrap service.
+//!
+//! Every request is a `POST` with the op in the `X-Op` header and a
+//! messagepack encoded body:
+//!
+//! - `put`: the body is an `AgentInfoSigned`. It is only stored if the
+//!   signature verifies and it has not expired. The response is nil.
+//! - `now`: the body is nil. The response is the service's current time
+//!   in milliseconds since the unix epoch.
+//! - `random`: the body is a `RandomQuery`. The response is a list of up to
+//!   `limit` messagepack encoded `AgentInfoSigned` from that space,
+//!   in random order.
+//!
+//! Request bodies over [`MAX_BODY_BYTES`] are refused, and the store holds
+//! a bounded number of agents per space and in total, see [`StoreLimits`].
+//! Any failure is answered with a non-success status and a text body.
+
+use kitsune_p2p_types::dependencies::ghost_actor::dependencies::tracing;
+
+mod store;
+pub use store::*;
+
+mod server;
+pub use server::*;
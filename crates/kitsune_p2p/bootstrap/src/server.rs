@@ -0,0 +1,323 @@
+//! The HTTP front end of the bootstrap service.
+
+use crate::store::now_ms;
+use crate::tracing;
+use crate::Store;
+use futures::StreamExt;
+use hyper::service::make_service_fn;
+use hyper::service::service_fn;
+use hyper::Body;
+use hyper::Method;
+use hyper::Request;
+use hyper::Response;
+use hyper::Server;
+use hyper::StatusCode;
+use kitsune_p2p::agent_store::AgentInfoSigned;
+use kitsune_p2p::KitsuneP2pError;
+use kitsune_p2p::KitsuneP2pResult;
+use kitsune_p2p::KitsuneSpace;
+use kitsune_p2p_types::dependencies::url2::*;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+/// The HTTP header name for setting the op on POST requests.
+pub const OP_HEADER: &str = "X-Op";
+/// The header op to put a signed agent info.
+pub const OP_PUT: &str = "put";
+/// The header op to get the service's opinion of 'now' in milliseconds.
+pub const OP_NOW: &str = "now";
+/// The header op to get a random set of agents in a specific space.
+pub const OP_RANDOM: &str = "random";
+
+/// The most agent infos a single `random` query will return,
+/// whatever limit it asks for.
+pub const RANDOM_LIMIT_MAX: u32 = 64;
+
+/// The largest request body the service reads. Signed agent infos and
+/// queries are far smaller, anything bigger is refused unread.
+pub const MAX_BODY_BYTES: usize = 16 * 1024;
+
+/// How often expired agent infos are pruned and the store is flushed to disk.
+const MAINTENANCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Body of the `random` op, the same shape the kitsune-p2p client encodes.
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct RandomQuery {
+    /// The space to get random agents from.
+    pub space: KitsuneSpace,
+    /// The maximum number of random agents to retrieve for this query.
+    pub limit: RandomLimit,
+}
+
+/// Limit on the number of agents returned by a `random` query.
+#[derive(serde::Deserialize, serde::Serialize, derive_more::From, derive_more::Into)]
+pub struct RandomLimit(pub u32);
+
+/// Handle to a running bootstrap service.
+pub struct BootstrapService {
+    local_addr: SocketAddr,
+    shutdown: tokio::sync::oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<KitsuneP2pResult<()>>,
+}
+
+impl BootstrapService {
+    /// The address the service is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// The url kitsune-p2p should be configured with to use this service.
+    pub fn url(&self) -> Url2 {
+        url2!("http://{}", self.local_addr)
+    }
+
+    /// Stop serving requests, and flush the store one last time.
+    pub async fn shutdown(self) -> KitsuneP2pResult<()> {
+        let _ = self.shutdown.send(());
+        self.task.await.map_err(KitsuneP2pError::other)?
+    }
+}
+
+/// Bind a bootstrap service backed by `store` to `bind_to` and serve
+/// it in the background until [`BootstrapService::shutdown`].
+pub async fn spawn_bootstrap_service(
+    bind_to: SocketAddr,
+    store: Store,
+) -> KitsuneP2pResult<BootstrapService> {
+    let service_store = store.clone();
+    let make_service = make_service_fn(move |_conn| {
+        let store = service_store.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(store.clone(), req))) }
+    });
+    let server = Server::try_bind(&bind_to)
+        .map_err(KitsuneP2pError::other)?
+        .serve(make_service);
+    let local_addr = server.local_addr();
+
+    let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let server = server.with_graceful_shutdown(async move {
+        let _ = shutdown_rx.await;
+    });
+
+    let task = tokio::task::spawn(async move {
+        let maintenance_store = store.clone();
+        let maintenance = async move {
+            loop {
+                tokio::time::delay_for(MAINTENANCE_INTERVAL).await;
+                if let Ok(now) = now_ms() {
+                    maintenance_store.prune(now);
+                }
+                if let Err(e) = maintenance_store.flush().await {
+                    tracing::error!(?e, "failed to persist bootstrap store");
+                }
+            }
+        };
+        tokio::select! {
+            r = server => r.map_err(KitsuneP2pError::other)?,
+            _ = maintenance => (),
+        }
+        store.flush().await
+    });
+
+    Ok(BootstrapService {
+        local_addr,
+        shutdown,
+        task,
+    })
+}
+
+async fn handle(store: Store, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let res = match respond(store, req).await {
+        Ok(body) => Response::builder()
+            .header(hyper::header::CONTENT_TYPE, "application/octet")
+            .body(Body::from(body)),
+        Err((status, reason)) => Response::builder().status(status).body(Body::from(reason)),
+    };
+    // The builders above only fail on invalid headers, which are all static.
+    Ok(res.expect("valid response"))
+}
+
+async fn respond(store: Store, req: Request<Body>) -> Result<Vec<u8>, (StatusCode, String)> {
+    let bad_request = |e: KitsuneP2pError| (StatusCode::BAD_REQUEST, e.to_string());
+    let internal = |e: KitsuneP2pError| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+    if req.method() != Method::POST {
+        return Err((
+            StatusCode::METHOD_NOT_ALLOWED,
+            "bootstrap requests must be POST".to_string(),
+        ));
+    }
+    let op = req
+        .headers()
+        .get(OP_HEADER)
+        .and_then(|op| op.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_default();
+    let body = read_body(req).await?;
+
+    let mut out = Vec::new();
+    match op.as_str() {
+        OP_PUT => {
+            let info: AgentInfoSigned = kitsune_p2p_types::codec::rmp_decode(&mut body.as_ref())
+                .map_err(|e| bad_request(e.into()))?;
+            store.put(info).await.map_err(bad_request)?;
+            kitsune_p2p_types::codec::rmp_encode(&mut out, ())
+        }
+        OP_NOW => kitsune_p2p_types::codec::rmp_encode(&mut out, now_ms().map_err(internal)?),
+        OP_RANDOM => {
+            let query: RandomQuery = kitsune_p2p_types::codec::rmp_decode(&mut body.as_ref())
+                .map_err(|e| bad_request(e.into()))?;
+            let limit = query.limit.0.min(RANDOM_LIMIT_MAX) as usize;
+            let mut infos = Vec::new();
+            for info in store.random(&query.space, limit).map_err(internal)? {
+                let mut data = Vec::new();
+                kitsune_p2p_types::codec::rmp_encode(&mut data, &info)
+                    .map_err(|e| internal(e.into()))?;
+                infos.push(serde_bytes::ByteBuf::from(data));
+            }
+            kitsune_p2p_types::codec::rmp_encode(&mut out, infos)
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("unknown bootstrap op: {:?}", op),
+            ))
+        }
+    }
+    .map_err(|e| internal(e.into()))?;
+    Ok(out)
+}
+
+/// Read the whole body of `req`, refusing any over [`MAX_BODY_BYTES`]
+/// whether or not it declares its length up front.
+async fn read_body(req: Request<Body>) -> Result<Vec<u8>, (StatusCode, String)> {
+    let too_large = || {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "bootstrap request bodies are limited to {} bytes",
+                MAX_BODY_BYTES
+            ),
+        )
+    };
+    let declared = req
+        .headers()
+        .get(hyper::header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<u64>().ok());
+    if declared.map_or(false, |len| len > MAX_BODY_BYTES as u64) {
+        return Err(too_large());
+    }
+
+    let mut body = req.into_body();
+    let mut out = Vec::new();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+        if out.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(too_large());
+        }
+        out.extend_from_slice(&chunk);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+
+    async fn call<I: serde::Serialize>(
+        service: &BootstrapService,
+        op: &str,
+        input: I,
+    ) -> reqwest::Response {
+        let mut body = Vec::new();
+        kitsune_p2p_types::codec::rmp_encode(&mut body, input).unwrap();
+        reqwest::Client::new()
+            .post(service.url().as_str())
+            .body(body)
+            .header(OP_HEADER, op)
+            .send()
+            .await
+            .unwrap()
+    }
+
+    async fn decode<O: serde::de::DeserializeOwned>(res: reqwest::Response) -> O {
+        assert!(res.status().is_success());
+        kitsune_p2p_types::codec::rmp_decode(&mut res.bytes().await.unwrap().as_ref()).unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn serves_the_bootstrap_api() {
+        let service = spawn_bootstrap_service(([127, 0, 0, 1], 0).into(), Store::default())
+            .await
+            .unwrap();
+
+        let now: u64 = decode(call(&service, OP_NOW, ()).await).await;
+        assert!(now_ms().unwrap() - now < 5000);
+
+        let space = fixt!(KitsuneSpace, Unpredictable);
+        let info = signed_agent_info(space.clone(), now, 1000 * 60).await;
+        let _: () = decode(call(&service, OP_PUT, &info).await).await;
+
+        let random: Vec<serde_bytes::ByteBuf> = decode(
+            call(
+                &service,
+                OP_RANDOM,
+                RandomQuery {
+                    space,
+                    limit: 16.into(),
+                },
+            )
+            .await,
+        )
+        .await;
+        let random: Vec<AgentInfoSigned> = random
+            .iter()
+            .map(|bytes| kitsune_p2p_types::codec::rmp_decode(&mut bytes.as_ref()).unwrap())
+            .collect();
+        assert_eq!(vec![info], random);
+
+        service.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_bad_requests() {
+        let service = spawn_bootstrap_service(([127, 0, 0, 1], 0).into(), Store::default())
+            .await
+            .unwrap();
+
+        let res = call(&service, OP_PUT, fixt!(AgentInfoSigned)).await;
+        assert_eq!(StatusCode::BAD_REQUEST.as_u16(), res.status().as_u16());
+
+        let res = call(&service, "bogus", ()).await;
+        assert_eq!(StatusCode::BAD_REQUEST.as_u16(), res.status().as_u16());
+
+        let res = call(&service, OP_PUT, vec![0_u8; MAX_BODY_BYTES + 1]).await;
+        assert_eq!(
+            StatusCode::PAYLOAD_TOO_LARGE.as_u16(),
+            res.status().as_u16()
+        );
+
+        // Without a declared length the body is still cut off at the limit
+        let chunks = futures::stream::iter(
+            std::iter::repeat(vec![0_u8; 1024])
+                .take(MAX_BODY_BYTES / 1024 + 1)
+                .map(Ok::<_, std::io::Error>),
+        );
+        let res = reqwest::Client::new()
+            .post(service.url().as_str())
+            .body(reqwest::Body::wrap_stream(chunks))
+            .header(OP_HEADER, OP_PUT)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(
+            StatusCode::PAYLOAD_TOO_LARGE.as_u16(),
+            res.status().as_u16()
+        );
+
+        service.shutdown().await.unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+//! In-memory storage of signed agent infos, optionally persisted to disk.
+
+use crate::tracing;
+use kitsune_p2p::agent_store::AgentInfoSigned;
+use kitsune_p2p::KitsuneAgent;
+use kitsune_p2p::KitsuneP2pError;
+use kitsune_p2p::KitsuneP2pResult;
+use kitsune_p2p::KitsuneSpace;
+use parking_lot::RwLock;
+use rand::seq::IteratorRandom;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// Milliseconds since the unix epoch according to the local clock.
+pub(crate) fn now_ms() -> KitsuneP2pResult<u64> {
+    Ok(u64::try_from(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis(),
+    )?)
+}
+
+/// The longest an agent info may be valid for after it was signed.
+/// Infos asking for longer are refused, so that an agent that goes away
+/// can't linger in the store and keep a slot under the [`StoreLimits`].
+pub const MAX_EXPIRES_AFTER_MS: u64 = 1000 * 60 * 60 * 4; // 4 hours
+
+/// Caps on how many agent infos a [`Store`] holds, so that a flood of
+/// freshly generated agents can't exhaust the service's memory.
+/// Agents already held can always replace their info, but new agents are
+/// refused while a cap is reached, until infos expire.
+#[derive(Clone, Copy, Debug)]
+pub struct StoreLimits {
+    /// The most agents held for any one space.
+    pub agents_per_space: usize,
+    /// The most agents held across all spaces.
+    pub agents: usize,
+}
+
+impl Default for StoreLimits {
+    fn default() -> Self {
+        Self {
+            agents_per_space: 1024,
+            agents: 1024 * 64,
+        }
+    }
+}
+
+struct Entry {
+    info: AgentInfoSigned,
+    signed_at_ms: u64,
+    expires_at_ms: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    spaces: RwLock<HashMap<KitsuneSpace, HashMap<KitsuneAgent, Entry>>>,
+    limits: StoreLimits,
+    persist: Option<PathBuf>,
+    dirty: AtomicBool,
+}
+
+/// The agent infos known to a bootstrap service, grouped by space.
+/// Cheap to clone, all clones share the same storage.
+#[derive(Clone, Default)]
+pub struct Store(Arc<Inner>);
+
+impl Store {
+    /// A store that only lives in memory.
+    pub fn new(limits: StoreLimits) -> Self {
+        Self(Arc::new(Inner {
+            limits,
+            ..Default::default()
+        }))
+    }
+
+    /// A store that is persisted to the file at `path` on [`Store::flush`].
+    /// Agent infos already in the file are loaded, dropping any that no
+    /// longer verify, have expired or don't fit the limits.
+    pub async fn open(path: PathBuf, limits: StoreLimits) -> KitsuneP2pResult<Self> {
+        let read_path = path.clone();
+        let infos: Vec<AgentInfoSigned> =
+            tokio::task::spawn_blocking(move || match std::fs::read(&read_path) {
+                Ok(data) => kitsune_p2p_types::codec::rmp_decode(&mut data.as_slice()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+                Err(e) => Err(e),
+            })
+            .await
+            .map_err(KitsuneP2pError::other)??;
+
+        let store = Self(Arc::new(Inner {
+            limits,
+            persist: Some(path),
+            ..Default::default()
+        }));
+        for info in infos {
+            if let Err(e) = store.put(info).await {
+                tracing::debug!(?e, "dropping persisted agent info");
+            }
+        }
+        store.0.dirty.store(false, Ordering::SeqCst);
+        Ok(store)
+    }
+
+    /// Verify and store a signed agent info.
+    /// An info that expires more than [`MAX_EXPIRES_AFTER_MS`] after it was
+    /// signed is refused. An info signed before the one already held for the
+    /// same agent is ignored, and an info for a new agent fails if the
+    /// [`StoreLimits`] are reached.
+    pub async fn put(&self, info: AgentInfoSigned) -> KitsuneP2pResult<()> {
+        let agent_info = info.verify_at(None, now_ms()?).await?;
+        if agent_info.expires_after_ms() > MAX_EXPIRES_AFTER_MS {
+            return Err(format!(
+                "agent info expires after {}ms, more than the maximum of {}ms",
+                agent_info.expires_after_ms(),
+                MAX_EXPIRES_AFTER_MS
+            )
+            .into());
+        }
+        let entry = Entry {
+            signed_at_ms: agent_info.signed_at_ms(),
+            expires_at_ms: agent_info
+                .signed_at_ms()
+                .saturating_add(agent_info.expires_after_ms()),
+            info,
+        };
+
+        let limits = self.0.limits;
+        let mut spaces = self.0.spaces.write();
+        let total: usize = spaces.values().map(HashMap::len).sum();
+        let space_len = spaces
+            .get(agent_info.as_space_ref())
+            .map(HashMap::len)
+            .unwrap_or(0);
+        let agents = spaces.entry(agent_info.as_space_ref().clone()).or_default();
+        match agents.get(agent_info.as_agent_ref()) {
+            Some(existing) if existing.signed_at_ms >= entry.signed_at_ms => (),
+            Some(_) => {
+                agents.insert(agent_info.as_agent_ref().clone(), entry);
+                self.0.dirty.store(true, Ordering::SeqCst);
+            }
+            None => {
+                if space_len >= limits.agents_per_space || total >= limits.agents {
+                    if agents.is_empty() {
+                        spaces.remove(agent_info.as_space_ref());
+                    }
+                    return Err("bootstrap store is full".into());
+                }
+                agents.insert(agent_info.as_agent_ref().clone(), entry);
+                self.0.dirty.store(true, Ordering::SeqCst);
+            }
+        }
+        Ok(())
+    }
+
+    /// Up to `limit` unexpired agent infos from `space`, in random order.
+    pub fn random(
+        &self,
+        space: &KitsuneSpace,
+        limit: usize,
+    ) -> KitsuneP2pResult<Vec<AgentInfoSigned>> {
+        let now = now_ms()?;
+        let mut rng = rand::thread_rng();
+        let spaces = self.0.spaces.read();
+        let mut out = match spaces.get(space) {
+            Some(agents) => agents
+                .values()
+                .filter(|entry| entry.expires_at_ms > now)
+                .map(|entry| entry.info.clone())
+                .choose_multiple(&mut rng, limit),
+            None => Vec::new(),
+        };
+        // `choose_multiple` does not randomize the order of the sample.
+        out.shuffle(&mut rng);
+        Ok(out)
+    }
+
+    /// Drop every agent info that has expired as of `now_ms`,
+    /// returning how many were dropped.
+    pub fn prune(&self, now_ms: u64) -> usize {
+        let mut pruned = 0;
+        let mut spaces = self.0.spaces.write();
+        spaces.retain(|_, agents| {
+            let before = agents.len();
+            agents.retain(|_, entry| entry.expires_at_ms > now_ms);
+            pruned += before - agents.len();
+            !agents.is_empty()
+        });
+        if pruned > 0 {
+            self.0.dirty.store(true, Ordering::SeqCst);
+        }
+        pruned
+    }
+
+    /// Write the store to its persistence file, if it has one and has
+    /// changed since the last flush.
+    pub async fn flush(&self) -> KitsuneP2pResult<()> {
+        let path = match &self.0.persist {
+            Some(path) => path.clone(),
+            None => return Ok(()),
+        };
+        if !self.0.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let infos: Vec<AgentInfoSigned> = self
+            .0
+            .spaces
+            .read()
+            .values()
+            .flat_map(|agents| agents.values().map(|entry| entry.info.clone()))
+            .collect();
+
+        let res = tokio::task::spawn_blocking(move || {
+            let mut data = Vec::new();
+            kitsune_p2p_types::codec::rmp_encode(&mut data, &infos)?;
+            // Write then rename so a crash never leaves a truncated file.
+            let mut tmp = path.clone().into_os_string();
+            tmp.push(".tmp");
+            std::fs::write(&tmp, &data)?;
+            std::fs::rename(&tmp, &path)
+        })
+        .await
+        .map_err(KitsuneP2pError::other)?;
+        if res.is_err() {
+            // Try again on the next flush.
+            self.0.dirty.store(true, Ordering::SeqCst);
+        }
+        Ok(res?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use kitsune_p2p::fixt::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn stores_and_samples_per_space() {
+        let store = Store::new(StoreLimits::default());
+        let space = fixt!(KitsuneSpace, Unpredictable);
+        let now = now_ms().unwrap();
+
+        let mut expected = Vec::new();
+        for _ in 0..3 {
+            let info = signed_agent_info(space.clone(), now, 1000 * 60).await;
+            store.put(info.clone()).await.unwrap();
+            expected.push(info);
+        }
+        // Someone else's space is not mixed in.
+        store
+            .put(signed_agent_info(fixt!(KitsuneSpace, Unpredictable), now, 1000 * 60).await)
+            .await
+            .unwrap();
+
+        let mut all = store.random(&space, 10).unwrap();
+        all.sort();
+        expected.sort();
+        assert_eq!(expected, all);
+
+        let one = store.random(&space, 1).unwrap();
+        assert_eq!(1, one.len());
+        assert!(expected.contains(&one[0]));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn caps_agents_per_space_and_in_total() {
+        let store = Store::new(StoreLimits {
+            agents_per_space: 2,
+            agents: 3,
+        });
+        let space = fixt!(KitsuneSpace, Unpredictable);
+        let other_space = fixt!(KitsuneSpace, Unpredictable);
+        let now = now_ms().unwrap();
+
+        let first = signed_agent_info(space.clone(), now, 1000 * 60).await;
+        store.put(first.clone()).await.unwrap();
+        store
+            .put(signed_agent_info(space.clone(), now, 1000 * 60).await)
+            .await
+            .unwrap();
+        // The space is full
+        assert!(store
+            .put(signed_agent_info(space.clone(), now, 1000 * 60).await)
+            .await
+            .is_err());
+        store
+            .put(signed_agent_info(other_space.clone(), now, 1000 * 60).await)
+            .await
+            .unwrap();
+        // The store is full
+        assert!(store
+            .put(signed_agent_info(other_space.clone(), now, 1000 * 60).await)
+            .await
+            .is_err());
+        assert!(store
+            .put(signed_agent_info(fixt!(KitsuneSpace, Unpredictable), now, 1000 * 60).await)
+            .await
+            .is_err());
+        assert_eq!(2, store.0.spaces.read().len());
+        // Agents already held can still refresh their info
+        store.put(first).await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_forged_agent_info() {
+        let store = Store::new(StoreLimits::default());
+        assert!(store.put(fixt!(AgentInfoSigned)).await.is_err());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_long_lived_agent_info() {
+        let store = Store::new(StoreLimits::default());
+        let space = fixt!(KitsuneSpace, Unpredictable);
+        let now = now_ms().unwrap();
+        assert!(store
+            .put(signed_agent_info(space.clone(), now, MAX_EXPIRES_AFTER_MS + 1).await)
+            .await
+            .is_err());
+        assert!(store.random(&space, 10).unwrap().is_empty());
+        store
+            .put(signed_agent_info(space.clone(), now, MAX_EXPIRES_AFTER_MS).await)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn prunes_expired_agent_infos() {
+        let store = Store::new(StoreLimits::default());
+        let space = fixt!(KitsuneSpace, Unpredictable);
+        let now = now_ms().unwrap();
+        store
+            .put(signed_agent_info(space.clone(), now, 1000).await)
+            .await
+            .unwrap();
+
+        assert_eq!(0, store.prune(now));
+        assert_eq!(1, store.prune(now + 1000));
+        assert!(store.random(&space, 10).unwrap().is_empty());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn persists_across_reopen() {
+        let dir = tempdir::TempDir::new("kitsune_p2p_bootstrap").unwrap();
+        let path = dir.path().join("agents.bin");
+        let space = fixt!(KitsuneSpace, Unpredictable);
+        let info = signed_agent_info(space.clone(), now_ms().unwrap(), 1000 * 60).await;
+
+        let store = Store::open(path.clone(), StoreLimits::default())
+            .await
+            .unwrap();
+        store.put(info.clone()).await.unwrap();
+        store.flush().await.unwrap();
+
+        let store = Store::open(path, StoreLimits::default()).await.unwrap();
+        assert_eq!(vec![info], store.random(&space, 10).unwrap());
+    }
+}
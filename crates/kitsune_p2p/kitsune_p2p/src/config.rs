@@ -23,6 +23,12 @@ pub struct KitsuneP2pTuningParams {
     pub default_rpc_multi_remote_agent_count: u32,
     pub default_rpc_multi_timeout_ms: u32,
     pub agent_info_expires_after_ms: u32,
+    pub quic_max_connections: u32,
+    pub quic_connection_idle_timeout_ms: u32,
+    pub quic_max_concurrent_streams: u32,
+    pub peer_ban_duration_ms: u32,
+    pub event_queue_len_per_space: u32,
+    pub event_interactive_burst: u32,
 }
 
 impl Default for KitsuneP2pTuningParams {
@@ -35,6 +41,12 @@ impl Default for KitsuneP2pTuningParams {
             default_rpc_multi_remote_agent_count: 2,
             default_rpc_multi_timeout_ms: 2000,
             agent_info_expires_after_ms: 1000 * 60 * 20, // 20 minutes
+            quic_max_connections: 1024,
+            quic_connection_idle_timeout_ms: 1000 * 30,
+            quic_max_concurrent_streams: 128,
+            peer_ban_duration_ms: 1000 * 60 * 10, // 10 minutes
+            event_queue_len_per_space: 10,
+            event_interactive_burst: 8,
         }
     }
 }
@@ -74,6 +86,30 @@ impl serde::Serialize for KitsuneP2pTuningParams {
             "agent_info_expires_after_ms",
             &format!("{}", self.agent_info_expires_after_ms),
         )?;
+        m.serialize_entry(
+            "quic_max_connections",
+            &format!("{}", self.quic_max_connections),
+        )?;
+        m.serialize_entry(
+            "quic_connection_idle_timeout_ms",
+            &format!("{}", self.quic_connection_idle_timeout_ms),
+        )?;
+        m.serialize_entry(
+            "quic_max_concurrent_streams",
+            &format!("{}", self.quic_max_concurrent_streams),
+        )?;
+        m.serialize_entry(
+            "peer_ban_duration_ms",
+            &format!("{}", self.peer_ban_duration_ms),
+        )?;
+        m.serialize_entry(
+            "event_queue_len_per_space",
+            &format!("{}", self.event_queue_len_per_space),
+        )?;
+        m.serialize_entry(
+            "event_interactive_burst",
+            &format!("{}", self.event_interactive_burst),
+        )?;
         m.end()
     }
 }
@@ -115,6 +151,30 @@ impl<'de> serde::Deserialize<'de> for KitsuneP2pTuningParams {
                     Ok(v) => out.agent_info_expires_after_ms = v,
                     Err(e) => tracing::warn!("failed to parse {}: {}", k, e),
                 },
+                "quic_max_connections" => match v.parse::<u32>() {
+                    Ok(v) => out.quic_max_connections = v,
+                    Err(e) => tracing::warn!("failed to parse {}: {}", k, e),
+                },
+                "quic_connection_idle_timeout_ms" => match v.parse::<u32>() {
+                    Ok(v) => out.quic_connection_idle_timeout_ms = v,
+                    Err(e) => tracing::warn!("failed to parse {}: {}", k, e),
+                },
+                "quic_max_concurrent_streams" => match v.parse::<u32>() {
+                    Ok(v) => out.quic_max_concurrent_streams = v,
+                    Err(e) => tracing::warn!("failed to parse {}: {}", k, e),
+                },
+                "peer_ban_duration_ms" => match v.parse::<u32>() {
+                    Ok(v) => out.peer_ban_duration_ms = v,
+                    Err(e) => tracing::warn!("failed to parse {}: {}", k, e),
+                },
+                "event_queue_len_per_space" => match v.parse::<u32>() {
+                    Ok(v) => out.event_queue_len_per_space = v,
+                    Err(e) => tracing::warn!("failed to parse {}: {}", k, e),
+                },
+                "event_interactive_burst" => match v.parse::<u32>() {
+                    Ok(v) => out.event_interactive_burst = v,
+                    Err(e) => tracing::warn!("failed to parse {}: {}", k, e),
+                },
                 _ => tracing::warn!("INVALID TUNING PARAM: '{}'", k),
             }
         }
@@ -136,6 +196,10 @@ pub struct KitsuneP2pConfig {
     /// a warning will be printed in the tracing log.
     #[serde(default)]
     pub tuning_params: KitsuneP2pTuningParams,
+    /// Announce and discover peers on the local network over udp multicast.
+    /// Default: None = no local network discovery.
+    #[serde(default)]
+    pub lan_discovery: Option<LanDiscoveryConfig>,
 }
 
 impl Default for KitsuneP2pConfig {
@@ -144,6 +208,41 @@ impl Default for KitsuneP2pConfig {
             transport_pool: Vec::new(),
             bootstrap_service: None,
             tuning_params: KitsuneP2pTuningParams::default(),
+            lan_discovery: None,
+        }
+    }
+}
+
+/// Configure discovery of peers on the local network, for deployments
+/// with no bootstrap service or no internet access at all.
+/// Every joined agent's signed agent info is periodically multicast on the
+/// local subnet, and agent info multicast by others is added to the peer
+/// store.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LanDiscoveryConfig {
+    /// The multicast group and port to announce on and listen to.
+    /// Default: "239.255.42.98:44498".
+    pub multicast_addr: std::net::SocketAddrV4,
+
+    /// The network interface to announce and listen on.
+    /// Default: "0.0.0.0" = let the OS choose.
+    pub interface: std::net::Ipv4Addr,
+
+    /// How often to announce our local agents, in milliseconds.
+    /// Default: 30000.
+    pub announce_interval_ms: u32,
+}
+
+impl Default for LanDiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            multicast_addr: std::net::SocketAddrV4::new(
+                std::net::Ipv4Addr::new(239, 255, 42, 98),
+                44498,
+            ),
+            interface: std::net::Ipv4Addr::UNSPECIFIED,
+            announce_interval_ms: 1000 * 30,
         }
     }
 }
@@ -172,6 +271,24 @@ pub enum TransportConfig {
         /// Default: None = use NIC port.
         override_port: Option<u16>,
     },
+    /// A transport that uses TLS over TCP, for networks that block UDP.
+    /// Channels to a peer are multiplexed over a single connection.
+    Tcp {
+        /// To which network interface / port should we bind?
+        /// Default: "kitsune-tcp://0.0.0.0:0".
+        bind_to: Option<Url2>,
+
+        /// If you have port-forwarding set up,
+        /// or wish to apply a vanity domain name,
+        /// you may need to override the local NIC ip.
+        /// Default: None = use NIC ip.
+        override_host: Option<String>,
+
+        /// If you have port-forwarding set up,
+        /// you may need to override the local NIC port.
+        /// Default: None = use NIC port.
+        override_port: Option<u16>,
+    },
     /// A transport that tls tunnels through a sub-transport (ALPN kitsune-proxy/0)
     Proxy {
         /// The 'Proxy' transport is a wrapper around a sub-transport
@@ -192,8 +309,12 @@ pub enum TransportConfig {
 pub enum ProxyConfig {
     /// We want to be hosted at a remote proxy location.
     RemoteProxyClient {
-        /// The remote proxy url to be hosted at
-        proxy_url: Url2,
+        /// The remote proxy urls we may be hosted at, in order of preference.
+        /// We are hosted at the first that grants us a contract, and fail
+        /// over to the next should it stop answering. Must not be empty.
+        /// A single `proxy_url` is still accepted.
+        #[serde(alias = "proxy_url", deserialize_with = "url_or_urls")]
+        proxy_urls: Vec<Url2>,
     },
 
     /// We want to be a proxy server for others.
@@ -205,6 +326,25 @@ pub enum ProxyConfig {
     },
 }
 
+/// Deserialize either a single url or a list of them.
+fn url_or_urls<'de, D>(deserializer: D) -> Result<Vec<Url2>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum UrlOrUrls {
+        Url(Url2),
+        Urls(Vec<Url2>),
+    }
+    Ok(
+        match <UrlOrUrls as serde::Deserialize>::deserialize(deserializer)? {
+            UrlOrUrls::Url(url) => vec![url],
+            UrlOrUrls::Urls(urls) => urls,
+        },
+    )
+}
+
 /// Whether we are willing to proxy on behalf of others
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -4,6 +4,8 @@ use crate::agent_store::AgentInfo;
 use crate::agent_store::AgentInfoSigned;
 use crate::agent_store::Urls;
 use crate::dependencies::url2;
+use crate::dht_arc::DhtArc;
+use crate::dht_arc::MAX_HALF_LENGTH;
 use crate::KitsuneAgent;
 use crate::KitsuneBinType;
 use crate::KitsuneSignature;
@@ -51,9 +53,14 @@ fixturator!(
     from SixtyFourBytes;
 );
 
+fixturator!(
+    DhtArc;
+    constructor fn new(U32, U32);
+);
+
 fixturator!(
     AgentInfo;
-    constructor fn new(KitsuneSpace, KitsuneAgent, Urls, U64, U64);
+    constructor fn new(KitsuneSpace, KitsuneAgent, Urls, DhtArc, U64, U64);
 );
 
 fixturator!(
@@ -86,3 +93,32 @@ fixturator!(
         ).unwrap()
     };
 );
+
+/// Sign an [`AgentInfo`] for a freshly generated agent in `space`,
+/// so that it passes [`AgentInfoSigned::verify`].
+pub async fn signed_agent_info(
+    space: KitsuneSpace,
+    signed_at_ms: u64,
+    expires_after_ms: u64,
+) -> AgentInfoSigned {
+    let keypair =
+        lair_keystore_api::internal::sign_ed25519::sign_ed25519_keypair_new_from_entropy()
+            .await
+            .unwrap();
+    let agent = KitsuneAgent::new((*keypair.pub_key.0).clone());
+    let info = AgentInfo::new(
+        space,
+        agent.clone(),
+        fixt!(Urls),
+        DhtArc::new(agent.get_loc(), MAX_HALF_LENGTH),
+        signed_at_ms,
+        expires_after_ms,
+    );
+    let mut data = Vec::new();
+    kitsune_p2p_types::codec::rmp_encode(&mut data, &info).unwrap();
+    let signature = keypair
+        .sign(std::sync::Arc::new(data.clone()))
+        .await
+        .unwrap();
+    AgentInfoSigned::try_new(agent, KitsuneSignature((*signature.0).clone()), data).unwrap()
+}
@@ -13,6 +13,7 @@ use kitsune_p2p_types::transport::*;
 use kitsune_p2p_types::transport_pool::*;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 /// The bootstrap service is much more thoroughly documented in the default service implementation.
@@ -20,6 +21,9 @@ use std::sync::Arc;
 mod bootstrap;
 mod discover;
 mod gossip;
+mod lan_discovery;
+mod reputation;
+mod scheduler;
 mod space;
 use ghost_actor::dependencies::must_future;
 use ghost_actor::dependencies::tracing;
@@ -35,15 +39,17 @@ ghost_actor::ghost_chan! {
 pub(crate) struct KitsuneP2pActor {
     channel_factory: ghost_actor::actor_builder::GhostActorChannelFactory<Self>,
     internal_sender: ghost_actor::GhostSender<Internal>,
-    evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+    events: scheduler::EventScheduler,
     transport: ghost_actor::GhostSender<TransportListener>,
     spaces: HashMap<Arc<KitsuneSpace>, AsyncLazy<ghost_actor::GhostSender<KitsuneP2p>>>,
+    reputations: reputation::Reputations,
     config: Arc<KitsuneP2pConfig>,
 }
 
 fn build_transport(
     t_conf: TransportConfig,
     tls_config: Arc<kitsune_p2p_proxy::TlsConfig>,
+    tuning_params: KitsuneP2pTuningParams,
 ) -> must_future::MustBoxFuture<
     'static,
     TransportResult<(
@@ -64,20 +70,38 @@ fn build_transport(
                 let sub_conf = kitsune_p2p_transport_quic::ConfigListenerQuic::default()
                     .set_bind_to(bind_to)
                     .set_override_host(override_host)
-                    .set_override_port(override_port);
+                    .set_override_port(override_port)
+                    .set_max_connections(tuning_params.quic_max_connections as usize)
+                    .set_idle_timeout_ms(tuning_params.quic_connection_idle_timeout_ms)
+                    .set_max_concurrent_streams(tuning_params.quic_max_concurrent_streams);
                 Ok(kitsune_p2p_transport_quic::spawn_transport_listener_quic(sub_conf).await?)
             }
+            TransportConfig::Tcp {
+                bind_to,
+                override_host,
+                override_port,
+            } => {
+                let sub_conf = kitsune_p2p_transport_tcp::ConfigListenerTcp::default()
+                    .set_bind_to(bind_to)
+                    .set_override_host(override_host)
+                    .set_override_port(override_port)
+                    .set_tls(Some((
+                        tls_config.cert.clone(),
+                        tls_config.cert_priv_key.clone(),
+                    )));
+                Ok(kitsune_p2p_transport_tcp::spawn_transport_listener_tcp(sub_conf).await?)
+            }
             TransportConfig::Proxy {
                 sub_transport,
                 proxy_config,
             } => {
                 let (sub_lstn, sub_evt) =
-                    build_transport(*sub_transport, tls_config.clone()).await?;
+                    build_transport(*sub_transport, tls_config.clone(), tuning_params).await?;
                 let sub_conf = match proxy_config {
-                    ProxyConfig::RemoteProxyClient { proxy_url } => {
-                        kitsune_p2p_proxy::ProxyConfig::remote_proxy_client(
+                    ProxyConfig::RemoteProxyClient { proxy_urls } => {
+                        kitsune_p2p_proxy::ProxyConfig::remote_proxy_client_list(
                             (*tls_config).clone(),
-                            proxy_url.into(),
+                            proxy_urls.into_iter().map(Into::into).collect(),
                         )
                     }
                     ProxyConfig::LocalProxyServer {
@@ -115,19 +139,44 @@ impl KitsuneP2pActor {
         let tls_config = Arc::new(tls_config);
         let (t_pool, transport, t_event) = spawn_transport_pool().await?;
         for t_conf in config.transport_pool.clone() {
-            let (l, e) = build_transport(t_conf, tls_config.clone()).await?;
+            let (l, e) =
+                build_transport(t_conf, tls_config.clone(), config.tuning_params.clone()).await?;
             t_pool.push_sub_transport(l, e).await?;
         }
 
+        let reputations = reputation::Reputations::default();
+        let events = scheduler::EventScheduler::spawn(evt_sender, &config.tuning_params)?;
+        let ban_duration_ms = config.tuning_params.peer_ban_duration_ms as u64;
+        let inbound = scheduler::InboundLimit::new(
+            scheduler::MAX_INBOUND_REQUESTS,
+            config.tuning_params.event_queue_len_per_space as usize,
+        );
+
         tokio::task::spawn({
-            let evt_sender = evt_sender.clone();
-            t_event.for_each_concurrent(/* limit */ 10, move |event| {
-                let evt_sender = evt_sender.clone();
-                async move {
-                    let evt_sender = &evt_sender;
+            let events = events.clone();
+            let reputations = reputations.clone();
+            t_event.for_each(move |event| {
+                let events = events.clone();
+                let reputations = reputations.clone();
+                // Every request is handled in its own task, so a space whose
+                // requests are slow to answer can't hold up the others.
+                let mut slot = match inbound.try_start() {
+                    Some(slot) => slot,
+                    None => {
+                        tracing::warn!("dropping an incoming request, too many are in flight");
+                        return futures::future::ready(());
+                    }
+                };
+                tokio::task::spawn(async move {
                     match event {
-                        TransportEvent::IncomingChannel(_url, mut write, read) => {
-                            let read = read.read_to_end().await;
+                        TransportEvent::IncomingChannel(url, mut write, read) => {
+                            let read = match read.read_to_end().await {
+                                Ok(read) => read,
+                                Err(err) => {
+                                    tracing::debug!(?err, "incoming channel reset");
+                                    return;
+                                }
+                            };
                             use kitsune_p2p_types::codec::Codec;
                             let read = match wire::Wire::decode_ref(&read) {
                                 Err(err) => {
@@ -139,6 +188,82 @@ impl KitsuneP2pActor {
                                 }
                                 Ok((_, r)) => r,
                             };
+                            // refuse requests for a space that already has
+                            // its share in flight, rather than queue them
+                            let space = match &read {
+                                wire::Wire::Call(wire::Call { space, .. })
+                                | wire::Wire::Notify(wire::Notify { space, .. })
+                                | wire::Wire::FetchOpHashes(wire::FetchOpHashes {
+                                    space, ..
+                                })
+                                | wire::Wire::FetchOpData(wire::FetchOpData { space, .. })
+                                | wire::Wire::Gossip(wire::Gossip { space, .. })
+                                | wire::Wire::AgentInfoQuery(wire::AgentInfoQuery {
+                                    space, ..
+                                }) => Some(space),
+                                _ => None,
+                            };
+                            if let Some(space) = space {
+                                if !slot.try_enter_space(space) {
+                                    let busy =
+                                        wire::Wire::busy(space.clone()).encode_vec().unwrap();
+                                    KitsuneMetrics::count(KitsuneMetrics::Fail, busy.len());
+                                    let _ = write.write_and_close(busy).await;
+                                    return;
+                                }
+                            }
+                            // refuse to deal with agents we have banned,
+                            // whoever the remote claims to be
+                            let from = match &read {
+                                wire::Wire::Call(wire::Call {
+                                    space, from_agent, ..
+                                })
+                                | wire::Wire::Notify(wire::Notify {
+                                    space, from_agent, ..
+                                })
+                                | wire::Wire::FetchOpHashes(wire::FetchOpHashes {
+                                    space,
+                                    from_agent,
+                                    ..
+                                })
+                                | wire::Wire::FetchOpData(wire::FetchOpData {
+                                    space,
+                                    from_agent,
+                                    ..
+                                })
+                                | wire::Wire::Gossip(wire::Gossip {
+                                    space, from_agent, ..
+                                }) => Some((space, from_agent)),
+                                _ => None,
+                            };
+                            if let Some((space, from_agent)) = from {
+                                let identity = reputation::transport_identity(&url);
+                                if !reputations.is_bound(space, from_agent, &identity)
+                                    && is_agent_identity(
+                                        &events,
+                                        space.clone(),
+                                        from_agent.clone(),
+                                        &identity,
+                                    )
+                                    .await
+                                {
+                                    reputations
+                                        .get(space, ban_duration_ms)
+                                        .bind_identity(from_agent, identity.clone());
+                                }
+                                if reputations.is_banned(space, from_agent)
+                                    || reputations.is_identity_banned(space, &identity)
+                                {
+                                    let reason = format!(
+                                        "{:?}",
+                                        KitsuneP2pError::BannedAgent(from_agent.clone())
+                                    );
+                                    let fail = wire::Wire::failure(reason).encode_vec().unwrap();
+                                    KitsuneMetrics::count(KitsuneMetrics::Fail, fail.len());
+                                    let _ = write.write_and_close(fail).await;
+                                    return;
+                                }
+                            }
                             match read {
                                 wire::Wire::Call(wire::Call {
                                     space,
@@ -147,8 +272,19 @@ impl KitsuneP2pActor {
                                     data,
                                     ..
                                 }) => {
-                                    let res = match evt_sender
-                                        .call(space, to_agent, from_agent, data.into())
+                                    let res = match events
+                                        .send(
+                                            space.clone(),
+                                            scheduler::Lane::Interactive,
+                                            move |evt_sender| {
+                                                evt_sender.call(
+                                                    space,
+                                                    to_agent,
+                                                    from_agent,
+                                                    data.into(),
+                                                )
+                                            },
+                                        )
                                         .await
                                     {
                                         Err(err) => {
@@ -173,8 +309,19 @@ impl KitsuneP2pActor {
                                     data,
                                     ..
                                 }) => {
-                                    if let Err(err) = evt_sender
-                                        .notify(space, to_agent, from_agent, data.into())
+                                    if let Err(err) = events
+                                        .send(
+                                            space.clone(),
+                                            scheduler::Lane::Interactive,
+                                            move |evt_sender| {
+                                                evt_sender.notify(
+                                                    space,
+                                                    to_agent,
+                                                    from_agent,
+                                                    data.into(),
+                                                )
+                                            },
+                                        )
                                         .await
                                     {
                                         let reason = format!("{:?}", err);
@@ -205,12 +352,15 @@ impl KitsuneP2pActor {
                                         until_utc_epoch_s,
                                         Default::default(),
                                     );
-                                    let (hashes, agent_hashes) = match local_req_op_hashes(
-                                        &evt_sender,
-                                        space,
-                                        input,
-                                    )
-                                    .await
+                                    let (hashes, agent_hashes) = match events
+                                        .send(
+                                            space.clone(),
+                                            scheduler::Lane::Bulk,
+                                            move |evt_sender| {
+                                                local_req_op_hashes(&evt_sender, space, input)
+                                            },
+                                        )
+                                        .await
                                     {
                                         Err(err) => {
                                             let reason = format!("{:?}", err);
@@ -258,22 +408,26 @@ impl KitsuneP2pActor {
                                         op_hashes,
                                         peer_hashes,
                                     );
-                                    let (op_data, agent_infos) =
-                                        match local_req_op_data(&evt_sender, space, input).await {
-                                            Err(err) => {
-                                                let reason = format!("{:?}", err);
-                                                let fail = wire::Wire::failure(reason)
-                                                    .encode_vec()
-                                                    .unwrap();
-                                                KitsuneMetrics::count(
-                                                    KitsuneMetrics::Fail,
-                                                    fail.len(),
-                                                );
-                                                let _ = write.write_and_close(fail).await;
-                                                return;
-                                            }
-                                            Ok(r) => r,
-                                        };
+                                    let (op_data, agent_infos) = match events
+                                        .send(
+                                            space.clone(),
+                                            scheduler::Lane::Bulk,
+                                            move |evt_sender| {
+                                                local_req_op_data(&evt_sender, space, input)
+                                            },
+                                        )
+                                        .await
+                                    {
+                                        Err(err) => {
+                                            let reason = format!("{:?}", err);
+                                            let fail =
+                                                wire::Wire::failure(reason).encode_vec().unwrap();
+                                            KitsuneMetrics::count(KitsuneMetrics::Fail, fail.len());
+                                            let _ = write.write_and_close(fail).await;
+                                            return;
+                                        }
+                                        Ok(r) => r,
+                                    };
                                     let op_data =
                                         op_data.into_iter().map(|(h, op)| (h, op.into())).collect();
                                     let resp =
@@ -287,7 +441,12 @@ impl KitsuneP2pActor {
                                     let _ = write.write_and_close(resp).await;
                                 }
                                 wire::Wire::AgentInfoQuery(q) => {
-                                    match agent_info_query(q, evt_sender.clone()).await {
+                                    let res = events.send(
+                                        q.space.clone(),
+                                        scheduler::Lane::Interactive,
+                                        move |evt_sender| agent_info_query(q, evt_sender),
+                                    );
+                                    match res.await {
                                         Ok(r) => {
                                             let resp = wire::Wire::agent_info_query_resp(r)
                                                 .encode_vec()
@@ -320,8 +479,15 @@ impl KitsuneP2pActor {
                                         ops.into_iter().map(|(k, v)| (k, v.into())).collect(),
                                         agents,
                                     );
-                                    if let Err(err) =
-                                        local_gossip_ops(&evt_sender, space, input).await
+                                    if let Err(err) = events
+                                        .send(
+                                            space.clone(),
+                                            scheduler::Lane::Bulk,
+                                            move |evt_sender| {
+                                                local_gossip_ops(&evt_sender, space, input)
+                                            },
+                                        )
+                                        .await
                                     {
                                         let reason = format!("{:?}", err);
                                         tracing::error!("got err: {}", reason);
@@ -339,21 +505,63 @@ impl KitsuneP2pActor {
                             }
                         }
                     }
-                }
+                });
+                futures::future::ready(())
             })
         });
 
         Ok(Self {
             channel_factory,
             internal_sender,
-            evt_sender,
+            events,
             transport,
             spaces: HashMap::new(),
+            reputations,
             config: Arc::new(config),
         })
     }
+
+    /// The reputation of the remote agents in a space.
+    fn reputation(&self, space: &Arc<KitsuneSpace>) -> reputation::PeerReputation {
+        self.reputations
+            .get(space, self.config.tuning_params.peer_ban_duration_ms as u64)
+    }
+}
+
+/// Whether the signed agent info of `agent` in our peer store lists a url
+/// with the transport `identity`, i.e. whether a remote with that identity
+/// claiming to be `agent` can be believed.
+async fn is_agent_identity(
+    events: &scheduler::EventScheduler,
+    space: Arc<KitsuneSpace>,
+    agent: Arc<KitsuneAgent>,
+    identity: &str,
+) -> bool {
+    let info = match events
+        .send(
+            space.clone(),
+            scheduler::Lane::Interactive,
+            move |evt_sender| {
+                evt_sender.get_agent_info_signed(GetAgentInfoSignedEvt { space, agent })
+            },
+        )
+        .await
+    {
+        Ok(Some(info)) => info,
+        _ => return false,
+    };
+    match crate::agent_store::AgentInfo::try_from(&info) {
+        Ok(info) => info
+            .as_urls_ref()
+            .iter()
+            .any(|url| reputation::transport_identity(url) == identity),
+        Err(_) => false,
+    }
 }
 
+/// The most agents we return when asked for agents near a basis.
+const AGENT_INFO_QUERY_NEAR_BASIS_LIMIT: usize = 8;
+
 async fn agent_info_query(
     q: wire::AgentInfoQuery,
     evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
@@ -377,19 +585,51 @@ async fn agent_info_query(
         } else {
             Ok(vec![])
         }
-    } else if let Some(_by_basis_arc) = by_basis_arc {
-        Ok(evt_sender
+    } else if let Some((basis, arc)) = by_basis_arc {
+        // answer with the agents that cover the basis, or that are at least
+        // as near to it as the asker already knows of, best first
+        let basis_loc = basis.get_loc();
+        let mut near = evt_sender
             .query_agent_info_signed(QueryAgentInfoSignedEvt {
                 space,
                 agent: to_agent,
             })
-            .await?)
+            .await?
+            .into_iter()
+            .filter_map(|signed| {
+                let info = crate::types::agent_store::AgentInfo::try_from(&signed).ok()?;
+                let order = discover::basis_order(&info, basis_loc);
+                if info.storage_arc().contains(basis_loc)
+                    || arc.contains(info.storage_arc().center_loc)
+                {
+                    Some((order, signed))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        near.sort_by_key(|(order, _)| *order);
+        near.truncate(AGENT_INFO_QUERY_NEAR_BASIS_LIMIT);
+        Ok(near.into_iter().map(|(_, signed)| signed).collect())
     } else {
         Err("must specify by_agent or by_basis_arc".into())
     }
 }
 
-impl ghost_actor::GhostControlHandler for KitsuneP2pActor {}
+impl ghost_actor::GhostControlHandler for KitsuneP2pActor {
+    fn handle_ghost_actor_shutdown(self) -> must_future::MustBoxFuture<'static, ()> {
+        use ghost_actor::GhostControlSender;
+        async move {
+            // The transport isn't owned by this actor alone, it is a separate
+            // actor which would otherwise keep its listener, and so its
+            // connections, alive. Peers must no longer be able to reach us,
+            // a proxy that was shut down must no longer host its clients.
+            let _ = self.transport.ghost_actor_shutdown().await;
+        }
+        .boxed()
+        .into()
+    }
+}
 
 impl ghost_actor::GhostHandler<Internal> for KitsuneP2pActor {}
 
@@ -415,21 +655,33 @@ impl KitsuneP2pEventHandler for KitsuneP2pActor {
         &mut self,
         input: crate::event::PutAgentInfoSignedEvt,
     ) -> KitsuneP2pEventHandlerResult<()> {
-        Ok(self.evt_sender.put_agent_info_signed(input))
+        Ok(self.events.send(
+            input.space.clone(),
+            scheduler::Lane::Interactive,
+            move |evt_sender| evt_sender.put_agent_info_signed(input),
+        ))
     }
 
     fn handle_get_agent_info_signed(
         &mut self,
         input: crate::event::GetAgentInfoSignedEvt,
     ) -> KitsuneP2pEventHandlerResult<Option<crate::types::agent_store::AgentInfoSigned>> {
-        Ok(self.evt_sender.get_agent_info_signed(input))
+        Ok(self.events.send(
+            input.space.clone(),
+            scheduler::Lane::Interactive,
+            move |evt_sender| evt_sender.get_agent_info_signed(input),
+        ))
     }
 
     fn handle_query_agent_info_signed(
         &mut self,
         input: crate::event::QueryAgentInfoSignedEvt,
     ) -> KitsuneP2pEventHandlerResult<Vec<crate::types::agent_store::AgentInfoSigned>> {
-        Ok(self.evt_sender.query_agent_info_signed(input))
+        Ok(self.events.send(
+            input.space.clone(),
+            scheduler::Lane::Interactive,
+            move |evt_sender| evt_sender.query_agent_info_signed(input),
+        ))
     }
 
     fn handle_call(
@@ -439,7 +691,11 @@ impl KitsuneP2pEventHandler for KitsuneP2pActor {
         from_agent: Arc<KitsuneAgent>,
         payload: Vec<u8>,
     ) -> KitsuneP2pEventHandlerResult<Vec<u8>> {
-        Ok(self.evt_sender.call(space, to_agent, from_agent, payload))
+        Ok(self.events.send(
+            space.clone(),
+            scheduler::Lane::Interactive,
+            move |evt_sender| evt_sender.call(space, to_agent, from_agent, payload),
+        ))
     }
 
     fn handle_notify(
@@ -449,7 +705,11 @@ impl KitsuneP2pEventHandler for KitsuneP2pActor {
         from_agent: Arc<KitsuneAgent>,
         payload: Vec<u8>,
     ) -> KitsuneP2pEventHandlerResult<()> {
-        Ok(self.evt_sender.notify(space, to_agent, from_agent, payload))
+        Ok(self.events.send(
+            space.clone(),
+            scheduler::Lane::Interactive,
+            move |evt_sender| evt_sender.notify(space, to_agent, from_agent, payload),
+        ))
     }
 
     fn handle_gossip(
@@ -461,29 +721,43 @@ impl KitsuneP2pEventHandler for KitsuneP2pActor {
         op_data: Vec<u8>,
     ) -> KitsuneP2pEventHandlerResult<()> {
         Ok(self
-            .evt_sender
-            .gossip(space, to_agent, from_agent, op_hash, op_data))
+            .events
+            .send(space.clone(), scheduler::Lane::Bulk, move |evt_sender| {
+                evt_sender.gossip(space, to_agent, from_agent, op_hash, op_data)
+            }))
     }
 
     fn handle_fetch_op_hashes_for_constraints(
         &mut self,
         input: FetchOpHashesForConstraintsEvt,
     ) -> KitsuneP2pEventHandlerResult<Vec<Arc<KitsuneOpHash>>> {
-        Ok(self.evt_sender.fetch_op_hashes_for_constraints(input))
+        Ok(self.events.send(
+            input.space.clone(),
+            scheduler::Lane::Bulk,
+            move |evt_sender| evt_sender.fetch_op_hashes_for_constraints(input),
+        ))
     }
 
     fn handle_fetch_op_hash_data(
         &mut self,
         input: FetchOpHashDataEvt,
     ) -> KitsuneP2pEventHandlerResult<Vec<(Arc<KitsuneOpHash>, Vec<u8>)>> {
-        Ok(self.evt_sender.fetch_op_hash_data(input))
+        Ok(self.events.send(
+            input.space.clone(),
+            scheduler::Lane::Bulk,
+            move |evt_sender| evt_sender.fetch_op_hash_data(input),
+        ))
     }
 
     fn handle_sign_network_data(
         &mut self,
         input: SignNetworkDataEvt,
     ) -> KitsuneP2pEventHandlerResult<KitsuneSignature> {
-        Ok(self.evt_sender.sign_network_data(input))
+        Ok(self.events.send(
+            input.space.clone(),
+            scheduler::Lane::Interactive,
+            move |evt_sender| evt_sender.sign_network_data(input),
+        ))
     }
 }
 
@@ -511,11 +785,13 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         let internal_sender = self.internal_sender.clone();
         let space2 = space.clone();
         let transport = self.transport.clone();
+        let reputation = self.reputation(&space);
         let config = Arc::clone(&self.config);
+        self.events.join(&space, &agent);
         let space_sender = match self.spaces.entry(space.clone()) {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => entry.insert(AsyncLazy::new(async move {
-                let (send, evt_recv) = spawn_space(space2, transport, config)
+                let (send, evt_recv) = spawn_space(space2, transport, reputation, config)
                     .await
                     .expect("cannot fail to create space");
                 internal_sender
@@ -540,8 +816,13 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
             None => return Ok(async move { Ok(()) }.boxed().into()),
             Some(space) => space.get(),
         };
+        let events = self.events.clone();
         Ok(async move {
-            space_sender.await.leave(space.clone(), agent).await?;
+            space_sender
+                .await
+                .leave(space.clone(), agent.clone())
+                .await?;
+            events.leave(&space, &agent);
             Ok(())
         }
         .boxed()
@@ -593,4 +874,114 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
             .boxed()
             .into())
     }
+
+    fn handle_report_invalid_op(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+    ) -> KitsuneP2pHandlerResult<()> {
+        self.reputation(&space).record_invalid_op(&agent);
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_ban_agent(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        duration_ms: Option<u64>,
+    ) -> KitsuneP2pHandlerResult<()> {
+        tracing::info!(?agent, ?duration_ms, "banning agent");
+        let reputation = self.reputation(&space);
+        reputation.ban(&agent, duration_ms);
+        let info = self
+            .events
+            .send(space.clone(), scheduler::Lane::Interactive, {
+                let agent = agent.clone();
+                move |evt_sender| {
+                    evt_sender.get_agent_info_signed(GetAgentInfoSignedEvt { space, agent })
+                }
+            });
+        Ok(async move {
+            // also refuse the agent when it claims to be someone else,
+            // which we can only tell in spaces we have joined
+            let info = match info.await {
+                Err(KitsuneP2pError::RoutingSpaceError(_)) => None,
+                info => info?,
+            };
+            if let Some(info) = info {
+                for url in crate::agent_store::AgentInfo::try_from(&info)?.as_urls_ref() {
+                    reputation.bind_identity(&agent, reputation::transport_identity(url));
+                }
+            }
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_unban_agent(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+    ) -> KitsuneP2pHandlerResult<()> {
+        self.reputation(&space).unban(&agent);
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_peer_scores(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<PeerScore>> {
+        let out = self.reputation(&space).dump();
+        Ok(async move { Ok(out) }.boxed().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent_store::AgentInfo;
+    use crate::agent_store::AgentInfoSigned;
+    use crate::dht_arc::DhtArc;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+
+    fn info_with_arc(arc: DhtArc) -> AgentInfoSigned {
+        let agent = fixt!(KitsuneAgent);
+        let info = AgentInfo::new(fixt!(KitsuneSpace), agent.clone(), fixt!(Urls), arc, 0, 0);
+        let mut data = Vec::new();
+        kitsune_p2p_types::codec::rmp_encode(&mut data, &info).unwrap();
+        AgentInfoSigned::try_new(agent, fixt!(KitsuneSignature), data).unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn agent_info_query_by_basis_only_returns_near_agents() {
+        // located at 0
+        let basis = Arc::new(KitsuneBasis::new(vec![0; 36]));
+        let covering = info_with_arc(DhtArc::new(10, 20));
+        let nearer = info_with_arc(DhtArc::new(100, 1));
+        let farther = info_with_arc(DhtArc::new(u32::MAX / 2, 1));
+
+        let (evt_sender, mut evt_recv) = futures::channel::mpsc::channel(10);
+        let store = vec![farther, nearer.clone(), covering.clone()];
+        tokio::task::spawn(async move {
+            while let Some(evt) = evt_recv.next().await {
+                if let KitsuneP2pEvent::QueryAgentInfoSigned { respond, .. } = evt {
+                    let store = store.clone();
+                    respond.r(Ok(async move { Ok(store) }.boxed().into()));
+                }
+            }
+        });
+
+        let query = wire::AgentInfoQuery {
+            space: Arc::new(fixt!(KitsuneSpace)),
+            to_agent: Arc::new(fixt!(KitsuneAgent)),
+            by_agent: None,
+            by_basis_arc: Some((basis, DhtArc::new(0, 200))),
+        };
+        assert_eq!(
+            agent_info_query(query, evt_sender).await.unwrap(),
+            vec![covering, nearer]
+        );
+    }
 }
@@ -210,8 +210,20 @@ mod tests {
     use lair_keystore_api::internal::sign_ed25519::sign_ed25519_keypair_new_from_entropy;
     use std::convert::TryInto;
 
+    /// Run the bootstrap api against a local service rather than the
+    /// hosted one, so these tests don't need the internet.
+    async fn local_service() -> kitsune_p2p_bootstrap::BootstrapService {
+        kitsune_p2p_bootstrap::spawn_bootstrap_service(
+            ([127, 0, 0, 1], 0).into(),
+            kitsune_p2p_bootstrap::Store::new(),
+        )
+        .await
+        .unwrap()
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn test_bootstrap() {
+        let service = local_service().await;
         let keypair = sign_ed25519_keypair_new_from_entropy().await.unwrap();
         let space = fixt!(KitsuneSpace);
         let agent = KitsuneAgent::new((*keypair.pub_key.0).clone());
@@ -225,6 +237,7 @@ mod tests {
             space,
             agent.clone(),
             urls,
+            fixt!(DhtArc),
             (millis - 100).try_into().unwrap(),
             1000 * 60 * 20,
         );
@@ -239,24 +252,19 @@ mod tests {
                 .unwrap();
 
         // Simply hitting the endpoint should be OK.
-        super::put(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
-            agent_info_signed,
-        )
-        .await
-        .unwrap();
+        super::put(Some(service.url()), agent_info_signed)
+            .await
+            .unwrap();
 
         // We should get back an error if we don't have a good signature.
-        assert!(super::put(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
-            fixt!(AgentInfoSigned)
-        )
-        .await
-        .is_err());
+        assert!(super::put(Some(service.url()), fixt!(AgentInfoSigned))
+            .await
+            .is_err());
     }
 
     #[tokio::test(threaded_scheduler)]
     async fn test_now() {
+        let service = local_service().await;
         let local_now = std::time::SystemTime::now();
         let local_millis: u64 = local_now
             .duration_since(std::time::UNIX_EPOCH)
@@ -266,24 +274,14 @@ mod tests {
             .unwrap();
 
         // We should be able to get a milliseconds timestamp back.
-        let remote_now: u64 = super::now(Some(url2::url2!(
-            "{}",
-            crate::config::BOOTSTRAP_SERVICE_DEV
-        )))
-        .await
-        .unwrap();
+        let remote_now: u64 = super::now(Some(service.url())).await.unwrap();
         let threshold = 5000;
 
         assert!((remote_now - local_millis) < threshold);
 
         // Now once should return some number and the remote server offset should be set in the
         // NOW_OFFSET_MILLIS once cell.
-        let _: u64 = super::now_once(Some(url2::url2!(
-            "{}",
-            crate::config::BOOTSTRAP_SERVICE_DEV
-        )))
-        .await
-        .unwrap();
+        let _: u64 = super::now_once(Some(service.url())).await.unwrap();
         assert!(super::NOW_OFFSET_MILLIS.get().is_some());
     }
 
@@ -292,13 +290,9 @@ mod tests {
     // Fixturator seed: 17591570467001263546
     // thread 'spawn::actor::bootstrap::tests::test_random' panicked at 'dispatch dropped without returning error', /rustc/d3fb005a39e62501b8b0b356166e515ae24e2e54/src/libstd/macros.rs:13:23
     async fn test_random() {
+        let service = local_service().await;
         let space = fixt!(KitsuneSpace, Unpredictable);
-        let now = super::now(Some(url2::url2!(
-            "{}",
-            crate::config::BOOTSTRAP_SERVICE_DEV
-        )))
-        .await
-        .unwrap();
+        let now = super::now(Some(service.url())).await.unwrap();
 
         let alice = sign_ed25519_keypair_new_from_entropy().await.unwrap();
         let bob = sign_ed25519_keypair_new_from_entropy().await.unwrap();
@@ -310,6 +304,7 @@ mod tests {
                 space.clone(),
                 kitsune_agent.clone(),
                 fixt!(Urls),
+                fixt!(DhtArc),
                 now,
                 1000 * 60 * 20,
             );
@@ -323,18 +318,15 @@ mod tests {
             )
             .unwrap();
 
-            super::put(
-                Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
-                agent_info_signed.clone(),
-            )
-            .await
-            .unwrap();
+            super::put(Some(service.url()), agent_info_signed.clone())
+                .await
+                .unwrap();
 
             expected.push(agent_info_signed);
         }
 
         let mut random = super::random(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
+            Some(service.url()),
             super::RandomQuery {
                 space: Arc::new(space.clone()),
                 ..Default::default()
@@ -350,7 +342,7 @@ mod tests {
         assert!(random == expected);
 
         let random_single = super::random(
-            Some(url2::url2!("{}", crate::config::BOOTSTRAP_SERVICE_DEV)),
+            Some(service.url()),
             super::RandomQuery {
                 space: Arc::new(space.clone()),
                 limit: 1.into(),
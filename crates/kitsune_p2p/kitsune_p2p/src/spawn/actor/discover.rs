@@ -1,8 +1,12 @@
 #![allow(dead_code)]
 use super::*;
 use crate::agent_store::AgentInfo;
+use crate::agent_store::AgentInfoSigned;
 use ghost_actor::dependencies::must_future::MustBoxFuture;
 use kitsune_p2p_types::codec::Codec;
+use kitsune_p2p_types::dht_arc;
+use kitsune_p2p_types::dht_arc::DhtArc;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 
@@ -31,8 +35,13 @@ pub(crate) fn peer_discover(
     let evt_sender = space.evt_sender.clone();
     let tx = space.transport.clone();
     let bootstrap_service = space.config.bootstrap_service.clone();
+    let reputation = space.reputation.clone();
     let space = space.space.clone();
     async move {
+        if reputation.is_banned(&to_agent) {
+            return PeerDiscoverResult::Err(KitsuneP2pError::BannedAgent(to_agent));
+        }
+
         // run tx.create_channel an conver success result into our return type
         let try_connect = |url| async {
             let (url, write, read) = tx.create_channel(url).await?;
@@ -77,7 +86,9 @@ pub(crate) fn peer_discover(
                 Arc::new(KitsuneBasis(to_agent.to_vec())),
                 i_s.clone(),
                 evt_sender.clone(),
+                tx.clone(),
                 bootstrap_service.clone(),
+                reputation.clone(),
             )
             .await?;
 
@@ -109,7 +120,7 @@ pub(crate) fn peer_discover(
                     write.write_and_close(msg).await?;
 
                     // parse the response
-                    let res = read.read_to_end().await;
+                    let res = read.read_to_end().await?;
                     let (_, res) = wire::Wire::decode_ref(&res)?;
                     match res {
                         wire::Wire::AgentInfoQueryResp(wire::AgentInfoQueryResp {
@@ -129,6 +140,12 @@ pub(crate) fn peer_discover(
             }))
             .await?;
 
+            // don't trust the peer that answered, only the agent's own signature
+            let info = req_info.verify(Some(&*space)).await?;
+            if info.as_agent_ref() != &*to_agent {
+                return Err("peer returned info for the wrong agent".into());
+            }
+
             // we got a result - let's add it to our store for the future
             let _ = evt_sender
                 .put_agent_info_signed(PutAgentInfoSignedEvt {
@@ -139,7 +156,6 @@ pub(crate) fn peer_discover(
                 .await;
 
             // we got a result, try to connect to it
-            let info = types::agent_store::AgentInfo::try_from(&req_info)?;
             let url = info
                 .as_urls_ref()
                 .get(0)
@@ -189,8 +205,7 @@ pub(crate) fn message_neighborhood<T, F>(
     target_node_count: u8,
     stage_1_timeout_if_any_ms: u64,
     stage_2_timeout_even_if_none_ms: u64,
-    // ignored while full-sync
-    _basis: Arc<KitsuneBasis>,
+    basis: Arc<KitsuneBasis>,
     payload: wire::Wire,
     accept_result_cb: F,
 ) -> MustBoxFuture<'static, Vec<T>>
@@ -202,6 +217,7 @@ where
     let evt_sender = space.evt_sender.clone();
     let tx = space.transport.clone();
     let bootstrap_service = space.config.bootstrap_service.clone();
+    let reputation = space.reputation.clone();
     let space = space.space.clone();
     let accept_result_cb = Arc::new(accept_result_cb);
     async move {
@@ -238,10 +254,12 @@ where
             if let Ok(nodes) = get_5_or_less_non_local_agents_near_basis(
                 space.clone(),
                 from_agent.clone(),
-                _basis.clone(),
+                basis.clone(),
                 i_s.clone(),
                 evt_sender.clone(),
+                tx.clone(),
                 bootstrap_service.clone(),
+                reputation.clone(),
             )
             .await
             {
@@ -257,7 +275,11 @@ where
                         let mut payload = payload.clone();
                         let accept_result_cb = accept_result_cb.clone();
                         let out = out.clone();
-                        tokio::task::spawn(async move {
+                        let reputation = reputation.clone();
+                        let peer = to_agent.clone();
+                        let remaining_ms = stage_2_timeout_even_if_none_ms
+                            .saturating_sub(start_time.elapsed().as_millis() as u64);
+                        let request = async move {
                             let (_, mut write, read) = fut.await?;
                             let metric_type = match &mut payload {
                                 wire::Wire::Notify(n) => {
@@ -273,13 +295,28 @@ where
                             let payload = payload.encode_vec()?;
                             KitsuneMetrics::count(metric_type, payload.len());
                             write.write_and_close(payload).await?;
-                            let res = read.read_to_end().await;
+                            let res = read.read_to_end().await?;
                             let (_, res) = wire::Wire::decode_ref(&res)?;
-                            if let Ok(res) = accept_result_cb(to_agent, res) {
-                                out.lock().await.push(res);
+                            if let wire::Wire::Busy(wire::Busy { space }) = res {
+                                return Err(KitsuneP2pError::RemoteBusy(space));
+                            }
+                            KitsuneP2pResult::Ok(accept_result_cb(to_agent, res))
+                        };
+                        tokio::task::spawn(async move {
+                            match tokio::time::timeout(
+                                std::time::Duration::from_millis(remaining_ms),
+                                request,
+                            )
+                            .await
+                            {
+                                Ok(Ok(Ok(res))) => {
+                                    reputation.record_success(&peer);
+                                    out.lock().await.push(res);
+                                }
+                                Ok(Err(KitsuneP2pError::RemoteBusy(_))) => {}
+                                Ok(Ok(Err(()))) | Ok(Err(_)) => reputation.record_failure(&peer),
+                                Err(_) => reputation.record_timeout(&peer),
                             }
-
-                            KitsuneP2pResult::Ok(())
                         });
                     }
                 }
@@ -306,91 +343,222 @@ where
     .into()
 }
 
-/// search for agents to contact
+/// How many agents we try to route a message for a basis to.
+const NEAR_BASIS_AGENT_COUNT: usize = 5;
+
+/// How many rounds of asking peers for agents nearer to a basis
+/// we make before settling for what we have.
+const MAX_WALK_HOPS: usize = 3;
+
+/// How many of the nearest peers we ask in each round of the walk.
+const WALK_WIDTH: usize = 3;
+
+/// How long we wait for a peer to answer a walk query.
+const WALK_QUERY_TIMEOUT_MS: u64 = 1000;
+
+/// Sort key for routing to a basis location.
+/// Agents whose storage arc covers the basis sort first,
+/// then within each group the agent whose arc is centered nearest the basis.
+pub(crate) fn basis_order(info: &AgentInfo, basis_loc: u32) -> (bool, u32) {
+    let arc = info.storage_arc();
+    (
+        !arc.contains(basis_loc),
+        dht_arc::shortest_arc_distance(arc.center_loc, basis_loc),
+    )
+}
+
+/// search for agents to contact, best first:
+/// those whose storage arc covers the basis, ordered by arc distance,
+/// with agents that have a low score last and banned agents left out
+/// - if we don't know of enough, ask the bootstrap service
+/// - if we still don't know of enough, walk towards the basis
+///   by asking the nearest agents we know of for agents nearer still
 pub(crate) fn get_5_or_less_non_local_agents_near_basis(
     space: Arc<KitsuneSpace>,
     from_agent: Arc<KitsuneAgent>,
-    // ignored while full-sync
-    _basis: Arc<KitsuneBasis>,
+    basis: Arc<KitsuneBasis>,
     i_s: ghost_actor::GhostSender<SpaceInternal>,
     evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+    tx: ghost_actor::GhostSender<TransportListener>,
     bootstrap_service: Option<url2::Url2>,
-) -> MustBoxFuture<'static, KitsuneP2pResult<HashSet<AgentInfo>>> {
+    reputation: super::reputation::PeerReputation,
+) -> MustBoxFuture<'static, KitsuneP2pResult<Vec<AgentInfo>>> {
     async move {
-        let mut out = HashSet::new();
+        let basis_loc = basis.get_loc();
+        let mut known: HashMap<KitsuneAgent, AgentInfo> = HashMap::new();
+        // banned agents are neither contacted nor asked about others
+        let is_remote = |info: &AgentInfo| {
+            let banned = reputation.is_banned(info.as_agent_ref());
+            let f = i_s.is_agent_local(Arc::new(info.as_agent_ref().clone()));
+            async move { !banned && matches!(f.await, Ok(false)) }
+        };
+        let covering = |known: &HashMap<KitsuneAgent, AgentInfo>| {
+            known
+                .values()
+                .filter(|info| info.storage_arc().contains(basis_loc))
+                .count()
+        };
 
-        if let Ok(mut list) = evt_sender
+        if let Ok(list) = evt_sender
             .query_agent_info_signed(QueryAgentInfoSignedEvt {
                 space: space.clone(),
                 agent: from_agent.clone(),
             })
             .await
         {
-            // randomize the results
-            rand::seq::SliceRandom::shuffle(&mut list[..], &mut rand::thread_rng());
             for item in list {
                 if let Ok(info) = AgentInfo::try_from(&item) {
-                    if let Ok(is_local) = i_s
-                        .is_agent_local(Arc::new(info.as_agent_ref().clone()))
-                        .await
-                    {
-                        if !is_local {
-                            out.insert(info);
-                        }
+                    if is_remote(&info).await {
+                        known.insert(info.as_agent_ref().clone(), info);
                     }
                 }
-                if out.len() >= 5 {
-                    return Ok(out);
-                }
             }
         }
 
-        if let Ok(list) = super::bootstrap::random(
-            bootstrap_service,
-            super::bootstrap::RandomQuery {
-                space: space.clone(),
-                // grap a couple extra incase they happen to be local
-                limit: 8.into(),
-            },
-        )
-        .await
-        {
-            for item in list {
-                // TODO - someday some validation here
-                if let Ok(info) = AgentInfo::try_from(&item) {
-                    if let Ok(is_local) = i_s
-                        .is_agent_local(Arc::new(info.as_agent_ref().clone()))
-                        .await
-                    {
-                        if !is_local {
+        if covering(&known) < NEAR_BASIS_AGENT_COUNT {
+            if let Ok(list) = super::bootstrap::random(
+                bootstrap_service,
+                super::bootstrap::RandomQuery {
+                    space: space.clone(),
+                    // grap a couple extra incase they happen to be local
+                    limit: 8.into(),
+                },
+            )
+            .await
+            {
+                for item in list {
+                    if let Ok(info) = item.verify(Some(&*space)).await {
+                        if !known.contains_key(info.as_agent_ref()) && is_remote(&info).await {
                             // we got a result - let's add it to our store for the future
                             let _ = evt_sender
                                 .put_agent_info_signed(PutAgentInfoSignedEvt {
                                     space: space.clone(),
                                     agent: from_agent.clone(),
-                                    agent_info_signed: item.clone(),
+                                    agent_info_signed: item,
                                 })
                                 .await;
-                            out.insert(info);
+                            known.insert(info.as_agent_ref().clone(), info);
                         }
                     }
                 }
-                if out.len() >= 5 {
-                    return Ok(out);
+            }
+        }
+
+        let mut asked = HashSet::new();
+        for _ in 0..MAX_WALK_HOPS {
+            if covering(&known) >= NEAR_BASIS_AGENT_COUNT {
+                break;
+            }
+            let mut nearest = known
+                .values()
+                .filter(|info| !asked.contains(info.as_agent_ref()))
+                .cloned()
+                .collect::<Vec<_>>();
+            nearest.sort_by_key(|info| basis_order(info, basis_loc));
+            nearest.truncate(WALK_WIDTH);
+            if nearest.is_empty() {
+                break;
+            }
+
+            // ask only for agents at least as near as the nearest we know of
+            let nearest_distance = known
+                .values()
+                .map(|info| basis_order(info, basis_loc).1)
+                .min()
+                .unwrap_or(u32::MAX);
+            let query_arc = DhtArc::new(basis_loc, nearest_distance.saturating_add(1));
+
+            let queries = nearest.into_iter().map(|peer| {
+                asked.insert(peer.as_agent_ref().clone());
+                let query = query_agents_near_basis(
+                    tx.clone(),
+                    space.clone(),
+                    peer,
+                    basis.clone(),
+                    query_arc,
+                );
+                tokio::time::timeout(
+                    std::time::Duration::from_millis(WALK_QUERY_TIMEOUT_MS),
+                    query,
+                )
+            });
+            let mut found_new = false;
+            for list in futures::future::join_all(queries).await {
+                let list = match list {
+                    Ok(Ok(list)) => list,
+                    _ => continue,
+                };
+                for item in list {
+                    // don't trust the peer that answered, only the agent's own signature
+                    if let Ok(info) = item.verify(Some(&*space)).await {
+                        if !known.contains_key(info.as_agent_ref()) && is_remote(&info).await {
+                            let _ = evt_sender
+                                .put_agent_info_signed(PutAgentInfoSignedEvt {
+                                    space: space.clone(),
+                                    agent: from_agent.clone(),
+                                    agent_info_signed: item,
+                                })
+                                .await;
+                            known.insert(info.as_agent_ref().clone(), info);
+                            found_new = true;
+                        }
+                    }
                 }
             }
+            if !found_new {
+                break;
+            }
         }
 
-        if out.is_empty() {
+        if known.is_empty() {
             return Err("could not find any peers".into());
         }
 
+        let mut out = known.into_iter().map(|(_, info)| info).collect::<Vec<_>>();
+        // randomize first, so equally good agents share the load
+        rand::seq::SliceRandom::shuffle(&mut out[..], &mut rand::thread_rng());
+        // agents with a low score are only tried if there are too few others
+        out.sort_by_cached_key(|info| {
+            (
+                reputation.is_low_score(info.as_agent_ref()),
+                basis_order(info, basis_loc),
+            )
+        });
+        out.truncate(NEAR_BASIS_AGENT_COUNT);
         Ok(out)
     }
     .boxed()
     .into()
 }
 
+/// ask a peer for the agents it knows of near a basis,
+/// see [`basis_order`] and the `AgentInfoQuery` handler
+async fn query_agents_near_basis(
+    tx: ghost_actor::GhostSender<TransportListener>,
+    space: Arc<KitsuneSpace>,
+    peer: AgentInfo,
+    basis: Arc<KitsuneBasis>,
+    query_arc: DhtArc,
+) -> KitsuneP2pResult<Vec<AgentInfoSigned>> {
+    let url = peer
+        .as_urls_ref()
+        .get(0)
+        .ok_or_else(|| KitsuneP2pError::from("no url"))?
+        .clone();
+    let (_, mut write, read) = tx.create_channel(url).await?;
+    let msg =
+        wire::Wire::agent_info_query(space, Arc::new(peer.into()), None, Some((basis, query_arc)))
+            .encode_vec()?;
+    KitsuneMetrics::count(KitsuneMetrics::AgentInfoQuery, msg.len());
+    write.write_and_close(msg).await?;
+    let res = read.read_to_end().await?;
+    let (_, res) = wire::Wire::decode_ref(&res)?;
+    match res {
+        wire::Wire::AgentInfoQueryResp(wire::AgentInfoQueryResp { agent_infos }) => Ok(agent_infos),
+        r => Err(format!("invalid response: {:?}", r).into()),
+    }
+}
+
 pub(crate) fn add_5_or_less_non_local_agents(
     space: Arc<KitsuneSpace>,
     from_agent: Arc<KitsuneAgent>,
@@ -409,8 +577,7 @@ pub(crate) fn add_5_or_less_non_local_agents(
         .await
         {
             for item in list {
-                // TODO - someday some validation here
-                if let Ok(info) = AgentInfo::try_from(&item) {
+                if let Ok(info) = item.verify(Some(&*space)).await {
                     if let Ok(is_local) = i_s
                         .is_agent_local(Arc::new(info.as_agent_ref().clone()))
                         .await
@@ -434,3 +601,44 @@ pub(crate) fn add_5_or_less_non_local_agents(
     .boxed()
     .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+
+    fn info_with_arc(arc: DhtArc) -> AgentInfo {
+        AgentInfo::new(
+            fixt!(KitsuneSpace),
+            fixt!(KitsuneAgent),
+            fixt!(Urls),
+            arc,
+            0,
+            0,
+        )
+    }
+
+    #[test]
+    fn orders_covering_agents_first_by_distance() {
+        let basis_loc = 1000;
+        let far_cover = info_with_arc(DhtArc::new(basis_loc + 500, 600));
+        let near_cover = info_with_arc(DhtArc::new(basis_loc - 10, 20));
+        let near_miss = info_with_arc(DhtArc::new(basis_loc + 5, 1));
+        let far_miss = info_with_arc(DhtArc::new(u32::MAX / 2, 100));
+        let mut infos = vec![
+            far_miss.clone(),
+            near_miss.clone(),
+            far_cover.clone(),
+            near_cover.clone(),
+        ];
+        infos.sort_by_key(|info| basis_order(info, basis_loc));
+        assert_eq!(infos, vec![near_cover, far_cover, near_miss, far_miss]);
+    }
+
+    #[test]
+    fn basis_distance_wraps_around() {
+        let wrapped = info_with_arc(DhtArc::new(u32::MAX - 5, 20));
+        assert_eq!(basis_order(&wrapped, 5), (false, 11));
+    }
+}
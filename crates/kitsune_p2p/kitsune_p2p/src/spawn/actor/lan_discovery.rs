@@ -0,0 +1,337 @@
+//! Discovery of peers on the local network over udp multicast,
+//! see [`LanDiscoveryConfig`].
+
+use super::*;
+use crate::agent_store::AgentInfoSigned;
+use std::net::Ipv4Addr;
+use std::net::SocketAddr;
+use std::net::SocketAddrV4;
+
+/// Prefixed to every announcement, so unrelated traffic that happens to
+/// share the multicast group is ignored.
+const ANNOUNCE_MAGIC: &[u8] = b"kitsune-lan/0";
+
+/// Large enough for any udp datagram.
+const MAX_DATAGRAM_LEN: usize = 65_507;
+
+/// How long the listener first waits after failing to receive,
+/// doubling on each further failure up to [`MAX_RECV_BACKOFF`].
+const MIN_RECV_BACKOFF: std::time::Duration = std::time::Duration::from_millis(100);
+const MAX_RECV_BACKOFF: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The most remote agents the listener remembers having put at once.
+const MAX_SEEN: usize = 4096;
+
+/// Multicasts the signed agent info of the local agents in one space,
+/// and puts agent info that others multicast for the same space into
+/// the peer store. Stops when the last clone is dropped.
+#[derive(Clone)]
+pub(crate) struct LanDiscovery(Arc<Inner>);
+
+struct Inner {
+    group: SocketAddrV4,
+    send: tokio::sync::Mutex<tokio::net::udp::SendHalf>,
+    local: std::sync::Mutex<HashMap<Arc<KitsuneAgent>, AgentInfoSigned>>,
+    // Never sent, the listener stops when this is dropped.
+    _stop: tokio::sync::oneshot::Sender<()>,
+}
+
+impl LanDiscovery {
+    pub(crate) fn spawn(
+        space: Arc<KitsuneSpace>,
+        config: &LanDiscoveryConfig,
+        evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+    ) -> KitsuneP2pResult<Self> {
+        let socket = bind_multicast(config)?;
+        let (recv, send) = tokio::net::UdpSocket::from_std(socket)?.split();
+        let (stop, stop_rx) = tokio::sync::oneshot::channel();
+        let inner = Arc::new(Inner {
+            group: config.multicast_addr,
+            send: tokio::sync::Mutex::new(send),
+            local: Default::default(),
+            _stop: stop,
+        });
+
+        tokio::task::spawn(listen(
+            space,
+            Arc::downgrade(&inner),
+            recv,
+            stop_rx,
+            evt_sender,
+        ));
+
+        let announce_interval =
+            std::time::Duration::from_millis(config.announce_interval_ms as u64);
+        let weak_inner = Arc::downgrade(&inner);
+        tokio::task::spawn(async move {
+            loop {
+                tokio::time::delay_for(announce_interval).await;
+                match weak_inner.upgrade() {
+                    Some(inner) => inner.announce_all().await,
+                    None => break,
+                }
+            }
+        });
+
+        Ok(Self(inner))
+    }
+
+    /// Announce a local agent's signed agent info now, and again on every
+    /// interval until it is replaced or retracted.
+    pub(crate) async fn announce(&self, agent: Arc<KitsuneAgent>, info: AgentInfoSigned) {
+        self.0.local.lock().unwrap().insert(agent, info.clone());
+        self.0.send_announcement(&info).await;
+    }
+
+    /// Stop announcing an agent that has left the space.
+    pub(crate) fn retract(&self, agent: &KitsuneAgent) {
+        self.0.local.lock().unwrap().remove(agent);
+    }
+}
+
+impl Inner {
+    async fn announce_all(&self) {
+        let infos: Vec<AgentInfoSigned> = self.local.lock().unwrap().values().cloned().collect();
+        for info in infos {
+            self.send_announcement(&info).await;
+        }
+    }
+
+    async fn send_announcement(&self, info: &AgentInfoSigned) {
+        let mut data = ANNOUNCE_MAGIC.to_vec();
+        if let Err(e) = kitsune_p2p_types::codec::rmp_encode(&mut data, info) {
+            tracing::error!(msg = "Failed to encode lan discovery announcement", ?e);
+            return;
+        }
+        let group = SocketAddr::V4(self.group);
+        if let Err(e) = self.send.lock().await.send_to(&data, &group).await {
+            tracing::warn!(msg = "Failed to send lan discovery announcement", ?e);
+        }
+    }
+}
+
+fn bind_multicast(config: &LanDiscoveryConfig) -> KitsuneP2pResult<std::net::UdpSocket> {
+    use socket2::*;
+    let socket = Socket::new(Domain::ipv4(), Type::dgram(), Some(Protocol::udp()))?;
+    // Every space, and every other conductor on this host,
+    // listens on the same group port.
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SockAddr::from(SocketAddrV4::new(
+        Ipv4Addr::UNSPECIFIED,
+        config.multicast_addr.port(),
+    )))?;
+    socket.join_multicast_v4(config.multicast_addr.ip(), &config.interface)?;
+    socket.set_multicast_if_v4(&config.interface)?;
+    // So other conductors on this host hear us too.
+    socket.set_multicast_loop_v4(true)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket.into_udp_socket())
+}
+
+async fn listen(
+    space: Arc<KitsuneSpace>,
+    inner: std::sync::Weak<Inner>,
+    mut recv: tokio::net::udp::RecvHalf,
+    mut stop_rx: tokio::sync::oneshot::Receiver<()>,
+    evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+) {
+    let mut buf = vec![0; MAX_DATAGRAM_LEN];
+    let mut seen = Seen::default();
+    let mut backoff = MIN_RECV_BACKOFF;
+    loop {
+        let received = tokio::select! {
+            r = recv.recv_from(&mut buf) => r,
+            _ = &mut stop_rx => break,
+        };
+        let len = match received {
+            Ok((len, _)) => {
+                backoff = MIN_RECV_BACKOFF;
+                len
+            }
+            Err(e) => {
+                tracing::warn!(msg = "Failed to receive lan discovery announcement", ?e);
+                tokio::select! {
+                    _ = tokio::time::delay_for(backoff) => (),
+                    _ = &mut stop_rx => break,
+                }
+                backoff = (backoff * 2).min(MAX_RECV_BACKOFF);
+                continue;
+            }
+        };
+        if !buf[..len].starts_with(ANNOUNCE_MAGIC) {
+            continue;
+        }
+        let info_signed: AgentInfoSigned =
+            match kitsune_p2p_types::codec::rmp_decode(&mut &buf[ANNOUNCE_MAGIC.len()..len]) {
+                Ok(info_signed) => info_signed,
+                Err(e) => {
+                    tracing::debug!(msg = "Ignoring malformed lan discovery announcement", ?e);
+                    continue;
+                }
+            };
+        let agent = Arc::new(info_signed.as_agent_ref().clone());
+        match inner.upgrade() {
+            // We also hear our own announcements.
+            Some(inner) if inner.local.lock().unwrap().contains_key(&agent) => continue,
+            Some(_) => (),
+            None => break,
+        }
+        // Also ignores announcements for other spaces.
+        let info = match info_signed.verify(Some(&*space)).await {
+            Ok(info) => info,
+            Err(e) => {
+                tracing::debug!(msg = "Ignoring lan discovery announcement", ?e);
+                continue;
+            }
+        };
+        if seen.has_newer(&agent, info.signed_at_ms()) {
+            continue;
+        }
+        match evt_sender
+            .put_agent_info_signed(PutAgentInfoSignedEvt {
+                space: space.clone(),
+                agent: agent.clone(),
+                agent_info_signed: info_signed,
+            })
+            .await
+        {
+            Ok(()) => seen.insert(
+                agent,
+                info.signed_at_ms(),
+                info.signed_at_ms().saturating_add(info.expires_after_ms()),
+            ),
+            Err(e) => tracing::warn!(msg = "Failed to store lan discovered agent info", ?e),
+        }
+    }
+}
+
+/// When the newest agent info the listener has put for each remote agent
+/// was signed, so repeated announcements aren't put again. Agents are
+/// forgotten once that info expires, and the oldest are forgotten first
+/// when more than [`MAX_SEEN`] are remembered.
+#[derive(Default)]
+struct Seen(HashMap<Arc<KitsuneAgent>, SeenInfo>);
+
+struct SeenInfo {
+    signed_at_ms: u64,
+    expires_at_ms: u64,
+}
+
+impl Seen {
+    fn has_newer(&self, agent: &KitsuneAgent, signed_at_ms: u64) -> bool {
+        self.0
+            .get(agent)
+            .map_or(false, |seen| seen.signed_at_ms >= signed_at_ms)
+    }
+
+    fn insert(&mut self, agent: Arc<KitsuneAgent>, signed_at_ms: u64, expires_at_ms: u64) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.prune(now);
+        if self.0.len() >= MAX_SEEN && !self.0.contains_key(&agent) {
+            let oldest = self
+                .0
+                .iter()
+                .min_by_key(|(_, seen)| seen.expires_at_ms)
+                .map(|(agent, _)| agent.clone());
+            if let Some(oldest) = oldest {
+                self.0.remove(&oldest);
+            }
+        }
+        self.0.insert(
+            agent,
+            SeenInfo {
+                signed_at_ms,
+                expires_at_ms,
+            },
+        );
+    }
+
+    fn prune(&mut self, now_ms: u64) {
+        self.0.retain(|_, seen| seen.expires_at_ms > now_ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn discovers_agents_over_loopback() {
+        // Borrow a free port for the multicast group.
+        let port = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = LanDiscoveryConfig {
+            multicast_addr: SocketAddrV4::new(Ipv4Addr::new(239, 255, 42, 98), port),
+            interface: Ipv4Addr::LOCALHOST,
+            announce_interval_ms: 100,
+        };
+        let space = Arc::new(fixt!(KitsuneSpace, Unpredictable));
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let (alice_send, _alice_recv) = futures::channel::mpsc::channel(10);
+        let (bob_send, mut bob_recv) = futures::channel::mpsc::channel(10);
+        let alice = LanDiscovery::spawn(space.clone(), &config, alice_send).unwrap();
+        let _bob = LanDiscovery::spawn(space.clone(), &config, bob_send).unwrap();
+
+        // Agents of other spaces sharing the group are not put.
+        let other_space = signed_agent_info(fixt!(KitsuneSpace, Unpredictable), now, 60_000).await;
+        alice
+            .announce(Arc::new(other_space.as_agent_ref().clone()), other_space)
+            .await;
+        let info = signed_agent_info((*space).clone(), now, 60_000).await;
+        alice
+            .announce(Arc::new(info.as_agent_ref().clone()), info.clone())
+            .await;
+
+        let put = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while let Some(evt) = bob_recv.next().await {
+                if let KitsuneP2pEvent::PutAgentInfoSigned { respond, input, .. } = evt {
+                    respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                    return input.agent_info_signed;
+                }
+            }
+            unreachable!("bob's event channel closed")
+        })
+        .await
+        .unwrap();
+        assert_eq!(info, put);
+    }
+
+    #[test]
+    fn seen_agents_expire() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let mut seen = Seen::default();
+        let agent = Arc::new(fixt!(KitsuneAgent, Unpredictable));
+        seen.insert(agent.clone(), now, now + 60_000);
+        assert!(seen.has_newer(&agent, now));
+        assert!(!seen.has_newer(&agent, now + 1));
+
+        seen.prune(now + 60_000);
+        assert!(!seen.has_newer(&agent, now));
+
+        for _ in 0..MAX_SEEN + 1 {
+            seen.insert(
+                Arc::new(fixt!(KitsuneAgent, Unpredictable)),
+                now,
+                now + 60_000,
+            );
+        }
+        assert_eq!(MAX_SEEN, seen.0.len());
+    }
+}
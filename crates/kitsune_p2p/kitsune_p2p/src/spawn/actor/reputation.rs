@@ -0,0 +1,453 @@
+//! Per-space scoring of remote agents, see [`PeerScore`].
+//!
+//! Every remote agent starts at a score of zero. Answered requests raise
+//! the score a little, timeouts and failures lower it, and invalid ops
+//! reported by the implementor lower it a lot. An agent whose score falls
+//! to [`AUTO_BAN_SCORE`] is banned for `peer_ban_duration_ms`, after which
+//! it starts over on probation at half that score.
+//!
+//! Remote agents are only who they claim to be on the wire, so a ban also
+//! covers the transport identities the agent is known to use, see
+//! [`transport_identity`]. Invalid op reports name the agent the op claimed
+//! to come from, so they can push a score down to [`LOW_SCORE`] but never
+//! get an agent banned.
+
+use super::*;
+use std::collections::HashSet;
+
+/// Answered requests can't raise a score beyond this.
+const MAX_SCORE: i32 = 100;
+
+/// Agents scoring below this are tried last by routing and gossip.
+const LOW_SCORE: i32 = -20;
+
+/// Agents scoring this low are banned for `peer_ban_duration_ms`.
+const AUTO_BAN_SCORE: i32 = -100;
+
+const SUCCESS_DELTA: i32 = 1;
+const TIMEOUT_DELTA: i32 = -5;
+const FAILURE_DELTA: i32 = -5;
+const INVALID_OP_DELTA: i32 = -25;
+
+/// Invalid op reports lower a score no further than this.
+const INVALID_OP_FLOOR: i32 = LOW_SCORE - 1;
+
+/// The part of a remote's url its transport vouches for: the tls
+/// certificate digest of a proxy or tcp url, otherwise the whole url.
+/// Tcp urls a remote connects from name its listening port, which changes
+/// from run to run, so the whole url would not follow it.
+pub(crate) fn transport_identity(url: &url2::Url2) -> String {
+    if let Some(cert_digest) = kitsune_p2p_transport_tcp::url_cert_digest(url) {
+        return cert_digest;
+    }
+    match (url.scheme(), url.host_str()) {
+        ("kitsune-proxy", Some(cert_digest)) => cert_digest.to_string(),
+        _ => url.as_str().to_string(),
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[derive(Default)]
+struct Record {
+    score: i32,
+    successes: u32,
+    timeouts: u32,
+    failures: u32,
+    invalid_ops: u32,
+    ban: Option<PeerBan>,
+    /// transport identities the agent has proven to be reachable at
+    identities: HashSet<String>,
+}
+
+impl Record {
+    /// Lift the ban if it has expired, returning whether one is in place.
+    fn check_ban(&mut self, now_ms: u64) -> bool {
+        match &self.ban {
+            Some(PeerBan {
+                until_ms: Some(until_ms),
+                ..
+            }) if *until_ms <= now_ms => {
+                self.ban = None;
+                // probation
+                self.score = self.score.max(AUTO_BAN_SCORE / 2);
+                false
+            }
+            Some(_) => true,
+            None => false,
+        }
+    }
+}
+
+struct Inner {
+    ban_duration_ms: u64,
+    peers: HashMap<KitsuneAgent, Record>,
+}
+
+/// The reputation of the remote agents in a single space.
+#[derive(Clone)]
+pub(crate) struct PeerReputation(Arc<std::sync::Mutex<Inner>>);
+
+impl PeerReputation {
+    pub(crate) fn new(ban_duration_ms: u64) -> Self {
+        Self(Arc::new(std::sync::Mutex::new(Inner {
+            ban_duration_ms,
+            peers: HashMap::new(),
+        })))
+    }
+
+    /// Apply `delta` to the agent's score, lowering it no further than
+    /// `floor` unless it already is.
+    fn update(
+        &self,
+        agent: &KitsuneAgent,
+        delta: i32,
+        floor: i32,
+        count: impl FnOnce(&mut Record),
+    ) {
+        let mut inner = self.0.lock().unwrap();
+        let ban_duration_ms = inner.ban_duration_ms;
+        let record = inner.peers.entry(agent.clone()).or_default();
+        let now_ms = now_ms();
+        let banned = record.check_ban(now_ms);
+        count(record);
+        record.score = (record.score + delta)
+            .max(floor.min(record.score))
+            .max(AUTO_BAN_SCORE)
+            .min(MAX_SCORE);
+        if record.score <= AUTO_BAN_SCORE && !banned {
+            tracing::warn!(?agent, "banning agent for a low score");
+            record.ban = Some(PeerBan {
+                until_ms: Some(now_ms + ban_duration_ms),
+                by_admin: false,
+            });
+        }
+    }
+
+    /// The agent answered a request.
+    pub(crate) fn record_success(&self, agent: &KitsuneAgent) {
+        self.update(agent, SUCCESS_DELTA, AUTO_BAN_SCORE, |r| r.successes += 1);
+    }
+
+    /// The agent did not answer a request in time.
+    pub(crate) fn record_timeout(&self, agent: &KitsuneAgent) {
+        self.update(agent, TIMEOUT_DELTA, AUTO_BAN_SCORE, |r| r.timeouts += 1);
+    }
+
+    /// A request to the agent failed.
+    pub(crate) fn record_failure(&self, agent: &KitsuneAgent) {
+        self.update(agent, FAILURE_DELTA, AUTO_BAN_SCORE, |r| r.failures += 1);
+    }
+
+    /// The implementor reports the agent sent us invalid data.
+    /// The report can't be tied to the connection the data came over,
+    /// so it lowers the score no further than [`INVALID_OP_FLOOR`].
+    pub(crate) fn record_invalid_op(&self, agent: &KitsuneAgent) {
+        self.update(agent, INVALID_OP_DELTA, INVALID_OP_FLOOR, |r| {
+            r.invalid_ops += 1
+        });
+    }
+
+    /// Record the outcome of a request we made of `agent`.
+    /// Not knowing how to reach the agent, or it being busy, is not held
+    /// against it.
+    pub(crate) fn record_result<T>(&self, agent: &KitsuneAgent, res: &KitsuneP2pResult<T>) {
+        match res {
+            Ok(_) => self.record_success(agent),
+            Err(KitsuneP2pError::RoutingAgentError(_))
+            | Err(KitsuneP2pError::BannedAgent(_))
+            | Err(KitsuneP2pError::RemoteBusy(_)) => {}
+            Err(_) => self.record_failure(agent),
+        }
+    }
+
+    /// Await a request we made of `agent`, recording its outcome.
+    pub(crate) async fn track<T>(
+        self,
+        agent: Arc<KitsuneAgent>,
+        request: impl std::future::Future<Output = KitsuneP2pResult<T>>,
+    ) -> KitsuneP2pResult<T> {
+        let res = request.await;
+        self.record_result(&agent, &res);
+        res
+    }
+
+    /// Ban the agent for `duration_ms` or, if None, until it is unbanned.
+    pub(crate) fn ban(&self, agent: &KitsuneAgent, duration_ms: Option<u64>) {
+        let mut inner = self.0.lock().unwrap();
+        let record = inner.peers.entry(agent.clone()).or_default();
+        record.ban = Some(PeerBan {
+            until_ms: duration_ms.map(|duration_ms| now_ms() + duration_ms),
+            by_admin: true,
+        });
+    }
+
+    /// Lift any ban on the agent, and forget its score.
+    pub(crate) fn unban(&self, agent: &KitsuneAgent) {
+        self.0.lock().unwrap().peers.remove(agent);
+    }
+
+    pub(crate) fn is_banned(&self, agent: &KitsuneAgent) -> bool {
+        let now_ms = now_ms();
+        match self.0.lock().unwrap().peers.get_mut(agent) {
+            Some(record) => record.check_ban(now_ms),
+            None => false,
+        }
+    }
+
+    /// Remember that the agent is reachable at a transport `identity`,
+    /// so while it is banned that identity is refused whoever it claims
+    /// to be.
+    pub(crate) fn bind_identity(&self, agent: &KitsuneAgent, identity: String) {
+        let mut inner = self.0.lock().unwrap();
+        let record = inner.peers.entry(agent.clone()).or_default();
+        record.identities.insert(identity);
+    }
+
+    pub(crate) fn is_bound(&self, agent: &KitsuneAgent, identity: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .peers
+            .get(agent)
+            .map_or(false, |record| record.identities.contains(identity))
+    }
+
+    /// Whether a banned agent is reachable at the transport `identity`.
+    pub(crate) fn is_identity_banned(&self, identity: &str) -> bool {
+        let now_ms = now_ms();
+        self.0
+            .lock()
+            .unwrap()
+            .peers
+            .values_mut()
+            .any(|record| record.identities.contains(identity) && record.check_ban(now_ms))
+    }
+
+    pub(crate) fn score(&self, agent: &KitsuneAgent) -> i32 {
+        self.0
+            .lock()
+            .unwrap()
+            .peers
+            .get(agent)
+            .map_or(0, |record| record.score)
+    }
+
+    /// Whether the agent should be tried after others.
+    pub(crate) fn is_low_score(&self, agent: &KitsuneAgent) -> bool {
+        self.score(agent) < LOW_SCORE
+    }
+
+    pub(crate) fn dump(&self) -> Vec<PeerScore> {
+        let now_ms = now_ms();
+        let mut inner = self.0.lock().unwrap();
+        inner
+            .peers
+            .iter_mut()
+            .map(|(agent, record)| {
+                record.check_ban(now_ms);
+                PeerScore {
+                    agent: agent.clone(),
+                    score: record.score,
+                    successes: record.successes,
+                    timeouts: record.timeouts,
+                    failures: record.failures,
+                    invalid_ops: record.invalid_ops,
+                    ban: record.ban.clone(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The reputations of every space,
+/// shared between the actor and incoming request handling.
+#[derive(Clone, Default)]
+pub(crate) struct Reputations(Arc<std::sync::Mutex<HashMap<Arc<KitsuneSpace>, PeerReputation>>>);
+
+impl Reputations {
+    pub(crate) fn get(&self, space: &Arc<KitsuneSpace>, ban_duration_ms: u64) -> PeerReputation {
+        self.0
+            .lock()
+            .unwrap()
+            .entry(space.clone())
+            .or_insert_with(|| PeerReputation::new(ban_duration_ms))
+            .clone()
+    }
+
+    pub(crate) fn is_banned(&self, space: &KitsuneSpace, agent: &KitsuneAgent) -> bool {
+        let reputation = self.0.lock().unwrap().get(space).cloned();
+        reputation.map_or(false, |reputation| reputation.is_banned(agent))
+    }
+
+    pub(crate) fn is_identity_banned(&self, space: &KitsuneSpace, identity: &str) -> bool {
+        let reputation = self.0.lock().unwrap().get(space).cloned();
+        reputation.map_or(false, |reputation| reputation.is_identity_banned(identity))
+    }
+
+    pub(crate) fn is_bound(
+        &self,
+        space: &KitsuneSpace,
+        agent: &KitsuneAgent,
+        identity: &str,
+    ) -> bool {
+        let reputation = self.0.lock().unwrap().get(space).cloned();
+        reputation.map_or(false, |reputation| reputation.is_bound(agent, identity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+
+    #[test]
+    fn successes_raise_the_score_up_to_a_cap() {
+        let reputation = PeerReputation::new(1000);
+        let agent = fixt!(KitsuneAgent);
+        for _ in 0..200 {
+            reputation.record_success(&agent);
+        }
+        assert_eq!(MAX_SCORE, reputation.score(&agent));
+        assert_eq!(200, reputation.dump()[0].successes);
+    }
+
+    #[test]
+    fn timeouts_and_failures_lower_the_score() {
+        let reputation = PeerReputation::new(1000);
+        let agent = fixt!(KitsuneAgent);
+        reputation.record_timeout(&agent);
+        reputation.record_failure(&agent);
+        assert_eq!(TIMEOUT_DELTA + FAILURE_DELTA, reputation.score(&agent));
+        assert!(!reputation.is_low_score(&agent));
+        reputation.record_invalid_op(&agent);
+        assert!(reputation.is_low_score(&agent));
+        assert!(!reputation.is_banned(&agent));
+    }
+
+    #[test]
+    fn busy_replies_never_ban() {
+        let reputation = PeerReputation::new(1000 * 60);
+        let agent = fixt!(KitsuneAgent);
+        let busy: KitsuneP2pResult<()> =
+            Err(KitsuneP2pError::RemoteBusy(Arc::new(fixt!(KitsuneSpace))));
+        for _ in 0..100 {
+            reputation.record_result(&agent, &busy);
+        }
+        assert!(!reputation.is_banned(&agent));
+        assert_eq!(0, reputation.score(&agent));
+    }
+
+    #[test]
+    fn invalid_op_reports_never_ban() {
+        let reputation = PeerReputation::new(1000 * 60);
+        let agent = fixt!(KitsuneAgent);
+        for _ in 0..10 {
+            reputation.record_invalid_op(&agent);
+        }
+        assert!(!reputation.is_banned(&agent));
+        assert_eq!(INVALID_OP_FLOOR, reputation.score(&agent));
+        assert_eq!(10, reputation.dump()[0].invalid_ops);
+
+        // nor do they raise a score that is already lower
+        for _ in 0..10 {
+            reputation.record_timeout(&agent);
+        }
+        let score = reputation.score(&agent);
+        reputation.record_invalid_op(&agent);
+        assert_eq!(score, reputation.score(&agent));
+    }
+
+    #[test]
+    fn low_scores_are_banned_temporarily() {
+        let agent = fixt!(KitsuneAgent);
+
+        let reputation = PeerReputation::new(1000 * 60);
+        for _ in 0..20 {
+            reputation.record_timeout(&agent);
+        }
+        assert!(reputation.is_banned(&agent));
+        let dump = reputation.dump();
+        assert_eq!(AUTO_BAN_SCORE, dump[0].score);
+        assert_eq!(20, dump[0].timeouts);
+        assert_eq!(Some(false), dump[0].ban.as_ref().map(|ban| ban.by_admin));
+
+        let reputation = PeerReputation::new(0);
+        for _ in 0..20 {
+            reputation.record_failure(&agent);
+        }
+        // the ban has already expired, the agent is on probation
+        assert!(!reputation.is_banned(&agent));
+        assert_eq!(AUTO_BAN_SCORE / 2, reputation.score(&agent));
+    }
+
+    #[test]
+    fn admin_bans_last_until_unbanned() {
+        let reputation = PeerReputation::new(0);
+        let agent = fixt!(KitsuneAgent);
+        reputation.record_success(&agent);
+        reputation.ban(&agent, None);
+        assert!(reputation.is_banned(&agent));
+        assert_eq!(
+            Some(PeerBan {
+                until_ms: None,
+                by_admin: true,
+            }),
+            reputation.dump()[0].ban
+        );
+
+        reputation.unban(&agent);
+        assert!(!reputation.is_banned(&agent));
+        assert!(reputation.dump().is_empty());
+    }
+
+    #[test]
+    fn bans_are_per_space() {
+        let reputations = Reputations::default();
+        let space = Arc::new(fixt!(KitsuneSpace));
+        let other_space = fixt!(KitsuneSpace);
+        let agent = fixt!(KitsuneAgent);
+        reputations.get(&space, 0).ban(&agent, None);
+        assert!(reputations.is_banned(&space, &agent));
+        assert!(!reputations.is_banned(&other_space, &agent));
+    }
+
+    #[test]
+    fn bans_cover_the_agents_transport_identities() {
+        let reputation = PeerReputation::new(0);
+        let agent = fixt!(KitsuneAgent);
+        let proxy_url = url2::url2!("kitsune-proxy://CeRtDiGeSt/kitsune-quic/h/1.2.3.4/p/5/--");
+        let identity = transport_identity(&proxy_url);
+        assert_eq!("CeRtDiGeSt", identity);
+
+        reputation.bind_identity(&agent, identity.clone());
+        assert!(reputation.is_bound(&agent, &identity));
+        assert!(!reputation.is_identity_banned(&identity));
+        reputation.ban(&agent, None);
+        assert!(reputation.is_identity_banned(&identity));
+        assert!(
+            !reputation.is_identity_banned(&transport_identity(&url2::url2!(
+                "kitsune-quic://1.2.3.4:5"
+            )))
+        );
+
+        reputation.unban(&agent);
+        assert!(!reputation.is_identity_banned(&identity));
+    }
+
+    #[test]
+    fn tcp_identity_is_the_certificate_not_the_port() {
+        let identity = transport_identity(&url2::url2!("kitsune-tcp://1.2.3.4:5?cert=CeRtDiGeSt"));
+        assert_eq!("CeRtDiGeSt", identity);
+        assert_eq!(
+            identity,
+            transport_identity(&url2::url2!("kitsune-tcp://1.2.3.4:6?cert=CeRtDiGeSt"))
+        );
+    }
+}
@@ -0,0 +1,547 @@
+//! Fair scheduling of the events we emit to our implementor.
+//!
+//! Every joined space gets its own queue in each [`Lane`], which holds at
+//! most `event_queue_len_per_space` events our implementor hasn't answered
+//! yet. Whenever our implementor can take another event, the next one is
+//! picked from the [`Lane::Interactive`] queues before the [`Lane::Bulk`]
+//! ones, going round-robin over the spaces within a lane. So that bulk work
+//! is never starved outright, at most `event_interactive_burst` interactive
+//! events are emitted in a row while bulk events are waiting. The queues of
+//! a space go away when its last local agent leaves.
+//!
+//! Incoming requests are each handled in their own task, and an
+//! [`InboundLimit`] refuses requests for a space that already has
+//! `event_queue_len_per_space` of them in flight, so a space whose
+//! requests are slow to answer doesn't hold up requests for the others.
+
+use super::*;
+use futures::channel::mpsc;
+use ghost_actor::dependencies::must_future::MustBoxFuture;
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use tokio::sync::Semaphore;
+
+/// The priority lanes of the [`EventScheduler`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Lane {
+    /// Calls, notifies, agent info and signing: someone is waiting on these.
+    Interactive,
+    /// Gossip and op fetches.
+    Bulk,
+}
+
+type Register<T> = (Lane, mpsc::Receiver<T>);
+
+/// Hands out room in the per space, per lane queues feeding our implementor.
+#[derive(Clone)]
+pub(crate) struct EventScheduler<T = KitsuneP2pEvent>(Arc<std::sync::Mutex<Inner<T>>>);
+
+struct Inner<T> {
+    queue_len: usize,
+    spaces: HashMap<Arc<KitsuneSpace>, SpaceQueues<T>>,
+    register: mpsc::UnboundedSender<Register<T>>,
+}
+
+/// The queues of a space with local agents.
+struct SpaceQueues<T> {
+    agents: HashSet<Arc<KitsuneAgent>>,
+    interactive: Queue<T>,
+    bulk: Queue<T>,
+}
+
+/// The sender of a queue, and the room left in it.
+struct Queue<T> {
+    sender: mpsc::Sender<T>,
+    room: Arc<Semaphore>,
+}
+
+impl<T> Inner<T> {
+    fn queue(&mut self, lane: Lane) -> Queue<T> {
+        let (sender, recv) = mpsc::channel(self.queue_len);
+        // if the schedule is gone, so is our implementor,
+        // and sending will report that soon enough
+        let _ = self.register.unbounded_send((lane, recv));
+        Queue {
+            sender,
+            room: Arc::new(Semaphore::new(self.queue_len)),
+        }
+    }
+}
+
+impl<T: 'static + Send> EventScheduler<T> {
+    fn new(queue_len: usize, interactive_burst: u32) -> (Self, Schedule<T>) {
+        let (register, registrations) = mpsc::unbounded();
+        let scheduler = Self(Arc::new(std::sync::Mutex::new(Inner {
+            queue_len,
+            spaces: HashMap::new(),
+            register,
+        })));
+        let schedule = Schedule {
+            registrations: Some(registrations),
+            interactive: LaneQueues::default(),
+            bulk: LaneQueues::default(),
+            interactive_burst,
+            burst: 0,
+        };
+        (scheduler, schedule)
+    }
+
+    /// Spawn a task emitting the scheduled events into `evt_sender`.
+    pub(crate) fn spawn(
+        evt_sender: mpsc::Sender<T>,
+        tuning_params: &KitsuneP2pTuningParams,
+    ) -> KitsuneP2pResult<Self> {
+        if tuning_params.event_queue_len_per_space == 0
+            || tuning_params.event_interactive_burst == 0
+        {
+            return Err(
+                "event_queue_len_per_space and event_interactive_burst must not be 0".into(),
+            );
+        }
+        let (scheduler, schedule) = Self::new(
+            tuning_params.event_queue_len_per_space as usize,
+            tuning_params.event_interactive_burst,
+        );
+        tokio::task::spawn(async move {
+            // this only fails once our implementor has hung up
+            let _ = schedule
+                .map(Ok::<_, mpsc::SendError>)
+                .forward(evt_sender)
+                .await;
+        });
+        Ok(scheduler)
+    }
+
+    /// `agent` joined `space`, create the space's queues if it is the first.
+    pub(crate) fn join(&self, space: &Arc<KitsuneSpace>, agent: &Arc<KitsuneAgent>) {
+        let mut inner = self.0.lock().unwrap();
+        if !inner.spaces.contains_key(space) {
+            let queues = SpaceQueues {
+                agents: HashSet::new(),
+                interactive: inner.queue(Lane::Interactive),
+                bulk: inner.queue(Lane::Bulk),
+            };
+            inner.spaces.insert(space.clone(), queues);
+        }
+        inner
+            .spaces
+            .get_mut(space)
+            .unwrap()
+            .agents
+            .insert(agent.clone());
+    }
+
+    /// `agent` left `space`, drop the space's queues if it was the last.
+    /// Events already queued are still emitted.
+    pub(crate) fn leave(&self, space: &Arc<KitsuneSpace>, agent: &Arc<KitsuneAgent>) {
+        let mut inner = self.0.lock().unwrap();
+        if let Some(queues) = inner.spaces.get_mut(space) {
+            queues.agents.remove(agent);
+            if queues.agents.is_empty() {
+                inner.spaces.remove(space);
+            }
+        }
+    }
+
+    /// Queue the event `f` sends about `space` in `lane`, once the space's
+    /// queue has room for it. The room is only given back when `f`'s future
+    /// resolves, that is when our implementor has answered.
+    pub(crate) fn send<R, F, Fut>(
+        &self,
+        space: Arc<KitsuneSpace>,
+        lane: Lane,
+        f: F,
+    ) -> MustBoxFuture<'static, KitsuneP2pResult<R>>
+    where
+        R: 'static + Send,
+        F: 'static + Send + FnOnce(mpsc::Sender<T>) -> Fut,
+        Fut: 'static + Send + std::future::Future<Output = KitsuneP2pResult<R>>,
+    {
+        let queue = self.0.lock().unwrap().spaces.get(&space).map(|queues| {
+            let queue = match lane {
+                Lane::Interactive => &queues.interactive,
+                Lane::Bulk => &queues.bulk,
+            };
+            (queue.sender.clone(), queue.room.clone())
+        });
+        async move {
+            let (sender, room) = match queue {
+                Some(queue) => queue,
+                None => return Err(KitsuneP2pError::RoutingSpaceError(space)),
+            };
+            let _room = room.acquire().await;
+            f(sender).await
+        }
+        .boxed()
+        .into()
+    }
+}
+
+/// The most incoming requests handled at once, across all spaces.
+pub(crate) const MAX_INBOUND_REQUESTS: usize = 1024;
+
+/// Counts the incoming requests in flight, in total and per space.
+#[derive(Clone)]
+pub(crate) struct InboundLimit(Arc<std::sync::Mutex<InboundCounts>>);
+
+struct InboundCounts {
+    max: usize,
+    max_per_space: usize,
+    total: usize,
+    spaces: HashMap<Arc<KitsuneSpace>, usize>,
+}
+
+impl InboundLimit {
+    pub(crate) fn new(max: usize, max_per_space: usize) -> Self {
+        Self(Arc::new(std::sync::Mutex::new(InboundCounts {
+            max,
+            max_per_space,
+            total: 0,
+            spaces: HashMap::new(),
+        })))
+    }
+
+    /// Start handling a request, unless too many are in flight already.
+    pub(crate) fn try_start(&self) -> Option<InboundSlot> {
+        let mut counts = self.0.lock().unwrap();
+        if counts.total >= counts.max {
+            return None;
+        }
+        counts.total += 1;
+        Some(InboundSlot {
+            limit: self.clone(),
+            space: None,
+        })
+    }
+}
+
+/// A request in flight, until dropped.
+pub(crate) struct InboundSlot {
+    limit: InboundLimit,
+    space: Option<Arc<KitsuneSpace>>,
+}
+
+impl InboundSlot {
+    /// Count the request against `space`, unless that space already has
+    /// its share in flight.
+    pub(crate) fn try_enter_space(&mut self, space: &Arc<KitsuneSpace>) -> bool {
+        let mut counts = self.limit.0.lock().unwrap();
+        let max_per_space = counts.max_per_space;
+        let count = counts.spaces.entry(space.clone()).or_insert(0);
+        if *count >= max_per_space {
+            return false;
+        }
+        *count += 1;
+        self.space = Some(space.clone());
+        true
+    }
+}
+
+impl Drop for InboundSlot {
+    fn drop(&mut self) {
+        let mut counts = self.limit.0.lock().unwrap();
+        counts.total -= 1;
+        if let Some(space) = self.space.take() {
+            if let Some(count) = counts.spaces.get_mut(&space) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.spaces.remove(&space);
+                }
+            }
+        }
+    }
+}
+
+/// The queues of every space in a lane.
+struct LaneQueues<T> {
+    queues: Vec<mpsc::Receiver<T>>,
+    next: usize,
+}
+
+impl<T> Default for LaneQueues<T> {
+    fn default() -> Self {
+        Self {
+            queues: Vec::new(),
+            next: 0,
+        }
+    }
+}
+
+impl<T> LaneQueues<T> {
+    /// Take the next event, starting with the space after the last served.
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Option<T> {
+        let mut polled = 0;
+        while polled < self.queues.len() {
+            let idx = (self.next + polled) % self.queues.len();
+            match self.queues[idx].poll_next_unpin(cx) {
+                Poll::Ready(Some(evt)) => {
+                    self.next = idx + 1;
+                    return Some(evt);
+                }
+                Poll::Ready(None) => {
+                    self.queues.remove(idx);
+                }
+                Poll::Pending => polled += 1,
+            }
+        }
+        None
+    }
+}
+
+/// The stream of scheduled events.
+struct Schedule<T> {
+    registrations: Option<mpsc::UnboundedReceiver<Register<T>>>,
+    interactive: LaneQueues<T>,
+    bulk: LaneQueues<T>,
+    interactive_burst: u32,
+    burst: u32,
+}
+
+impl<T> futures::stream::Stream for Schedule<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = &mut *self;
+
+        while let Some(registrations) = &mut this.registrations {
+            match registrations.poll_next_unpin(cx) {
+                Poll::Ready(Some((Lane::Interactive, recv))) => this.interactive.queues.push(recv),
+                Poll::Ready(Some((Lane::Bulk, recv))) => this.bulk.queues.push(recv),
+                Poll::Ready(None) => this.registrations = None,
+                Poll::Pending => break,
+            }
+        }
+
+        if this.burst < this.interactive_burst {
+            if let Some(evt) = this.interactive.poll_next(cx) {
+                this.burst += 1;
+                return Poll::Ready(Some(evt));
+            }
+        }
+        if let Some(evt) = this.bulk.poll_next(cx) {
+            this.burst = 0;
+            return Poll::Ready(Some(evt));
+        }
+        // nothing bulk is waiting, so there is nothing to make way for
+        if let Some(evt) = this.interactive.poll_next(cx) {
+            return Poll::Ready(Some(evt));
+        }
+
+        if this.registrations.is_none()
+            && this.interactive.queues.is_empty()
+            && this.bulk.queues.is_empty()
+        {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+    use futures::future::BoxFuture;
+    use futures::sink::SinkExt;
+
+    fn next(schedule: &mut Schedule<&'static str>) -> Option<&'static str> {
+        schedule.next().now_or_never().flatten()
+    }
+
+    fn joined(scheduler: &EventScheduler<&'static str>) -> Arc<KitsuneSpace> {
+        let space = Arc::new(fixt!(KitsuneSpace));
+        scheduler.join(&space, &Arc::new(fixt!(KitsuneAgent)));
+        space
+    }
+
+    /// Queue `evt`, which is never answered so keeps its room.
+    fn unanswered(
+        scheduler: &EventScheduler<&'static str>,
+        space: &Arc<KitsuneSpace>,
+        lane: Lane,
+        evt: &'static str,
+    ) -> BoxFuture<'static, KitsuneP2pResult<()>> {
+        scheduler
+            .send(space.clone(), lane, move |mut sender| async move {
+                sender.send(evt).await.map_err(KitsuneP2pError::other)?;
+                futures::future::pending::<KitsuneP2pResult<()>>().await
+            })
+            .boxed()
+    }
+
+    fn poll_all(futs: &mut [BoxFuture<'static, KitsuneP2pResult<()>>]) {
+        let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+        for fut in futs {
+            assert!(fut.poll_unpin(&mut cx).is_pending());
+        }
+    }
+
+    fn queue(
+        scheduler: &EventScheduler<&'static str>,
+        space: &Arc<KitsuneSpace>,
+        lane: Lane,
+        evt: &'static str,
+    ) {
+        scheduler
+            .send(space.clone(), lane, move |mut sender| async move {
+                sender.send(evt).await.map_err(KitsuneP2pError::other)
+            })
+            .now_or_never()
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn interactive_events_go_first() {
+        let (scheduler, mut schedule) = EventScheduler::new(10, 8);
+        let space = joined(&scheduler);
+        queue(&scheduler, &space, Lane::Bulk, "gossip");
+        queue(&scheduler, &space, Lane::Interactive, "call");
+
+        assert_eq!(Some("call"), next(&mut schedule));
+        assert_eq!(Some("gossip"), next(&mut schedule));
+        assert_eq!(None, next(&mut schedule));
+    }
+
+    #[test]
+    fn spaces_take_turns() {
+        let (scheduler, mut schedule) = EventScheduler::new(10, 8);
+        let busy_space = joined(&scheduler);
+        let quiet_space = joined(&scheduler);
+        for _ in 0..3 {
+            queue(&scheduler, &busy_space, Lane::Bulk, "busy");
+        }
+        queue(&scheduler, &quiet_space, Lane::Bulk, "quiet");
+
+        assert_eq!(Some("busy"), next(&mut schedule));
+        assert_eq!(Some("quiet"), next(&mut schedule));
+        assert_eq!(Some("busy"), next(&mut schedule));
+        assert_eq!(Some("busy"), next(&mut schedule));
+    }
+
+    #[test]
+    fn queues_are_bounded_per_space() {
+        let (scheduler, mut schedule) = EventScheduler::new(2, 8);
+        let busy_space = joined(&scheduler);
+        let quiet_space = joined(&scheduler);
+        let mut busy = (0..3)
+            .map(|_| unanswered(&scheduler, &busy_space, Lane::Bulk, "busy"))
+            .collect::<Vec<_>>();
+        poll_all(&mut busy);
+        queue(&scheduler, &quiet_space, Lane::Bulk, "quiet");
+
+        // the third busy event waits for one of the first two to be answered
+        assert_eq!(Some("busy"), next(&mut schedule));
+        assert_eq!(Some("quiet"), next(&mut schedule));
+        assert_eq!(Some("busy"), next(&mut schedule));
+        assert_eq!(None, next(&mut schedule));
+
+        drop(busy.remove(0));
+        poll_all(&mut busy);
+        assert_eq!(Some("busy"), next(&mut schedule));
+    }
+
+    #[test]
+    fn queues_exist_while_agents_are_joined() {
+        let (scheduler, mut schedule) = EventScheduler::new(10, 8);
+        let space = Arc::new(fixt!(KitsuneSpace));
+        let send = |evt| {
+            scheduler
+                .send(
+                    space.clone(),
+                    Lane::Interactive,
+                    move |mut sender| async move {
+                        sender.send(evt).await.map_err(KitsuneP2pError::other)
+                    },
+                )
+                .now_or_never()
+                .unwrap()
+        };
+        assert!(matches!(
+            send("early"),
+            Err(KitsuneP2pError::RoutingSpaceError(_))
+        ));
+
+        let one = Arc::new(fixt!(KitsuneAgent));
+        let two = Arc::new(fixt!(KitsuneAgent));
+        scheduler.join(&space, &one);
+        scheduler.join(&space, &two);
+        scheduler.leave(&space, &one);
+        send("call").unwrap();
+
+        scheduler.leave(&space, &two);
+        assert!(matches!(
+            send("late"),
+            Err(KitsuneP2pError::RoutingSpaceError(_))
+        ));
+        assert!(scheduler.0.lock().unwrap().spaces.is_empty());
+        // what was queued is still emitted, then the queues are gone
+        assert_eq!(Some("call"), next(&mut schedule));
+        assert_eq!(None, next(&mut schedule));
+        assert!(schedule.interactive.queues.is_empty());
+        assert!(schedule.bulk.queues.is_empty());
+    }
+
+    #[test]
+    fn bulk_events_are_not_starved() {
+        let (scheduler, mut schedule) = EventScheduler::new(10, 2);
+        let space = joined(&scheduler);
+        queue(&scheduler, &space, Lane::Bulk, "gossip");
+        for _ in 0..4 {
+            queue(&scheduler, &space, Lane::Interactive, "call");
+        }
+
+        assert_eq!(Some("call"), next(&mut schedule));
+        assert_eq!(Some("call"), next(&mut schedule));
+        assert_eq!(Some("gossip"), next(&mut schedule));
+        assert_eq!(Some("call"), next(&mut schedule));
+        assert_eq!(Some("call"), next(&mut schedule));
+    }
+
+    #[test]
+    fn inbound_requests_are_limited_per_space() {
+        let limit = InboundLimit::new(3, 1);
+        let busy_space = Arc::new(fixt!(KitsuneSpace));
+        let quiet_space = Arc::new(fixt!(KitsuneSpace));
+
+        let mut busy = limit.try_start().unwrap();
+        assert!(busy.try_enter_space(&busy_space));
+        let mut refused = limit.try_start().unwrap();
+        assert!(!refused.try_enter_space(&busy_space));
+        let mut quiet = limit.try_start().unwrap();
+        assert!(quiet.try_enter_space(&quiet_space));
+        // every slot is taken
+        assert!(limit.try_start().is_none());
+
+        drop(refused);
+        drop(busy);
+        let mut next = limit.try_start().unwrap();
+        assert!(next.try_enter_space(&busy_space));
+    }
+
+    #[test]
+    fn zero_tuning_params_are_rejected() {
+        let (evt_sender, _) = mpsc::channel::<&'static str>(1);
+        let mut tuning_params = KitsuneP2pTuningParams::default();
+        tuning_params.event_interactive_burst = 0;
+        assert!(EventScheduler::spawn(evt_sender.clone(), &tuning_params).is_err());
+        let mut tuning_params = KitsuneP2pTuningParams::default();
+        tuning_params.event_queue_len_per_space = 0;
+        assert!(EventScheduler::spawn(evt_sender, &tuning_params).is_err());
+    }
+
+    #[test]
+    fn ends_when_the_scheduler_is_dropped() {
+        let (scheduler, mut schedule) = EventScheduler::new(10, 8);
+        let space = joined(&scheduler);
+        queue(&scheduler, &space, Lane::Interactive, "call");
+        drop(scheduler);
+
+        assert_eq!(Some("call"), next(&mut schedule));
+        assert_eq!(Some(None), schedule.next().now_or_never());
+    }
+}
@@ -11,6 +11,10 @@ use std::convert::TryFrom;
 /// (david.b) this is not currently used
 const DEFAULT_RPC_MULTI_RACE_TIMEOUT_MS: u64 = 200;
 
+/// How often we check whether our urls have changed,
+/// e.g. because we failed over to another proxy.
+const BOUND_URL_CHECK_INTERVAL_MS: u64 = 5000;
+
 ghost_actor::ghost_chan! {
     pub(crate) chan SpaceInternal<crate::KitsuneP2pError> {
         /// List online agents that claim to be covering a basis hash
@@ -27,6 +31,7 @@ ghost_actor::ghost_chan! {
 pub(crate) async fn spawn_space(
     space: Arc<KitsuneSpace>,
     transport: ghost_actor::GhostSender<TransportListener>,
+    reputation: super::reputation::PeerReputation,
     config: Arc<KitsuneP2pConfig>,
 ) -> KitsuneP2pResult<(
     ghost_actor::GhostSender<KitsuneP2p>,
@@ -53,7 +58,9 @@ pub(crate) async fn spawn_space(
         .create_channel::<KitsuneP2p>()
         .await?;
 
-    tokio::task::spawn(builder.spawn(Space::new(space, i_s, evt_send, transport, config)));
+    tokio::task::spawn(builder.spawn(Space::new(
+        space, i_s, evt_send, transport, reputation, config,
+    )));
 
     Ok((sender, evt_recv))
 }
@@ -71,6 +78,7 @@ impl gossip::GossipEventHandler for Space {
             .cloned()
             .collect::<HashSet<_>>();
         let agent = self.local_joined_agents.iter().next().cloned();
+        let reputation = self.reputation.clone();
         let fut = match agent {
             Some(agent) => self
                 .evt_sender
@@ -81,12 +89,14 @@ impl gossip::GossipEventHandler for Space {
             None => async { Ok(Vec::new()) }.boxed().into(),
         };
         Ok(async move {
-            let remote_agents = fut
+            let mut remote_agents = fut
                 .await?
                 .into_iter()
                 .map(|ai| Arc::new(ai.into_agent()))
-                .filter(|a| !local_agents.contains(a))
+                .filter(|a| !local_agents.contains(a) && !reputation.is_banned(a))
                 .collect::<Vec<_>>();
+            // gossip with the best behaved agents first
+            remote_agents.sort_by_cached_key(|a| std::cmp::Reverse(reputation.score(a)));
             let local_agents = local_agents.into_iter().collect::<Vec<_>>();
             Ok((local_agents, remote_agents))
         }
@@ -117,7 +127,9 @@ impl gossip::GossipEventHandler for Space {
             let transport_tx = self.transport.clone();
             let evt_sender = self.evt_sender.clone();
             let space = self.space.clone();
-            Ok(async move {
+            let reputation = self.reputation.clone();
+            let peer = to_agent.clone();
+            let request = async move {
                 // see if we have an entry for this agent in our agent_store
                 let info = match evt_sender
                     .get_agent_info_signed(GetAgentInfoSignedEvt {
@@ -144,19 +156,21 @@ impl gossip::GossipEventHandler for Space {
                 let (_, mut write, read) = transport_tx.create_channel(url).await?;
                 KitsuneMetrics::count(KitsuneMetrics::FetchOpHashes, data.len());
                 write.write_and_close(data.to_vec()).await?;
-                let read = read.read_to_end().await;
+                let read = read.read_to_end().await?;
                 let (_, read) = wire::Wire::decode_ref(&read)?;
                 match read {
                     wire::Wire::Failure(wire::Failure { reason }) => Err(reason.into()),
+                    wire::Wire::Busy(wire::Busy { space }) => {
+                        Err(KitsuneP2pError::RemoteBusy(space))
+                    }
                     wire::Wire::FetchOpHashesResponse(wire::FetchOpHashesResponse {
                         hashes,
                         peer_hashes,
                     }) => Ok((hashes, peer_hashes)),
                     _ => unreachable!(),
                 }
-            }
-            .boxed()
-            .into())
+            };
+            Ok(reputation.track(peer, request).boxed().into())
         }
     }
 
@@ -177,7 +191,9 @@ impl gossip::GossipEventHandler for Space {
             let transport_tx = self.transport.clone();
             let evt_sender = self.evt_sender.clone();
             let space = self.space.clone();
-            Ok(async move {
+            let reputation = self.reputation.clone();
+            let peer = to_agent.clone();
+            let request = async move {
                 // see if we have an entry for this agent in our agent_store
                 let info = match evt_sender
                     .get_agent_info_signed(GetAgentInfoSignedEvt {
@@ -197,10 +213,13 @@ impl gossip::GossipEventHandler for Space {
                 let (_, mut write, read) = transport_tx.create_channel(url).await?;
                 KitsuneMetrics::count(KitsuneMetrics::FetchOpData, data.len());
                 write.write_and_close(data.to_vec()).await?;
-                let read = read.read_to_end().await;
+                let read = read.read_to_end().await?;
                 let (_, read) = wire::Wire::decode_ref(&read)?;
                 match read {
                     wire::Wire::Failure(wire::Failure { reason }) => Err(reason.into()),
+                    wire::Wire::Busy(wire::Busy { space }) => {
+                        Err(KitsuneP2pError::RemoteBusy(space))
+                    }
                     wire::Wire::FetchOpDataResponse(wire::FetchOpDataResponse {
                         op_data,
                         agent_infos,
@@ -210,9 +229,8 @@ impl gossip::GossipEventHandler for Space {
                     )),
                     _ => unreachable!(),
                 }
-            }
-            .boxed()
-            .into())
+            };
+            Ok(reputation.track(peer, request).boxed().into())
         }
     }
 
@@ -230,7 +248,9 @@ impl gossip::GossipEventHandler for Space {
             let transport_tx = self.transport.clone();
             let evt_sender = self.evt_sender.clone();
             let space = self.space.clone();
-            Ok(async move {
+            let reputation = self.reputation.clone();
+            let peer = to_agent.clone();
+            let request = async move {
                 // see if we have an entry for this agent in our agent_store
                 let info = match evt_sender
                     .get_agent_info_signed(GetAgentInfoSignedEvt {
@@ -255,17 +275,22 @@ impl gossip::GossipEventHandler for Space {
                 let (_, mut write, read) = transport_tx.create_channel(url.clone()).await?;
                 KitsuneMetrics::count(KitsuneMetrics::Gossip, data.len());
                 write.write_and_close(data.to_vec()).await?;
-                let read = read.read_to_end().await;
+                let read = read.read_to_end().await?;
                 let (_, read) = wire::Wire::decode_ref(&read)?;
                 match read {
                     wire::Wire::Failure(wire::Failure { reason }) => Err(dbg!(reason.into())),
+                    wire::Wire::Busy(wire::Busy { space }) => {
+                        Err(KitsuneP2pError::RemoteBusy(space))
+                    }
                     wire::Wire::GossipResp(_) => Ok(()),
                     _ => unreachable!(),
                 }
-            }
-            .instrument(tracing::debug_span!("handle_gossip_ops"))
-            .boxed()
-            .into())
+            };
+            Ok(reputation
+                .track(peer, request)
+                .instrument(tracing::debug_span!("handle_gossip_ops"))
+                .boxed()
+                .into())
         }
     }
 }
@@ -369,11 +394,21 @@ pub fn local_gossip_ops(
     let all_agents = agents
         .into_iter()
         .map(|agent_info_signed| {
-            evt_sender.put_agent_info_signed(PutAgentInfoSignedEvt {
-                space: space.clone(),
-                agent: to_agent.clone(),
-                agent_info_signed,
-            })
+            let evt_sender = evt_sender.clone();
+            let space = space.clone();
+            let to_agent = to_agent.clone();
+            async move {
+                // peers can gossip anyone's info, so only the agent's own signature counts
+                agent_info_signed.verify(Some(&*space)).await?;
+                evt_sender
+                    .put_agent_info_signed(PutAgentInfoSignedEvt {
+                        space,
+                        agent: to_agent,
+                        agent_info_signed,
+                    })
+                    .await?;
+                KitsuneP2pResult::Ok(())
+            }
         })
         .collect::<Vec<_>>();
     async move {
@@ -404,10 +439,9 @@ impl SpaceInternalHandler for Space {
         &mut self,
         _space: Arc<KitsuneSpace>,
         from_agent: Arc<KitsuneAgent>,
-        // during short-circuit / full-sync mode,
-        // we're ignoring the basis_hash and just returning everyone.
-        _basis: Arc<KitsuneBasis>,
+        basis: Arc<KitsuneBasis>,
     ) -> SpaceInternalHandlerResult<HashSet<Arc<KitsuneAgent>>> {
+        // our own agents are always online
         let mut res: HashSet<Arc<KitsuneAgent>> =
             self.local_joined_agents.iter().cloned().collect();
         let all_peers_fut = self
@@ -416,9 +450,13 @@ impl SpaceInternalHandler for Space {
                 space: self.space.clone(),
                 agent: from_agent,
             });
+        let basis_loc = basis.get_loc();
         Ok(async move {
             for peer in all_peers_fut.await? {
-                res.insert(Arc::new(peer.as_agent_ref().clone()));
+                let info = crate::types::agent_store::AgentInfo::try_from(&peer)?;
+                if info.storage_arc().contains(basis_loc) {
+                    res.insert(Arc::new(peer.as_agent_ref().clone()));
+                }
             }
             Ok(res)
         }
@@ -432,6 +470,7 @@ impl SpaceInternalHandler for Space {
         let bound_url = self.transport.bound_url();
         let evt_sender = self.evt_sender.clone();
         let bootstrap_service = self.config.bootstrap_service.clone();
+        let lan_discovery = self.lan_discovery.clone();
         let expires_after = self.config.tuning_params.agent_info_expires_after_ms as u64;
         Ok(async move {
             let bound_url = bound_url.await?;
@@ -444,6 +483,8 @@ impl SpaceInternalHandler for Space {
                     (*space).clone(),
                     (*agent).clone(),
                     urls.clone(),
+                    // while full-sync every agent holds the whole dht
+                    crate::dht_arc::DhtArc::new(agent.get_loc(), crate::dht_arc::MAX_HALF_LENGTH),
                     crate::spawn::actor::bootstrap::now_once(None).await?,
                     expires_after,
                 );
@@ -464,11 +505,18 @@ impl SpaceInternalHandler for Space {
                 evt_sender
                     .put_agent_info_signed(PutAgentInfoSignedEvt {
                         space: space.clone(),
-                        agent,
+                        agent: agent.clone(),
                         agent_info_signed: agent_info_signed.clone(),
                     })
                     .await?;
 
+                // Announce on the local network.
+                if let Some(lan_discovery) = &lan_discovery {
+                    lan_discovery
+                        .announce(agent, agent_info_signed.clone())
+                        .await;
+                }
+
                 // Push to the bootstrap as well.
                 crate::spawn::actor::bootstrap::put(bootstrap_service.clone(), agent_info_signed)
                     .await?;
@@ -499,6 +547,46 @@ impl KitsuneP2pHandler for Space {
         )
     }
 
+    fn handle_report_invalid_op(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        _agent: Arc<KitsuneAgent>,
+    ) -> KitsuneP2pHandlerResult<()> {
+        unreachable!(
+            "These requests are handled at the to actor level and are never propagated down to the space."
+        )
+    }
+
+    fn handle_ban_agent(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        _agent: Arc<KitsuneAgent>,
+        _duration_ms: Option<u64>,
+    ) -> KitsuneP2pHandlerResult<()> {
+        unreachable!(
+            "These requests are handled at the to actor level and are never propagated down to the space."
+        )
+    }
+
+    fn handle_unban_agent(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        _agent: Arc<KitsuneAgent>,
+    ) -> KitsuneP2pHandlerResult<()> {
+        unreachable!(
+            "These requests are handled at the to actor level and are never propagated down to the space."
+        )
+    }
+
+    fn handle_peer_scores(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<PeerScore>> {
+        unreachable!(
+            "These requests are handled at the to actor level and are never propagated down to the space."
+        )
+    }
+
     fn handle_join(
         &mut self,
         space: Arc<KitsuneSpace>,
@@ -544,6 +632,9 @@ impl KitsuneP2pHandler for Space {
         agent: Arc<KitsuneAgent>,
     ) -> KitsuneP2pHandlerResult<()> {
         self.local_joined_agents.remove(&agent);
+        if let Some(lan_discovery) = &self.lan_discovery {
+            lan_discovery.retract(&agent);
+        }
         Ok(async move { Ok(()) }.boxed().into())
     }
 
@@ -564,6 +655,7 @@ impl KitsuneP2pHandler for Space {
 
         let discover_fut =
             discover::peer_discover(self, to_agent.clone(), from_agent.clone(), timeout_ms);
+        let reputation = self.reputation.clone();
 
         Ok(async move {
             match discover_fut.await {
@@ -582,13 +674,35 @@ impl KitsuneP2pHandler for Space {
                     )
                     .encode_vec()?;
                     KitsuneMetrics::count(KitsuneMetrics::Call, payload.len());
-                    write.write_and_close(payload).await?;
-                    let res = read.read_to_end().await;
-                    let (_, res) = wire::Wire::decode_ref(&res)?;
-                    match res {
-                        wire::Wire::Failure(wire::Failure { reason }) => Err(reason.into()),
-                        wire::Wire::CallResp(wire::CallResp { data }) => Ok(data.into()),
-                        r => Err(format!("invalid response: {:?}", r).into()),
+                    let request = async move {
+                        write.write_and_close(payload).await?;
+                        let res = read.read_to_end().await?;
+                        let (_, res) = wire::Wire::decode_ref(&res)?;
+                        match res {
+                            wire::Wire::Failure(wire::Failure { reason }) => Err(reason.into()),
+                            wire::Wire::Busy(wire::Busy { space }) => {
+                                Err(KitsuneP2pError::RemoteBusy(space))
+                            }
+                            wire::Wire::CallResp(wire::CallResp { data }) => {
+                                KitsuneP2pResult::Ok(data.into())
+                            }
+                            r => Err(format!("invalid response: {:?}", r).into()),
+                        }
+                    };
+                    match tokio::time::timeout(
+                        std::time::Duration::from_millis(timeout_ms),
+                        request,
+                    )
+                    .await
+                    {
+                        Ok(res) => {
+                            reputation.record_result(&to_agent, &res);
+                            res
+                        }
+                        Err(_) => {
+                            reputation.record_timeout(&to_agent);
+                            Err("timeout".into())
+                        }
                     }
                 }
                 discover::PeerDiscoverResult::Err(e) => Err(e),
@@ -688,6 +802,8 @@ pub(crate) struct Space {
     pub(crate) transport: ghost_actor::GhostSender<TransportListener>,
     pub(crate) local_joined_agents: HashSet<Arc<KitsuneAgent>>,
     pub(crate) config: Arc<KitsuneP2pConfig>,
+    pub(crate) lan_discovery: Option<super::lan_discovery::LanDiscovery>,
+    pub(crate) reputation: super::reputation::PeerReputation,
 }
 
 impl Space {
@@ -697,6 +813,7 @@ impl Space {
         i_s: ghost_actor::GhostSender<SpaceInternal>,
         evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
         transport: ghost_actor::GhostSender<TransportListener>,
+        reputation: super::reputation::PeerReputation,
         config: Arc<KitsuneP2pConfig>,
     ) -> Self {
         let i_s_c = i_s.clone();
@@ -709,6 +826,54 @@ impl Space {
             }
         });
 
+        // peers can only reach us at the urls in our agent info,
+        // so publish it again as soon as they change
+        let i_s_c = i_s.clone();
+        let transport_c = transport.clone();
+        tokio::task::spawn(async move {
+            use ghost_actor::GhostControlSender;
+            let mut published_url = None;
+            loop {
+                tokio::time::delay_for(std::time::Duration::from_millis(
+                    BOUND_URL_CHECK_INTERVAL_MS,
+                ))
+                .await;
+                let bound_url = match transport_c.bound_url().await {
+                    Ok(bound_url) => bound_url,
+                    Err(_) => break,
+                };
+                if published_url
+                    .as_ref()
+                    .map_or(false, |url| url != &bound_url)
+                {
+                    tracing::info!(%bound_url, "our urls changed, publishing agent info");
+                    if let Err(e) = i_s_c.update_agent_info().await {
+                        if !i_s_c.ghost_actor_is_active() {
+                            break;
+                        }
+                        tracing::warn!(msg = "failed to publish agent info", ?e);
+                        // try again next time round
+                        continue;
+                    }
+                }
+                published_url = Some(bound_url);
+            }
+        });
+
+        let lan_discovery = config.lan_discovery.as_ref().and_then(|lan_config| {
+            match super::lan_discovery::LanDiscovery::spawn(
+                space.clone(),
+                lan_config,
+                evt_sender.clone(),
+            ) {
+                Ok(lan_discovery) => Some(lan_discovery),
+                Err(e) => {
+                    tracing::error!(msg = "Failed to start lan discovery", ?e);
+                    None
+                }
+            }
+        });
+
         Self {
             space,
             i_s,
@@ -716,6 +881,8 @@ impl Space {
             transport,
             local_joined_agents: HashSet::new(),
             config,
+            lan_discovery,
+            reputation,
         }
     }
 
@@ -5,6 +5,7 @@ mod tests {
     use crate::*;
     use ghost_actor::dependencies::tracing;
     use ghost_actor::GhostControlSender;
+    use std::convert::TryFrom;
     use std::sync::Arc;
 
     #[tokio::test(threaded_scheduler)]
@@ -71,6 +72,111 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_rpc_multi_walks_to_unknown_agents() -> Result<(), KitsuneP2pError> {
+        observability::test_run().ok();
+        let (harness, _evt) = spawn_test_harness_mem().await?;
+        // keep gossip from sharing peer info behind the walk's back
+        let mut tuning_params = KitsuneP2pTuningParams::default();
+        tuning_params.gossip_loop_iteration_delay_ms = 1000 * 60;
+        harness.set_tuning_params(tuning_params).await?;
+
+        let space = harness.add_space().await?;
+        let (a1, p2p1) = harness.add_direct_agent("one".into()).await?;
+        let (a2, _p2p2) = harness.add_direct_agent("two".into()).await?;
+        let (a3, _p2p3) = harness.add_direct_agent("tre".into()).await?;
+
+        // "one" only knows of "two", and only "two" knows of "tre",
+        // so "one" can only reach "tre" by asking "two" for agents
+        // covering the basis
+        let own_info = |agent: Arc<KitsuneAgent>| {
+            let harness = harness.clone();
+            async move {
+                harness
+                    .dump_local_peer_data(agent.clone())
+                    .await?
+                    .remove(&agent)
+                    .ok_or_else(|| KitsuneP2pError::from("no agent info"))
+            }
+        };
+        let info2 = own_info(a2.clone()).await?;
+        let info3 = own_info(a3.clone()).await?;
+        harness.inject_peer_info(a1.clone(), vec![info2]).await?;
+        harness.inject_peer_info(a2.clone(), vec![info3]).await?;
+
+        let res = p2p1
+            .rpc_multi(actor::RpcMulti {
+                space: space,
+                from_agent: a1.clone(),
+                basis: TestVal::test_val(),
+                remote_agent_count: Some(5),
+                timeout_ms: Some(2000),
+                as_race: false,
+                race_timeout_ms: None,
+                payload: b"test-walk".to_vec(),
+            })
+            .await
+            .unwrap();
+
+        assert!(res.iter().any(|r| r.agent == a3));
+        assert!(harness.dump_local_peer_data(a1).await?.contains_key(&a3));
+
+        harness.ghost_actor_shutdown().await?;
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_busy_space_does_not_block_other_spaces() -> Result<(), KitsuneP2pError> {
+        observability::test_run().ok();
+        let (harness, _evt) = spawn_test_harness_mem().await?;
+        let mut tuning_params = KitsuneP2pTuningParams::default();
+        tuning_params.event_queue_len_per_space = 2;
+        harness.set_tuning_params(tuning_params).await?;
+
+        let busy_space = harness.add_space().await?;
+        let quiet_space = harness.add_space().await?;
+        let (a1, p2p1) = harness.add_direct_agent("one".into()).await?;
+        let (a2, _p2p2) = harness.add_direct_agent("two".into()).await?;
+
+        // needed until we have some way of bootstrapping
+        harness.magic_peer_info_exchange().await?;
+
+        // more calls than "two" used to handle at once across all spaces,
+        // none of which it ever answers
+        let hung = futures::future::join_all((0..12).map(|_| {
+            p2p1.rpc_single(
+                busy_space.clone(),
+                a2.clone(),
+                a1.clone(),
+                b"hang".to_vec(),
+                Some(500),
+            )
+        }))
+        .await;
+        assert!(hung.iter().all(|res| res.is_err()));
+
+        let res = p2p1
+            .rpc_single(
+                quiet_space,
+                a2.clone(),
+                a1.clone(),
+                b"hello".to_vec(),
+                Some(2000),
+            )
+            .await?;
+        assert_eq!(b"echo: hello".to_vec(), res);
+
+        // while the busy space turns calls away
+        let err = p2p1
+            .rpc_single(busy_space, a2, a1, b"hello".to_vec(), Some(2000))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, KitsuneP2pError::RemoteBusy(_)), "{:?}", err);
+
+        harness.ghost_actor_shutdown().await?;
+        Ok(())
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn test_transport_notify_coms() -> Result<(), KitsuneP2pError> {
         observability::test_run().ok();
@@ -168,6 +274,28 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_tcp_transport_binding() -> Result<(), KitsuneP2pError> {
+        observability::test_run().ok();
+
+        let (harness, _evt) = spawn_test_harness_tcp().await?;
+        harness.add_space().await?;
+        let (_, p2p) = harness.add_direct_agent("DIRECT".into()).await?;
+
+        // The proxy tunnels through the tcp transport just like quic.
+        let bindings = p2p.list_transport_bindings().await?;
+        assert_eq!(1, bindings.len());
+        let binding = &bindings[0];
+        assert_eq!("kitsune-proxy", binding.scheme());
+        assert_eq!(
+            "kitsune-tcp",
+            binding.path_segments().unwrap().next().unwrap()
+        );
+
+        harness.ghost_actor_shutdown().await?;
+        Ok(())
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn test_request_workflow() -> Result<(), KitsuneP2pError> {
         observability::test_run().ok();
@@ -450,4 +578,71 @@ mod tests {
         harness.ghost_actor_shutdown().await?;
         Ok(())
     }
+
+    /// The url an agent advertises in its own agent info.
+    async fn advertised_url(
+        harness: &ghost_actor::GhostSender<HarnessControlApi>,
+        agent: &Arc<KitsuneAgent>,
+    ) -> Result<kitsune_p2p_proxy::ProxyUrl, KitsuneP2pError> {
+        let info = harness
+            .dump_local_peer_data(agent.clone())
+            .await?
+            .remove(agent)
+            .expect("agent has its own info");
+        let info = crate::types::agent_store::AgentInfo::try_from(&*info)?;
+        Ok(info.as_urls_ref()[0].clone().into())
+    }
+
+    /// Test that a nat agent fails over to another proxy when its current one
+    /// goes away, and republishes its agent info at the new location.
+    #[tokio::test(threaded_scheduler)]
+    async fn test_nat_agent_fails_over_to_next_proxy() -> Result<(), KitsuneP2pError> {
+        observability::test_run().ok();
+        let (harness, _evt) = spawn_test_harness_mem().await?;
+
+        harness.add_space().await?;
+        let (proxy1, proxy1_p2p) = harness.add_proxy_agent("proxy1".into()).await?;
+        let (proxy2, _) = harness.add_proxy_agent("proxy2".into()).await?;
+        let proxy1_url = advertised_url(&harness, &proxy1).await?;
+        let proxy2_url = advertised_url(&harness, &proxy2).await?;
+
+        let (nat, nat_p2p) = harness
+            .add_nat_agent(
+                "nat".into(),
+                vec![proxy1_url.clone().into(), proxy2_url.clone().into()],
+            )
+            .await?;
+
+        // - We start out hosted at the first proxy
+        let bound: kitsune_p2p_proxy::ProxyUrl =
+            nat_p2p.list_transport_bindings().await?[0].clone().into();
+        assert_eq!(proxy1_url.as_base(), bound.as_base());
+        let advertised = advertised_url(&harness, &nat).await?;
+        assert_eq!(proxy1_url.as_base(), advertised.as_base());
+
+        // - Kill that proxy
+        proxy1_p2p.ghost_actor_shutdown().await?;
+
+        // - We should be bound at, and advertise, the other one
+        //   within a keepalive and a url check
+        let moved = tokio::time::timeout(std::time::Duration::from_secs(60), async {
+            loop {
+                let bound: kitsune_p2p_proxy::ProxyUrl =
+                    nat_p2p.list_transport_bindings().await?[0].clone().into();
+                let advertised = advertised_url(&harness, &nat).await?;
+                if bound.as_base() == proxy2_url.as_base()
+                    && advertised.as_base() == proxy2_url.as_base()
+                {
+                    return Result::<(), KitsuneP2pError>::Ok(());
+                }
+                tokio::time::delay_for(std::time::Duration::from_millis(500)).await;
+            }
+        })
+        .await
+        .expect("nat agent never moved to the other proxy");
+        moved?;
+
+        harness.ghost_actor_shutdown().await?;
+        Ok(())
+    }
 }
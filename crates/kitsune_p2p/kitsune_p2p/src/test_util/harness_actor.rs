@@ -21,15 +21,21 @@ ghost_actor::ghost_chan! {
             ghost_actor::GhostSender<KitsuneP2p>,
         );
 
-        /// Create a new agent that will connect via proxy.
-        fn add_nat_agent(nick: String, proxy_url: url2::Url2) -> (
+        /// Create a new agent that will connect via one of these proxies.
+        fn add_nat_agent(nick: String, proxy_urls: Vec<url2::Url2>) -> (
             Arc<KitsuneAgent>,
             ghost_actor::GhostSender<KitsuneP2p>,
         );
 
+        /// Use these tuning params for agents added from now on
+        fn set_tuning_params(tuning_params: KitsuneP2pTuningParams) -> ();
+
         /// Magically exchange peer data between peers in harness
         fn magic_peer_info_exchange() -> ();
 
+        /// Give one specific agent peer data, without sharing it with others
+        fn inject_peer_info(agent: Arc<KitsuneAgent>, info: Vec<Arc<AgentInfoSigned>>) -> ();
+
         /// Inject data for one specific agent to gossip to others
         fn inject_gossip_data(agent: Arc<KitsuneAgent>, data: String) -> Arc<KitsuneOpHash>;
 
@@ -68,6 +74,22 @@ pub async fn spawn_test_harness_quic() -> Result<
     .await
 }
 
+/// construct a test suite around a tcp transport
+pub async fn spawn_test_harness_tcp() -> Result<
+    (
+        ghost_actor::GhostSender<HarnessControlApi>,
+        HarnessEventChannel,
+    ),
+    KitsuneP2pError,
+> {
+    spawn_test_harness(TransportConfig::Tcp {
+        bind_to: Some(url2::url2!("kitsune-tcp://0.0.0.0:0")),
+        override_host: None,
+        override_port: None,
+    })
+    .await
+}
+
 /// construct a test suite around a sub transport config concept
 pub async fn spawn_test_harness(
     sub_config: TransportConfig,
@@ -112,6 +134,7 @@ struct HarnessActor {
     i_s: ghost_actor::GhostSender<HarnessInner>,
     harness_chan: HarnessEventChannel,
     sub_config: TransportConfig,
+    tuning_params: KitsuneP2pTuningParams,
     space_list: Vec<Arc<KitsuneSpace>>,
     agents: HashMap<
         Arc<KitsuneAgent>,
@@ -132,6 +155,7 @@ impl HarnessActor {
             i_s,
             harness_chan,
             sub_config,
+            tuning_params: KitsuneP2pTuningParams::default(),
             space_list: Vec::new(),
             agents: HashMap::new(),
         }
@@ -207,7 +231,10 @@ impl HarnessControlApiHandler for HarnessActor {
         nick: String,
     ) -> HarnessControlApiHandlerResult<(Arc<KitsuneAgent>, ghost_actor::GhostSender<KitsuneP2p>)>
     {
-        let mut proxy_agent_config = KitsuneP2pConfig::default();
+        let mut proxy_agent_config = KitsuneP2pConfig {
+            tuning_params: self.tuning_params.clone(),
+            ..Default::default()
+        };
         proxy_agent_config
             .transport_pool
             .push(TransportConfig::Proxy {
@@ -235,7 +262,10 @@ impl HarnessControlApiHandler for HarnessActor {
         nick: String,
     ) -> HarnessControlApiHandlerResult<(Arc<KitsuneAgent>, ghost_actor::GhostSender<KitsuneP2p>)>
     {
-        let mut direct_agent_config = KitsuneP2pConfig::default();
+        let mut direct_agent_config = KitsuneP2pConfig {
+            tuning_params: self.tuning_params.clone(),
+            ..Default::default()
+        };
         direct_agent_config
             .transport_pool
             .push(TransportConfig::Proxy {
@@ -261,15 +291,18 @@ impl HarnessControlApiHandler for HarnessActor {
     fn handle_add_nat_agent(
         &mut self,
         nick: String,
-        proxy_url: url2::Url2,
+        proxy_urls: Vec<url2::Url2>,
     ) -> HarnessControlApiHandlerResult<(Arc<KitsuneAgent>, ghost_actor::GhostSender<KitsuneP2p>)>
     {
-        let mut nat_agent_config = KitsuneP2pConfig::default();
+        let mut nat_agent_config = KitsuneP2pConfig {
+            tuning_params: self.tuning_params.clone(),
+            ..Default::default()
+        };
         nat_agent_config
             .transport_pool
             .push(TransportConfig::Proxy {
                 sub_transport: Box::new(self.sub_config.clone()),
-                proxy_config: ProxyConfig::RemoteProxyClient { proxy_url },
+                proxy_config: ProxyConfig::RemoteProxyClient { proxy_urls },
             });
 
         let sub_harness = self.harness_chan.sub_clone(nick);
@@ -285,6 +318,14 @@ impl HarnessControlApiHandler for HarnessActor {
         .into())
     }
 
+    fn handle_set_tuning_params(
+        &mut self,
+        tuning_params: KitsuneP2pTuningParams,
+    ) -> HarnessControlApiHandlerResult<()> {
+        self.tuning_params = tuning_params;
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
     fn handle_magic_peer_info_exchange(&mut self) -> HarnessControlApiHandlerResult<()> {
         let ctrls = self
             .agents
@@ -309,6 +350,23 @@ impl HarnessControlApiHandler for HarnessActor {
         .into())
     }
 
+    fn handle_inject_peer_info(
+        &mut self,
+        agent: Arc<KitsuneAgent>,
+        info: Vec<Arc<AgentInfoSigned>>,
+    ) -> HarnessControlApiHandlerResult<()> {
+        let (_, ctrl) = self
+            .agents
+            .get(&agent)
+            .ok_or_else(|| KitsuneP2pError::from("invalid agent"))?;
+        let info = info
+            .into_iter()
+            .map(|i| (Arc::new(i.as_agent_ref().clone()), i))
+            .collect();
+        let fut = ctrl.inject_agent_info(info);
+        Ok(async move { fut.await }.boxed().into())
+    }
+
     fn handle_inject_gossip_data(
         &mut self,
         agent: Arc<KitsuneAgent>,
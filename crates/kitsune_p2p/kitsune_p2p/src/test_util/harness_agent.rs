@@ -134,7 +134,10 @@ impl KitsuneP2pEventHandler for AgentHarness {
         input: PutAgentInfoSignedEvt,
     ) -> KitsuneP2pEventHandlerResult<()> {
         let info = Arc::new(input.agent_info_signed);
-        self.agent_store.insert(input.agent.clone(), info.clone());
+        // `input.agent` is the local agent the info was found for,
+        // which isn't necessarily the agent the info is about
+        self.agent_store
+            .insert(Arc::new(info.as_agent_ref().clone()), info.clone());
         self.harness_chan.publish(HarnessEventType::StoreAgentInfo {
             agent: (&input.agent).into(),
             agent_info: info,
@@ -172,6 +175,12 @@ impl KitsuneP2pEventHandler for AgentHarness {
             from_agent: from_agent.into(),
             payload: data.to_string(),
         });
+        // lets tests tie up the remote's request handling
+        if data == "hang" {
+            return Ok(futures::future::pending::<KitsuneP2pResult<Vec<u8>>>()
+                .boxed()
+                .into());
+        }
         let data = format!("echo: {}", data);
         let data = data.into_bytes();
         Ok(async move { Ok(data) }.boxed().into())
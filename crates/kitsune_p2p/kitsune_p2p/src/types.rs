@@ -16,6 +16,15 @@ pub enum KitsuneP2pError {
     #[error("Routing Agent Error: {0:?}")]
     RoutingAgentError(Arc<KitsuneAgent>),
 
+    /// The remote agent is banned in this space, see `KitsuneP2pSender::ban_agent`.
+    #[error("Banned Agent: {0:?}")]
+    BannedAgent(Arc<KitsuneAgent>),
+
+    /// The remote turned the request away because it already has as many
+    /// in flight for this space as it will take. It isn't held against it.
+    #[error("Remote Busy: {0:?}")]
+    RemoteBusy(Arc<KitsuneSpace>),
+
     /// DecodingError
     #[error("Decoding Error: {0}")]
     DecodingError(Box<str>),
@@ -36,6 +45,10 @@ pub enum KitsuneP2pError {
     #[error("Bootstrap Error: {0}")]
     Bootstrap(Box<str>),
 
+    /// A signed agent info failed verification.
+    #[error("Invalid Agent Info: {0}")]
+    InvalidAgentInfo(Box<str>),
+
     /// SystemTime call failed.
     #[error(transparent)]
     SystemTime(#[from] std::time::SystemTimeError),
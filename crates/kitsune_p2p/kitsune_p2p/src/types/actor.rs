@@ -63,6 +63,39 @@ pub struct NotifyMulti {
     pub payload: Vec<u8>,
 }
 
+/// How a remote agent has behaved towards us in a space.
+/// Routing and gossip try agents with a low score last,
+/// and don't contact banned agents at all.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PeerScore {
+    /// The remote agent.
+    pub agent: super::KitsuneAgent,
+    /// Raised by answered requests, lowered by timeouts, failures
+    /// and reports of invalid ops.
+    pub score: i32,
+    /// How many of our requests the agent answered.
+    pub successes: u32,
+    /// How many of our requests the agent did not answer in time.
+    pub timeouts: u32,
+    /// How many of our requests failed.
+    pub failures: u32,
+    /// How many invalid ops were reported for the agent.
+    pub invalid_ops: u32,
+    /// Set while the agent is banned.
+    pub ban: Option<PeerBan>,
+}
+
+/// A ban on talking to a remote agent.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PeerBan {
+    /// When the ban is lifted, in ms since the unix epoch.
+    /// None = until the agent is unbanned.
+    pub until_ms: Option<u64>,
+    /// Whether the ban was issued through `ban_agent`,
+    /// rather than for a low score.
+    pub by_admin: bool,
+}
+
 ghost_actor::ghost_chan! {
     /// The KitsuneP2pSender allows async remote-control of the KitsuneP2p actor.
     pub chan KitsuneP2p<super::KitsuneP2pError> {
@@ -87,5 +120,21 @@ ghost_actor::ghost_chan! {
         /// Returns an approximate number of nodes reached.
         /// The remote sides will see these messages as "Notify" events.
         fn notify_multi(input: NotifyMulti) -> u8;
+
+        /// Report that a remote agent sent us invalid data,
+        /// e.g. ops that failed validation. This lowers the agent's score,
+        /// but never enough to ban it: the report can't be tied to the
+        /// connection the data came over.
+        fn report_invalid_op(space: Arc<super::KitsuneSpace>, agent: Arc<super::KitsuneAgent>) -> ();
+
+        /// Stop talking to a remote agent in a space,
+        /// for `duration_ms` or, if None, until it is unbanned.
+        fn ban_agent(space: Arc<super::KitsuneSpace>, agent: Arc<super::KitsuneAgent>, duration_ms: Option<u64>) -> ();
+
+        /// Lift a ban on a remote agent, and forget its score.
+        fn unban_agent(space: Arc<super::KitsuneSpace>, agent: Arc<super::KitsuneAgent>) -> ();
+
+        /// The scores of the remote agents we have dealt with in a space.
+        fn peer_scores(space: Arc<super::KitsuneSpace>) -> Vec<PeerScore>;
     }
 }
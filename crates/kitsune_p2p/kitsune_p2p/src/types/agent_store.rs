@@ -1,11 +1,21 @@
 //! Data structures to be stored in the agent/peer database.
 
 use crate::types::KitsuneAgent;
+use crate::types::KitsuneBinType;
 use crate::types::KitsuneP2pError;
 use crate::types::KitsuneSignature;
 use crate::types::KitsuneSpace;
+use kitsune_p2p_types::dht_arc::DhtArc;
+use lair_keystore_api::internal::sign_ed25519::SignEd25519PubKey;
+use lair_keystore_api::internal::sign_ed25519::SignEd25519Signature;
+use std::convert::TryFrom;
+use std::sync::Arc;
 use url2::Url2;
 
+/// How far ahead of our own clock an agent info may claim to have been signed.
+/// Allows for some drift between the clocks of different agents.
+pub const AGENT_INFO_MAX_CLOCK_SKEW_MS: u64 = 1000 * 60 * 5;
+
 /// A list of Urls.
 pub type Urls = Vec<Url2>;
 
@@ -33,8 +43,7 @@ pub struct AgentInfoSigned {
 
 impl AgentInfoSigned {
     /// Build a new AgentInfoSigned struct given a valid signature of the AgentInfo.
-    // @todo fail this if the signature does not verify against the agent info.
-    // It should not be possible to express a signed agent info type  with no valid signature.
+    /// The signature is not checked here, see [`AgentInfoSigned::verify`].
     pub fn try_new(
         agent: KitsuneAgent,
         signature: KitsuneSignature,
@@ -66,6 +75,57 @@ impl AgentInfoSigned {
     pub fn as_agent_info_ref(&self) -> &[u8] {
         self.agent_info.as_ref()
     }
+
+    /// Check this agent info can be trusted, as of the current system time,
+    /// and decode it. See [`AgentInfoSigned::verify_at`].
+    pub async fn verify(&self, space: Option<&KitsuneSpace>) -> Result<AgentInfo, KitsuneP2pError> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis();
+        self.verify_at(space, u64::try_from(now)?).await
+    }
+
+    /// Check this agent info can be trusted at `now_ms`, and decode it.
+    /// - The signature must be valid for the agent info bytes.
+    /// - The signing agent must be the agent the info is about.
+    /// - If a space is given, the info must be for that space.
+    /// - The info must not have expired, nor be signed in the future
+    ///   by more than [`AGENT_INFO_MAX_CLOCK_SKEW_MS`].
+    pub async fn verify_at(
+        &self,
+        space: Option<&KitsuneSpace>,
+        now_ms: u64,
+    ) -> Result<AgentInfo, KitsuneP2pError> {
+        let invalid = |reason: &str| KitsuneP2pError::InvalidAgentInfo(reason.into());
+        let info = AgentInfo::try_from(self)?;
+        if info.agent != self.agent {
+            return Err(invalid("signer is not the agent in the agent info"));
+        }
+        if let Some(space) = space {
+            if info.space != *space {
+                return Err(invalid("agent info is for a different space"));
+            }
+        }
+        if info.is_expired(now_ms) {
+            return Err(invalid("agent info has expired"));
+        }
+        if info.signed_at_ms > now_ms.saturating_add(AGENT_INFO_MAX_CLOCK_SKEW_MS) {
+            return Err(invalid("agent info is signed in the future"));
+        }
+        if self.signature.0.len() != 64 {
+            return Err(invalid("malformed signature"));
+        }
+        let pub_key = SignEd25519PubKey::from(self.agent.get_bytes().to_vec());
+        let signature = SignEd25519Signature::from(self.signature.0.clone());
+        let valid = pub_key
+            .verify(Arc::new(self.agent_info.clone()), signature)
+            .await
+            .map_err(KitsuneP2pError::other)?;
+        if !valid {
+            return Err(invalid("signature does not match the agent info"));
+        }
+        Ok(info)
+    }
 }
 
 /// Value that an agent signs to represent themselves on the network.
@@ -79,6 +139,9 @@ pub struct AgentInfo {
     agent: KitsuneAgent,
     // List of urls the agent can be reached at, in the agent's own preference order.
     urls: Urls,
+    // The region of the dht this agent claims to hold data for.
+    #[as_ref(ignore)]
+    storage_arc: DhtArc,
     // The unix ms timestamp that the agent info was signed at, according to the agent's own clock.
     #[as_ref(ignore)]
     signed_at_ms: u64,
@@ -102,6 +165,7 @@ impl AgentInfo {
         space: KitsuneSpace,
         agent: KitsuneAgent,
         urls: Urls,
+        storage_arc: DhtArc,
         signed_at_ms: u64,
         expires_after_ms: u64,
     ) -> Self {
@@ -109,6 +173,7 @@ impl AgentInfo {
             space,
             agent,
             urls,
+            storage_arc,
             signed_at_ms,
             expires_after_ms,
         }
@@ -137,6 +202,11 @@ impl AgentInfo {
         self.as_ref()
     }
 
+    /// Accessor for storage_arc.
+    pub fn storage_arc(&self) -> &DhtArc {
+        &self.storage_arc
+    }
+
     /// Accessor for signed_at_ms.
     pub fn signed_at_ms(&self) -> u64 {
         self.signed_at_ms
@@ -146,6 +216,15 @@ impl AgentInfo {
     pub fn expires_after_ms(&self) -> u64 {
         self.expires_after_ms
     }
+
+    /// True if this info had expired by `now_ms`.
+    /// An expiry that overflows is treated as already expired.
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        match self.signed_at_ms.checked_add(self.expires_after_ms) {
+            Some(expires) => expires <= now_ms,
+            None => true,
+        }
+    }
 }
 
 impl From<AgentInfoSigned> for KitsuneAgent {
@@ -159,3 +238,71 @@ impl From<AgentInfo> for KitsuneAgent {
         ai.agent
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::*;
+    use ::fixt::prelude::*;
+
+    const NOW: u64 = 1_000_000_000_000;
+
+    fn is_invalid(r: Result<AgentInfo, KitsuneP2pError>) -> bool {
+        matches!(r, Err(KitsuneP2pError::InvalidAgentInfo(_)))
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn verifies_valid_agent_info() {
+        let space = fixt!(KitsuneSpace);
+        let signed = signed_agent_info(space.clone(), NOW - 100, 1000).await;
+        let info = signed.verify_at(Some(&space), NOW).await.unwrap();
+        assert_eq!(info.as_agent_ref(), signed.as_agent_ref());
+        // Without a space any space is accepted.
+        signed.verify_at(None, NOW).await.unwrap();
+        // Clock skew within the limit is tolerated.
+        signed
+            .verify_at(Some(&space), NOW - 100 - AGENT_INFO_MAX_CLOCK_SKEW_MS)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_invalid_agent_info() {
+        let space = fixt!(KitsuneSpace);
+        let a = signed_agent_info(space.clone(), NOW, 1000).await;
+        let b = signed_agent_info(space.clone(), NOW, 1000).await;
+
+        // Claimed by someone other than the agent in the info.
+        let forged = AgentInfoSigned::try_new(
+            b.as_agent_ref().clone(),
+            a.as_signature_ref().clone(),
+            a.as_agent_info_ref().to_vec(),
+        )
+        .unwrap();
+        assert!(is_invalid(forged.verify_at(None, NOW).await));
+
+        // Signature over different bytes.
+        let swapped = AgentInfoSigned::try_new(
+            a.as_agent_ref().clone(),
+            b.as_signature_ref().clone(),
+            a.as_agent_info_ref().to_vec(),
+        )
+        .unwrap();
+        assert!(is_invalid(swapped.verify_at(None, NOW).await));
+
+        // Wrong space.
+        assert!(is_invalid(
+            a.verify_at(Some(&fixt!(KitsuneSpace)), NOW).await
+        ));
+
+        // Expired.
+        let expired = signed_agent_info(space.clone(), NOW - 1000, 1000).await;
+        assert!(is_invalid(expired.verify_at(None, NOW).await));
+        let overflow = signed_agent_info(space.clone(), u64::MAX - 1, 1000).await;
+        assert!(is_invalid(overflow.verify_at(None, u64::MAX - 1).await));
+
+        // Signed too far in the future.
+        let future = signed_agent_info(space, NOW + AGENT_INFO_MAX_CLOCK_SKEW_MS + 1, 1000).await;
+        assert!(is_invalid(future.verify_at(None, NOW).await));
+    }
+}
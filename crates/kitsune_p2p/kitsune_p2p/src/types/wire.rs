@@ -21,6 +21,12 @@ kitsune_p2p_types::write_codec_enum! {
             reason.0: String,
         },
 
+        /// The remote already has as many requests in flight for this
+        /// space as it will take, try again later.
+        Busy(0x01) {
+            space.0: Arc<KitsuneSpace>,
+        },
+
         /// "Call" to the remote.
         Call(0x010) {
             space.0: Arc<KitsuneSpace>,
@@ -22,6 +22,22 @@ pub struct Opt {
     /// Default: None = use NIC ip.
     #[structopt(short = "h", long)]
     pub override_host: Option<String>,
+
+    /// How many connections to keep open at once.
+    /// When full, the least recently used idle connection is closed.
+    /// Default: 1024.
+    #[structopt(long)]
+    pub max_connections: Option<usize>,
+
+    /// Close connections with no open channels for this long.
+    /// Default: 30000.
+    #[structopt(long)]
+    pub idle_timeout_ms: Option<u32>,
+
+    /// How many channels may be open at once on a single connection.
+    /// Default: 128.
+    #[structopt(long)]
+    pub max_concurrent_streams: Option<u32>,
 }
 
 impl From<&Opt> for kitsune_p2p_transport_quic::ConfigListenerQuic {
@@ -33,6 +49,15 @@ impl From<&Opt> for kitsune_p2p_transport_quic::ConfigListenerQuic {
         if let Some(h) = &o.override_host {
             out = out.set_override_host(Some(h));
         }
+        if let Some(m) = o.max_connections {
+            out = out.set_max_connections(m);
+        }
+        if let Some(i) = o.idle_timeout_ms {
+            out = out.set_idle_timeout_ms(i);
+        }
+        if let Some(m) = o.max_concurrent_streams {
+            out = out.set_max_concurrent_streams(m);
+        }
         out
     }
 }
@@ -64,7 +64,7 @@ async fn inner() -> TransportResult<()> {
 
         let (_url, mut write, read) = listener.create_channel((&proxy_url).into()).await?;
         write.write_and_close(Vec::with_capacity(0)).await?;
-        let res = read.read_to_end().await;
+        let res = read.read_to_end().await?;
         println!(
             "#DEBUG:START#\n{}\n#DEBUG:END#",
             String::from_utf8_lossy(&res)
@@ -234,7 +234,7 @@ async fn client_loop(
         let start = std::time::Instant::now();
         let (_, mut write, read) = con.create_channel(con_url.clone()).await?;
         write.write_and_close(b"".to_vec()).await?;
-        read.read_to_end().await;
+        read.read_to_end().await?;
         metric_send
             .send(Metric::RequestOverhead(
                 start.elapsed().as_millis() as u64 - opt.process_delay_ms as u64,
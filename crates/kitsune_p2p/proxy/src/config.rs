@@ -60,8 +60,11 @@ pub enum ProxyConfig {
         /// The Tls config for this proxy endpoint.
         tls: TlsConfig,
 
-        /// The remote proxy url to be hosted at.
-        proxy_url: ProxyUrl,
+        /// The remote proxy urls we may be hosted at, in order of
+        /// preference. We are hosted at the first that grants us a
+        /// contract, and fail over to the next should it stop answering.
+        /// Must not be empty.
+        proxy_urls: Vec<ProxyUrl>,
     },
 
     /// We want to be a proxy server for others.
@@ -79,7 +82,12 @@ pub enum ProxyConfig {
 impl ProxyConfig {
     /// We want to be hosted at a remote proxy location.
     pub fn remote_proxy_client(tls: TlsConfig, proxy_url: ProxyUrl) -> Arc<Self> {
-        Arc::new(Self::RemoteProxyClient { tls, proxy_url })
+        Self::remote_proxy_client_list(tls, vec![proxy_url])
+    }
+
+    /// We want to be hosted at one of several remote proxy locations.
+    pub fn remote_proxy_client_list(tls: TlsConfig, proxy_urls: Vec<ProxyUrl>) -> Arc<Self> {
+        Arc::new(Self::RemoteProxyClient { tls, proxy_urls })
     }
 
     /// We want to be a proxy server for others.
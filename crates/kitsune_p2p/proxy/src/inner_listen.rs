@@ -11,6 +11,9 @@ const PROXY_KEEPALIVE_MS: u64 = 15000;
 /// How much longer the proxy should wait to remove the contract
 /// if no keep alive is received.
 const KEEPALIVE_MULTIPLIER: u64 = 3;
+/// How long a proxy has to grant us a contract
+/// before we consider it unhealthy.
+const PROXY_REQ_TIMEOUT_MS: u64 = 5000;
 
 /// Wrap a transport listener sender/receiver in kitsune proxy logic.
 pub async fn spawn_kitsune_proxy_listener(
@@ -22,17 +25,22 @@ pub async fn spawn_kitsune_proxy_listener(
     TransportEventReceiver,
 )> {
     // sort out our proxy config
-    let (tls, accept_proxy_cb, proxy_url): (TlsConfig, AcceptProxyCallback, Option<ProxyUrl>) =
+    let (tls, accept_proxy_cb, proxy_urls): (TlsConfig, AcceptProxyCallback, Vec<ProxyUrl>) =
         match proxy_config.as_ref() {
-            ProxyConfig::RemoteProxyClient { tls, proxy_url } => (
-                tls.clone(),
-                AcceptProxyCallback::reject_all(),
-                Some(proxy_url.clone()),
-            ),
+            ProxyConfig::RemoteProxyClient { tls, proxy_urls } => {
+                if proxy_urls.is_empty() {
+                    return Err("RemoteProxyClient requires at least one proxy url".into());
+                }
+                (
+                    tls.clone(),
+                    AcceptProxyCallback::reject_all(),
+                    proxy_urls.clone(),
+                )
+            }
             ProxyConfig::LocalProxyServer {
                 tls,
                 accept_proxy_cb,
-            } => (tls.clone(), accept_proxy_cb.clone(), None),
+            } => (tls.clone(), accept_proxy_cb.clone(), Vec::new()),
         };
 
     // Configure our own proxy url based of connection details / tls cert.
@@ -67,35 +75,53 @@ pub async fn spawn_kitsune_proxy_listener(
         ),
     );
 
-    // if we want to be proxied, we need to connect to our proxy
+    // if we want to be proxied, we need to connect to one of our proxies
     // and manage that connection contract
-    if let Some(proxy_url) = proxy_url {
-        if let Err(e) = i_s.req_proxy(proxy_url.clone()).await {
-            tracing::error!(
-                msg = "Request proxy failed. Check proxy_url / network status.",
-                ?proxy_url,
-                ?e
-            );
-        }
+    if !proxy_urls.is_empty() {
+        let mut current = select_proxy(&i_s, &proxy_urls, None).await;
 
-        // Set up a timer to refresh our proxy contract at keepalive interval
+        // Set up a timer to refresh our proxy contract at keepalive interval,
+        // and check on our standby proxies while at it
         let i_s_c = i_s.clone();
         metric_task(async move {
             loop {
                 tokio::time::delay_for(std::time::Duration::from_millis(PROXY_KEEPALIVE_MS)).await;
 
-                if let Err(e) = i_s_c.req_proxy(proxy_url.clone()).await {
-                    tracing::error!(msg = "renewing proxy failed", ?proxy_url, ?e);
-                    // either we failed because the actor is already shutdown
-                    // or the remote end rejected us.
-                    // if it's the latter - shut down our ghost actor : )
-                    if !i_s_c.ghost_actor_is_active() {
-                        tracing::debug!("Ghost actor has closed so exiting keep alive");
-                        break;
+                let renewed = match &current {
+                    Some(proxy_url) => match request_proxy(&i_s_c, proxy_url.clone()).await {
+                        Ok(this_url) => {
+                            tracing::info!("Proxy renewed for {:?}", proxy_url);
+                            i_s_c.set_proxy_url(this_url).await.is_ok()
+                        }
+                        Err(e) => {
+                            tracing::error!(msg = "renewing proxy failed", ?proxy_url, ?e);
+                            false
+                        }
+                    },
+                    None => false,
+                };
+
+                if renewed {
+                    // move back to a proxy we prefer as soon as it recovers
+                    if let Some(current) = &mut current {
+                        if let Some(preferred) = check_standbys(&i_s_c, &proxy_urls, current).await
+                        {
+                            *current = preferred;
+                        }
                     }
-                } else {
-                    tracing::info!("Proxy renewed for {:?}", proxy_url);
+                    continue;
+                }
+
+                // either we failed because the actor is already shutdown
+                // or the remote end is gone / rejected us.
+                if !i_s_c.ghost_actor_is_active() {
+                    tracing::debug!("Ghost actor has closed so exiting keep alive");
+                    break;
                 }
+
+                // if it's the latter - fail over to the next proxy
+                // still answering, which may yet be the same one
+                current = select_proxy(&i_s_c, &proxy_urls, current.as_ref()).await;
             }
             tracing::error!("Keep alive closed");
             <Result<(), ()>>::Ok(())
@@ -128,6 +154,102 @@ pub async fn spawn_kitsune_proxy_listener(
     Ok((sender, evt_recv))
 }
 
+/// Request that a proxy host us, within [`PROXY_REQ_TIMEOUT_MS`].
+async fn request_proxy(
+    i_s: &ghost_actor::GhostSender<Internal>,
+    proxy_url: ProxyUrl,
+) -> TransportResult<ProxyUrl> {
+    match tokio::time::timeout(
+        std::time::Duration::from_millis(PROXY_REQ_TIMEOUT_MS),
+        i_s.req_proxy(proxy_url),
+    )
+    .await
+    {
+        Ok(res) => res,
+        Err(_) => Err("timeout requesting proxy".into()),
+    }
+}
+
+/// Ask our proxies in turn to host us, starting with the one after
+/// `failed` (if any), and stop at the first that grants us a contract.
+/// Returns that proxy, if any did.
+async fn select_proxy(
+    i_s: &ghost_actor::GhostSender<Internal>,
+    proxy_urls: &[ProxyUrl],
+    failed: Option<&ProxyUrl>,
+) -> Option<ProxyUrl> {
+    let start = failed
+        .and_then(|failed| proxy_urls.iter().position(|u| u == failed))
+        .map_or(0, |idx| idx + 1);
+    for proxy_url in proxy_urls.iter().cycle().skip(start).take(proxy_urls.len()) {
+        match request_proxy(i_s, proxy_url.clone()).await {
+            Ok(this_url) => {
+                tracing::info!("Proxying through {:?}", proxy_url);
+                if let Err(e) = i_s.set_proxy_url(this_url).await {
+                    tracing::error!(msg = "setting proxy url failed", ?e);
+                    return None;
+                }
+                return Some(proxy_url.clone());
+            }
+            Err(e) => tracing::warn!(msg = "proxy is unhealthy", ?proxy_url, ?e),
+        }
+    }
+    tracing::error!(
+        msg = "Request proxy failed. Check proxy_urls / network status.",
+        ?proxy_urls
+    );
+    None
+}
+
+/// Whether we can reach `proxy_url` at all, within [`PROXY_REQ_TIMEOUT_MS`].
+/// Unlike [`request_proxy`], this leaves the proxy no contract to keep.
+async fn probe_proxy(i_s: &ghost_actor::GhostSender<Internal>, proxy_url: &ProxyUrl) -> bool {
+    matches!(
+        tokio::time::timeout(
+            std::time::Duration::from_millis(PROXY_REQ_TIMEOUT_MS),
+            i_s.create_low_level_channel(proxy_url.clone().into_base()),
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+/// Check that our standby proxies, all but the `current` one, can be
+/// reached. If one we prefer over `current` can, ask it to host us again
+/// and return it once it has.
+async fn check_standbys(
+    i_s: &ghost_actor::GhostSender<Internal>,
+    proxy_urls: &[ProxyUrl],
+    current: &ProxyUrl,
+) -> Option<ProxyUrl> {
+    let mut preferred = true;
+    for proxy_url in proxy_urls {
+        if proxy_url == current {
+            preferred = false;
+            continue;
+        }
+        if !probe_proxy(i_s, proxy_url).await {
+            tracing::warn!(msg = "standby proxy is unhealthy", ?proxy_url);
+            continue;
+        }
+        if !preferred {
+            continue;
+        }
+        match request_proxy(i_s, proxy_url.clone()).await {
+            Ok(this_url) => {
+                if let Err(e) = i_s.set_proxy_url(this_url).await {
+                    tracing::error!(msg = "setting proxy url failed", ?e);
+                    return None;
+                }
+                tracing::info!("Failed back to {:?}", proxy_url);
+                return Some(proxy_url.clone());
+            }
+            Err(e) => tracing::warn!(msg = "proxy is unhealthy", ?proxy_url, ?e),
+        }
+    }
+    None
+}
+
 #[derive(Debug)]
 /// An item in our proxy_list - a client we have agreed to proxy for
 struct ProxyTo {
@@ -226,7 +348,7 @@ ghost_actor::ghost_chan! {
 
         fn register_proxy_to(proxy_url: ProxyUrl, base_url: url2::Url2) -> ();
 
-        fn req_proxy(proxy_url: ProxyUrl) -> ();
+        fn req_proxy(proxy_url: ProxyUrl) -> ProxyUrl;
         fn set_proxy_url(proxy_url: ProxyUrl) -> ();
     }
 }
@@ -478,7 +600,7 @@ impl InternalHandler for InnerListen {
         Ok(async move { Ok(()) }.boxed().into())
     }
 
-    fn handle_req_proxy(&mut self, proxy_url: ProxyUrl) -> InternalHandlerResult<()> {
+    fn handle_req_proxy(&mut self, proxy_url: ProxyUrl) -> InternalHandlerResult<ProxyUrl> {
         tracing::info!(
             "{}: wishes to proxy through {}:{}",
             self.this_url.short(),
@@ -487,7 +609,6 @@ impl InternalHandler for InnerListen {
         );
         let cert_digest = self.tls.cert_digest.clone();
         let fut = self.i_s.create_low_level_channel(proxy_url.into_base());
-        let i_s = self.i_s.clone();
         Ok(async move {
             let (mut write, mut read) = fut.await?;
 
@@ -506,8 +627,7 @@ impl InternalHandler for InnerListen {
                 }
                 _ => return Err(format!("unexpected: {:?}", res).into()),
             };
-            i_s.set_proxy_url(proxy_url.into()).await?;
-            Ok(())
+            Ok(proxy_url.into())
         }
         .boxed()
         .into())
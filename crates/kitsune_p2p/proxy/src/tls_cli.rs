@@ -97,7 +97,8 @@ async fn tls_client(
             }
 
             match merge.next().await {
-                Some(Left(Some(data))) => {
+                Some(Left(Some(Err(e)))) => return Err(e),
+                Some(Left(Some(Ok(data)))) => {
                     tracing::trace!("{}: CLI outgoing {} bytes", short, data.len());
                     cli.write_all(&data).map_err(TransportError::other)?;
                 }
@@ -88,7 +88,8 @@ async fn tls_server(
             }
 
             match merge.next().await {
-                Some(Left(Some(data))) => {
+                Some(Left(Some(Err(e)))) => return Err(e),
+                Some(Left(Some(Ok(data)))) => {
                     tracing::trace!("{}: SRV outgoing {} bytes", short, data.len());
                     srv.write_all(&data).map_err(TransportError::other)?;
                 }
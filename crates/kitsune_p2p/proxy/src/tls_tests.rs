@@ -34,7 +34,7 @@ async fn tls_server_and_client_inner() -> TransportResult<()> {
             match evt {
                 TransportEvent::IncomingChannel(_url, mut send, recv) => {
                     tracing::warn!("incoming channel - reading...");
-                    let data = recv.read_to_end().await;
+                    let data = recv.read_to_end().await?;
                     let data = String::from_utf8_lossy(&data);
                     let data = format!("echo: {}", data);
                     tracing::warn!("incoming channel - responding...");
@@ -77,8 +77,7 @@ async fn tls_server_and_client_inner() -> TransportResult<()> {
     cli_data_send2.write_and_close(large_msg.clone()).await?;
 
     tracing::warn!("about to recv");
-    let res = cli_data_recv2.collect::<Vec<_>>().await;
-    let res = res.into_iter().flat_map(|a| a).collect::<Vec<_>>();
+    let res = cli_data_recv2.read_to_end().await?;
     let data = String::from_utf8_lossy(&res);
     assert_eq!(data.len(), 70_406);
     assert_eq!(
@@ -13,7 +13,7 @@ pub(crate) fn wrap_wire_read(
     metric_task(async move {
         let mut buf = Vec::new();
         while let Some(data) = read.next().await {
-            buf.extend_from_slice(&data);
+            buf.extend_from_slice(&data?);
             tracing::trace!("proxy read pending {} bytes", buf.len());
             while let Ok((read_size, wire)) = ProxyWire::decode_ref(&buf) {
                 tracing::trace!("proxy read {:?}", wire);
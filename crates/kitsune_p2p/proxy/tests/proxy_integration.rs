@@ -1,8 +1,11 @@
+use futures::future::FutureExt;
 use futures::stream::StreamExt;
 use ghost_actor::dependencies::tracing;
 use kitsune_p2p_proxy::*;
 use kitsune_p2p_types::dependencies::ghost_actor;
 use kitsune_p2p_types::transport::*;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 fn init_tracing() {
@@ -36,7 +39,7 @@ async fn connect(
             match evt {
                 TransportEvent::IncomingChannel(url, mut write, read) => {
                     tracing::warn!("Incoming PROXY: {}", url);
-                    let data = read.read_to_end().await;
+                    let data = read.read_to_end().await?;
                     let data = String::from_utf8_lossy(&data);
                     tracing::warn!("PROXY_READ_DATA: {}", data);
                     let data = format!("echo: {}", data);
@@ -73,14 +76,14 @@ async fn test_inner() -> TransportResult<()> {
 
     let (_url, mut write, read) = bind2.create_channel(addr3.clone()).await?;
     write.write_and_close(b"test".to_vec()).await?;
-    let data = read.read_to_end().await;
+    let data = read.read_to_end().await?;
     let data = String::from_utf8_lossy(&data);
     assert_eq!("echo: test", data);
 
     // run a second time to prove out session resumption
     let (_url, mut write, read) = bind2.create_channel(addr3).await?;
     write.write_and_close(b"test".to_vec()).await?;
-    let data = read.read_to_end().await;
+    let data = read.read_to_end().await?;
     let data = String::from_utf8_lossy(&data);
     assert_eq!("echo: test", data);
 
@@ -88,3 +91,135 @@ async fn test_inner() -> TransportResult<()> {
 
     Ok(())
 }
+
+#[tokio::test(threaded_scheduler)]
+async fn test_proxy_integration_skips_unhealthy_proxies() {
+    if let Err(e) = test_skips_unhealthy_inner().await {
+        panic!("{:?}", e);
+    }
+}
+
+async fn test_skips_unhealthy_inner() -> TransportResult<()> {
+    init_tracing();
+
+    let rejecting_config = ProxyConfig::local_proxy_server(
+        TlsConfig::new_ephemeral().await?,
+        AcceptProxyCallback::reject_all(),
+    );
+    let rejecting = connect(rejecting_config).await?;
+    let rejecting_addr = rejecting.bound_url().await?;
+
+    let accepting_config = ProxyConfig::local_proxy_server(
+        TlsConfig::new_ephemeral().await?,
+        AcceptProxyCallback::accept_all(),
+    );
+    let accepting = connect(accepting_config).await?;
+    let accepting_addr = accepting.bound_url().await?;
+
+    let client_config = ProxyConfig::remote_proxy_client_list(
+        TlsConfig::new_ephemeral().await?,
+        vec![rejecting_addr.into(), accepting_addr.clone().into()],
+    );
+    let client = connect(client_config).await?;
+    let client_addr = client.bound_url().await?;
+    assert_eq!(
+        ProxyUrl::from(accepting_addr).as_base(),
+        ProxyUrl::from(client_addr.clone()).as_base()
+    );
+
+    let (_url, mut write, read) = rejecting.create_channel(client_addr).await?;
+    write.write_and_close(b"test".to_vec()).await?;
+    let data = read.read_to_end().await?;
+    let data = String::from_utf8_lossy(&data);
+    assert_eq!("echo: test", data);
+
+    tracing::warn!("TEST COMPLETE");
+
+    Ok(())
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_proxy_integration_fails_back_to_preferred_proxy() {
+    if let Err(e) = test_fails_back_inner().await {
+        panic!("{:?}", e);
+    }
+}
+
+async fn test_fails_back_inner() -> TransportResult<()> {
+    init_tracing();
+
+    // - The preferred proxy turns us away until it has recovered
+    let recovered = Arc::new(AtomicBool::new(false));
+    let preferred_config = ProxyConfig::local_proxy_server(
+        TlsConfig::new_ephemeral().await?,
+        AcceptProxyCallback({
+            let recovered = recovered.clone();
+            Arc::new(move |_| {
+                let recovered = recovered.load(Ordering::SeqCst);
+                async move { recovered }.boxed().into()
+            })
+        }),
+    );
+    let preferred = connect(preferred_config).await?;
+    let preferred_addr = preferred.bound_url().await?;
+
+    let standby_config = ProxyConfig::local_proxy_server(
+        TlsConfig::new_ephemeral().await?,
+        AcceptProxyCallback::accept_all(),
+    );
+    let standby = connect(standby_config).await?;
+    let standby_addr = standby.bound_url().await?;
+
+    let client_config = ProxyConfig::remote_proxy_client_list(
+        TlsConfig::new_ephemeral().await?,
+        vec![preferred_addr.clone().into(), standby_addr.clone().into()],
+    );
+    let client = connect(client_config).await?;
+    let client_addr = client.bound_url().await?;
+    assert_eq!(
+        ProxyUrl::from(standby_addr).as_base(),
+        ProxyUrl::from(client_addr).as_base()
+    );
+
+    // - We are back at the preferred proxy within a keepalive of it recovering
+    recovered.store(true, Ordering::SeqCst);
+    let client_addr = tokio::time::timeout(std::time::Duration::from_secs(60), async {
+        loop {
+            let client_addr = client.bound_url().await?;
+            if ProxyUrl::from(client_addr.clone()).as_base()
+                == ProxyUrl::from(preferred_addr.clone()).as_base()
+            {
+                return TransportResult::Ok(client_addr);
+            }
+            tokio::time::delay_for(std::time::Duration::from_millis(500)).await;
+        }
+    })
+    .await
+    .expect("client never failed back to the preferred proxy")?;
+
+    let (_url, mut write, read) = standby.create_channel(client_addr).await?;
+    write.write_and_close(b"test".to_vec()).await?;
+    let data = read.read_to_end().await?;
+    let data = String::from_utf8_lossy(&data);
+    assert_eq!("echo: test", data);
+
+    tracing::warn!("TEST COMPLETE");
+
+    Ok(())
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn test_proxy_integration_rejects_empty_proxy_urls() {
+    init_tracing();
+
+    let (bind, evt) = kitsune_p2p_types::transport_mem::spawn_bind_transport_mem()
+        .await
+        .unwrap();
+    let config = ProxyConfig::remote_proxy_client_list(
+        TlsConfig::new_ephemeral().await.unwrap(),
+        Vec::new(),
+    );
+    assert!(spawn_kitsune_proxy_listener(config, bind, evt)
+        .await
+        .is_err());
+}
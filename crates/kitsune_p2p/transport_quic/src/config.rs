@@ -24,6 +24,20 @@ pub struct ConfigListenerQuic {
         lair_keystore_api::actor::Cert,
         lair_keystore_api::actor::CertPrivKey,
     )>,
+
+    /// How many connections to remote urls are kept open at once.
+    /// When full, the least recently used idle connection is closed
+    /// to make room. If none are idle, new connections are refused.
+    /// Must not be 0. Default: 1024.
+    pub max_connections: usize,
+
+    /// Connections with no open channels for this long are closed.
+    /// Must not be 0. Default: 30000.
+    pub idle_timeout_ms: u32,
+
+    /// How many channels may be open at once on a single connection.
+    /// Must not be 0. Default: 128.
+    pub max_concurrent_streams: u32,
 }
 
 impl Default for ConfigListenerQuic {
@@ -33,6 +47,9 @@ impl Default for ConfigListenerQuic {
             override_host: None,
             override_port: None,
             tls: None,
+            max_connections: 1024,
+            idle_timeout_ms: 1000 * 30,
+            max_concurrent_streams: 128,
         }
     }
 }
@@ -67,4 +84,22 @@ impl ConfigListenerQuic {
         self.tls = tls;
         self
     }
+
+    /// Set 'max_connections' builder pattern.
+    pub fn set_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Set 'idle_timeout_ms' builder pattern.
+    pub fn set_idle_timeout_ms(mut self, idle_timeout_ms: u32) -> Self {
+        self.idle_timeout_ms = idle_timeout_ms;
+        self
+    }
+
+    /// Set 'max_concurrent_streams' builder pattern.
+    pub fn set_max_concurrent_streams(mut self, max_concurrent_streams: u32) -> Self {
+        self.max_concurrent_streams = max_concurrent_streams;
+        self
+    }
 }
@@ -10,17 +10,79 @@ use kitsune_p2p_types::dependencies::url2;
 use kitsune_p2p_types::transport::*;
 use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+/// How long to wait for a remote to accept a new channel on an existing
+/// connection - it may be enforcing a lower stream limit than ours.
+const OPEN_CHANNEL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Activity on one connection, shared with the tasks of its open channels.
+#[derive(Debug)]
+struct ConnectionStats {
+    /// how many channels are open on this connection
+    open_streams: AtomicUsize,
+    /// when a channel was last opened or closed
+    last_active: std::sync::Mutex<Instant>,
+}
+
+impl ConnectionStats {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            open_streams: AtomicUsize::new(0),
+            last_active: std::sync::Mutex::new(Instant::now()),
+        })
+    }
+
+    fn open_streams(&self) -> usize {
+        self.open_streams.load(Ordering::SeqCst)
+    }
+
+    /// How long this connection has had no open channels,
+    /// or None if channels are open.
+    fn idle_for(&self) -> Option<Duration> {
+        if self.open_streams() > 0 {
+            return None;
+        }
+        Some(self.last_active.lock().unwrap().elapsed())
+    }
+
+    /// Count a channel as open until the returned guard is dropped.
+    fn open_stream(self: &Arc<Self>) -> StreamGuard {
+        self.open_streams.fetch_add(1, Ordering::SeqCst);
+        *self.last_active.lock().unwrap() = Instant::now();
+        StreamGuard(self.clone())
+    }
+}
+
+/// Held by the tasks of an open channel.
+struct StreamGuard(Arc<ConnectionStats>);
+
+impl Drop for StreamGuard {
+    fn drop(&mut self) {
+        *self.0.last_active.lock().unwrap() = Instant::now();
+        self.0.open_streams.fetch_sub(1, Ordering::SeqCst);
+    }
+}
 
 /// Convert quinn async read/write streams into Vec<u8> senders / receivers.
 /// Quic bi-streams are Async Read/Write - But the kitsune transport api
 /// uses Vec<u8> Streams / Sinks - This code translates into that.
+/// The channel counts as open on its connection until both halves are done.
 fn tx_bi_chan(
     mut bi_send: quinn::SendStream,
     mut bi_recv: quinn::RecvStream,
+    guard: StreamGuard,
 ) -> (TransportChannelWrite, TransportChannelRead) {
+    let guard = Arc::new(guard);
+    let write_guard = guard.clone();
     let (write_send, mut write_recv) = futures::channel::mpsc::channel::<Vec<u8>>(10);
     let write_send = write_send.sink_map_err(TransportError::other);
     metric_task(async move {
+        let _guard = write_guard;
         while let Some(data) = write_recv.next().await {
             bi_send
                 .write_all(&data)
@@ -30,20 +92,27 @@ fn tx_bi_chan(
         bi_send.finish().await.map_err(TransportError::other)?;
         TransportResult::Ok(())
     });
-    let (mut read_send, read_recv) = futures::channel::mpsc::channel::<Vec<u8>>(10);
+    let (mut read_send, read_recv) =
+        futures::channel::mpsc::channel::<TransportResult<Vec<u8>>>(10);
     metric_task(async move {
+        let _guard = guard;
         let mut buf = [0_u8; 4096];
-        while let Some(read) = bi_recv
-            .read(&mut buf)
-            .await
-            .map_err(TransportError::other)?
-        {
+        loop {
+            let read = match bi_recv.read(&mut buf).await {
+                Ok(Some(read)) => read,
+                Ok(None) => break,
+                Err(e) => {
+                    // let the reader know the stream was reset
+                    let _ = read_send.send(Err(TransportError::other(e))).await;
+                    break;
+                }
+            };
             if read == 0 {
                 continue;
             }
             tracing::debug!("QUIC received {} bytes", read);
             read_send
-                .send(buf[0..read].to_vec())
+                .send(Ok(buf[0..read].to_vec()))
                 .await
                 .map_err(TransportError::other)?;
         }
@@ -65,7 +134,64 @@ struct TransportListenerQuic {
     /// the quinn binding (akin to a socket listener)
     quinn_endpoint: quinn::Endpoint,
     /// pool of active connections
-    connections: HashMap<Url2, quinn::Connection>,
+    connections: HashMap<Url2, Connection>,
+    /// see ConfigListenerQuic::max_connections
+    max_connections: usize,
+    /// see ConfigListenerQuic::idle_timeout_ms
+    idle_timeout: Duration,
+    /// see ConfigListenerQuic::max_concurrent_streams
+    max_concurrent_streams: usize,
+    /// connections closed for being idle
+    idle_evicted_count: u64,
+    /// connections refused because max_connections were all busy
+    refused_connection_count: u64,
+    /// channels refused because max_concurrent_streams were open
+    refused_stream_count: u64,
+}
+
+/// An entry in our pool of active connections.
+struct Connection {
+    con: quinn::Connection,
+    stats: Arc<ConnectionStats>,
+}
+
+impl TransportListenerQuic {
+    /// If our pool is full, close the least recently used idle connection.
+    /// Returns false if the pool is full and no connection is idle.
+    fn make_room(&mut self) -> bool {
+        if self.connections.len() < self.max_connections {
+            return true;
+        }
+        let lru = self
+            .connections
+            .iter()
+            .filter_map(|(url, c)| c.stats.idle_for().map(|idle| (idle, url)))
+            .max_by_key(|(idle, _)| *idle)
+            .map(|(_, url)| url.clone());
+        match lru {
+            Some(url) => {
+                tracing::debug!("QUIC connection limit reached, closing: {}", url);
+                self.close_connection(&url);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn close_connection(&mut self, url: &Url2) {
+        if let Some(c) = self.connections.remove(url) {
+            c.con.close(0_u8.into(), b"");
+        }
+    }
+
+    fn connection_limit_error(&mut self) -> TransportError {
+        self.refused_connection_count += 1;
+        format!(
+            "QUIC connection limit reached: all {} connections are busy",
+            self.max_connections
+        )
+        .into()
+    }
 }
 
 impl ghost_actor::GhostControlHandler for TransportListenerQuic {
@@ -78,9 +204,9 @@ impl ghost_actor::GhostControlHandler for TransportListenerQuic {
             // in some signals to start rejecting incoming connections,
             // then we could use `quinn_endpoint.wait_idle().await`.
             let _ = self.incoming_channel_sender.close_channel();
-            for (_, con) in self.connections.into_iter() {
-                con.close(0_u8.into(), b"");
-                drop(con);
+            for (_, c) in self.connections.into_iter() {
+                c.con.close(0_u8.into(), b"");
+                drop(c);
             }
             self.quinn_endpoint.close(0_u8.into(), b"");
         }
@@ -109,15 +235,21 @@ ghost_actor::ghost_chan! {
 
         /// Finalization step for taking control of a connection.
         /// Places it in our hash map for use establishing outgoing channels.
+        /// Refused if our pool is full of busy connections.
         fn set_connection(
             url: Url2,
             con: quinn::Connection,
+            stats: Arc<ConnectionStats>,
         ) -> ();
 
         /// If we get an error making outgoing channels,
         /// or if the incoming channel receiver stops,
         /// we want to remove this connection from our pool. It is done.
         fn drop_connection(url: Url2) -> ();
+
+        /// Close connections that have had no open channels
+        /// for our idle timeout.
+        fn evict_idle() -> ();
     }
 }
 
@@ -153,11 +285,14 @@ impl ListenerInnerHandler for TransportListenerQuic {
                 ..
             } = maybe_con.await.map_err(TransportError::other)?;
 
+            let stats = ConnectionStats::new();
+
             // if we are making an outgoing connection
             // we also need to make an initial channel
             let out = if with_channel {
+                let guard = stats.open_stream();
                 let (bi_send, bi_recv) = con.open_bi().await.map_err(TransportError::other)?;
-                Some(tx_bi_chan(bi_send, bi_recv))
+                Some(tx_bi_chan(bi_send, bi_recv, guard))
             } else {
                 None
             };
@@ -167,13 +302,13 @@ impl ListenerInnerHandler for TransportListenerQuic {
             tracing::debug!("QUIC handle connection: {}", url);
 
             // pass the connection off to our actor
-            i_s.set_connection(url.clone(), con).await?;
+            i_s.set_connection(url.clone(), con, stats.clone()).await?;
 
             // pass any incoming channels off to our actor
             let url_clone = url.clone();
             metric_task(async move {
                 while let Some(Ok((bi_send, bi_recv))) = bi_streams.next().await {
-                    let (write, read) = tx_bi_chan(bi_send, bi_recv);
+                    let (write, read) = tx_bi_chan(bi_send, bi_recv, stats.open_stream());
                     if incoming_channel_sender
                         .send(TransportEvent::IncomingChannel(
                             url_clone.clone(),
@@ -199,8 +334,13 @@ impl ListenerInnerHandler for TransportListenerQuic {
         &mut self,
         url: Url2,
         con: quinn::Connection,
+        stats: Arc<ConnectionStats>,
     ) -> ListenerInnerHandlerResult<()> {
-        self.connections.insert(url, con);
+        if !self.connections.contains_key(&url) && !self.make_room() {
+            con.close(0_u8.into(), b"");
+            return Err(self.connection_limit_error());
+        }
+        self.connections.insert(url, Connection { con, stats });
         Ok(async move { Ok(()) }.boxed().into())
     }
 
@@ -208,22 +348,47 @@ impl ListenerInnerHandler for TransportListenerQuic {
         self.connections.remove(&url);
         Ok(async move { Ok(()) }.boxed().into())
     }
+
+    fn handle_evict_idle(&mut self) -> ListenerInnerHandlerResult<()> {
+        let idle_timeout = self.idle_timeout;
+        let idle = self
+            .connections
+            .iter()
+            .filter(|(_, c)| {
+                c.stats
+                    .idle_for()
+                    .map_or(false, |idle| idle >= idle_timeout)
+            })
+            .map(|(url, _)| url.clone())
+            .collect::<Vec<_>>();
+        for url in idle {
+            tracing::debug!("QUIC closing idle connection: {}", url);
+            self.close_connection(&url);
+            self.idle_evicted_count += 1;
+        }
+        Ok(async move { Ok(()) }.boxed().into())
+    }
 }
 
 impl ghost_actor::GhostHandler<TransportListener> for TransportListenerQuic {}
 
 impl TransportListenerHandler for TransportListenerQuic {
     fn handle_debug(&mut self) -> TransportListenerHandlerResult<serde_json::Value> {
-        let url = self.bound_url.clone();
-        let connections = self.connections.keys().cloned().collect::<Vec<_>>();
-        Ok(async move {
-            Ok(serde_json::json! {{
-                "url": url,
-                "connection_count": connections.len(),
-            }})
-        }
-        .boxed()
-        .into())
+        let dump = serde_json::json! {{
+            "url": self.bound_url,
+            "connection_count": self.connections.len(),
+            "open_stream_count": self
+                .connections
+                .values()
+                .map(|c| c.stats.open_streams())
+                .sum::<usize>(),
+            "max_connections": self.max_connections,
+            "max_concurrent_streams": self.max_concurrent_streams,
+            "idle_evicted_count": self.idle_evicted_count,
+            "refused_connection_count": self.refused_connection_count,
+            "refused_stream_count": self.refused_stream_count,
+        }};
+        Ok(async move { Ok(dump) }.boxed().into())
     }
 
     fn handle_bound_url(&mut self) -> TransportListenerHandlerResult<Url2> {
@@ -237,19 +402,45 @@ impl TransportListenerHandler for TransportListenerQuic {
     ) -> TransportListenerHandlerResult<(Url2, TransportChannelWrite, TransportChannelRead)> {
         // if we already have an open connection to the remote end,
         // just directly try to open the bi-stream channel.
-        let maybe_bi = self.connections.get(&url).map(|con| con.open_bi());
+        let maybe_bi = match self.connections.get(&url) {
+            Some(c) => {
+                let open_streams = c.stats.open_streams();
+                if open_streams >= self.max_concurrent_streams {
+                    self.refused_stream_count += 1;
+                    return Err(format!(
+                        "QUIC stream limit reached: {} channels already open to {}",
+                        open_streams, url
+                    )
+                    .into());
+                }
+                Some((c.con.open_bi(), c.stats.open_stream()))
+            }
+            None => {
+                if !self.make_room() {
+                    return Err(self.connection_limit_error());
+                }
+                None
+            }
+        };
 
         let i_s = self.internal_sender.clone();
         Ok(async move {
             // if we already had a connection and the bi-stream
             // channel is successfully opened, return early using that
-            if let Some(maybe_bi) = maybe_bi {
-                match maybe_bi.await {
-                    Ok((bi_send, bi_recv)) => {
-                        let (write, read) = tx_bi_chan(bi_send, bi_recv);
+            if let Some((maybe_bi, guard)) = maybe_bi {
+                match tokio::time::timeout(OPEN_CHANNEL_TIMEOUT, maybe_bi).await {
+                    Err(_) => {
+                        return Err(format!(
+                            "QUIC timed out waiting for {} to accept a new channel",
+                            url
+                        )
+                        .into());
+                    }
+                    Ok(Ok((bi_send, bi_recv))) => {
+                        let (write, read) = tx_bi_chan(bi_send, bi_recv, guard);
                         return Ok((url, write, read));
                     }
-                    Err(_) => {
+                    Ok(Err(_)) => {
                         // otherwise, we should drop any existing channel
                         // we have... it no longer works for us
                         i_s.drop_connection(url.clone()).await?;
@@ -277,15 +468,27 @@ pub async fn spawn_transport_listener_quic(
     ghost_actor::GhostSender<TransportListener>,
     TransportEventReceiver,
 )> {
+    // a zero would refuse every connection or channel,
+    // or evict connections as fast as they are made.
+    if config.max_connections == 0
+        || config.idle_timeout_ms == 0
+        || config.max_concurrent_streams == 0
+    {
+        return Err(TransportError::from(
+            "QUIC max_connections, idle_timeout_ms and max_concurrent_streams must not be 0",
+        ));
+    }
     let bind_to = config
         .bind_to
         .unwrap_or_else(|| url2::url2!("kitsune-quic://0.0.0.0:0"));
-    let server_config = danger::configure_server(config.tls)
+    let idle_timeout = Duration::from_millis(config.idle_timeout_ms as u64);
+    let transport = danger::configure_transport(idle_timeout, config.max_concurrent_streams)?;
+    let server_config = danger::configure_server(config.tls, transport.clone())
         .await
         .map_err(|e| TransportError::from(format!("cert error: {:?}", e)))?;
     let mut builder = quinn::Endpoint::builder();
     builder.listen(server_config);
-    builder.default_client_config(danger::configure_client());
+    builder.default_client_config(danger::configure_client(transport));
     let (quinn_endpoint, incoming) = builder
         .bind(&crate::url_to_addr(&bind_to, crate::SCHEME).await?)
         .map_err(TransportError::other)?;
@@ -321,6 +524,18 @@ pub async fn spawn_transport_listener_quic(
         TransportResult::Ok(())
     });
 
+    let i_s = internal_sender.clone();
+    metric_task(async move {
+        loop {
+            tokio::time::delay_for(idle_timeout / 2).await;
+            if i_s.evict_idle().await.is_err() {
+                // the listener has shut down
+                break;
+            }
+        }
+        <Result<(), ()>>::Ok(())
+    });
+
     let mut bound_url = url2!(
         "{}://{}",
         crate::SCHEME,
@@ -351,6 +566,12 @@ pub async fn spawn_transport_listener_quic(
         bound_url,
         quinn_endpoint,
         connections: HashMap::new(),
+        max_connections: config.max_connections,
+        idle_timeout,
+        max_concurrent_streams: config.max_concurrent_streams as usize,
+        idle_evicted_count: 0,
+        refused_connection_count: 0,
+        refused_stream_count: 0,
     };
 
     metric_task(builder.spawn(actor));
@@ -362,7 +583,6 @@ pub async fn spawn_transport_listener_quic(
 mod danger {
     use kitsune_p2p_types::transport::TransportError;
     use kitsune_p2p_types::transport::TransportResult;
-    use once_cell::sync::Lazy;
     use quinn::Certificate;
     use quinn::CertificateChain;
     use quinn::ClientConfig;
@@ -373,8 +593,11 @@ mod danger {
     use quinn::TransportConfig;
     use std::sync::Arc;
 
-    static TRANSPORT: Lazy<Arc<quinn::TransportConfig>> = Lazy::new(|| {
-        let mut transport = quinn::TransportConfig::default();
+    pub(crate) fn configure_transport(
+        idle_timeout: std::time::Duration,
+        max_concurrent_streams: u32,
+    ) -> TransportResult<Arc<TransportConfig>> {
+        let mut transport = TransportConfig::default();
 
         // We don't use uni streams in kitsune - only bidi streams
         transport.stream_window_uni(0);
@@ -387,15 +610,18 @@ mod danger {
         // any metrics we implement will be opt-in self reporting
         transport.allow_spin(false);
 
+        // The remote may not open more channels than this at once.
+        transport.stream_window_bidi(max_concurrent_streams as u64);
+
         // see also `keep_alive_interval`.
         // right now keep_alive_interval is None,
-        // so connections will idle timeout after 20 seconds.
+        // so connections will idle timeout on their own as well.
         transport
-            .max_idle_timeout(Some(std::time::Duration::from_millis(30_000)))
-            .unwrap();
+            .max_idle_timeout(Some(idle_timeout))
+            .map_err(TransportError::other)?;
 
-        Arc::new(transport)
-    });
+        Ok(Arc::new(transport))
+    }
 
     #[allow(dead_code)]
     pub(crate) async fn configure_server(
@@ -403,6 +629,7 @@ mod danger {
             lair_keystore_api::actor::Cert,
             lair_keystore_api::actor::CertPrivKey,
         )>,
+        transport: Arc<TransportConfig>,
     ) -> TransportResult<ServerConfig> {
         let (cert, cert_priv) = match cert {
             Some(r) => r,
@@ -432,7 +659,7 @@ mod danger {
 
         let mut cfg = cfg_builder.build();
 
-        cfg.transport = TRANSPORT.clone();
+        cfg.transport = transport;
         Ok(cfg)
     }
 
@@ -458,7 +685,7 @@ mod danger {
         }
     }
 
-    pub(crate) fn configure_client() -> ClientConfig {
+    pub(crate) fn configure_client(transport: Arc<TransportConfig>) -> ClientConfig {
         let mut cfg = ClientConfigBuilder::default().build();
         let tls_cfg: &mut rustls::ClientConfig = Arc::get_mut(&mut cfg.crypto).unwrap();
         // this is only available when compiled with "dangerous_configuration" feature
@@ -466,7 +693,7 @@ mod danger {
             .dangerous()
             .set_certificate_verifier(SkipServerVerification::new());
 
-        cfg.transport = TRANSPORT.clone();
+        cfg.transport = transport;
         cfg
     }
 }
@@ -2,6 +2,7 @@
 mod tests {
     use crate::*;
     use futures::stream::StreamExt;
+    use kitsune_p2p_types::dependencies::ghost_actor;
     use kitsune_p2p_types::transport::*;
 
     #[tokio::test(threaded_scheduler)]
@@ -25,7 +26,7 @@ mod tests {
                 match evt {
                     TransportEvent::IncomingChannel(url, mut write, read) => {
                         println!("events2 incoming connection: {}", url,);
-                        let data = read.read_to_end().await;
+                        let data = read.read_to_end().await?;
                         println!("message from {} : {}", url, String::from_utf8_lossy(&data),);
                         let data = format!("echo: {}", String::from_utf8_lossy(&data)).into_bytes();
                         write.write_and_close(data).await?;
@@ -65,7 +66,7 @@ mod tests {
             while let Some(evt) = events2.next().await {
                 match evt {
                     TransportEvent::IncomingChannel(_url, mut write, read) => {
-                        let data = read.read_to_end().await;
+                        let data = read.read_to_end().await?;
                         let data = format!("echo: {}", String::from_utf8_lossy(&data)).into_bytes();
                         write.write_and_close(data).await?;
                     }
@@ -85,4 +86,100 @@ mod tests {
         );
         assert_eq!(resp.len(), 70_006);
     }
+
+    async fn spawn_echo(config: ConfigListenerQuic) -> ghost_actor::GhostSender<TransportListener> {
+        let (listener, mut events) = spawn_transport_listener_quic(config).await.unwrap();
+        metric_task(async move {
+            while let Some(evt) = events.next().await {
+                match evt {
+                    TransportEvent::IncomingChannel(_url, mut write, read) => {
+                        metric_task(async move {
+                            let data = read.read_to_end().await?;
+                            write.write_and_close(data).await?;
+                            TransportResult::Ok(())
+                        });
+                    }
+                }
+            }
+            TransportResult::Ok(())
+        });
+        listener
+    }
+
+    fn local() -> ConfigListenerQuic {
+        ConfigListenerQuic::default().set_override_host(Some("127.0.0.1"))
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_stream_limit() {
+        let listener1 = spawn_echo(local().set_max_concurrent_streams(1)).await;
+        let listener2 = spawn_echo(local()).await;
+        let bound2 = listener2.bound_url().await.unwrap();
+
+        let _open = listener1.create_channel(bound2.clone()).await.unwrap();
+        let err = listener1.create_channel(bound2).await.unwrap_err();
+        assert!(format!("{:?}", err).contains("stream limit"));
+
+        let debug = listener1.debug().await.unwrap();
+        assert_eq!(1, debug["open_stream_count"]);
+        assert_eq!(1, debug["refused_stream_count"]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_connection_limit() {
+        let listener1 = spawn_echo(local().set_max_connections(1)).await;
+        let listener2 = spawn_echo(local()).await;
+        let listener3 = spawn_echo(local()).await;
+        let bound2 = listener2.bound_url().await.unwrap();
+        let bound3 = listener3.bound_url().await.unwrap();
+
+        // the only connection is busy
+        let open = listener1.create_channel(bound2).await.unwrap();
+        let err = listener1.create_channel(bound3.clone()).await.unwrap_err();
+        assert!(format!("{:?}", err).contains("connection limit"));
+
+        // once it is idle, it makes room for the next
+        drop(open);
+        let mut resp = None;
+        for _ in 0..100 {
+            if let Ok(r) = listener1.request(bound3.clone(), b"hello".to_vec()).await {
+                resp = Some(r);
+                break;
+            }
+            tokio::time::delay_for(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(Some(b"hello".to_vec()), resp);
+
+        let debug = listener1.debug().await.unwrap();
+        assert_eq!(1, debug["connection_count"]);
+        assert!(debug["refused_connection_count"].as_u64().unwrap() >= 1);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_idle_eviction() {
+        let listener1 = spawn_echo(local().set_idle_timeout_ms(200)).await;
+        let listener2 = spawn_echo(local()).await;
+        let bound2 = listener2.bound_url().await.unwrap();
+
+        let resp = listener1.request(bound2, b"hello".to_vec()).await.unwrap();
+        assert_eq!(b"hello".to_vec(), resp);
+        assert_eq!(1, listener1.debug().await.unwrap()["connection_count"]);
+
+        tokio::time::delay_for(std::time::Duration::from_millis(600)).await;
+        let debug = listener1.debug().await.unwrap();
+        assert_eq!(0, debug["connection_count"]);
+        assert_eq!(1, debug["idle_evicted_count"]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_rejects_zero_limits() {
+        for config in vec![
+            local().set_max_connections(0),
+            local().set_idle_timeout_ms(0),
+            local().set_max_concurrent_streams(0),
+        ] {
+            let err = spawn_transport_listener_quic(config).await.unwrap_err();
+            assert!(format!("{:?}", err).contains("must not be 0"));
+        }
+    }
 }
@@ -0,0 +1,121 @@
+//! The framing that multiplexes channels over a single TLS stream.
+//!
+//! Every frame is a 9 byte header - kind (u8), channel id (u32 BE),
+//! payload length (u32 BE) - followed by the payload.
+
+use kitsune_p2p_types::transport::*;
+use std::convert::TryInto;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+/// Larger channel writes are split across several frames.
+pub(crate) const MAX_FRAME_PAYLOAD: usize = 1024 * 64;
+
+const HEADER_LEN: usize = 9;
+
+const KIND_HELLO: u8 = 0;
+const KIND_DATA: u8 = 1;
+const KIND_CLOSE: u8 = 2;
+const KIND_RESET: u8 = 3;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Frame {
+    /// The first frame sent by the connecting side:
+    /// the url it is bound to, so it can be reached on this connection.
+    Hello(String),
+
+    /// Data written to a channel.
+    Data(u32, Vec<u8>),
+
+    /// Nothing more will be written to a channel.
+    Close(u32),
+
+    /// A channel was abandoned, in both directions.
+    Reset(u32),
+}
+
+pub(crate) async fn write_frame<W: AsyncWrite + Unpin>(
+    write: &mut W,
+    frame: &Frame,
+) -> TransportResult<()> {
+    let (kind, id, payload): (u8, u32, &[u8]) = match frame {
+        Frame::Hello(url) => (KIND_HELLO, 0, url.as_bytes()),
+        Frame::Data(id, data) => (KIND_DATA, *id, data),
+        Frame::Close(id) => (KIND_CLOSE, *id, &[]),
+        Frame::Reset(id) => (KIND_RESET, *id, &[]),
+    };
+    let mut buf = Vec::with_capacity(HEADER_LEN + payload.len());
+    buf.push(kind);
+    buf.extend_from_slice(&id.to_be_bytes());
+    buf.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    buf.extend_from_slice(payload);
+    write.write_all(&buf).await.map_err(TransportError::other)?;
+    write.flush().await.map_err(TransportError::other)?;
+    Ok(())
+}
+
+/// Read the next frame, or None if the stream closed cleanly between frames.
+pub(crate) async fn read_frame<R: AsyncRead + Unpin>(
+    read: &mut R,
+) -> TransportResult<Option<Frame>> {
+    let mut header = [0_u8; HEADER_LEN];
+    match read.read_exact(&mut header).await {
+        Ok(_) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(TransportError::other(e)),
+    }
+    let id = u32::from_be_bytes(header[1..5].try_into().unwrap());
+    let len = u32::from_be_bytes(header[5..9].try_into().unwrap()) as usize;
+    if len > MAX_FRAME_PAYLOAD {
+        return Err(format!("frame payload of {} bytes is too large", len).into());
+    }
+    let mut payload = vec![0; len];
+    read.read_exact(&mut payload)
+        .await
+        .map_err(TransportError::other)?;
+    match header[0] {
+        KIND_HELLO => Ok(Some(Frame::Hello(
+            String::from_utf8(payload).map_err(TransportError::other)?,
+        ))),
+        KIND_DATA => Ok(Some(Frame::Data(id, payload))),
+        KIND_CLOSE => Ok(Some(Frame::Close(id))),
+        KIND_RESET => Ok(Some(Frame::Reset(id))),
+        kind => Err(format!("unknown frame kind {}", kind).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn frames_round_trip() {
+        let frames = vec![
+            Frame::Hello("kitsune-tcp://127.0.0.1:42".to_string()),
+            Frame::Data(1, b"hello".to_vec()),
+            Frame::Data(3, Vec::new()),
+            Frame::Close(1),
+            Frame::Reset(3),
+        ];
+        let mut buf = Vec::new();
+        for frame in &frames {
+            write_frame(&mut buf, frame).await.unwrap();
+        }
+
+        let mut read = buf.as_slice();
+        let mut out = Vec::new();
+        while let Some(frame) = read_frame(&mut read).await.unwrap() {
+            out.push(frame);
+        }
+        assert_eq!(frames, out);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rejects_oversized_frames() {
+        let mut buf = vec![KIND_DATA, 0, 0, 0, 1];
+        buf.extend_from_slice(&(MAX_FRAME_PAYLOAD as u32 + 1).to_be_bytes());
+        assert!(read_frame(&mut buf.as_slice()).await.is_err());
+    }
+}
@@ -0,0 +1,88 @@
+#![deny(missing_docs)]
+//! TCP / TLS transport module for kitsune-p2p
+//!
+//! For networks where UDP, and so QUIC, is blocked.
+//! Every peer gets a single TLS connection, over which any number of
+//! kitsune channels are multiplexed.
+//!
+//! Urls carry the digest of the TLS certificate the peer listening at them
+//! uses, as in `kitsune-tcp://1.2.3.4:5?cert=<digest>`, and both sides of a
+//! connection check the other's certificate against it.
+
+/// Re-exported dependencies.
+pub mod dependencies {
+    pub use ::kitsune_p2p_types;
+    pub use ::tokio_rustls;
+}
+
+use kitsune_p2p_types::dependencies::url2::*;
+use kitsune_p2p_types::metrics::metric_task;
+use kitsune_p2p_types::transport::TransportResult;
+use std::net::SocketAddr;
+
+const SCHEME: &str = "kitsune-tcp";
+
+/// The url query parameter holding the TLS certificate digest.
+const CERT_DIGEST_PARAM: &str = "cert";
+
+/// The digest of a TLS certificate, as it appears in urls.
+pub(crate) fn cert_digest(cert: &[u8]) -> String {
+    let digest = blake2b_simd::Params::new()
+        .hash_length(32)
+        .to_state()
+        .update(cert)
+        .finalize();
+    base64::encode_config(digest.as_bytes(), base64::URL_SAFE_NO_PAD)
+}
+
+/// The digest of the TLS certificate the peer at a `kitsune-tcp` url
+/// proves it holds when connected to, if the url names one.
+pub fn url_cert_digest(url: &Url2) -> Option<String> {
+    if url.scheme() != SCHEME {
+        return None;
+    }
+    url.query_pairs()
+        .find(|(k, _)| k == CERT_DIGEST_PARAM)
+        .map(|(_, v)| v.into_owned())
+}
+
+/// internal helper convert urls to socket addrs for binding / connection
+pub(crate) async fn url_to_addr(url: &Url2, scheme: &str) -> TransportResult<SocketAddr> {
+    if url.scheme() != scheme || url.host_str().is_none() || url.port().is_none() {
+        return Err(format!(
+            "invalid input. got: '{}', expected: '{}://host:port'",
+            url, scheme
+        )
+        .into());
+    }
+
+    let rendered = format!("{}:{}", url.host_str().unwrap(), url.port().unwrap());
+
+    if let Ok(iter) = tokio::net::lookup_host(rendered.clone()).await {
+        let mut fallback = None;
+        for addr in iter {
+            if addr.is_ipv4() {
+                return Ok(addr);
+            }
+            if fallback.is_none() {
+                fallback = Some(addr);
+            }
+        }
+        if let Some(addr) = fallback {
+            return Ok(addr);
+        }
+    }
+
+    Err(format!("could not parse '{}', as 'host:port'", rendered).into())
+}
+
+mod config;
+pub use config::*;
+
+mod frame;
+mod mux;
+
+mod listener;
+pub use listener::*;
+
+mod test;
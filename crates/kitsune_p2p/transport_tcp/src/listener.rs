@@ -0,0 +1,425 @@
+use crate::frame::*;
+use crate::mux::Connection;
+use crate::*;
+use futures::future::FutureExt;
+use futures::stream::StreamExt;
+use ghost_actor::dependencies::tracing;
+use kitsune_p2p_types::dependencies::ghost_actor;
+use kitsune_p2p_types::dependencies::serde_json;
+use kitsune_p2p_types::dependencies::url2;
+use kitsune_p2p_types::transport::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::Session;
+use tokio_rustls::webpki;
+
+/// Tls ALPN identifier for kitsune tcp connections
+const ALPN_KITSUNE_TCP_0: &[u8] = b"kitsune-tcp/0";
+
+/// A peer that does not finish the TLS handshake and say hello
+/// within this time is disconnected.
+const HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// TCP implementation of kitsune TransportListener actor.
+struct TransportListenerTcp {
+    /// internal api logic
+    internal_sender: ghost_actor::GhostSender<ListenerInner>,
+    /// incoming channel send to our owner
+    incoming_channel_sender: TransportEventSender,
+    /// the url to return on 'bound_url' calls - what we bound to
+    bound_url: Url2,
+    /// tls for outgoing connections
+    tls_connector: tokio_rustls::TlsConnector,
+    /// pool of active connections
+    connections: HashMap<Url2, Connection>,
+    /// dropping this stops accepting incoming connections
+    _stop_accepting: tokio::sync::oneshot::Sender<()>,
+}
+
+impl ghost_actor::GhostControlHandler for TransportListenerTcp {
+    fn handle_ghost_actor_shutdown(
+        mut self,
+    ) -> ghost_actor::dependencies::must_future::MustBoxFuture<'static, ()> {
+        async move {
+            let _ = self.incoming_channel_sender.close_channel();
+            // dropping our connection handles closes them once their
+            // open channels are done.
+            self.connections.clear();
+        }
+        .boxed()
+        .into()
+    }
+}
+
+ghost_actor::ghost_chan! {
+    /// Internal Sender
+    chan ListenerInner<TransportError> {
+        /// Place a connection in our pool for establishing outgoing channels.
+        /// Unless `replace`, a connection we already have is kept.
+        fn set_connection(url: Url2, con: Connection, replace: bool) -> ();
+
+        /// Drop the connection to `url` from our pool, if it has closed.
+        fn prune_connection(url: Url2) -> ();
+    }
+}
+
+impl ghost_actor::GhostHandler<ListenerInner> for TransportListenerTcp {}
+
+impl ListenerInnerHandler for TransportListenerTcp {
+    fn handle_set_connection(
+        &mut self,
+        url: Url2,
+        con: Connection,
+        replace: bool,
+    ) -> ListenerInnerHandlerResult<()> {
+        match self.connections.get(&url) {
+            Some(existing) if !replace && !existing.is_closed() => (),
+            _ => {
+                self.connections.insert(url, con);
+            }
+        }
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_prune_connection(&mut self, url: Url2) -> ListenerInnerHandlerResult<()> {
+        if self
+            .connections
+            .get(&url)
+            .map_or(false, Connection::is_closed)
+        {
+            self.connections.remove(&url);
+        }
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+}
+
+/// Prune the connection to `url` from the pool once it closes.
+fn prune_on_close(
+    i_s: ghost_actor::GhostSender<ListenerInner>,
+    url: Url2,
+) -> futures::future::BoxFuture<'static, ()> {
+    async move {
+        // errors if the listener has already shut down
+        let _ = i_s.prune_connection(url).await;
+    }
+    .boxed()
+}
+
+impl ghost_actor::GhostHandler<TransportListener> for TransportListenerTcp {}
+
+impl TransportListenerHandler for TransportListenerTcp {
+    fn handle_debug(&mut self) -> TransportListenerHandlerResult<serde_json::Value> {
+        let url = self.bound_url.clone();
+        let connection_count = self
+            .connections
+            .values()
+            .filter(|con| !con.is_closed())
+            .count();
+        Ok(async move {
+            Ok(serde_json::json! {{
+                "url": url,
+                "connection_count": connection_count,
+            }})
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_bound_url(&mut self) -> TransportListenerHandlerResult<Url2> {
+        let out = self.bound_url.clone();
+        Ok(async move { Ok(out) }.boxed().into())
+    }
+
+    fn handle_create_channel(
+        &mut self,
+        url: Url2,
+    ) -> TransportListenerHandlerResult<(Url2, TransportChannelWrite, TransportChannelRead)> {
+        // if we already have an open connection to the remote end,
+        // just open a new channel on it.
+        if let Some(con) = self.connections.get(&url) {
+            if let Ok((write, read)) = con.open_channel() {
+                return Ok(async move { Ok((url, write, read)) }.boxed().into());
+            }
+        }
+
+        // otherwise establish a new connection
+        let i_s = self.internal_sender.clone();
+        let tls_connector = self.tls_connector.clone();
+        let bound_url = self.bound_url.clone();
+        let incoming_channel_sender = self.incoming_channel_sender.clone();
+        Ok(async move {
+            let addr = crate::url_to_addr(&url, crate::SCHEME).await?;
+            tracing::debug!("TCP connect: {}", url);
+            let stream = tokio::net::TcpStream::connect(addr)
+                .await
+                .map_err(TransportError::other)?;
+            stream.set_nodelay(true).map_err(TransportError::other)?;
+            let dns_name = webpki::DNSNameRef::try_from_ascii_str("stub.stub").unwrap();
+            let mut stream =
+                tokio::time::timeout(HANDSHAKE_TIMEOUT, tls_connector.connect(dns_name, stream))
+                    .await
+                    .map_err(TransportError::other)?
+                    .map_err(TransportError::other)?;
+            let remote_digest = peer_cert_digest(stream.get_ref().1.get_peer_certificates())?;
+            if crate::url_cert_digest(&url).map_or(false, |digest| digest != remote_digest) {
+                return Err(format!("{} presented a different tls certificate", url).into());
+            }
+
+            // let the remote know where we can be reached
+            write_frame(&mut stream, &Frame::Hello(bound_url.to_string())).await?;
+
+            let con = Connection::spawn(
+                stream,
+                url.clone(),
+                true,
+                incoming_channel_sender,
+                prune_on_close(i_s.clone(), url.clone()),
+            );
+            let (write, read) = con.open_channel()?;
+            i_s.set_connection(url.clone(), con, true).await?;
+            Ok((url, write, read))
+        }
+        .boxed()
+        .into())
+    }
+}
+
+/// The digest of the certificate a peer presented during the tls handshake.
+fn peer_cert_digest(certs: Option<Vec<rustls::Certificate>>) -> TransportResult<String> {
+    certs
+        .as_ref()
+        .and_then(|certs| certs.get(0))
+        .map(|cert| crate::cert_digest(cert.as_ref()))
+        .ok_or_else(|| "peer presented no tls certificate".into())
+}
+
+/// Take an incoming tcp connection through the tls handshake and hello.
+async fn accept(
+    stream: tokio::net::TcpStream,
+    tls_acceptor: tokio_rustls::TlsAcceptor,
+    i_s: ghost_actor::GhostSender<ListenerInner>,
+    incoming_channel_sender: TransportEventSender,
+) -> TransportResult<()> {
+    let peer_addr = stream.peer_addr().map_err(TransportError::other)?;
+    stream.set_nodelay(true).map_err(TransportError::other)?;
+    let (mut stream, hello) = tokio::time::timeout(HANDSHAKE_TIMEOUT, async {
+        let mut stream = tls_acceptor
+            .accept(stream)
+            .await
+            .map_err(TransportError::other)?;
+        let hello = read_frame(&mut stream).await?;
+        TransportResult::Ok((stream, hello))
+    })
+    .await
+    .map_err(TransportError::other)??;
+    let url = match hello {
+        Some(Frame::Hello(url)) => Url2::try_parse(url).map_err(TransportError::other)?,
+        _ => return Err("expected hello frame".into()),
+    };
+    // the url is who the remote's channels come from,
+    // so it must name the certificate the remote proved it holds.
+    let remote_digest = peer_cert_digest(stream.get_ref().1.get_peer_certificates())?;
+    if crate::url_cert_digest(&url).as_ref() != Some(&remote_digest) {
+        return Err(format!("{} said hello with another tls certificate", url).into());
+    }
+    tracing::debug!("TCP accept: {} from {}", url, peer_addr);
+
+    let con = Connection::spawn(
+        stream,
+        url.clone(),
+        false,
+        incoming_channel_sender,
+        prune_on_close(i_s.clone(), url.clone()),
+    );
+
+    // only reuse this connection for our outgoing channels if the
+    // remote's claimed url actually points at it.
+    let claims_peer_ip = url
+        .host_str()
+        .and_then(|host| host.parse::<std::net::IpAddr>().ok())
+        .map_or(false, |ip| ip == peer_addr.ip());
+    if claims_peer_ip {
+        i_s.set_connection(url, con, false).await?;
+    }
+    Ok(())
+}
+
+/// Spawn a new TCP TransportListenerSender.
+pub async fn spawn_transport_listener_tcp(
+    config: ConfigListenerTcp,
+) -> TransportListenerResult<(
+    ghost_actor::GhostSender<TransportListener>,
+    TransportEventReceiver,
+)> {
+    let bind_to = config
+        .bind_to
+        .unwrap_or_else(|| url2::url2!("kitsune-tcp://0.0.0.0:0"));
+    let (tls_acceptor, tls_connector, cert_digest) = tls::configure(config.tls).await?;
+    let mut tcp_listener =
+        tokio::net::TcpListener::bind(crate::url_to_addr(&bind_to, crate::SCHEME).await?)
+            .await
+            .map_err(TransportError::other)?;
+    let local_addr = tcp_listener.local_addr().map_err(TransportError::other)?;
+
+    let (incoming_channel_sender, receiver) = futures::channel::mpsc::channel(10);
+
+    let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
+
+    let internal_sender = builder.channel_factory().create_channel().await?;
+
+    let sender = builder.channel_factory().create_channel().await?;
+
+    let (stop_accepting, mut stop_accepting_recv) = tokio::sync::oneshot::channel::<()>();
+    let i_s = internal_sender.clone();
+    let incoming_clone = incoming_channel_sender.clone();
+    metric_task(async move {
+        let mut incoming = tcp_listener.incoming();
+        loop {
+            let stream = tokio::select! {
+                stream = incoming.next() => match stream {
+                    Some(Ok(stream)) => stream,
+                    Some(Err(err)) => {
+                        tracing::warn!(?err, "TCP accept error");
+                        continue;
+                    }
+                    None => break,
+                },
+                _ = &mut stop_accepting_recv => break,
+            };
+            let accept = accept(
+                stream,
+                tls_acceptor.clone(),
+                i_s.clone(),
+                incoming_clone.clone(),
+            );
+            metric_task(async move {
+                if let Err(err) = accept.await {
+                    tracing::debug!(?err, "TCP incoming connection failed");
+                }
+                <Result<(), ()>>::Ok(())
+            });
+        }
+        TransportResult::Ok(())
+    });
+
+    let mut bound_url = url2!("{}://{}", crate::SCHEME, local_addr);
+    if let Some(override_host) = &config.override_host {
+        bound_url.set_host(Some(override_host)).unwrap();
+    } else if let Some(host) = bound_url.host_str() {
+        if host == "0.0.0.0" {
+            for iface in if_addrs::get_if_addrs().map_err(TransportError::other)? {
+                // super naive - just picking the first v4 that is not 127.0.0.1
+                let addr = iface.addr.ip();
+                if let std::net::IpAddr::V4(addr) = addr {
+                    if addr != std::net::Ipv4Addr::from([127, 0, 0, 1]) {
+                        bound_url
+                            .set_host(Some(&iface.addr.ip().to_string()))
+                            .unwrap();
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    if let Some(override_port) = config.override_port {
+        bound_url.set_port(Some(override_port)).unwrap();
+    }
+    bound_url.set_query(Some(&format!(
+        "{}={}",
+        crate::CERT_DIGEST_PARAM,
+        cert_digest
+    )));
+
+    let actor = TransportListenerTcp {
+        internal_sender,
+        incoming_channel_sender,
+        bound_url,
+        tls_connector,
+        connections: HashMap::new(),
+        _stop_accepting: stop_accepting,
+    };
+
+    metric_task(builder.spawn(actor));
+
+    Ok((sender, receiver))
+}
+
+mod tls {
+    use super::*;
+
+    /// Kitsune peers are authenticated by the certificate digest in their
+    /// url, not by a certificate authority, so any certificate is
+    /// accepted here and checked against the url once connected.
+    struct SkipServerVerification;
+
+    impl rustls::ServerCertVerifier for SkipServerVerification {
+        fn verify_server_cert(
+            &self,
+            _roots: &rustls::RootCertStore,
+            _presented_certs: &[rustls::Certificate],
+            _dns_name: webpki::DNSNameRef,
+            _ocsp_response: &[u8],
+        ) -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+            Ok(rustls::ServerCertVerified::assertion())
+        }
+    }
+
+    /// The tls acceptor and connector for our certificate, and its digest.
+    pub(crate) async fn configure(
+        cert: Option<(
+            lair_keystore_api::actor::Cert,
+            lair_keystore_api::actor::CertPrivKey,
+        )>,
+    ) -> TransportResult<(
+        tokio_rustls::TlsAcceptor,
+        tokio_rustls::TlsConnector,
+        String,
+    )> {
+        let (cert, cert_priv) = match cert {
+            Some(r) => r,
+            None => {
+                let mut options = lair_keystore_api::actor::TlsCertOptions::default();
+                options.alg = lair_keystore_api::actor::TlsCertAlg::PkcsEcdsaP256Sha256;
+                let cert = lair_keystore_api::internal::tls::tls_cert_self_signed_new_from_entropy(
+                    options,
+                )
+                .await
+                .map_err(TransportError::other)?;
+                (cert.cert_der, cert.priv_key_der)
+            }
+        };
+
+        let cert_digest = crate::cert_digest(&cert.0);
+        let cert = rustls::Certificate(cert.0.to_vec());
+        let cert_priv = rustls::PrivateKey(cert_priv.0.to_vec());
+
+        // connecting peers present their lair certificate too,
+        // so we learn their digest.
+        let root_cert =
+            rustls::Certificate(lair_keystore_api::internal::tls::WK_CA_CERT_DER.to_vec());
+        let mut root_store = rustls::RootCertStore::empty();
+        root_store.add(&root_cert).unwrap();
+        let mut server_config =
+            rustls::ServerConfig::new(rustls::AllowAnyAuthenticatedClient::new(root_store));
+        server_config
+            .set_single_cert(vec![cert.clone()], cert_priv.clone())
+            .map_err(TransportError::other)?;
+        server_config.set_protocols(&[ALPN_KITSUNE_TCP_0.to_vec()]);
+
+        let mut client_config = rustls::ClientConfig::new();
+        client_config
+            .set_single_client_cert(vec![cert], cert_priv)
+            .map_err(TransportError::other)?;
+        client_config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(SkipServerVerification));
+        client_config.set_protocols(&[ALPN_KITSUNE_TCP_0.to_vec()]);
+
+        Ok((
+            tokio_rustls::TlsAcceptor::from(Arc::new(server_config)),
+            tokio_rustls::TlsConnector::from(Arc::new(client_config)),
+            cert_digest,
+        ))
+    }
+}
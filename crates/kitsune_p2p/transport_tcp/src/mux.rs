@@ -0,0 +1,252 @@
+//! Multiplexing kitsune channels over a single TLS connection.
+//!
+//! Each side numbers the channels it opens - odd for the side that
+//! connected, even for the side that accepted - so a frame for a channel
+//! id we have not seen yet, with the remote's parity, opens a new
+//! incoming channel.
+//!
+//! One task reads every frame of a connection, so it never waits on a
+//! channel's reader: a channel whose reader falls [`CHANNEL_READ_BUFFER`]
+//! frames behind is reset, rather than stalling every other channel, and
+//! incoming channels are handed to the listener from their own task.
+//! A reset channel's reader gets an error once it has read what was
+//! buffered, and both sides stop writing to it.
+
+use crate::frame::*;
+use crate::*;
+use futures::future::BoxFuture;
+use futures::future::FutureExt;
+use futures::sink::SinkExt;
+use futures::stream::StreamExt;
+use kitsune_p2p_types::dependencies::ghost_actor::dependencies::tracing;
+use kitsune_p2p_types::transport::*;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// How many received frames a channel buffers for its reader.
+const CHANNEL_READ_BUFFER: usize = 32;
+
+/// Our end of a channel's read half.
+#[derive(Debug)]
+struct ChannelReader {
+    data: futures::channel::mpsc::Sender<Vec<u8>>,
+    reset: futures::channel::oneshot::Sender<TransportError>,
+}
+
+/// A TLS connection to a peer, carrying any number of channels.
+#[derive(Clone, Debug)]
+pub(crate) struct Connection {
+    frames: futures::channel::mpsc::Sender<Frame>,
+    readers: Arc<Mutex<HashMap<u32, ChannelReader>>>,
+    /// Set to stop a channel's writer once the channel is reset.
+    writers: Arc<Mutex<HashMap<u32, Arc<AtomicBool>>>>,
+    next_id: Arc<AtomicU32>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Connection {
+    /// Start multiplexing channels over `stream`.
+    /// Channels the remote opens are sent to `incoming` as from `url`.
+    /// `on_close` runs once the connection stops reading.
+    pub(crate) fn spawn<S>(
+        stream: S,
+        url: Url2,
+        connected: bool,
+        incoming: TransportEventSender,
+        on_close: BoxFuture<'static, ()>,
+    ) -> Self
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + 'static,
+    {
+        let (mut read, mut write) = tokio::io::split(stream);
+        let (frames, mut frames_recv) = futures::channel::mpsc::channel(10);
+        let con = Self {
+            frames,
+            readers: Arc::new(Mutex::new(HashMap::new())),
+            writers: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU32::new(if connected { 1 } else { 2 })),
+            closed: Arc::new(AtomicBool::new(false)),
+        };
+
+        let closed = con.closed.clone();
+        metric_task(async move {
+            let res = async {
+                while let Some(frame) = frames_recv.next().await {
+                    write_frame(&mut write, &frame).await?;
+                }
+                TransportResult::Ok(())
+            }
+            .await;
+            closed.store(true, Ordering::SeqCst);
+            res
+        });
+
+        let reader = con.clone();
+        metric_task(async move {
+            let res = reader.read_loop(&mut read, url, connected, incoming).await;
+            reader.closed.store(true, Ordering::SeqCst);
+            // every channel still open was cut short
+            let ids: Vec<_> = reader.readers.lock().unwrap().keys().copied().collect();
+            for id in ids {
+                reader.reset_channel(id, "connection closed");
+            }
+            on_close.await;
+            res
+        });
+
+        con
+    }
+
+    /// Once closed, a connection can open no more channels.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Open a new outgoing channel on this connection.
+    pub(crate) fn open_channel(
+        &self,
+    ) -> TransportResult<(TransportChannelWrite, TransportChannelRead)> {
+        if self.is_closed() {
+            return Err("connection closed".into());
+        }
+        let id = self.next_id.fetch_add(2, Ordering::SeqCst);
+        Ok(self.channel(id))
+    }
+
+    fn channel(&self, id: u32) -> (TransportChannelWrite, TransportChannelRead) {
+        let (data_send, data_recv) =
+            futures::channel::mpsc::channel::<Vec<u8>>(CHANNEL_READ_BUFFER);
+        let (reset_send, reset_recv) = futures::channel::oneshot::channel();
+        self.readers.lock().unwrap().insert(
+            id,
+            ChannelReader {
+                data: data_send,
+                reset: reset_send,
+            },
+        );
+        let reset = Arc::new(AtomicBool::new(false));
+        self.writers.lock().unwrap().insert(id, reset.clone());
+
+        let (write_send, mut write_recv) = futures::channel::mpsc::channel::<Vec<u8>>(10);
+        let mut frames = self.frames.clone();
+        let writers = self.writers.clone();
+        metric_task(async move {
+            let res = async {
+                while let Some(data) = write_recv.next().await {
+                    for chunk in data.chunks(MAX_FRAME_PAYLOAD) {
+                        if reset.load(Ordering::SeqCst) {
+                            return Err("channel reset".into());
+                        }
+                        frames
+                            .send(Frame::Data(id, chunk.to_vec()))
+                            .await
+                            .map_err(TransportError::other)?;
+                    }
+                }
+                frames
+                    .send(Frame::Close(id))
+                    .await
+                    .map_err(TransportError::other)?;
+                TransportResult::Ok(())
+            }
+            .await;
+            writers.lock().unwrap().remove(&id);
+            res
+        });
+
+        let write: TransportChannelWrite = Box::new(write_send.sink_map_err(TransportError::other));
+        // the reason a channel was reset follows the data read before it
+        let reset = reset_recv
+            .into_stream()
+            .filter_map(|reason| futures::future::ready(reason.ok().map(Err)));
+        let read: TransportChannelRead = Box::new(data_recv.map(Ok).chain(reset));
+        (write, read)
+    }
+
+    /// Abandon a channel: its reader gets `reason` as an error after
+    /// any data it has yet to read, and its writer stops.
+    fn reset_channel(&self, id: u32, reason: &str) {
+        if let Some(reader) = self.readers.lock().unwrap().remove(&id) {
+            let _ = reader.reset.send(reason.into());
+        }
+        if let Some(reset) = self.writers.lock().unwrap().get(&id) {
+            reset.store(true, Ordering::SeqCst);
+        }
+    }
+
+    async fn read_loop<R: tokio::io::AsyncRead + Unpin>(
+        &self,
+        read: &mut R,
+        url: Url2,
+        connected: bool,
+        incoming: TransportEventSender,
+    ) -> TransportResult<()> {
+        let remote_parity = if connected { 0 } else { 1 };
+        let mut last_remote_id = 0;
+        while let Some(frame) = read_frame(read).await? {
+            let (id, data) = match frame {
+                Frame::Data(id, data) => (id, Some(data)),
+                Frame::Close(id) => (id, None),
+                Frame::Reset(id) => {
+                    self.reset_channel(id, "channel reset by the remote");
+                    continue;
+                }
+                Frame::Hello(_) => return Err("unexpected hello frame".into()),
+            };
+
+            if id % 2 == remote_parity && id > last_remote_id {
+                last_remote_id = id;
+                if incoming.is_closed() {
+                    // our listener is shutting down
+                    break;
+                }
+                // hand the channel over in its own task,
+                // its first frames buffer meanwhile.
+                let (write, read) = self.channel(id);
+                let mut incoming = incoming.clone();
+                let url = url.clone();
+                metric_task(async move {
+                    incoming
+                        .send(TransportEvent::IncomingChannel(url, write, read))
+                        .await
+                        .map_err(TransportError::other)
+                });
+            }
+
+            match data {
+                Some(data) => {
+                    let too_slow = {
+                        let mut readers = self.readers.lock().unwrap();
+                        match readers.get_mut(&id).map(|r| r.data.try_send(data)) {
+                            Some(Err(e)) if e.is_full() => true,
+                            Some(Err(_)) => {
+                                // it's ok if the reader was dropped.
+                                readers.remove(&id);
+                                false
+                            }
+                            _ => false,
+                        }
+                    };
+                    if too_slow {
+                        tracing::warn!("resetting channel {} from {}: reader is too slow", id, url);
+                        self.reset_channel(id, "channel reset: reader is too slow");
+                        // so the remote stops writing to it
+                        self.frames
+                            .clone()
+                            .send(Frame::Reset(id))
+                            .await
+                            .map_err(TransportError::other)?;
+                    }
+                }
+                None => {
+                    self.readers.lock().unwrap().remove(&id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
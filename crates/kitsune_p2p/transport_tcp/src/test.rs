@@ -0,0 +1,219 @@
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use futures::stream::StreamExt;
+    use kitsune_p2p_types::transport::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_message() {
+        let (listener1, _events1) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        let bound1 = listener1.bound_url().await.unwrap();
+        assert_eq!("127.0.0.1", bound1.host_str().unwrap());
+        println!("listener1 bound to: {}", bound1);
+
+        let (listener2, mut events2) = spawn_transport_listener_tcp(ConfigListenerTcp::default())
+            .await
+            .unwrap();
+
+        metric_task(async move {
+            while let Some(evt) = events2.next().await {
+                match evt {
+                    TransportEvent::IncomingChannel(url, mut write, read) => {
+                        println!("events2 incoming connection: {}", url,);
+                        let data = read.read_to_end().await?;
+                        println!("message from {} : {}", url, String::from_utf8_lossy(&data),);
+                        let data = format!("echo: {}", String::from_utf8_lossy(&data)).into_bytes();
+                        write.write_and_close(data).await?;
+                    }
+                }
+            }
+            TransportResult::Ok(())
+        });
+
+        let bound2 = listener2.bound_url().await.unwrap();
+        println!("listener2 bound to: {}", bound2);
+
+        let resp = listener1.request(bound2, b"hello".to_vec()).await.unwrap();
+
+        println!("got resp: {}", String::from_utf8_lossy(&resp));
+
+        assert_eq!("echo: hello", &String::from_utf8_lossy(&resp));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_large_message() {
+        let (listener1, _events1) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        let bound1 = listener1.bound_url().await.unwrap();
+        assert_eq!("127.0.0.1", bound1.host_str().unwrap());
+        println!("listener1 bound to: {}", bound1);
+
+        let (listener2, mut events2) = spawn_transport_listener_tcp(ConfigListenerTcp::default())
+            .await
+            .unwrap();
+
+        metric_task(async move {
+            while let Some(evt) = events2.next().await {
+                match evt {
+                    TransportEvent::IncomingChannel(_url, mut write, read) => {
+                        let data = read.read_to_end().await?;
+                        let data = format!("echo: {}", String::from_utf8_lossy(&data)).into_bytes();
+                        write.write_and_close(data).await?;
+                    }
+                }
+            }
+            TransportResult::Ok(())
+        });
+
+        let bound2 = listener2.bound_url().await.unwrap();
+
+        let large_msg = std::iter::repeat(b"a"[0]).take(70_000).collect::<Vec<_>>();
+        let resp = listener1.request(bound2, large_msg.clone()).await.unwrap();
+
+        assert_eq!(
+            format!("echo: {}", String::from_utf8_lossy(&large_msg)),
+            String::from_utf8_lossy(&resp)
+        );
+        assert_eq!(resp.len(), 70_006);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_channels_share_one_connection() {
+        let (listener1, _events1) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        let (listener2, mut events2) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        metric_task(async move {
+            while let Some(evt) = events2.next().await {
+                match evt {
+                    TransportEvent::IncomingChannel(_url, mut write, read) => {
+                        metric_task(async move {
+                            let data = read.read_to_end().await?;
+                            write.write_and_close(data).await?;
+                            TransportResult::Ok(())
+                        });
+                    }
+                }
+            }
+            TransportResult::Ok(())
+        });
+
+        let bound1 = listener1.bound_url().await.unwrap();
+        let bound2 = listener2.bound_url().await.unwrap();
+
+        for i in 0..10_u8 {
+            let resp = listener1.request(bound2.clone(), vec![i]).await.unwrap();
+            assert_eq!(vec![i], resp);
+        }
+
+        // the connection listener2 accepted is reused in the other direction
+        let (_url, mut write, read) = listener2.create_channel(bound1).await.unwrap();
+        write.write_and_close(b"back".to_vec()).await.unwrap();
+        drop(read);
+
+        assert_eq!(1, listener1.debug().await.unwrap()["connection_count"]);
+        assert_eq!(1, listener2.debug().await.unwrap()["connection_count"]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_urls_name_the_tls_certificate() {
+        let (listener1, _events1) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        let (listener2, _events2) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        let bound2 = listener2.bound_url().await.unwrap();
+        assert!(url_cert_digest(&bound2).is_some());
+
+        // someone else's certificate at the same address is refused
+        let mut imposter = bound2.clone();
+        imposter.set_query(Some("cert=bm90LXRoZS1yaWdodC1jZXJ0"));
+        assert!(listener1.create_channel(imposter).await.is_err());
+        assert!(listener1.create_channel(bound2).await.is_ok());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_stalled_channel_does_not_block_others() {
+        let (listener1, _events1) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        let (listener2, mut events2) = spawn_transport_listener_tcp(
+            ConfigListenerTcp::default().set_override_host(Some("127.0.0.1")),
+        )
+        .await
+        .unwrap();
+
+        let (stalled_send, stalled_recv) = tokio::sync::oneshot::channel();
+        metric_task(async move {
+            // never read the first channel
+            let mut stalled_send = Some(stalled_send);
+            while let Some(evt) = events2.next().await {
+                match evt {
+                    TransportEvent::IncomingChannel(_url, mut write, read) => {
+                        if let Some(stalled_send) = stalled_send.take() {
+                            let _ = stalled_send.send((write, read));
+                            continue;
+                        }
+                        metric_task(async move {
+                            let data = read.read_to_end().await?;
+                            write.write_and_close(data).await?;
+                            TransportResult::Ok(())
+                        });
+                    }
+                }
+            }
+            TransportResult::Ok(())
+        });
+
+        let bound2 = listener2.bound_url().await.unwrap();
+
+        let big_msg = vec![0_u8; 1024 * 64 * 64];
+        let (_url, mut write, read) = listener1.create_channel(bound2.clone()).await.unwrap();
+        write.write_and_close(big_msg.clone()).await.unwrap();
+        let (_stalled_write, stalled) = stalled_recv.await.unwrap();
+
+        let resp = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            listener1.request(bound2, b"hello".to_vec()),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(b"hello".to_vec(), resp);
+
+        // the stalled channel was reset rather than buffered,
+        // which its reader and the remote both see as an error
+        assert!(stalled.read_to_end().await.is_err());
+        let remote = tokio::time::timeout(std::time::Duration::from_secs(5), read.read_to_end())
+            .await
+            .unwrap();
+        assert!(remote.is_err());
+    }
+}
@@ -3,6 +3,7 @@
 use futures::future::FutureExt;
 use futures::sink::SinkExt;
 use futures::stream::StreamExt;
+use futures::stream::TryStreamExt;
 
 observability::metrics!(KitsuneTransportMetrics, Write, Read);
 
@@ -53,36 +54,40 @@ impl From<TransportError> for () {
 /// Result type for remote communication.
 pub type TransportResult<T> = Result<T, TransportError>;
 
-/// Receiver side of the channel
+/// Receiver side of the channel.
+/// An error means the channel was reset, and no more data will arrive.
 pub type TransportChannelRead =
-    Box<dyn futures::stream::Stream<Item = Vec<u8>> + Send + Unpin + 'static>;
+    Box<dyn futures::stream::Stream<Item = TransportResult<Vec<u8>>> + Send + Unpin + 'static>;
 
 /// Extension trait for channel readers
 pub trait TransportChannelReadExt {
     /// Read the stream to close into a single byte vec.
-    fn read_to_end(self)
-        -> ghost_actor::dependencies::must_future::MustBoxFuture<'static, Vec<u8>>;
+    /// Fails if the channel is reset before it closes.
+    fn read_to_end(
+        self,
+    ) -> ghost_actor::dependencies::must_future::MustBoxFuture<'static, TransportResult<Vec<u8>>>;
 }
 
-impl<T: futures::stream::Stream<Item = Vec<u8>> + Send + Unpin + 'static> TransportChannelReadExt
-    for T
+impl<T: futures::stream::Stream<Item = TransportResult<Vec<u8>>> + Send + Unpin + 'static>
+    TransportChannelReadExt for T
 {
     fn read_to_end(
         self,
-    ) -> ghost_actor::dependencies::must_future::MustBoxFuture<'static, Vec<u8>> {
+    ) -> ghost_actor::dependencies::must_future::MustBoxFuture<'static, TransportResult<Vec<u8>>>
+    {
         async move {
             let r = self
-                .fold(Vec::new(), |mut acc, x| async move {
+                .try_fold(Vec::new(), |mut acc, x| async move {
                     acc.extend_from_slice(&x);
-                    acc
+                    Ok(acc)
                 })
-                .await;
+                .await?;
             KitsuneTransportMetrics::count_filter(
                 KitsuneTransportMetrics::Read,
                 r.len(),
                 "transport",
             );
-            r
+            Ok(r)
         }
         .boxed()
         .into()
@@ -130,15 +135,15 @@ pub fn create_transport_channel_pair() -> (
     (TransportChannelWrite, TransportChannelRead),
     (TransportChannelWrite, TransportChannelRead),
 ) {
-    let (send1, recv1) = futures::channel::mpsc::channel(10);
+    let (send1, recv1) = futures::channel::mpsc::channel::<Vec<u8>>(10);
     let send1 = send1.sink_map_err(TransportError::other);
-    let (send2, recv2) = futures::channel::mpsc::channel(10);
+    let (send2, recv2) = futures::channel::mpsc::channel::<Vec<u8>>(10);
     let send2 = send2.sink_map_err(TransportError::other);
 
     let send1 = Box::new(send1);
-    let recv1 = Box::new(recv1);
+    let recv1 = Box::new(recv1.map(TransportResult::Ok));
     let send2 = Box::new(send2);
-    let recv2 = Box::new(recv2);
+    let recv2 = Box::new(recv2.map(TransportResult::Ok));
 
     ((send1, recv2), (send2, recv1))
 }
@@ -209,7 +214,7 @@ impl<T: TransportListenerSender> TransportListenerSenderExt for T {
                 "transport",
             );
             write.write_and_close(data).await?;
-            let r = read.read_to_end().await;
+            let r = read.read_to_end().await?;
             KitsuneTransportMetrics::count_filter(
                 KitsuneTransportMetrics::Read,
                 r.len(),
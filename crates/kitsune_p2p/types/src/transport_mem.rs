@@ -10,6 +10,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+pub mod sim;
+
 const SCHEME: &str = "kitsune-mem";
 
 static CORE: Lazy<Arc<Mutex<HashMap<url2::Url2, TransportEventSender>>>> =
@@ -42,6 +44,7 @@ fn drop_core(url: url2::Url2) {
     crate::metrics::metric_task(async move {
         let mut lock = CORE.lock().await;
         lock.remove(&url);
+        sim::forget_core(&url);
 
         <Result<(), ()>>::Ok(())
     });
@@ -119,7 +122,17 @@ impl TransportListenerHandler for InnerListen {
         Ok(async move {
             let mut evt_send = get_core(url.clone()).await?;
 
+            let conditions = match sim::link(&this_url, &url) {
+                sim::Link::Deliver(conditions) => conditions,
+                sim::Link::Drop => {
+                    let (send, recv) = sim::dropped_channel();
+                    return Ok((url, send, recv));
+                }
+            };
+
             let ((send1, recv1), (send2, recv2)) = create_transport_channel_pair();
+            let send1 = sim::delay_write(send1, conditions.clone());
+            let send2 = sim::delay_write(send2, conditions);
 
             // if we don't spawn here there can be a deadlock on
             // incoming_channel trying to process all channel data
@@ -149,7 +162,7 @@ mod tests {
             while let Some(evt) = recv.next().await {
                 match evt {
                     TransportEvent::IncomingChannel(url, mut write, read) => {
-                        let data = read.read_to_end().await;
+                        let data = read.read_to_end().await?;
                         let data = format!("echo({}): {}", url, String::from_utf8_lossy(&data),);
                         write.write_and_close(data.into_bytes()).await?;
                     }
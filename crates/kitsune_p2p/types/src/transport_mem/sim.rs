@@ -0,0 +1,299 @@
+//! Simulated network conditions for the mem-only transport.
+//!
+//! By default mem cores talk to each other instantly and reliably.
+//! Tests can degrade the links between cores at any time:
+//! - [`set_core_conditions`] degrades every link to or from one core.
+//! - [`set_link_conditions`] degrades the link between two cores.
+//! - [`set_partition`] cuts a named group of cores off from every core
+//!   outside it, until [`heal_partition`].
+//!
+//! Conditions are applied when a channel is created. A dropped or
+//! partitioned channel is never delivered to the remote, and the caller's
+//! read never completes, so the caller sees a timeout, just like on a real
+//! network. Latency, jitter and bandwidth delay every chunk written in
+//! either direction, so separate channels may overtake each other, while
+//! data within a channel stays in order.
+
+use crate::transport::*;
+use futures::sink::SinkExt;
+use futures::stream::StreamExt;
+use once_cell::sync::Lazy;
+use rand::Rng;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Conditions simulated on links between mem cores.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemLinkConditions {
+    /// Delay added to every chunk of data.
+    pub latency: Duration,
+
+    /// Up to this much more random delay is added to every chunk of data.
+    pub jitter: Duration,
+
+    /// Chunks of data are further delayed as if sent over a link of this
+    /// many bytes per second. Default: None = unlimited.
+    pub bandwidth_bytes_per_sec: Option<u64>,
+
+    /// The probability, from 0.0 to 1.0, that a new channel is dropped.
+    pub drop_rate: f64,
+}
+
+impl MemLinkConditions {
+    /// Conditions for a link that passes through both `self` and `other`.
+    fn and(&self, other: &Self) -> Self {
+        Self {
+            latency: self.latency + other.latency,
+            jitter: self.jitter + other.jitter,
+            bandwidth_bytes_per_sec: match (
+                self.bandwidth_bytes_per_sec,
+                other.bandwidth_bytes_per_sec,
+            ) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+            drop_rate: 1.0 - (1.0 - self.drop_rate) * (1.0 - other.drop_rate),
+        }
+    }
+
+    fn is_instant(&self) -> bool {
+        self.latency == Duration::default()
+            && self.jitter == Duration::default()
+            && self.bandwidth_bytes_per_sec.is_none()
+    }
+
+    /// How long a chunk of `len` bytes takes to cross the link.
+    fn delay(&self, len: usize) -> Duration {
+        let mut delay = self.latency;
+        if self.jitter > Duration::default() {
+            delay += rand::thread_rng().gen_range(Duration::default(), self.jitter);
+        }
+        if let Some(bytes_per_sec) = self.bandwidth_bytes_per_sec {
+            delay += Duration::from_secs_f64(len as f64 / bytes_per_sec.max(1) as f64);
+        }
+        delay
+    }
+}
+
+#[derive(Default)]
+struct SimState {
+    cores: HashMap<url2::Url2, MemLinkConditions>,
+    links: HashMap<(url2::Url2, url2::Url2), MemLinkConditions>,
+    partitions: HashMap<String, HashSet<url2::Url2>>,
+}
+
+static SIM: Lazy<Mutex<SimState>> = Lazy::new(|| Mutex::new(SimState::default()));
+
+/// Apply `conditions` to every link to or from the core bound at `url`,
+/// on top of any conditions on the other end. `None` restores the default.
+pub fn set_core_conditions(url: &url2::Url2, conditions: Option<MemLinkConditions>) {
+    let mut sim = SIM.lock().unwrap();
+    match conditions {
+        Some(conditions) => sim.cores.insert(url.clone(), conditions),
+        None => sim.cores.remove(url),
+    };
+}
+
+/// Apply `conditions` to the link between the cores bound at `a` and `b`,
+/// in both directions, instead of the conditions of either core.
+/// `None` restores the default.
+pub fn set_link_conditions(a: &url2::Url2, b: &url2::Url2, conditions: Option<MemLinkConditions>) {
+    let mut sim = SIM.lock().unwrap();
+    let key = link_key(a, b);
+    match conditions {
+        Some(conditions) => sim.links.insert(key, conditions),
+        None => sim.links.remove(&key),
+    };
+}
+
+/// Cut the cores bound at `urls` off from every core not in `urls`,
+/// replacing any partition with the same name.
+pub fn set_partition(name: &str, urls: impl IntoIterator<Item = url2::Url2>) {
+    SIM.lock()
+        .unwrap()
+        .partitions
+        .insert(name.to_string(), urls.into_iter().collect());
+}
+
+/// Remove the named partition.
+pub fn heal_partition(name: &str) {
+    SIM.lock().unwrap().partitions.remove(name);
+}
+
+/// Forget everything about a core that is no longer bound.
+pub(crate) fn forget_core(url: &url2::Url2) {
+    let mut sim = SIM.lock().unwrap();
+    sim.cores.remove(url);
+    sim.links.retain(|(a, b), _| a != url && b != url);
+}
+
+fn link_key(a: &url2::Url2, b: &url2::Url2) -> (url2::Url2, url2::Url2) {
+    if a.as_str() <= b.as_str() {
+        (a.clone(), b.clone())
+    } else {
+        (b.clone(), a.clone())
+    }
+}
+
+/// What happens to a new channel from `from` to `to`.
+pub(crate) enum Link {
+    /// Deliver it with these conditions.
+    Deliver(MemLinkConditions),
+    /// Lose it.
+    Drop,
+}
+
+pub(crate) fn link(from: &url2::Url2, to: &url2::Url2) -> Link {
+    let sim = SIM.lock().unwrap();
+    if sim
+        .partitions
+        .values()
+        .any(|p| p.contains(from) != p.contains(to))
+    {
+        return Link::Drop;
+    }
+    let conditions = match sim.links.get(&link_key(from, to)) {
+        Some(conditions) => conditions.clone(),
+        None => {
+            let none = MemLinkConditions::default();
+            sim.cores
+                .get(from)
+                .unwrap_or(&none)
+                .and(sim.cores.get(to).unwrap_or(&none))
+        }
+    };
+    if conditions.drop_rate > 0.0 && rand::thread_rng().gen_bool(conditions.drop_rate.min(1.0)) {
+        return Link::Drop;
+    }
+    Link::Deliver(conditions)
+}
+
+/// The local halves of a channel that was dropped:
+/// writes go nowhere and reads never complete.
+pub(crate) fn dropped_channel() -> (TransportChannelWrite, TransportChannelRead) {
+    let write = futures::sink::drain::<Vec<u8>>().sink_map_err(TransportError::other);
+    let read = futures::stream::pending::<TransportResult<Vec<u8>>>();
+    (Box::new(write), Box::new(read))
+}
+
+/// Delay every chunk written to `write` according to `conditions`.
+pub(crate) fn delay_write(
+    write: TransportChannelWrite,
+    conditions: MemLinkConditions,
+) -> TransportChannelWrite {
+    if conditions.is_instant() {
+        return write;
+    }
+    let (send, mut recv) = futures::channel::mpsc::channel::<Vec<u8>>(10);
+    crate::metrics::metric_task(async move {
+        let mut write = write;
+        while let Some(chunk) = recv.next().await {
+            tokio::time::delay_for(conditions.delay(chunk.len())).await;
+            // it's ok if this errors... the remote has hung up.
+            if write.send(chunk).await.is_err() {
+                break;
+            }
+        }
+        let _ = write.close().await;
+
+        <Result<(), ()>>::Ok(())
+    });
+    Box::new(send.sink_map_err(TransportError::other))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport_mem::*;
+
+    async fn echo_core() -> ghost_actor::GhostSender<TransportListener> {
+        let (bind, mut evt) = spawn_bind_transport_mem().await.unwrap();
+        crate::metrics::metric_task(async move {
+            while let Some(TransportEvent::IncomingChannel(_, mut write, read)) = evt.next().await {
+                let data = read.read_to_end().await?;
+                write.write_and_close(data).await?;
+            }
+            TransportResult::Ok(())
+        });
+        bind
+    }
+
+    async fn request(
+        from: &ghost_actor::GhostSender<TransportListener>,
+        to: &url2::Url2,
+    ) -> Option<Duration> {
+        let start = std::time::Instant::now();
+        let res = tokio::time::timeout(
+            Duration::from_millis(500),
+            from.request(to.clone(), b"test".to_vec()),
+        )
+        .await
+        .ok()?
+        .unwrap();
+        assert_eq!(b"test".to_vec(), res);
+        Some(start.elapsed())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn it_delays_degraded_links() {
+        let a = echo_core().await;
+        let b = echo_core().await;
+        let c = echo_core().await;
+        let b_url = b.bound_url().await.unwrap();
+        let c_url = c.bound_url().await.unwrap();
+
+        set_core_conditions(
+            &b_url,
+            Some(MemLinkConditions {
+                latency: Duration::from_millis(50),
+                ..Default::default()
+            }),
+        );
+        // There and back again.
+        assert!(request(&a, &b_url).await.unwrap() >= Duration::from_millis(100));
+        assert!(request(&a, &c_url).await.unwrap() < Duration::from_millis(100));
+
+        set_link_conditions(&b_url, &c_url, Some(MemLinkConditions::default()));
+        assert!(request(&c, &b_url).await.unwrap() < Duration::from_millis(100));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn it_drops_channels() {
+        let a = echo_core().await;
+        let b = echo_core().await;
+        let b_url = b.bound_url().await.unwrap();
+
+        set_core_conditions(
+            &b_url,
+            Some(MemLinkConditions {
+                drop_rate: 1.0,
+                ..Default::default()
+            }),
+        );
+        assert_eq!(None, request(&a, &b_url).await);
+
+        set_core_conditions(&b_url, None);
+        assert!(request(&a, &b_url).await.is_some());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn it_partitions_and_heals() {
+        let a = echo_core().await;
+        let b = echo_core().await;
+        let c = echo_core().await;
+        let a_url = a.bound_url().await.unwrap();
+        let b_url = b.bound_url().await.unwrap();
+        let c_url = c.bound_url().await.unwrap();
+
+        let name = nanoid::nanoid!();
+        set_partition(&name, vec![a_url.clone(), b_url.clone()]);
+        assert!(request(&a, &b_url).await.is_some());
+        assert_eq!(None, request(&a, &c_url).await);
+        assert_eq!(None, request(&c, &a_url).await);
+
+        heal_partition(&name);
+        assert!(request(&c, &a_url).await.is_some());
+    }
+}
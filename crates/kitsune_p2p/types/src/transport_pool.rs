@@ -206,7 +206,7 @@ mod tests {
             while let Some(evt) = recv.next().await {
                 match evt {
                     TransportEvent::IncomingChannel(url, mut write, read) => {
-                        let data = read.read_to_end().await;
+                        let data = read.read_to_end().await?;
                         let data = format!("echo({}): {}", url, String::from_utf8_lossy(&data),);
                         write.write_and_close(data.into_bytes()).await?;
                     }
@@ -1,13 +1,15 @@
 use crate::{
+    cache::ResourceCache,
     error::{BundleError, MrBundleResult},
     location::Location,
     manifest::Manifest,
     resource::ResourceBytes,
+    signature::BundleSignature,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
@@ -32,6 +34,12 @@ where
     /// are always relative paths (relative to the root_dir).
     resources: ResourceMap,
 
+    /// Publisher signatures embedded in the bundle.
+    /// Each one signs the `signing_bytes`, which don't include the
+    /// signatures themselves.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    signatures: Vec<BundleSignature>,
+
     /// Since the Manifest may contain local paths referencing unbundled files,
     /// on the local filesystem, we must have an absolute path at runtime for
     /// normalizing those locations.
@@ -44,6 +52,11 @@ where
     //       struct into two versions for each case.
     #[serde(skip)]
     root_dir: Option<PathBuf>,
+
+    /// Where remote resources with pinned hashes are cached.
+    /// If None, the [`ResourceCache::default_dir`] is used.
+    #[serde(skip)]
+    resource_cache: Option<ResourceCache>,
 }
 
 impl<M> Bundle<M>
@@ -101,7 +114,9 @@ where
         Ok(Self {
             manifest,
             resources,
+            signatures: Vec::new(),
             root_dir,
+            resource_cache: None,
         })
     }
 
@@ -117,15 +132,44 @@ where
         Ok(ffs::write(path, &self.encode()?).await?)
     }
 
+    /// Use this cache for remote resources with pinned hashes,
+    /// instead of the one in the default location
+    pub fn set_resource_cache(&mut self, cache: ResourceCache) {
+        self.resource_cache = Some(cache);
+    }
+
+    /// Get the resource at a location specified by the manifest.
+    /// If the manifest pins the hash of a non-bundled location, the resource
+    /// must match it, and remote resources are kept in the resource cache.
     pub async fn resolve(&self, location: &Location) -> MrBundleResult<Cow<'_, ResourceBytes>> {
-        let bytes = match &location.normalize(self.root_dir.as_ref())? {
-            Location::Bundled(path) => Cow::Borrowed(
+        let pinned = self.manifest.pinned_hashes().remove(location);
+        let bytes = match (&location.normalize(self.root_dir.as_ref())?, pinned) {
+            (Location::Bundled(path), _) => Cow::Borrowed(
                 self.resources
                     .get(path)
                     .ok_or_else(|| BundleError::BundledResourceMissing(path.clone()))?,
             ),
-            Location::Path(path) => Cow::Owned(crate::location::resolve_local(path).await?),
-            Location::Url(url) => Cow::Owned(crate::location::resolve_remote(url).await?),
+            (Location::Path(path), None) => Cow::Owned(crate::location::resolve_local(path).await?),
+            (Location::Path(path), Some(hash)) => Cow::Owned(crate::location::check_pinned_hash(
+                location,
+                &hash,
+                crate::location::resolve_local(path).await?,
+                M::resource_hash,
+            )?),
+            (Location::Url(url), None) => Cow::Owned(crate::location::resolve_remote(url).await?),
+            (Location::Url(url), Some(hash)) => {
+                let cache = self.resource_cache.clone().unwrap_or_default();
+                Cow::Owned(
+                    crate::location::resolve_pinned_remote(
+                        location,
+                        url,
+                        &hash,
+                        &cache,
+                        M::resource_hash,
+                    )
+                    .await?,
+                )
+            }
         };
         Ok(bytes)
     }
@@ -161,6 +205,47 @@ where
         &self.resources
     }
 
+    /// The canonical bytes which publishers sign: the manifest and
+    /// the bundled resources in path order, without any signatures.
+    /// Unlike `encode`, this is stable for the same bundle contents.
+    pub fn signing_bytes(&self) -> MrBundleResult<Vec<u8>> {
+        #[derive(Serialize)]
+        struct Signable<'a, M> {
+            manifest: &'a M,
+            resources: BTreeMap<&'a PathBuf, &'a ResourceBytes>,
+        }
+        Ok(rmp_serde::to_vec_named(&Signable {
+            manifest: &self.manifest,
+            resources: self.resources.iter().collect(),
+        })?)
+    }
+
+    /// The non-bundled locations whose content isn't pinned by the manifest.
+    /// A signature over this bundle says nothing about what they contain.
+    pub fn unpinned_locations(&self) -> Vec<Location> {
+        self.manifest
+            .locations()
+            .into_iter()
+            .filter(|location| {
+                !matches!(location, Location::Bundled(_)) && !self.manifest.is_pinned(location)
+            })
+            .collect()
+    }
+
+    /// The publisher signatures embedded in this bundle
+    pub fn signatures(&self) -> &[BundleSignature] {
+        &self.signatures
+    }
+
+    /// Embed a publisher signature in this bundle.
+    /// Signing doesn't change the `signing_bytes`, so a bundle can carry
+    /// signatures from several publishers.
+    pub fn add_signature(&mut self, signature: BundleSignature) {
+        if !self.signatures.contains(&signature) {
+            self.signatures.push(signature);
+        }
+    }
+
     /// An arbitrary and opaque encoding of the bundle data into a byte array
     pub fn encode(&self) -> MrBundleResult<Vec<u8>> {
         crate::encode(self)
@@ -217,6 +302,36 @@ mod tests {
     #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
     struct Thing(u32);
 
+    #[test]
+    fn signatures_are_not_signed() {
+        let manifest = TestManifest(vec![
+            Location::Bundled("1.thing".into()),
+            Location::Bundled("2.thing".into()),
+        ]);
+        let resources = vec![("1.thing".into(), vec![1]), ("2.thing".into(), vec![2])];
+        let mut bundle = Bundle::new_unchecked(manifest.clone(), resources.clone()).unwrap();
+        let unsigned_bytes = bundle.signing_bytes().unwrap();
+
+        // The same contents always have the same signing bytes
+        let reversed = Bundle::new_unchecked(manifest, resources.into_iter().rev()).unwrap();
+        assert_eq!(reversed.signing_bytes().unwrap(), unsigned_bytes);
+
+        let signature = BundleSignature {
+            publisher: vec![1; 32],
+            signature: vec![2; 64],
+        };
+        bundle.add_signature(signature.clone());
+        bundle.add_signature(signature.clone());
+        assert_eq!(bundle.signatures(), &[signature][..]);
+        assert_eq!(bundle.signing_bytes().unwrap(), unsigned_bytes);
+
+        // Signatures survive encoding, and unsigned bundles still decode
+        let decoded: Bundle<TestManifest> = Bundle::decode(&bundle.encode().unwrap()).unwrap();
+        assert_eq!(decoded, bundle);
+        let decoded: Bundle<TestManifest> = Bundle::decode(&reversed.encode().unwrap()).unwrap();
+        assert!(decoded.signatures().is_empty());
+    }
+
     #[tokio::test]
     async fn bundle_validation() {
         let manifest = TestManifest(vec![
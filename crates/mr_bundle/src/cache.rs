@@ -0,0 +1,68 @@
+use crate::{
+    error::MrBundleResult,
+    resource::{ResourceBytes, ResourceHash},
+};
+use std::path::{Path, PathBuf};
+
+/// A local content-addressed store of resources fetched from remote
+/// locations whose hash is pinned in a manifest.
+///
+/// Each resource is kept in a file named by its hash, so once a pinned
+/// resource has been fetched it can be resolved again without the network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResourceCache {
+    dir: PathBuf,
+}
+
+impl ResourceCache {
+    /// Use `dir` to store cached resources. It's created when first written to.
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The user's cache directory, e.g. "~/.cache/mr_bundle/resources",
+    /// or the system temp directory if there isn't one.
+    pub fn default_dir() -> PathBuf {
+        directories::BaseDirs::new()
+            .map(|dirs| dirs.cache_dir().to_owned())
+            .unwrap_or_else(std::env::temp_dir)
+            .join("mr_bundle")
+            .join("resources")
+    }
+
+    /// The directory this cache stores resources in
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, hash: &ResourceHash) -> PathBuf {
+        self.dir.join(hash.to_string())
+    }
+
+    /// Get a cached resource, whose content is hashed with `hash_of`.
+    /// A cached file whose content no longer matches its hash is treated as missing.
+    pub async fn get(
+        &self,
+        hash: &ResourceHash,
+        hash_of: fn(&[u8]) -> ResourceHash,
+    ) -> Option<ResourceBytes> {
+        let bytes = ffs::read(self.path(hash)).await.ok()?;
+        if hash_of(&bytes) == *hash {
+            Some(bytes)
+        } else {
+            None
+        }
+    }
+
+    /// Store a resource which has already been checked against its hash
+    pub async fn put(&self, hash: &ResourceHash, bytes: &[u8]) -> MrBundleResult<()> {
+        ffs::create_dir_all(&self.dir).await?;
+        Ok(ffs::write(self.path(hash), bytes).await?)
+    }
+}
+
+impl Default for ResourceCache {
+    fn default() -> Self {
+        Self::new(Self::default_dir())
+    }
+}
@@ -45,6 +45,15 @@ pub enum BundleError {
         "Cannot use relative paths for local locations. The following local path is relative: {0}"
     )]
     RelativeLocalPath(std::path::PathBuf),
+
+    #[error(
+        "The resource at {location:?} doesn't match the hash pinned in the manifest. Expected: {expected}, actual: {actual}"
+    )]
+    ResourceHashMismatch {
+        location: crate::Location,
+        expected: crate::ResourceHash,
+        actual: crate::ResourceHash,
+    },
 }
 pub type BundleResult<T> = Result<T, BundleError>;
 
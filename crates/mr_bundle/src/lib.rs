@@ -1,16 +1,20 @@
 mod bundle;
+mod cache;
 mod encoding;
 pub mod error;
 mod location;
 mod manifest;
 mod resource;
+mod signature;
 pub(crate) mod util;
 
 #[cfg(feature = "packing")]
 mod packing;
 
 pub use bundle::Bundle;
+pub use cache::ResourceCache;
 pub use encoding::{decode, encode};
 pub use location::Location;
 pub use manifest::Manifest;
-pub use resource::ResourceBytes;
+pub use resource::{ResourceBytes, ResourceHash};
+pub use signature::{BundleSignature, DETACHED_SIGNATURE_EXT};
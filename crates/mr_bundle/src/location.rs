@@ -1,6 +1,6 @@
 use crate::{
     error::{BundleError, MrBundleResult},
-    ResourceBytes,
+    ResourceBytes, ResourceCache, ResourceHash,
 };
 use std::path::{Path, PathBuf};
 
@@ -54,6 +54,46 @@ pub(crate) async fn resolve_remote(url: &str) -> MrBundleResult<ResourceBytes> {
         .collect())
 }
 
+/// Check that a resource, hashed with `hash_of`, matches the hash
+/// pinned for its location
+pub(crate) fn check_pinned_hash(
+    location: &Location,
+    expected: &ResourceHash,
+    bytes: ResourceBytes,
+    hash_of: fn(&[u8]) -> ResourceHash,
+) -> MrBundleResult<ResourceBytes> {
+    let actual = hash_of(&bytes);
+    if actual == *expected {
+        Ok(bytes)
+    } else {
+        Err(BundleError::ResourceHashMismatch {
+            location: location.clone(),
+            expected: expected.clone(),
+            actual,
+        }
+        .into())
+    }
+}
+
+/// Resolve a remote resource with a pinned hash, preferring the cached copy.
+/// A freshly fetched resource is only cached once it matches its hash.
+pub(crate) async fn resolve_pinned_remote(
+    location: &Location,
+    url: &str,
+    expected: &ResourceHash,
+    cache: &ResourceCache,
+    hash_of: fn(&[u8]) -> ResourceHash,
+) -> MrBundleResult<ResourceBytes> {
+    if let Some(bytes) = cache.get(expected, hash_of).await {
+        return Ok(bytes);
+    }
+    let bytes = check_pinned_hash(location, expected, resolve_remote(url).await?, hash_of)?;
+    // Caching is best-effort: the resource is valid either way, it just
+    // can't be resolved offline next time.
+    cache.put(expected, &bytes).await.ok();
+    Ok(bytes)
+}
+
 #[cfg(test)]
 mod tests {
 
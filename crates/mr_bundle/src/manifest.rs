@@ -1,6 +1,6 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
-use crate::location::Location;
+use crate::{location::Location, resource::ResourceHash};
 
 pub trait Manifest:
     Clone + Sized + PartialEq + Eq + serde::Serialize + serde::de::DeserializeOwned
@@ -9,6 +9,27 @@ pub trait Manifest:
     /// correctly implemented to enable resource resolution.
     fn locations(&self) -> Vec<Location>;
 
+    /// The expected hashes of non-bundled resources, for those locations
+    /// which the manifest pins to specific content. Resolving a pinned
+    /// location fails if the content found there doesn't match.
+    /// Hashes given for Bundled locations are ignored.
+    fn pinned_hashes(&self) -> HashMap<Location, ResourceHash> {
+        HashMap::new()
+    }
+
+    /// Whether the content of a non-bundled location is pinned by the
+    /// manifest, so that a signature over the bundle also covers it.
+    fn is_pinned(&self, location: &Location) -> bool {
+        self.pinned_hashes().contains_key(location)
+    }
+
+    /// The hash of a resource's content, as it is written in
+    /// [`pinned_hashes`](Manifest::pinned_hashes). Manifests which pin
+    /// resources with some other hash of their content can override this.
+    fn resource_hash(bytes: &[u8]) -> ResourceHash {
+        ResourceHash::of(bytes)
+    }
+
     /// When unpacking the bundle into a directory structure, this becomes
     /// the relative path of the manifest file.
     #[cfg(feature = "packing")]
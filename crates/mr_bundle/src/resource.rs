@@ -1 +1,78 @@
+use std::convert::TryFrom;
+
 pub type ResourceBytes = Vec<u8>;
+
+/// The expected content hash of a resource: a 256-bit Blake2b digest,
+/// written in manifests as 64 hex characters.
+///
+/// The hash of a file can be computed with `b2sum -l 256 <FILE>`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ResourceHash([u8; 32]);
+
+impl ResourceHash {
+    /// Hash the content of a resource
+    pub fn of(bytes: &[u8]) -> Self {
+        let digest = blake2b_simd::Params::new().hash_length(32).hash(bytes);
+        let mut hash = [0; 32];
+        hash.copy_from_slice(digest.as_bytes());
+        Self(hash)
+    }
+
+    /// The raw digest
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<[u8; 32]> for ResourceHash {
+    fn from(hash: [u8; 32]) -> Self {
+        Self(hash)
+    }
+}
+
+impl std::fmt::Display for ResourceHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(&self.0))
+    }
+}
+
+impl std::str::FromStr for ResourceHash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hash = [0; 32];
+        hex::decode_to_slice(s, &mut hash)
+            .map_err(|e| format!("Invalid resource hash '{}': {}", s, e))?;
+        Ok(Self(hash))
+    }
+}
+
+impl TryFrom<String> for ResourceHash {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ResourceHash> for String {
+    fn from(hash: ResourceHash) -> Self {
+        hash.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resource_hash_hex_roundtrip() {
+        let hash = ResourceHash::of(&[1, 2, 3]);
+        let yaml = serde_yaml::to_string(&hash).unwrap();
+        assert_eq!(serde_yaml::from_str::<ResourceHash>(&yaml).unwrap(), hash);
+        assert_eq!(hash.to_string().len(), 64);
+        assert_eq!(hash.to_string().parse::<ResourceHash>().unwrap(), hash);
+        assert!("abc".parse::<ResourceHash>().is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use crate::error::MrBundleResult;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The file extension appended to a bundle's path for its detached signature,
+/// e.g. `my-app.happ.sig`
+pub const DETACHED_SIGNATURE_EXT: &str = "sig";
+
+/// A publisher's signature over the [`Bundle::signing_bytes`] of a Bundle.
+///
+/// mr_bundle doesn't interpret the key or signature bytes itself.
+/// Holochain uses ed25519 keys held in lair, see `holochain_types::bundle_signature`.
+///
+/// A signature may be embedded in the bundle it signs, or kept alongside
+/// it in a detached signature file.
+///
+/// [`Bundle::signing_bytes`]: crate::Bundle::signing_bytes
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BundleSignature {
+    /// The public key of the publisher who made this signature
+    #[serde(with = "serde_bytes")]
+    pub publisher: Vec<u8>,
+
+    /// The signature over the bundle's signing bytes
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+}
+
+impl BundleSignature {
+    /// Encode the signature for storing in a detached signature file
+    pub fn encode(&self) -> MrBundleResult<Vec<u8>> {
+        crate::encode(self)
+    }
+
+    /// Decode bytes produced by `encode`
+    pub fn decode(bytes: &[u8]) -> MrBundleResult<Self> {
+        crate::decode(bytes)
+    }
+
+    /// Read a detached signature file
+    pub async fn read_from_file(path: &Path) -> MrBundleResult<Self> {
+        Self::decode(&ffs::read(path).await?)
+    }
+
+    /// Write a detached signature file
+    pub async fn write_to_file(&self, path: &Path) -> MrBundleResult<()> {
+        Ok(ffs::write(path, &self.encode()?).await?)
+    }
+
+    /// The conventional path of the detached signature for the bundle at `bundle_path`
+    pub fn detached_path(bundle_path: &Path) -> PathBuf {
+        let mut path = bundle_path.as_os_str().to_owned();
+        path.push(".");
+        path.push(DETACHED_SIGNATURE_EXT);
+        path.into()
+    }
+}
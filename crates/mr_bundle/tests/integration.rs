@@ -1,5 +1,15 @@
-use mr_bundle::{Bundle, Location, Manifest};
-use std::{collections::HashSet, path::PathBuf};
+use mr_bundle::{
+    error::{BundleError, MrBundleError},
+    Bundle, Location, Manifest, ResourceCache, ResourceHash,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "manifest_version")]
@@ -17,6 +27,16 @@ impl Manifest for TestManifest {
         }
     }
 
+    fn pinned_hashes(&self) -> HashMap<Location, ResourceHash> {
+        match self {
+            Self::V1(mani) => mani
+                .things
+                .iter()
+                .filter_map(|b| Some((b.location.clone(), b.resource_hash.clone()?)))
+                .collect(),
+        }
+    }
+
     #[cfg(feature = "packing")]
     fn path() -> PathBuf {
         "test-manifest.yaml".into()
@@ -38,6 +58,9 @@ struct ManifestV1 {
 struct ThingManifest {
     #[serde(flatten)]
     location: Location,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resource_hash: Option<ResourceHash>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
@@ -66,9 +89,11 @@ async fn resource_resolution() {
         things: vec![
             ThingManifest {
                 location: bundled_location.clone(),
+                resource_hash: None,
             },
             ThingManifest {
                 location: local_location.clone(),
+                resource_hash: None,
             },
         ],
     });
@@ -143,9 +168,11 @@ async fn unpack_roundtrip() {
         things: vec![
             ThingManifest {
                 location: bundled_location.clone(),
+                resource_hash: None,
             },
             ThingManifest {
                 location: local_location.clone(),
+                resource_hash: None,
             },
         ],
     });
@@ -180,3 +207,97 @@ async fn unpack_roundtrip() {
 
     assert_eq!(bundle, reconstructed);
 }
+
+/// Serve `body` over HTTP on a local port, counting the requests made.
+/// A stand-in for a remote resource host.
+async fn serve_resource(body: Vec<u8>) -> (String, Arc<AtomicUsize>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/thing", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).await;
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).await.unwrap();
+            stream.write_all(&body).await.unwrap();
+        }
+    });
+    (url, requests)
+}
+
+fn pinned_manifest(location: Location, hash: ResourceHash) -> TestManifest {
+    TestManifest::V1(ManifestV1 {
+        name: "pinned".to_string(),
+        things: vec![ThingManifest {
+            location,
+            resource_hash: Some(hash),
+        }],
+    })
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn pinned_resource_resolution() {
+    let dir = tempdir::TempDir::new("mr_bundle").unwrap();
+    let cache = ResourceCache::new(dir.path().join("cache"));
+    let thing = mr_bundle::encode(&Thing("remote".into())).unwrap();
+    let hash = ResourceHash::of(&thing);
+    let (url, requests) = serve_resource(thing.clone()).await;
+
+    // A matching remote resource is fetched once, then served from the cache
+    let location = Location::Url(url.clone());
+    let mut bundle =
+        Bundle::new_unchecked(pinned_manifest(location.clone(), hash.clone()), vec![]).unwrap();
+    bundle.set_resource_cache(cache.clone());
+    assert_eq!(bundle.resolve(&location).await.unwrap().into_owned(), thing);
+    assert_eq!(bundle.resolve(&location).await.unwrap().into_owned(), thing);
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    assert!(cache.dir().join(hash.to_string()).is_file());
+
+    // The cache works offline, for any location pinned to the same content
+    let offline = Location::Url("http://127.0.0.1:1/gone".into());
+    let mut bundle =
+        Bundle::new_unchecked(pinned_manifest(offline.clone(), hash.clone()), vec![]).unwrap();
+    bundle.set_resource_cache(cache.clone());
+    assert_eq!(bundle.resolve(&offline).await.unwrap().into_owned(), thing);
+
+    // A remote resource with the wrong content is rejected and not cached
+    let wrong = ResourceHash::of(&[1, 2, 3]);
+    let mut bundle =
+        Bundle::new_unchecked(pinned_manifest(location.clone(), wrong.clone()), vec![]).unwrap();
+    bundle.set_resource_cache(cache.clone());
+    match bundle.resolve(&location).await {
+        Err(MrBundleError::BundleError(BundleError::ResourceHashMismatch {
+            expected,
+            actual,
+            ..
+        })) => {
+            assert_eq!(expected, wrong);
+            assert_eq!(actual, hash);
+        }
+        other => panic!("expected a hash mismatch, got {:?}", other),
+    }
+    assert!(!cache.dir().join(wrong.to_string()).exists());
+
+    // Local paths are checked too
+    let local_path = dir.path().join("local.thing");
+    std::fs::write(&local_path, &thing).unwrap();
+    let local = Location::Path(local_path);
+    let bundle = Bundle::new_unchecked(pinned_manifest(local.clone(), hash), vec![]).unwrap();
+    assert_eq!(bundle.resolve(&local).await.unwrap().into_owned(), thing);
+    let bundle = Bundle::new_unchecked(pinned_manifest(local.clone(), wrong), vec![]).unwrap();
+    assert!(matches::matches!(
+        bundle.resolve(&local).await,
+        Err(MrBundleError::BundleError(
+            BundleError::ResourceHashMismatch { .. }
+        ))
+    ));
+}